@@ -0,0 +1,135 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use ffsr::lexer::Lexer;
+use ffsr::reader::datum::{Datum, SIdentifier};
+use ffsr::reader::Reader;
+use ffsr::visit::{walk, Visit, Walk};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::HashSet;
+use std::hint::black_box;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const SOURCE: &str = include_str!("everything.sld");
+
+// ------------------------------------------------------------------------------------------------
+// Allocation Counting
+// ------------------------------------------------------------------------------------------------
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+fn counting<T>(f: impl FnOnce() -> T) -> (T, usize, usize) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let result = f();
+    (
+        result,
+        ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes,
+    )
+}
+
+// ------------------------------------------------------------------------------------------------
+// Benchmarks
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Default)]
+struct Identifiers(Vec<SIdentifier>);
+
+impl Visit for Identifiers {
+    fn visit_identifier(&mut self, identifier: &SIdentifier) -> Walk {
+        self.0.push(identifier.clone());
+        Walk::Continue
+    }
+}
+
+fn read_everything() -> Vec<Datum> {
+    Reader::from(Lexer::from(SOURCE))
+        .iter()
+        .filter_map(Result::ok)
+        .collect()
+}
+
+fn identifiers(datums: &[Datum]) -> Vec<SIdentifier> {
+    let mut identifiers = Identifiers::default();
+    for datum in datums {
+        walk(&mut identifiers, datum);
+    }
+    identifiers.0
+}
+
+///
+/// Report the allocations made reading the file, and those that an owned `String` for each
+/// identifier read would add.
+///
+fn report_allocations() {
+    let (datums, allocations, bytes) = counting(read_everything);
+    let identifiers = identifiers(&datums);
+    let (owned, owned_allocations, owned_bytes) = counting(|| {
+        identifiers
+            .iter()
+            .map(|identifier| identifier.as_str().to_string())
+            .collect::<Vec<_>>()
+    });
+    println!(
+        "read {} identifiers, {} distinct, with {} allocations of {} bytes; \
+         owned strings would add {} allocations of {} bytes",
+        identifiers.len(),
+        SIdentifier::interned_count(),
+        allocations,
+        bytes,
+        owned_allocations - 1,
+        owned_bytes - owned.capacity() * std::mem::size_of::<String>(),
+    );
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    report_allocations();
+
+    c.bench_function("read_identifiers", |b| b.iter(read_everything));
+
+    // each identifier is parsed and interned, taking the table's lock, as the reader does.
+    let identifiers = identifiers(&read_everything());
+    let names: Vec<String> = identifiers.iter().map(|v| v.as_str().to_string()).collect();
+    c.bench_function("intern_identifiers", |b| {
+        b.iter(|| {
+            black_box(&names)
+                .iter()
+                .map(|name| SIdentifier::from_str(name).unwrap())
+                .collect::<Vec<_>>()
+        })
+    });
+
+    let define = SIdentifier::from_str("define").unwrap();
+    c.bench_function("compare_identifiers", |b| {
+        b.iter(|| {
+            black_box(&identifiers)
+                .iter()
+                .filter(|identifier| **identifier == define)
+                .count()
+        })
+    });
+    c.bench_function("hash_identifiers", |b| {
+        b.iter(|| black_box(&identifiers).iter().collect::<HashSet<_>>().len())
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -1,5 +1,7 @@
 use criterion::{criterion_group, criterion_main, Criterion};
 use ffsr::lexer::Lexer;
+#[cfg(feature = "arena")]
+use ffsr::reader::arena::Arena;
 use ffsr::reader::Reader;
 
 const SOURCE: &str = include_str!("everything.sld");
@@ -20,8 +22,27 @@ fn parse_large_file() {
     }
 }
 
+fn read_and_drop_large_file() -> usize {
+    let reader = Reader::from(Lexer::from(SOURCE));
+    let data: Vec<_> = reader.iter().filter_map(Result::ok).collect();
+    data.len()
+}
+
+#[cfg(feature = "arena")]
+fn read_and_drop_large_file_in_arena() -> usize {
+    let arena = Arena::new();
+    let reader = Reader::from(Lexer::from(SOURCE));
+    let data: Vec<_> = reader.arena_iter(&arena).filter_map(Result::ok).collect();
+    data.len()
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("large_file", |b| b.iter(parse_large_file));
+    c.bench_function("large_file_rc", |b| b.iter(read_and_drop_large_file));
+    #[cfg(feature = "arena")]
+    c.bench_function("large_file_arena", |b| {
+        b.iter(read_and_drop_large_file_in_arena)
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
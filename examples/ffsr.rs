@@ -0,0 +1,223 @@
+use ffsr::error::Error;
+use ffsr::input::lines::LineIndex;
+use ffsr::lexer::Lexer;
+use ffsr::query::Query;
+use ffsr::reader::Reader;
+use ffsr::writer::format::SourceFormatter;
+use ffsr::writer::pretty::IndentStyle;
+use std::io::{Read, Write};
+use std::{fmt::Display, path::PathBuf};
+use structopt::StructOpt;
+
+// ------------------------------------------------------------------------------------------------
+// Command-Line Structure
+// ------------------------------------------------------------------------------------------------
+
+const TOOL_NAME: &str = "ffsr";
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = TOOL_NAME)]
+enum Cli {
+    /// Format Scheme source files, or standard input if no files are given
+    Fmt(FmtCommand),
+    /// Print the datums selected by a query from Scheme source files, or standard input if no
+    /// files are given
+    Query(QueryCommand),
+}
+
+#[derive(Debug, StructOpt)]
+struct FmtCommand {
+    /// Report the files that are not formatted, and exit with an error if there are any
+    #[structopt(long, conflicts_with = "write")]
+    check: bool,
+
+    /// Write the formatted text back to each file, rather than to standard output
+    #[structopt(long, short)]
+    write: bool,
+
+    /// The line width to fit to
+    #[structopt(long, default_value = "80")]
+    width: usize,
+
+    /// The number of columns to indent a body by
+    #[structopt(long, default_value = "2")]
+    indent: usize,
+
+    /// Put each argument on its own line, rather than aligning them after the operator
+    #[structopt(long)]
+    block: bool,
+
+    /// The largest number of consecutive empty lines to keep
+    #[structopt(long, default_value = "1")]
+    blank_lines: usize,
+
+    files: Vec<PathBuf>,
+}
+
+#[derive(Debug, StructOpt)]
+struct QueryCommand {
+    /// The query, such as `define-library/export/*`
+    query: String,
+
+    /// Print only the number of datums selected from each file
+    #[structopt(long, short)]
+    count: bool,
+
+    files: Vec<PathBuf>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Command-Line Errors
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug)]
+enum ToolError {
+    Io(PathBuf, std::io::Error),
+    ReaderError(PathBuf, Error),
+    NotFormatted(usize),
+    InvalidQuery(String, Error),
+}
+
+impl Display for ToolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            Self::ReaderError(path, e) => write!(f, "{}: {}", path.display(), e),
+            Self::NotFormatted(count) => write!(f, "{} file(s) are not formatted", count),
+            Self::InvalidQuery(query, e) => write!(f, "`{}`: {}", query, e),
+        }
+    }
+}
+
+impl std::error::Error for ToolError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(_, source) => Some(source),
+            Self::ReaderError(_, source) => Some(source),
+            Self::InvalidQuery(_, source) => Some(source),
+            Self::NotFormatted(_) => None,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Main Function
+// ------------------------------------------------------------------------------------------------
+
+fn main() -> Result<(), ToolError> {
+    match Cli::from_args() {
+        Cli::Fmt(command) => fmt(command),
+        Cli::Query(command) => query(command),
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn fmt(command: FmtCommand) -> Result<(), ToolError> {
+    let formatter = SourceFormatter::default()
+        .with_width(command.width)
+        .with_indent(command.indent)
+        .with_style(if command.block {
+            IndentStyle::Block
+        } else {
+            IndentStyle::Aligned
+        })
+        .with_blank_lines(command.blank_lines);
+
+    if command.files.is_empty() {
+        let stdin = PathBuf::from("<stdin>");
+        let mut source = String::new();
+        std::io::stdin()
+            .read_to_string(&mut source)
+            .map_err(|e| ToolError::Io(stdin.clone(), e))?;
+        let formatted = formatter
+            .format_str(&source)
+            .map_err(|e| ToolError::ReaderError(stdin.clone(), e))?;
+        if command.check {
+            return check_result(usize::from(formatted != source));
+        }
+        return std::io::stdout()
+            .write_all(formatted.as_bytes())
+            .map_err(|e| ToolError::Io(stdin, e));
+    }
+
+    let mut not_formatted = 0;
+    for file in command.files {
+        let source = std::fs::read_to_string(&file).map_err(|e| ToolError::Io(file.clone(), e))?;
+        let formatted = formatter
+            .format_str(&source)
+            .map_err(|e| ToolError::ReaderError(file.clone(), e))?;
+        if command.check {
+            if formatted != source {
+                println!("{}", file.display());
+                not_formatted += 1;
+            }
+        } else if command.write {
+            if formatted != source {
+                std::fs::write(&file, formatted).map_err(|e| ToolError::Io(file.clone(), e))?;
+            }
+        } else {
+            print!("{formatted}");
+        }
+    }
+    check_result(not_formatted)
+}
+
+fn query(command: QueryCommand) -> Result<(), ToolError> {
+    let query: Query = command
+        .query
+        .parse()
+        .map_err(|e| ToolError::InvalidQuery(command.query.clone(), e))?;
+
+    let inputs = if command.files.is_empty() {
+        let stdin = PathBuf::from("<stdin>");
+        let mut source = String::new();
+        std::io::stdin()
+            .read_to_string(&mut source)
+            .map_err(|e| ToolError::Io(stdin.clone(), e))?;
+        vec![(stdin, source)]
+    } else {
+        command
+            .files
+            .into_iter()
+            .map(|file| {
+                std::fs::read_to_string(&file)
+                    .map(|source| (file.clone(), source))
+                    .map_err(|e| ToolError::Io(file, e))
+            })
+            .collect::<Result<_, _>>()?
+    };
+
+    for (file, source) in inputs {
+        let reader = Reader::from(Lexer::from(source.as_str()));
+        let forms: Vec<_> = reader
+            .syntax_iter()
+            .collect::<Result<_, _>>()
+            .map_err(|e| ToolError::ReaderError(file.clone(), e))?;
+        let selected = query.select(&forms);
+        if command.count {
+            println!("{}: {}", file.display(), selected.len());
+        } else {
+            let lines = LineIndex::new(&source);
+            for datum in selected {
+                println!(
+                    "{}:{}: {}",
+                    file.display(),
+                    datum.start_position(&lines),
+                    ffsr::writer::write(&datum.to_datum())
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_result(not_formatted: usize) -> Result<(), ToolError> {
+    if not_formatted == 0 {
+        Ok(())
+    } else {
+        Err(ToolError::NotFormatted(not_formatted))
+    }
+}
@@ -0,0 +1,435 @@
+/*!
+Provides the derive macros `FromDatum` and `IntoDatum` for the traits of the same names in
+`ffsr::convert`; use them through that module, with the `derive` feature of `ffsr`, which
+also describes the layout of the datums and the `#[datum(...)]` attributes.
+
+*/
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as Tokens};
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Data, DeriveInput, ExprPath, Fields, Generics,
+    Ident, LitStr, Member, Token, Type,
+};
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+#[proc_macro_derive(FromDatum, attributes(datum))]
+pub fn derive_from_datum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    from_datum(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(IntoDatum, attributes(datum))]
+pub fn derive_into_datum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    into_datum(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The `#[datum(...)]` attributes on a single item, with the span of each key.
+///
+#[derive(Default)]
+struct Attributes {
+    tag: Option<String>,
+    rename: Option<String>,
+    positional: bool,
+    default: Option<DefaultValue>,
+    keys: Vec<(&'static str, Span)>,
+}
+
+enum DefaultValue {
+    Trait,
+    Function(ExprPath),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Layout {
+    Unit,
+    Transparent,
+    Named,
+    Positional,
+}
+
+///
+/// A struct, or enum variant, to read or write.
+///
+struct Shape {
+    type_name: String,
+    tag: Option<String>,
+    layout: Layout,
+    fields: Vec<Field>,
+}
+
+struct Field {
+    member: Member,
+    binding: Ident,
+    name: String,
+    ty: Type,
+    default: Option<DefaultValue>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Attributes {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut result = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("datum")) {
+            attr.parse_nested_meta(|meta| {
+                let span = meta.path.get_ident().map_or_else(Span::call_site, Ident::span);
+                if meta.path.is_ident("tag") {
+                    result.tag = Some(meta.value()?.parse::<LitStr>()?.value());
+                    result.keys.push(("tag", span));
+                } else if meta.path.is_ident("rename") {
+                    result.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                    result.keys.push(("rename", span));
+                } else if meta.path.is_ident("positional") {
+                    result.positional = true;
+                    result.keys.push(("positional", span));
+                } else if meta.path.is_ident("default") {
+                    result.default = Some(if meta.input.peek(Token![=]) {
+                        DefaultValue::Function(meta.value()?.parse::<LitStr>()?.parse()?)
+                    } else {
+                        DefaultValue::Trait
+                    });
+                    result.keys.push(("default", span));
+                } else {
+                    return Err(meta.error(
+                        "unknown datum attribute, expecting one of `tag`, `rename`, `positional`, or `default`",
+                    ));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(result)
+    }
+
+    ///
+    /// Return an error for the first key not in `allowed`, for an item described by `on`.
+    ///
+    fn allow(self, allowed: &[&str], on: &str) -> syn::Result<Self> {
+        match self.keys.iter().find(|(key, _)| !allowed.contains(key)) {
+            Some((key, span)) => Err(syn::Error::new(
+                *span,
+                format!("the datum attribute `{key}` is not supported on {on}"),
+            )),
+            None => Ok(self),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Shape {
+    fn new(
+        type_name: String,
+        tag: Option<String>,
+        positional: bool,
+        fields: &Fields,
+        transparent: bool,
+    ) -> syn::Result<Self> {
+        let layout = match fields {
+            Fields::Unit => Layout::Unit,
+            Fields::Unnamed(unnamed) if transparent && unnamed.unnamed.len() == 1 => {
+                Layout::Transparent
+            }
+            Fields::Unnamed(_) => Layout::Positional,
+            Fields::Named(_) if positional => Layout::Positional,
+            Fields::Named(_) => Layout::Named,
+        };
+        let fields = fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| {
+                let attributes =
+                    Attributes::parse(&field.attrs)?.allow(&["rename", "default"], "a field")?;
+                let (member, name) = match &field.ident {
+                    Some(ident) => (Member::Named(ident.clone()), ident.to_string()),
+                    None => (Member::Unnamed(index.into()), index.to_string()),
+                };
+                Ok(Field {
+                    member,
+                    binding: format_ident!("__field{}", index),
+                    name: attributes.rename.unwrap_or(name),
+                    ty: field.ty.clone(),
+                    default: attributes.default,
+                })
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+        Ok(Self {
+            type_name,
+            tag,
+            layout,
+            fields,
+        })
+    }
+
+    fn tag(&self) -> Tokens {
+        match &self.tag {
+            Some(tag) => quote!(::core::option::Option::Some(#tag)),
+            None => quote!(::core::option::Option::None),
+        }
+    }
+
+    ///
+    /// The pattern, or constructor, `path` with each field as its binding, or as `values`.
+    ///
+    fn construct(&self, path: &Tokens, values: &[Tokens], named: bool) -> Tokens {
+        if self.layout == Layout::Unit {
+            path.clone()
+        } else if named {
+            let members = self.fields.iter().map(|field| &field.member);
+            quote!(#path { #(#members: #values),* })
+        } else {
+            quote!(#path( #(#values),* ))
+        }
+    }
+
+    fn pattern(&self, path: &Tokens, named: bool) -> Tokens {
+        let bindings: Vec<Tokens> = self
+            .fields
+            .iter()
+            .map(|field| {
+                let binding = &field.binding;
+                quote!(#binding)
+            })
+            .collect();
+        self.construct(path, &bindings, named)
+    }
+
+    ///
+    /// The statements that read `datum` into `path`, returning a `Result`.
+    ///
+    fn read(&self, path: &Tokens, named: bool) -> Tokens {
+        let type_name = &self.type_name;
+        let tag = self.tag();
+        match self.layout {
+            Layout::Unit => quote! {
+                ::ffsr::convert::unit(datum, #type_name, #tag)?;
+                ::core::result::Result::Ok(#path)
+            },
+            Layout::Transparent => {
+                let ty = &self.fields[0].ty;
+                let value = quote!(<#ty as ::ffsr::convert::FromDatum>::from_syntax(datum)?);
+                let value = self.construct(path, &[value], named);
+                quote!(::core::result::Result::Ok(#value))
+            }
+            Layout::Named | Layout::Positional => {
+                let fields = if self.layout == Layout::Named {
+                    quote!(named()?)
+                } else {
+                    quote!(positional())
+                };
+                let values: Vec<Tokens> = self
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        let name = &field.name;
+                        let ty = &field.ty;
+                        match &field.default {
+                            None => quote!(__fields.required::<#ty>(#name)?),
+                            Some(DefaultValue::Trait) => {
+                                quote!(__fields.optional::<#ty>(#name)?.unwrap_or_default())
+                            }
+                            Some(DefaultValue::Function(function)) => {
+                                quote!(__fields.optional::<#ty>(#name)?.unwrap_or_else(#function))
+                            }
+                        }
+                    })
+                    .collect();
+                let value = self.construct(path, &values, named);
+                quote! {
+                    let mut __fields = ::ffsr::convert::Fields::new(datum, #type_name, #tag)?.#fields;
+                    let __value = #value;
+                    __fields.finish()?;
+                    ::core::result::Result::Ok(__value)
+                }
+            }
+        }
+    }
+
+    ///
+    /// The expression that writes the bound fields as a datum.
+    ///
+    fn write(&self) -> Tokens {
+        let tag = self.tag();
+        let values = self.fields.iter().map(|field| {
+            let binding = &field.binding;
+            let value = quote!(::ffsr::convert::IntoDatum::into_datum(#binding));
+            if self.layout == Layout::Named {
+                let name = &field.name;
+                quote!(::ffsr::convert::entry(#name, #value))
+            } else {
+                value
+            }
+        });
+        match (self.layout, &self.tag) {
+            (Layout::Unit, Some(tag)) => quote!(::ffsr::convert::symbol(#tag)),
+            (Layout::Transparent, _) => {
+                let binding = &self.fields[0].binding;
+                quote!(::ffsr::convert::IntoDatum::into_datum(#binding))
+            }
+            _ => quote!(::ffsr::convert::tagged(#tag, ::std::vec![#(#values),*])),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn from_datum(input: &DeriveInput) -> syn::Result<Tokens> {
+    let ident = &input.ident;
+    let generics = bounded(&input.generics, quote!(::ffsr::convert::FromDatum));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let shape = struct_shape(input, &data.fields)?;
+            shape.read(&quote!(Self), is_named(&data.fields))
+        }
+        Data::Enum(data) => {
+            let type_name = ident.to_string();
+            let arms = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let shape = variant_shape(ident, variant)?;
+                    let tag = &shape.tag;
+                    let name = &variant.ident;
+                    let read = shape.read(&quote!(Self::#name), is_named(&variant.fields));
+                    Ok(quote!(#tag => { #read }))
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+            quote! {
+                let __variant = ::ffsr::convert::variant(datum, #type_name)?;
+                match __variant {
+                    #(#arms)*
+                    _ => ::ffsr::error::datum_conversion(
+                        datum.span(),
+                        #type_name,
+                        ::std::format!("unknown variant `{}`", __variant),
+                    ),
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "FromDatum cannot be derived for a union",
+            ))
+        }
+    };
+    Ok(quote! {
+        impl #impl_generics ::ffsr::convert::FromDatum for #ident #ty_generics #where_clause {
+            fn from_syntax(
+                datum: &::ffsr::reader::syntax::SyntaxDatum,
+            ) -> ::core::result::Result<Self, ::ffsr::error::Error> {
+                #body
+            }
+        }
+    })
+}
+
+fn into_datum(input: &DeriveInput) -> syn::Result<Tokens> {
+    let ident = &input.ident;
+    let generics = bounded(&input.generics, quote!(::ffsr::convert::IntoDatum));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let shape = struct_shape(input, &data.fields)?;
+            let pattern = shape.pattern(&quote!(Self), is_named(&data.fields));
+            let write = shape.write();
+            quote! {
+                let #pattern = self;
+                #write
+            }
+        }
+        Data::Enum(data) => {
+            let arms = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let shape = variant_shape(ident, variant)?;
+                    let name = &variant.ident;
+                    let pattern = shape.pattern(&quote!(Self::#name), is_named(&variant.fields));
+                    let write = shape.write();
+                    Ok(quote!(#pattern => #write,))
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "IntoDatum cannot be derived for a union",
+            ))
+        }
+    };
+    Ok(quote! {
+        impl #impl_generics ::ffsr::convert::IntoDatum for #ident #ty_generics #where_clause {
+            fn into_datum(self) -> ::ffsr::reader::datum::Datum {
+                #body
+            }
+        }
+    })
+}
+
+fn struct_shape(input: &DeriveInput, fields: &Fields) -> syn::Result<Shape> {
+    let attributes = Attributes::parse(&input.attrs)?.allow(&["tag", "positional"], "a struct")?;
+    let transparent = attributes.tag.is_none() && !attributes.positional;
+    Shape::new(
+        input.ident.to_string(),
+        attributes.tag,
+        attributes.positional,
+        fields,
+        transparent,
+    )
+}
+
+fn variant_shape(ident: &Ident, variant: &syn::Variant) -> syn::Result<Shape> {
+    let attributes =
+        Attributes::parse(&variant.attrs)?.allow(&["rename", "positional"], "an enum variant")?;
+    Shape::new(
+        format!("{}::{}", ident, variant.ident),
+        Some(
+            attributes
+                .rename
+                .unwrap_or_else(|| variant.ident.to_string()),
+        ),
+        attributes.positional,
+        &variant.fields,
+        false,
+    )
+}
+
+fn is_named(fields: &Fields) -> bool {
+    matches!(fields, Fields::Named(_))
+}
+
+///
+/// `generics` with the bound `bound` added to each type parameter.
+///
+fn bounded(generics: &Generics, bound: Tokens) -> Generics {
+    let mut generics = generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#bound));
+    }
+    generics
+}
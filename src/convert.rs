@@ -0,0 +1,673 @@
+/*!
+Provides the traits [`FromDatum`] and [`IntoDatum`] that convert between Rust values and
+datums, with implementations for common types.
+
+With the `derive` feature the derive macros `FromDatum` and `IntoDatum` implement these for a
+struct or enum. A struct with named fields is a list of `(name value)` entries, in any order
+when read, and a tuple struct is a list of its values in order; a newtype struct is its value.
+The attribute `#[datum(...)]` changes this:
+
+| Attribute                    | On             | Effect                                          |
+|------------------------------|----------------|-------------------------------------------------|
+| `tag = "point"`              | struct         | the list starts with the symbol, `(point ...)`  |
+| `positional`                 | struct, variant| fields are values in order, not entries         |
+| `rename = "name"`            | field, variant | the name used in the datum                      |
+| `default`                    | field          | `Default::default()` when the field is absent   |
+| `default = "path"`           | field          | the function `path()` when the field is absent  |
+
+A field of type `Option<T>` may always be absent. An enum variant is a list tagged with its
+name, `(Circle 1.5)`, or for a unit variant just the symbol, `Empty`. A unit struct is its
+tag, or `()` if it has none.
+
+Conversion errors are [`Error::DatumConversion`], with the span of the datum that could not
+be read and the field, if any, that it was read for.
+
+# Example
+
+```rust
+# #[cfg(feature = "derive")]
+# {
+use ffsr::convert::{from_str, FromDatum, IntoDatum};
+use ffsr::writer::write;
+
+#[derive(Debug, PartialEq, FromDatum, IntoDatum)]
+#[datum(tag = "point")]
+struct Point {
+    x: i64,
+    y: i64,
+    #[datum(rename = "label")]
+    name: Option<String>,
+}
+
+let point: Point = from_str("(point (y 2) (x 1))").unwrap();
+assert_eq!(point, Point { x: 1, y: 2, name: None });
+assert_eq!(write(&point.into_datum()).to_string(), "(point (x 1) (y 2) (label ()))");
+
+let error = from_str::<Point>("(point (x 1) (y \"2\"))").unwrap_err();
+assert_eq!(
+    error.to_string(),
+    "Could not convert datum to `Point`, field `y`: expecting an integer, found `string`; span: 16..19"
+);
+# }
+```
+
+*/
+
+use crate::error::{custom_error, datum_conversion, field_conversion, Error};
+use crate::lexer::token::Span;
+use crate::lexer::Lexer;
+use crate::reader::datum::numbers::Number;
+use crate::reader::datum::{Datum, SIdentifier, SList, SNumber, SString};
+use crate::reader::syntax::{SyntaxDatum, SyntaxValue};
+use crate::reader::Reader;
+use crate::syntax::IDENTIFIER_WRAPPER;
+use std::str::FromStr;
+
+#[cfg(feature = "derive")]
+pub use ffsr_derive::{FromDatum, IntoDatum};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A type that can be read from a datum, with its spans.
+///
+pub trait FromDatum: Sized {
+    fn from_syntax(datum: &SyntaxDatum) -> Result<Self, Error>;
+
+    ///
+    /// The value of a field of this type that is absent, or `None` if the field is required.
+    ///
+    #[inline(always)]
+    fn missing() -> Option<Self> {
+        None
+    }
+}
+
+///
+/// A type that can be written as a datum.
+///
+pub trait IntoDatum {
+    fn into_datum(self) -> Datum;
+}
+
+///
+/// The elements of a list, after its tag if it has one, read by a derived [`FromDatum`].
+///
+#[derive(Clone, Debug)]
+pub struct Fields<'a> {
+    type_name: &'static str,
+    span: Span,
+    elements: &'a [SyntaxDatum],
+}
+
+///
+/// The `(name value)` entries of a list, each taken at most once by field name.
+///
+#[derive(Clone, Debug)]
+pub struct NamedFields<'a> {
+    type_name: &'static str,
+    span: Span,
+    entries: Vec<Option<(&'a str, &'a SyntaxDatum)>>,
+    names: Vec<Span>,
+}
+
+///
+/// The elements of a list, taken in order by field.
+///
+#[derive(Clone, Debug)]
+pub struct PositionalFields<'a> {
+    type_name: &'static str,
+    span: Span,
+    elements: std::slice::Iter<'a, SyntaxDatum>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Read the single datum in `s` as a `T`; it is an error if `s` contains any other datum.
+///
+pub fn from_str<T>(s: &str) -> Result<T, Error>
+where
+    T: FromDatum,
+{
+    let reader = Reader::from(Lexer::from(s));
+    let mut iter = reader.syntax_iter();
+    let datum = match iter.next() {
+        Some(datum) => datum?,
+        None => return custom_error(None, "expecting a datum, found none"),
+    };
+    if let Some(next) = iter.next() {
+        return custom_error(Some(next?.span()), "expecting a single datum");
+    }
+    T::from_syntax(&datum)
+}
+
+///
+/// The symbol `name`, written between `|` if it is not a valid identifier as it is.
+///
+pub fn symbol(name: &str) -> Datum {
+    SIdentifier::from_str(name)
+        .or_else(|_| SIdentifier::from_str(&format!("|{name}|")))
+        .map(Datum::from)
+        .unwrap_or_else(|_| SString::from(name.to_string()).into())
+}
+
+///
+/// The list of `elements`, starting with the symbol `tag` if there is one.
+///
+pub fn tagged(tag: Option<&str>, elements: Vec<Datum>) -> Datum {
+    match tag {
+        Some(tag) => std::iter::once(symbol(tag))
+            .chain(elements)
+            .collect::<SList>()
+            .into(),
+        None => SList::from(elements).into(),
+    }
+}
+
+///
+/// The entry `(name value)`.
+///
+pub fn entry(name: &str, value: Datum) -> Datum {
+    tagged(Some(name), vec![value])
+}
+
+///
+/// The name of the enum variant that `datum` is, either the symbol itself or the symbol that
+/// starts a list.
+///
+pub fn variant<'a>(datum: &'a SyntaxDatum, type_name: &'static str) -> Result<&'a str, Error> {
+    let head = match datum.value() {
+        SyntaxValue::List(elements, None) => elements.first(),
+        _ => Some(datum),
+    };
+    match head.and_then(symbol_name) {
+        Some(name) => Ok(name),
+        None => expecting(
+            datum,
+            type_name,
+            "a symbol, or a list starting with a symbol",
+        ),
+    }
+}
+
+///
+/// Read a unit struct, or unit variant, that is the symbol `tag`, or a list of only the
+/// symbol; without a tag it is the empty list.
+///
+pub fn unit(datum: &SyntaxDatum, type_name: &'static str, tag: Option<&str>) -> Result<(), Error> {
+    match tag {
+        Some(tag) if symbol_name(datum) == Some(tag) => Ok(()),
+        _ => Fields::new(datum, type_name, tag)?.positional().finish(),
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Macros
+// ------------------------------------------------------------------------------------------------
+
+macro_rules! impl_integer {
+    ($($rust_type:ty),*) => {
+        $(
+            impl FromDatum for $rust_type {
+                fn from_syntax(datum: &SyntaxDatum) -> Result<Self, Error> {
+                    let type_name = stringify!($rust_type);
+                    match atom(datum) {
+                        Some(Datum::Number(SNumber::Fixnum(v))) => match <$rust_type>::try_from(*v.value()) {
+                            Ok(v) => Ok(v),
+                            Err(_) => datum_conversion(
+                                datum.span(),
+                                type_name,
+                                format!("the integer {} is out of range", v.value()),
+                            ),
+                        },
+                        _ => expecting(datum, type_name, "an integer"),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_into_datum {
+    ($($rust_type:ty),*) => {
+        $(
+            impl IntoDatum for $rust_type {
+                #[inline(always)]
+                fn into_datum(self) -> Datum {
+                    self.into()
+                }
+            }
+        )*
+    };
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl<'a> Fields<'a> {
+    ///
+    /// The elements of `datum`, which must be a list, and must start with the symbol `tag` if
+    /// there is one.
+    ///
+    pub fn new(
+        datum: &'a SyntaxDatum,
+        type_name: &'static str,
+        tag: Option<&str>,
+    ) -> Result<Self, Error> {
+        let elements = match datum.value() {
+            SyntaxValue::List(elements, None) => elements.as_slice(),
+            _ => return expecting(datum, type_name, "a list"),
+        };
+        let elements = match tag {
+            Some(tag) => match elements.split_first() {
+                Some((head, rest)) if symbol_name(head) == Some(tag) => rest,
+                Some((head, _)) => return expecting(head, type_name, format!("the tag `{tag}`")),
+                None => return expecting(datum, type_name, format!("a list starting `({tag}`")),
+            },
+            None => elements,
+        };
+        Ok(Self {
+            type_name,
+            span: datum.span(),
+            elements,
+        })
+    }
+
+    ///
+    /// Read the elements as `(name value)` entries; it is an error for a name to be repeated.
+    ///
+    pub fn named(self) -> Result<NamedFields<'a>, Error> {
+        let mut entries = Vec::with_capacity(self.elements.len());
+        let mut names = Vec::with_capacity(self.elements.len());
+        for element in self.elements {
+            let (name, value) = match element.value() {
+                SyntaxValue::List(entry, None) if entry.len() == 2 => {
+                    match symbol_name(&entry[0]) {
+                        Some(name) => ((name, entry[0].span()), &entry[1]),
+                        None => return expecting(&entry[0], self.type_name, "a field name"),
+                    }
+                }
+                _ => return expecting(element, self.type_name, "an entry `(name value)`"),
+            };
+            if entries
+                .iter()
+                .flatten()
+                .any(|(other, _): &(&str, _)| *other == name.0)
+            {
+                return datum_conversion(
+                    name.1,
+                    self.type_name,
+                    format!("duplicate field `{}`", name.0),
+                );
+            }
+            entries.push(Some((name.0, value)));
+            names.push(name.1);
+        }
+        Ok(NamedFields {
+            type_name: self.type_name,
+            span: self.span,
+            entries,
+            names,
+        })
+    }
+
+    ///
+    /// Read the elements in order.
+    ///
+    pub fn positional(self) -> PositionalFields<'a> {
+        PositionalFields {
+            type_name: self.type_name,
+            span: self.span,
+            elements: self.elements.iter(),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl NamedFields<'_> {
+    ///
+    /// Take the value of the entry `name`, which may be absent if `T` has a
+    /// [missing](FromDatum::missing) value.
+    ///
+    pub fn required<T: FromDatum>(&mut self, name: &'static str) -> Result<T, Error> {
+        match self.optional(name)? {
+            Some(value) => Ok(value),
+            None => match T::missing() {
+                Some(value) => Ok(value),
+                None => field_conversion(self.span, self.type_name, name, "missing field"),
+            },
+        }
+    }
+
+    ///
+    /// Take the value of the entry `name`, if present.
+    ///
+    pub fn optional<T: FromDatum>(&mut self, name: &'static str) -> Result<Option<T>, Error> {
+        let found = self
+            .entries
+            .iter_mut()
+            .find(|entry| matches!(entry, Some((other, _)) if *other == name))
+            .and_then(Option::take);
+        match found {
+            Some((_, value)) => field(T::from_syntax(value), self.type_name, name).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    ///
+    /// Check that every entry has been taken.
+    ///
+    pub fn finish(self) -> Result<(), Error> {
+        match self
+            .entries
+            .iter()
+            .zip(self.names)
+            .find_map(|(entry, span)| entry.map(|(name, _)| (name, span)))
+        {
+            Some((name, span)) => {
+                datum_conversion(span, self.type_name, format!("unknown field `{name}`"))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl PositionalFields<'_> {
+    ///
+    /// Take the next element as the field `name`, which may be absent if `T` has a
+    /// [missing](FromDatum::missing) value.
+    ///
+    pub fn required<T: FromDatum>(&mut self, name: &'static str) -> Result<T, Error> {
+        match self.optional(name)? {
+            Some(value) => Ok(value),
+            None => match T::missing() {
+                Some(value) => Ok(value),
+                None => field_conversion(self.span, self.type_name, name, "missing value"),
+            },
+        }
+    }
+
+    ///
+    /// Take the next element, if any, as the field `name`.
+    ///
+    pub fn optional<T: FromDatum>(&mut self, name: &'static str) -> Result<Option<T>, Error> {
+        match self.elements.next() {
+            Some(value) => field(T::from_syntax(value), self.type_name, name).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    ///
+    /// Check that every element has been taken.
+    ///
+    pub fn finish(mut self) -> Result<(), Error> {
+        match self.elements.next() {
+            Some(extra) => datum_conversion(extra.span(), self.type_name, "unexpected value"),
+            None => Ok(()),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl_integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl FromDatum for f64 {
+    fn from_syntax(datum: &SyntaxDatum) -> Result<Self, Error> {
+        match atom(datum) {
+            Some(Datum::Number(SNumber::Flonum(v))) => Ok(*v.value()),
+            Some(Datum::Number(SNumber::Fixnum(v))) => Ok(*v.value() as f64),
+            _ => expecting(datum, "f64", "a real number"),
+        }
+    }
+}
+
+impl FromDatum for f32 {
+    fn from_syntax(datum: &SyntaxDatum) -> Result<Self, Error> {
+        f64::from_syntax(datum).map(|v| v as f32)
+    }
+}
+
+impl FromDatum for bool {
+    fn from_syntax(datum: &SyntaxDatum) -> Result<Self, Error> {
+        match atom(datum) {
+            Some(Datum::Boolean(v)) => Ok(bool::from(*v)),
+            _ => expecting(datum, "bool", "a boolean"),
+        }
+    }
+}
+
+impl FromDatum for char {
+    fn from_syntax(datum: &SyntaxDatum) -> Result<Self, Error> {
+        match atom(datum) {
+            Some(Datum::Char(v)) => Ok(char::from(*v)),
+            _ => expecting(datum, "char", "a character"),
+        }
+    }
+}
+
+impl FromDatum for String {
+    fn from_syntax(datum: &SyntaxDatum) -> Result<Self, Error> {
+        match atom(datum) {
+            Some(Datum::String(v)) => Ok(v.as_str().to_string()),
+            _ => expecting(datum, "String", "a string"),
+        }
+    }
+}
+
+impl FromDatum for SIdentifier {
+    fn from_syntax(datum: &SyntaxDatum) -> Result<Self, Error> {
+        match atom(datum) {
+            Some(Datum::Identifier(v)) => Ok(v.clone()),
+            _ => expecting(datum, "SIdentifier", "a symbol"),
+        }
+    }
+}
+
+impl FromDatum for Datum {
+    #[inline(always)]
+    fn from_syntax(datum: &SyntaxDatum) -> Result<Self, Error> {
+        Ok(datum.to_datum())
+    }
+}
+
+impl FromDatum for SyntaxDatum {
+    #[inline(always)]
+    fn from_syntax(datum: &SyntaxDatum) -> Result<Self, Error> {
+        Ok(datum.clone())
+    }
+}
+
+///
+/// A list, or vector, of values.
+///
+impl<T: FromDatum> FromDatum for Vec<T> {
+    fn from_syntax(datum: &SyntaxDatum) -> Result<Self, Error> {
+        match datum.value() {
+            SyntaxValue::List(elements, None) | SyntaxValue::Vector(elements) => {
+                elements.iter().map(T::from_syntax).collect()
+            }
+            _ => expecting(datum, "Vec", "a list or vector"),
+        }
+    }
+}
+
+///
+/// The empty list is `None`, and so `Some` of a value that is the empty list cannot be read.
+///
+impl<T: FromDatum> FromDatum for Option<T> {
+    fn from_syntax(datum: &SyntaxDatum) -> Result<Self, Error> {
+        match datum.value() {
+            SyntaxValue::List(elements, None) if elements.is_empty() => Ok(None),
+            _ => T::from_syntax(datum).map(Some),
+        }
+    }
+
+    #[inline(always)]
+    fn missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl<T: FromDatum> FromDatum for Box<T> {
+    #[inline(always)]
+    fn from_syntax(datum: &SyntaxDatum) -> Result<Self, Error> {
+        T::from_syntax(datum).map(Box::new)
+    }
+
+    #[inline(always)]
+    fn missing() -> Option<Self> {
+        T::missing().map(Box::new)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl_into_datum!(i8, i16, i32, i64, u8, u16, u32, f64, bool, char);
+
+impl IntoDatum for f32 {
+    #[inline(always)]
+    fn into_datum(self) -> Datum {
+        f64::from(self).into()
+    }
+}
+
+impl IntoDatum for String {
+    #[inline(always)]
+    fn into_datum(self) -> Datum {
+        SString::from(self).into()
+    }
+}
+
+impl IntoDatum for &str {
+    #[inline(always)]
+    fn into_datum(self) -> Datum {
+        SString::from(self.to_string()).into()
+    }
+}
+
+impl IntoDatum for SIdentifier {
+    #[inline(always)]
+    fn into_datum(self) -> Datum {
+        self.into()
+    }
+}
+
+impl IntoDatum for Datum {
+    #[inline(always)]
+    fn into_datum(self) -> Datum {
+        self
+    }
+}
+
+impl IntoDatum for SyntaxDatum {
+    #[inline(always)]
+    fn into_datum(self) -> Datum {
+        self.to_datum()
+    }
+}
+
+impl<T: IntoDatum> IntoDatum for Vec<T> {
+    fn into_datum(self) -> Datum {
+        self.into_iter()
+            .map(IntoDatum::into_datum)
+            .collect::<SList>()
+            .into()
+    }
+}
+
+impl<T: IntoDatum> IntoDatum for Option<T> {
+    fn into_datum(self) -> Datum {
+        match self {
+            Some(value) => value.into_datum(),
+            None => SList::empty().into(),
+        }
+    }
+}
+
+impl<T: IntoDatum> IntoDatum for Box<T> {
+    #[inline(always)]
+    fn into_datum(self) -> Datum {
+        (*self).into_datum()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+#[inline(always)]
+fn atom(datum: &SyntaxDatum) -> Option<&Datum> {
+    match datum.value() {
+        SyntaxValue::Atom(atom) => Some(atom),
+        _ => None,
+    }
+}
+
+///
+/// The name of the symbol `datum`, without any `|` around it.
+///
+fn symbol_name(datum: &SyntaxDatum) -> Option<&str> {
+    match atom(datum) {
+        Some(Datum::Identifier(name)) => {
+            let name = name.as_str();
+            Some(
+                name.strip_prefix(IDENTIFIER_WRAPPER)
+                    .and_then(|name| name.strip_suffix(IDENTIFIER_WRAPPER))
+                    .unwrap_or(name),
+            )
+        }
+        _ => None,
+    }
+}
+
+fn expecting<T, S>(datum: &SyntaxDatum, type_name: &'static str, expected: S) -> Result<T, Error>
+where
+    S: std::fmt::Display,
+{
+    let found = match datum.value() {
+        SyntaxValue::Quote(_) => "quote",
+        SyntaxValue::QuasiQuote(_) => "quasiquote",
+        SyntaxValue::Unquote(_) => "unquote",
+        SyntaxValue::UnquoteSplicing(_) => "unquote-splicing",
+        SyntaxValue::Atom(atom) => atom.type_string(),
+        SyntaxValue::List(elements, None) if elements.is_empty() => "empty list",
+        SyntaxValue::List(_, _) => "list",
+        SyntaxValue::Vector(_) => "vector",
+        SyntaxValue::ByteVector(_) => "byte-vector",
+    };
+    datum_conversion(
+        datum.span(),
+        type_name,
+        format!("expecting {expected}, found `{found}`"),
+    )
+}
+
+///
+/// Name `field` of `type_name` in a conversion error that does not already name a field.
+///
+fn field<T>(
+    result: Result<T, Error>,
+    type_name: &'static str,
+    field: &'static str,
+) -> Result<T, Error> {
+    result.map_err(|mut e| {
+        if let Error::DatumConversion {
+            conversion,
+            span: _,
+        } = &mut e
+        {
+            conversion.in_field(type_name, field);
+        }
+        e
+    })
+}
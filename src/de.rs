@@ -0,0 +1,774 @@
+/*!
+Provides a serde [`Deserializer`] that reads Rust values from S-expressions, enabled by the
+`serde` feature.
+
+This reverses the conversion made by [`crate::ser`], with some leniency for hand-written
+text:
+
+* a struct, or map, may be an association list, `((name . value) ...)`, or a keyword list,
+  `(name: value ...)`; a missing `Option` field is `None`,
+* the value in an association list entry is the rest of the entry, `(name . 1)` or
+  `(name 1 2)`, and a single value may also be written as `(name 1)`,
+* a sequence may be a list or a vector, and bytes may also be a byte vector,
+* a string may also be written as a symbol,
+* an enum is a symbol for a unit variant, or a list tagged with the variant name,
+* a quoted datum, `'(...)`, is read as the datum quoted.
+
+Reading with [`from_str`] records the span of each datum, and so the [`Error`] returned
+for a datum that cannot be converted has the span of that datum, see [`Error::span`].
+
+# Example
+
+```rust
+use ffsr::de::from_str;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq)]
+enum Mode {
+    Fast,
+    Limit(u32),
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Server {
+    host: String,
+    ports: Vec<u16>,
+    mode: Mode,
+}
+
+let server: Server = from_str("((host . \"localhost\") (ports 80 443) (mode Limit 10))").unwrap();
+assert_eq!(
+    server,
+    Server { host: "localhost".into(), ports: vec![80, 443], mode: Mode::Limit(10) }
+);
+
+let error = from_str::<Server>("(host: \"localhost\" ports: (80 -1) mode: Fast)").unwrap_err();
+assert_eq!(error.span().unwrap().as_range(), 30..32);
+```
+
+*/
+
+use crate::error::{custom_error, Error};
+use crate::lexer::token::Span;
+use crate::lexer::Lexer;
+use crate::reader::datum::numbers::Number;
+use crate::reader::datum::{Datum, SList, SNumber};
+use crate::reader::syntax::{SyntaxDatum, SyntaxValue};
+use crate::reader::Reader;
+use crate::syntax::IDENTIFIER_WRAPPER;
+use serde::de::{self, DeserializeOwned, DeserializeSeed, Expected, Unexpected, Visitor};
+use std::fmt::Display;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A serde deserializer for a single datum.
+///
+#[derive(Clone, Debug)]
+pub struct Deserializer<'a> {
+    value: Value<'a>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A datum, from either a [`SyntaxDatum`] or a [`Datum`], with its span if known.
+///
+#[derive(Clone, Debug)]
+struct Value<'a> {
+    span: Option<Span>,
+    kind: Kind<'a>,
+    /// This is the rest of an association list entry, or a tagged enum.
+    rest: bool,
+}
+
+#[derive(Clone, Debug)]
+enum Kind<'a> {
+    Boolean(bool),
+    Char(char),
+    Number(&'a SNumber),
+    String(&'a str),
+    Symbol(&'a str),
+    List(Vec<Value<'a>>, Option<Box<Value<'a>>>),
+    Vector(Vec<Value<'a>>),
+    Bytes(Vec<u8>),
+    Unsupported(&'static str),
+}
+
+struct SeqAccess<'a> {
+    iter: std::vec::IntoIter<Value<'a>>,
+}
+
+struct MapAccess<'a> {
+    iter: std::vec::IntoIter<(Value<'a>, Value<'a>)>,
+    value: Option<Value<'a>>,
+}
+
+struct EnumAccess<'a> {
+    variant: Value<'a>,
+    content: Option<Value<'a>>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Read the single datum in `s` as a `T`; it is an error if `s` contains any other datum.
+///
+pub fn from_str<T>(s: &str) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    let reader = Reader::from(Lexer::from(s));
+    let mut iter = reader.syntax_iter();
+    let datum = match iter.next() {
+        Some(datum) => datum?,
+        None => return custom_error(None, "expecting a datum, found none"),
+    };
+    if let Some(next) = iter.next() {
+        return custom_error(Some(next?.span()), "expecting a single datum");
+    }
+    from_syntax(&datum)
+}
+
+///
+/// Convert `datum`, and its spans, into a `T`.
+///
+pub fn from_syntax<T>(datum: &SyntaxDatum) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    T::deserialize(Deserializer::from(datum))
+}
+
+///
+/// Convert `datum` into a `T`; any error has no span.
+///
+pub fn from_datum<T>(datum: &Datum) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    T::deserialize(Deserializer::from(datum))
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Macros
+// ------------------------------------------------------------------------------------------------
+
+macro_rules! deserialize_single {
+    ($($method:ident),*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Error>
+            where
+                V: Visitor<'de>,
+            {
+                Deserializer { value: self.value.single() }.deserialize_any(visitor)
+            }
+        )*
+    };
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        custom_error::<(), _>(None, msg).unwrap_err()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<'a> From<&'a SyntaxDatum> for Deserializer<'a> {
+    fn from(datum: &'a SyntaxDatum) -> Self {
+        Self {
+            value: Value::from(datum),
+        }
+    }
+}
+
+impl<'a> From<&'a Datum> for Deserializer<'a> {
+    fn from(datum: &'a Datum) -> Self {
+        Self {
+            value: Value::from(datum),
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        let span = self.value.span;
+        let result = match self.value.kind {
+            Kind::Boolean(v) => visitor.visit_bool(v),
+            Kind::Char(v) => visitor.visit_char(v),
+            Kind::Number(SNumber::Fixnum(v)) => visitor.visit_i64(*v.value()),
+            Kind::Number(SNumber::Flonum(v)) => visitor.visit_f64(*v.value()),
+            Kind::Number(v) => {
+                return custom_error(span, format!("a {} is not supported", v.type_string()))
+            }
+            Kind::String(v) | Kind::Symbol(v) => visitor.visit_str(v),
+            Kind::List(items, None) | Kind::Vector(items) => visit_seq(items, visitor),
+            Kind::List(_, Some(tail)) => {
+                return custom_error(tail.span.or(span), "an improper list is not supported")
+            }
+            Kind::Bytes(v) => visitor.visit_bytes(&v),
+            Kind::Unsupported(name) => {
+                return custom_error(span, format!("a {name} is not supported"))
+            }
+        };
+        result.map_err(|e| located(e, span))
+    }
+
+    deserialize_single!(
+        deserialize_bool,
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64,
+        deserialize_f32,
+        deserialize_f64,
+        deserialize_char,
+        deserialize_str,
+        deserialize_string,
+        deserialize_bytes,
+        deserialize_byte_buf,
+        deserialize_identifier
+    );
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        let span = self.value.span;
+        let result = if self.value.is_empty_list() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        };
+        result.map_err(|e| located(e, span))
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.value.is_empty_list() {
+            visitor
+                .visit_unit()
+                .map_err(|e| located(e, self.value.span))
+        } else {
+            Err(self.value.invalid_type(&visitor))
+        }
+    }
+
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        let span = self.value.span;
+        visitor
+            .visit_newtype_struct(self)
+            .map_err(|e| located(e, span))
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        let span = self.value.span;
+        match self.value.kind {
+            Kind::List(items, None) | Kind::Vector(items) => {
+                visit_seq(items, visitor).map_err(|e| located(e, span))
+            }
+            Kind::Bytes(bytes) => {
+                let mut seq = de::value::SeqDeserializer::<_, Error>::new(bytes.into_iter());
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            _ => Err(self.value.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        let span = self.value.span;
+        let entries = match self.value.entries() {
+            Some(entries) => entries?,
+            None => return Err(self.value.invalid_type(&visitor)),
+        };
+        let mut map = MapAccess {
+            iter: entries.into_iter(),
+            value: None,
+        };
+        let value = visitor.visit_map(&mut map).map_err(|e| located(e, span))?;
+        match map.iter.next() {
+            Some((key, _)) => custom_error(key.span.or(span), "unexpected entry in map"),
+            None => Ok(value),
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        let value = self.value.single();
+        let span = value.span;
+        let access = match value.kind {
+            Kind::Symbol(_) | Kind::String(_) => EnumAccess {
+                variant: value,
+                content: None,
+            },
+            Kind::List(mut items, tail)
+                if matches!(
+                    items.first(),
+                    Some(Value {
+                        kind: Kind::Symbol(_),
+                        ..
+                    })
+                ) =>
+            {
+                let variant = items.remove(0);
+                EnumAccess {
+                    variant,
+                    content: Some(Value::rest(items, tail, span)),
+                }
+            }
+            _ => return Err(value.invalid_type(&visitor)),
+        };
+        visitor.visit_enum(access).map_err(|e| located(e, span))
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<'a> From<&'a SyntaxDatum> for Value<'a> {
+    fn from(datum: &'a SyntaxDatum) -> Self {
+        let kind = match datum.value() {
+            SyntaxValue::Quote(quoted) => return Self::from(quoted.as_ref()),
+            SyntaxValue::QuasiQuote(_) => Kind::Unsupported("quasi-quote"),
+            SyntaxValue::Unquote(_) | SyntaxValue::UnquoteSplicing(_) => {
+                Kind::Unsupported("unquote")
+            }
+            SyntaxValue::Atom(atom) => return Self::from(atom).with_span(datum.span()),
+            SyntaxValue::List(items, tail) => Kind::List(
+                items.iter().map(Self::from).collect(),
+                tail.as_deref().map(|tail| Box::new(Self::from(tail))),
+            ),
+            SyntaxValue::Vector(items) => Kind::Vector(items.iter().map(Self::from).collect()),
+            SyntaxValue::ByteVector(items) => {
+                match items.iter().map(|item| byte(&item.to_datum())).collect() {
+                    Some(bytes) => Kind::Bytes(bytes),
+                    None => Kind::Unsupported("byte vector value"),
+                }
+            }
+        };
+        Self {
+            span: Some(datum.span()),
+            kind,
+            rest: false,
+        }
+    }
+}
+
+impl<'a> From<&'a Datum> for Value<'a> {
+    fn from(datum: &'a Datum) -> Self {
+        let kind = match datum {
+            Datum::Quote(quoted) => return Self::from(quoted.as_ref()),
+            Datum::QuasiQuote(_) => Kind::Unsupported("quasi-quote"),
+            Datum::Unquote(_) | Datum::UnquoteSplicing(_) => Kind::Unsupported("unquote"),
+            Datum::Identifier(v) => Kind::Symbol(symbol_name(v.as_str())),
+            Datum::Boolean(v) => Kind::Boolean(bool::from(*v)),
+            Datum::Char(v) => Kind::Char(char::from(*v)),
+            Datum::Number(v) => Kind::Number(v),
+            Datum::String(v) => Kind::String(v.as_str()),
+            Datum::List(list) => list_kind(list),
+            Datum::Vector(items) => Kind::Vector(items.iter().map(Self::from).collect()),
+            Datum::ByteVector(bytes) => {
+                Kind::Bytes(bytes.iter().map(|fixnum| *fixnum.value() as u8).collect())
+            }
+            Datum::Comment(_) => Kind::Unsupported("comment"),
+            Datum::Directive(_) => Kind::Unsupported("directive"),
+            Datum::Shared(_) => Kind::Unsupported("shared datum"),
+            Datum::Error(_) => Kind::Unsupported("datum with errors"),
+        };
+        Self {
+            span: None,
+            kind,
+            rest: false,
+        }
+    }
+}
+
+impl Value<'_> {
+    ///
+    /// The rest of a list, after its first element, with the span of the whole list; the rest
+    /// of `(a . b)` is `b`.
+    ///
+    fn rest(items: Vec<Self>, tail: Option<Box<Self>>, span: Option<Span>) -> Self {
+        match tail {
+            Some(tail) if items.is_empty() => *tail,
+            tail => Self {
+                span,
+                kind: Kind::List(items, tail),
+                rest: true,
+            },
+        }
+    }
+
+    #[inline(always)]
+    fn with_span(self, span: Span) -> Self {
+        Self {
+            span: Some(span),
+            ..self
+        }
+    }
+
+    ///
+    /// The single value in the rest of a list, if that is all there is, otherwise this value.
+    ///
+    fn single(self) -> Self {
+        match self.kind {
+            Kind::List(mut items, None) if self.rest && items.len() == 1 => items.remove(0),
+            kind => Self { kind, ..self },
+        }
+    }
+
+    fn is_empty_list(&self) -> bool {
+        matches!(&self.kind, Kind::List(items, None) if items.is_empty())
+    }
+
+    ///
+    /// The entries of an association list, or keyword list, or `None` if this is not a list.
+    ///
+    fn entries(&self) -> Option<Result<Vec<(Self, Self)>, Error>> {
+        let items = match &self.kind {
+            Kind::List(items, None) => items,
+            _ => return None,
+        };
+        if let Some(Kind::Symbol(name)) = items.first().map(|item| &item.kind) {
+            if keyword_name(name).is_some() {
+                return Some(keyword_entries(items, self.span));
+            }
+        }
+        Some(
+            items
+                .iter()
+                .map(|item| match &item.kind {
+                    Kind::List(entry, tail) if !entry.is_empty() => Ok((
+                        entry[0].clone(),
+                        Self::rest(entry[1..].to_vec(), tail.clone(), item.span),
+                    )),
+                    _ => custom_error(item.span, "expecting an association list entry"),
+                })
+                .collect(),
+        )
+    }
+
+    fn unexpected(&self) -> Unexpected<'_> {
+        match &self.kind {
+            Kind::Boolean(v) => Unexpected::Bool(*v),
+            Kind::Char(v) => Unexpected::Char(*v),
+            Kind::Number(SNumber::Fixnum(v)) => Unexpected::Signed(*v.value()),
+            Kind::Number(SNumber::Flonum(v)) => Unexpected::Float(*v.value()),
+            Kind::Number(_) => Unexpected::Other("number"),
+            Kind::String(v) => Unexpected::Str(v),
+            Kind::Symbol(_) => Unexpected::Other("symbol"),
+            Kind::List(items, None) if items.is_empty() => Unexpected::Unit,
+            Kind::List(_, None) => Unexpected::Seq,
+            Kind::List(_, Some(_)) => Unexpected::Other("improper list"),
+            Kind::Vector(_) => Unexpected::Seq,
+            Kind::Bytes(v) => Unexpected::Bytes(v),
+            Kind::Unsupported(name) => Unexpected::Other(name),
+        }
+    }
+
+    fn invalid_type(&self, expected: &dyn Expected) -> Error {
+        located(
+            <Error as de::Error>::invalid_type(self.unexpected(), expected),
+            self.span,
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<'de> de::SeqAccess<'de> for SeqAccess<'_> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.iter
+            .next()
+            .map(|value| seed.deserialize(Deserializer { value }))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+impl<'de> de::MapAccess<'de> for MapAccess<'_> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(Deserializer { value: key }).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        match self.value.take() {
+            Some(value) => seed.deserialize(Deserializer { value }),
+            None => custom_error(None, "map value requested before its key"),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+impl<'de> de::EnumAccess<'de> for EnumAccess<'_> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self), Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(Deserializer {
+            value: self.variant.clone(),
+        })?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for EnumAccess<'_> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self.content {
+            Some(content) if !content.is_empty_list() => custom_error(
+                content.span,
+                "expecting a unit variant, found a variant with values",
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.content {
+            Some(content) => seed.deserialize(Deserializer {
+                value: content.single(),
+            }),
+            None => custom_error(self.variant.span, "expecting a variant with a value"),
+        }
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.content {
+            Some(content) => {
+                de::Deserializer::deserialize_seq(Deserializer { value: content }, visitor)
+            }
+            None => custom_error(self.variant.span, "expecting a variant with values"),
+        }
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.content {
+            Some(content) => {
+                de::Deserializer::deserialize_map(Deserializer { value: content }, visitor)
+            }
+            None => custom_error(self.variant.span, "expecting a variant with fields"),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Give `error` the span `span` if it has none.
+///
+fn located(error: Error, span: Option<Span>) -> Error {
+    match error {
+        Error::Custom {
+            message,
+            span: None,
+        } => Error::Custom { message, span },
+        error => error,
+    }
+}
+
+fn visit_seq<'de, V>(items: Vec<Value<'_>>, visitor: V) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+{
+    let mut seq = SeqAccess {
+        iter: items.into_iter(),
+    };
+    let value = visitor.visit_seq(&mut seq)?;
+    match seq.iter.next() {
+        Some(extra) => custom_error(extra.span, "unexpected element in sequence"),
+        None => Ok(value),
+    }
+}
+
+fn list_kind(list: &SList) -> Kind<'_> {
+    let mut items = Vec::new();
+    let mut list = list;
+    while let Some(pair) = list.as_pair() {
+        items.push(Value::from(pair.car().as_ref()));
+        match pair.cdr().as_ref() {
+            Datum::List(next) => list = next,
+            tail => return Kind::List(items, Some(Box::new(Value::from(tail)))),
+        }
+    }
+    Kind::List(items, None)
+}
+
+fn keyword_entries<'a>(
+    items: &[Value<'a>],
+    span: Option<Span>,
+) -> Result<Vec<(Value<'a>, Value<'a>)>, Error> {
+    let mut entries = Vec::with_capacity(items.len() / 2);
+    let mut items = items.iter();
+    while let Some(key) = items.next() {
+        let name = match &key.kind {
+            Kind::Symbol(name) => keyword_name(name),
+            _ => None,
+        };
+        let name = match name {
+            Some(name) => name,
+            None => return custom_error(key.span, "expecting a keyword, such as `name:`"),
+        };
+        let value = match items.next() {
+            Some(value) => value.clone(),
+            None => return custom_error(key.span.or(span), "expecting a value after the keyword"),
+        };
+        entries.push((
+            Value {
+                kind: Kind::Symbol(name),
+                ..key.clone()
+            },
+            value,
+        ));
+    }
+    Ok(entries)
+}
+
+fn keyword_name(name: &str) -> Option<&str> {
+    name.strip_suffix(':').filter(|name| !name.is_empty())
+}
+
+fn symbol_name(name: &str) -> &str {
+    name.strip_prefix(IDENTIFIER_WRAPPER)
+        .and_then(|name| name.strip_suffix(IDENTIFIER_WRAPPER))
+        .unwrap_or(name)
+}
+
+fn byte(datum: &Datum) -> Option<u8> {
+    match datum {
+        Datum::Number(SNumber::Fixnum(v)) => u8::try_from(*v.value()).ok(),
+        _ => None,
+    }
+}
@@ -4,9 +4,14 @@ functions.
 
  */
 
+use crate::input::lines::{LineIndex, Position};
 use crate::lexer::token::{Span, TokenKind};
+use crate::source_map::{FileId, SourceMap};
+use crate::Sourced;
 use ariadne::{Color, Fmt, Label, Report, ReportKind, Source};
 use std::fmt::{Debug, Display};
+use std::hash::Hash;
+use std::ops::Range;
 use tracing::error;
 
 // ------------------------------------------------------------------------------------------------
@@ -28,6 +33,11 @@ pub enum Error {
     IncompleteSpecial {
         span: Span,
     },
+    /// A character that cannot start, or continue, any token.
+    UnexpectedCharacter {
+        span: Span,
+        character: char,
+    },
     // --------------------------------------------------------------
     // Directives
     // --------------------------------------------------------------
@@ -182,6 +192,9 @@ pub enum Error {
     IncompleteDatumComment {
         span: Span,
     },
+    InvalidCommentInput {
+        span: Span,
+    },
     // --------------------------------------------------------------
     // Unexpected
     // --------------------------------------------------------------
@@ -190,6 +203,52 @@ pub enum Error {
         token: TokenKind,
         within: Option<Span>,
     },
+    // --------------------------------------------------------------
+    // Custom
+    // --------------------------------------------------------------
+    /// An error with a message, such as one raised by a serde `Serialize` or `Deserialize`
+    /// implementation, with the span of the datum it refers to, if known.
+    Custom {
+        message: String,
+        span: Option<Span>,
+    },
+    // --------------------------------------------------------------
+    // Conversion
+    // --------------------------------------------------------------
+    /// A datum could not be converted into a Rust type, see
+    /// [`FromDatum`](crate::convert::FromDatum).
+    DatumConversion {
+        conversion: Box<Conversion>,
+        span: Span,
+    },
+    // --------------------------------------------------------------
+    // Patterns
+    // --------------------------------------------------------------
+    /// A datum could not be compiled as a pattern, see [`Pattern`](crate::pattern::Pattern),
+    /// with the span of the pattern, if known.
+    InvalidPattern {
+        message: String,
+        span: Option<Span>,
+    },
+    // --------------------------------------------------------------
+    // Queries
+    // --------------------------------------------------------------
+    /// The text of a [`Query`](crate::query::Query) could not be parsed; the span is of the
+    /// characters in that text.
+    InvalidQuery {
+        message: String,
+        span: Span,
+    },
+}
+
+///
+/// The Rust type, and field of that type, that a datum could not be converted into.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Conversion {
+    type_name: &'static str,
+    field: Option<&'static str>,
+    message: String,
 }
 
 ///
@@ -217,6 +276,12 @@ pub fn incomplete_special<T>(span: Span) -> Result<T> {
     Err(Error::IncompleteSpecial { span })
 }
 
+/// Construct an `UnexpectedCharacter` Error with the provided span and character.
+#[inline]
+pub fn unexpected_character<T>(span: Span, character: char) -> Result<T> {
+    Err(Error::UnexpectedCharacter { span, character })
+}
+
 // --------------------------------------------------------------
 // Directives
 // --------------------------------------------------------------
@@ -538,6 +603,12 @@ pub fn incomplete_datum_comment<T>(span: Span) -> Result<T> {
     Err(Error::IncompleteDatumComment { span })
 }
 
+/// Construct an `InvalidCommentInput` Error with the provided span.
+#[inline]
+pub fn invalid_comment_input<T>(span: Span) -> Result<T> {
+    Err(Error::InvalidCommentInput { span })
+}
+
 // --------------------------------------------------------------
 // Unexpected
 // --------------------------------------------------------------
@@ -562,6 +633,95 @@ pub fn unexpected_token_within<T>(span: Span, token: TokenKind, within: Span) ->
     })
 }
 
+// --------------------------------------------------------------
+// Custom
+// --------------------------------------------------------------
+
+/// Construct a `Custom` Error with the provided message and span.
+#[inline]
+pub fn custom_error<T, S>(span: Option<Span>, message: S) -> Result<T>
+where
+    S: Display,
+{
+    Err(Error::Custom {
+        message: message.to_string(),
+        span,
+    })
+}
+
+// --------------------------------------------------------------
+// Conversion
+// --------------------------------------------------------------
+
+/// Construct a `DatumConversion` Error, with no field, with the provided message and span.
+#[inline]
+pub fn datum_conversion<T, S>(span: Span, type_name: &'static str, message: S) -> Result<T>
+where
+    S: Display,
+{
+    Err(Error::DatumConversion {
+        conversion: Box::new(Conversion {
+            type_name,
+            field: None,
+            message: message.to_string(),
+        }),
+        span,
+    })
+}
+
+/// Construct a `DatumConversion` Error for the named field with the provided message and span.
+#[inline]
+pub fn field_conversion<T, S>(
+    span: Span,
+    type_name: &'static str,
+    field: &'static str,
+    message: S,
+) -> Result<T>
+where
+    S: Display,
+{
+    Err(Error::DatumConversion {
+        conversion: Box::new(Conversion {
+            type_name,
+            field: Some(field),
+            message: message.to_string(),
+        }),
+        span,
+    })
+}
+
+// --------------------------------------------------------------
+// Patterns
+// --------------------------------------------------------------
+
+/// Construct an `InvalidPattern` Error with the provided message and span.
+#[inline]
+pub fn invalid_pattern<T, S>(span: Option<Span>, message: S) -> Result<T>
+where
+    S: Display,
+{
+    Err(Error::InvalidPattern {
+        message: message.to_string(),
+        span,
+    })
+}
+
+// --------------------------------------------------------------
+// Queries
+// --------------------------------------------------------------
+
+/// Construct an `InvalidQuery` Error with the provided message and span.
+#[inline]
+pub fn invalid_query<T, S>(span: Span, message: S) -> Result<T>
+where
+    S: Display,
+{
+    Err(Error::InvalidQuery {
+        message: message.to_string(),
+        span,
+    })
+}
+
 // ------------------------------------------------------------------------------------------------
 // Implementations
 // ------------------------------------------------------------------------------------------------
@@ -576,6 +736,9 @@ impl Display for Error {
                     // --------------------------------------------------------------
                     Self::IncompleteSpecial { span } =>
                         format!("Incomplete special form, span: {span}"),
+                    Self::UnexpectedCharacter { span, character } => format!(
+                        "The character {character:?} was not expected; span: {span}"
+                    ),
                     Self::InvalidDirectiveInput { span } => format!(
                         "Invalid, or badly formed, directive input; span: {span}"
                     ),
@@ -704,6 +867,9 @@ impl Display for Error {
                     Self::IncompleteDatumComment { span } => format!(
                         "Datum comment symbol not followed by an actual datum; span: {span}"
                     ),
+                    Self::InvalidCommentInput { span } => format!(
+                        "Invalid, or badly formed, comment input; span: {span}"
+                    ),
                     // --------------------------------------------------------------
                     Self::UnexpectedToken {
                         token,
@@ -717,11 +883,69 @@ impl Display for Error {
                             "".into()
                         }
                     ),
+                    // --------------------------------------------------------------
+                    Self::Custom { message, span } => match span {
+                        Some(span) => format!("{message}; span: {span}"),
+                        None => message.clone(),
+                    },
+                    // --------------------------------------------------------------
+                    Self::DatumConversion { conversion, span } => format!(
+                        "Could not convert datum to {conversion}: {}; span: {span}",
+                        conversion.message
+                    ),
+                    // --------------------------------------------------------------
+                    Self::InvalidPattern { message, span } => match span {
+                        Some(span) => format!("Invalid pattern, {message}; span: {span}"),
+                        None => format!("Invalid pattern, {message}"),
+                    },
+                    // --------------------------------------------------------------
+                    Self::InvalidQuery { message, span } => {
+                        format!("Invalid query, {message}; span: {span}")
+                    }
                 }
             )
     }
 }
 
+impl Display for Conversion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.field {
+            Some(field) => write!(f, "`{}`, field `{}`", self.type_name, field),
+            None => write!(f, "`{}`", self.type_name),
+        }
+    }
+}
+
+impl Conversion {
+    #[inline(always)]
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    #[inline(always)]
+    pub fn field(&self) -> Option<&'static str> {
+        self.field
+    }
+
+    #[inline(always)]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    ///
+    /// Name `field` of `type_name` as the target of this conversion, unless a field is
+    /// already named.
+    ///
+    pub(crate) fn in_field(&mut self, type_name: &'static str, field: &'static str) {
+        if self.field.is_none() {
+            self.type_name = type_name;
+            self.field = Some(field);
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
@@ -754,6 +978,10 @@ impl Error {
             Self::DuplicateDatumLabel { label: _, span: _ } => 14,
             Self::UnknownDatumLabel { label: _, span: _ } => 15,
             Self::IncompleteDatumAssignment { span: _, label: _ } => 16,
+            Self::UnexpectedCharacter {
+                span: _,
+                character: _,
+            } => 17,
             // --------------------------------------------------------------
             Self::IncompleteIdentifier { span: _ } => 20,
             Self::InvalidIdentifierMnemonicEscape { span: _ } => 21,
@@ -792,15 +1020,171 @@ impl Error {
             // --------------------------------------------------------------
             Self::IncompleteBlockComment { span: _ } => 100,
             Self::IncompleteDatumComment { span: _ } => 101,
+            Self::InvalidCommentInput { span: _ } => 102,
             // --------------------------------------------------------------
             Self::UnexpectedToken {
                 token: _,
                 span: _,
                 within: _,
             } => 110,
+            // --------------------------------------------------------------
+            Self::Custom {
+                message: _,
+                span: _,
+            } => 120,
+            // --------------------------------------------------------------
+            Self::DatumConversion {
+                conversion: _,
+                span: _,
+            } => 130,
+            // --------------------------------------------------------------
+            Self::InvalidPattern {
+                message: _,
+                span: _,
+            } => 140,
+            // --------------------------------------------------------------
+            Self::InvalidQuery {
+                message: _,
+                span: _,
+            } => 150,
+        }
+    }
+    ///
+    /// The character span in the source that this error refers to, if any.
+    ///
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Self::IoError { source: _ } => None,
+            Self::IncompleteSpecial { span }
+            | Self::UnexpectedCharacter { span, character: _ }
+            | Self::InvalidDirectiveInput { span }
+            | Self::UnknownDirectiveName { span, name: _ }
+            | Self::InvalidDatumLabel { span }
+            | Self::DuplicateDatumLabel { span, label: _ }
+            | Self::UnknownDatumLabel { span, label: _ }
+            | Self::IncompleteDatumAssignment { span, label: _ }
+            | Self::IncompleteIdentifier { span }
+            | Self::InvalidIdentifierMnemonicEscape { span }
+            | Self::InvalidIdentifierHexEscape { span }
+            | Self::InvalidIdentifierInput { span, source: _ }
+            | Self::InvalidBooleanInput { span }
+            | Self::UnknownCharName { span, name: _ }
+            | Self::InvalidUnicodeValue { span }
+            | Self::InvalidCharInput { span, source: _ }
+            | Self::IncompleteString { span }
+            | Self::InvalidStringMnemonicEscape { span }
+            | Self::InvalidStringHexEscape { span }
+            | Self::InvalidStringInput { span, source: _ }
+            | Self::InvalidNumericInput { span, source: _ }
+            | Self::IncompleteList { span }
+            | Self::IncompletePair { span }
+            | Self::PairMissingCar { span }
+            | Self::PairMissingCdr { span }
+            | Self::PairAdditionalCdr { span }
+            | Self::InvalidPairInput { span, source: _ }
+            | Self::CannotAppendToImproperPair { span }
+            | Self::IncompleteVector { span }
+            | Self::InvalidByteVectorPrefix { span }
+            | Self::IncompleteByteVector { span }
+            | Self::InvalidByteInput { span }
+            | Self::IncompleteQuote { span }
+            | Self::IncompleteQuasiQuote { span }
+            | Self::IncompleteUnquote { span }
+            | Self::IncompleteUnquoteSplicing { span }
+            | Self::IncompleteBlockComment { span }
+            | Self::IncompleteDatumComment { span }
+            | Self::InvalidCommentInput { span }
+            | Self::UnexpectedToken {
+                span,
+                token: _,
+                within: _,
+            } => Some(*span),
+            Self::Custom { message: _, span } => *span,
+            Self::DatumConversion {
+                conversion: _,
+                span,
+            } => Some(*span),
+            Self::InvalidPattern { message: _, span } => *span,
+            Self::InvalidQuery { message: _, span } => Some(*span),
         }
     }
+
+    ///
+    /// Returns this error with the file, from a [`SourceMap`](crate::source_map::SourceMap),
+    /// recorded in its spans, including those of any nested error.
+    ///
+    pub fn with_file(self, file: FileId) -> Self {
+        self.map_spans(|span| span.with_file(file))
+    }
+
+    ///
+    /// Returns this error with `f` applied to each of its spans, including those of any
+    /// nested error.
+    ///
+    pub(crate) fn map_spans<F>(mut self, f: F) -> Self
+    where
+        F: Fn(Span) -> Span + Copy,
+    {
+        if let Some(span) = self.span_mut() {
+            *span = f(*span);
+        }
+        if let Self::UnexpectedToken {
+            span: _,
+            token: _,
+            within: Some(within),
+        } = &mut self
+        {
+            *within = f(*within);
+        }
+        if let Some(source) = self.self_source_mut() {
+            if let Some(inner) = source.take() {
+                *source = Some(Box::new(inner.map_spans(f)));
+            }
+        }
+        self
+    }
+
+    ///
+    /// The line and column, from `lines`, of the start of this error's span.
+    ///
+    pub fn start_position(&self, lines: &LineIndex) -> Option<Position> {
+        self.span().map(|span| lines.position(span.start()))
+    }
+
+    ///
+    /// The line and column, from `lines`, of the end of this error's span.
+    ///
+    pub fn end_position(&self, lines: &LineIndex) -> Option<Position> {
+        self.span().map(|span| lines.position(span.end()))
+    }
+
     pub fn report(&self) -> Option<Report> {
+        self.build_report((), |range| range)
+    }
+
+    ///
+    /// Build a report whose labels refer to the source identified by `id`, the report's
+    /// cache must then provide the text for `id`.
+    ///
+    pub fn report_for<Id>(&self, id: Id) -> Option<Report<(Id, Range<usize>)>>
+    where
+        Id: Debug + Hash + Eq + Clone,
+    {
+        self.build_report(id.clone(), |range| (id.clone(), range))
+    }
+
+    ///
+    /// Build a report for the source `id`, with each label's span made by `at` from its range.
+    ///
+    fn build_report<S>(
+        &self,
+        id: <S::SourceId as ToOwned>::Owned,
+        at: impl Fn(Range<usize>) -> S,
+    ) -> Option<Report<S>>
+    where
+        S: ariadne::Span,
+        <S::SourceId as ToOwned>::Owned: Clone,
+    {
         const SYNTAX: Color = Color::Magenta;
         const TYPES: Color = Color::Blue;
         const FUNCTIONS: Color = Color::Cyan;
@@ -809,33 +1193,43 @@ impl Error {
         match self {
             // --------------------------------------------------------------
             Self::IncompleteSpecial { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Incomplete special form")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("This is not a valid/complete special form"),
                     )
                     .with_note("Expecting a directive, boolean, character, numeric prefix, vector, or block/datum comment")
                     .finish(),
             ),
+            Self::UnexpectedCharacter { span, character } => Some(
+                Report::build(ReportKind::Error, id.clone(), span.start())
+                    .with_code(self.code())
+                    .with_message(format!("The character {character:?} was not expected"))
+                    .with_label(
+                        Label::new(at(span.as_range()))
+                            .with_message("This text is not a valid token"),
+                    )
+                    .finish(),
+            ),
             // --------------------------------------------------------------
             Self::InvalidDirectiveInput { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Invalid, or badly formed, directive input")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("Not a valid directive"),
                     )
                     .finish(),
             ),
             Self::UnknownDirectiveName { span, name } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Unknown directive name")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message(
                                 format!("{} is not a directive name", name.fg(SYNTAX))),
                     )
@@ -843,11 +1237,11 @@ impl Error {
             ),
             // --------------------------------------------------------------
             Self::InvalidDatumLabel { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Invalid, or badly formed, datum label")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("This does not work"),
                     )
                     .with_note(
@@ -859,33 +1253,33 @@ impl Error {
                     .finish(),
             ),
             Self::DuplicateDatumLabel { span, label } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Duplicate datum label assignment")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message(
                                 format!("The label {} has already been defined", label.to_string().fg(VALUES))),
                     )
                    .finish(),
             ),
             Self::UnknownDatumLabel { span, label } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Unknown datum label")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message(
                             format!("The label `{}` has not been defined", label.to_string().fg(VALUES))),
                     )
                    .finish(),
             ),
             Self::IncompleteDatumAssignment { span, label } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Incomplete datum assignment")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message(
                             format!(
                                     "The datum assignment {} was not followed by a datum",
@@ -893,7 +1287,7 @@ impl Error {
                                 )),
                     )
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message(
                             format!("The label `{}` is therefore not defined", label)),
                     )
@@ -901,21 +1295,21 @@ impl Error {
             ),
             // --------------------------------------------------------------
             Self::IncompleteIdentifier { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Incomplete identifier")
                     .with_label(
-                        Label::new(span.as_start_range())
+                        Label::new(at(span.as_start_range()))
                             .with_message(format!("Starts with {} here", "#\\|".fg(SYNTAX))),
                     )
                     .finish()
             ),
             Self::InvalidIdentifierMnemonicEscape { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Invalid, or badly formed, mnemonic escape in identifier")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("Not a valid escape sequence"),
                     )
                     .with_note(
@@ -926,11 +1320,11 @@ impl Error {
                     .finish()
             ),
             Self::InvalidIdentifierHexEscape { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Invalid, or badly formed, hex escape in identifier")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("Not a valid escape sequence"),
                     )
                     .with_note(
@@ -941,27 +1335,27 @@ impl Error {
                     .finish()
             ),
             Self::InvalidIdentifierInput { span, source: _ } => Some(if span.is_empty() {
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Invalid identifier")
                     .finish()
                 } else {
-                    Report::build(ReportKind::Error, (), span.start())
+                    Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Invalid, or badly formed, identifier input")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("Not a valid identifier"),
                     )
                     .finish()
                 }),
             // --------------------------------------------------------------
             Self::InvalidBooleanInput { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Invalid, or badly formed, boolean input")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("Not a valid boolean"),
                     )
                     .with_note(
@@ -974,21 +1368,21 @@ impl Error {
             ),
             // --------------------------------------------------------------
             Self::UnknownCharName { span, name } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message(format!("Unknown character name {:?}", name))
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("Character referenced here"),
                     )
                     .finish(),
             ),
             Self::InvalidUnicodeValue { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Invalid Unicode codepoint")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("Hex value here"),
                     )
                     .with_note(
@@ -997,11 +1391,11 @@ impl Error {
                     .finish(),
             ),
             Self::InvalidCharInput { span, source: _ } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Invalid, or badly formed, character input")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("Not a valid character"),
                     )
                     .with_note(
@@ -1015,25 +1409,25 @@ impl Error {
             ),
             // --------------------------------------------------------------
             Self::IncompleteString { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Incomplete string")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("String starts here"),
                     ).with_label(
-                        Label::new(span.as_end_range())
+                        Label::new(at(span.as_end_range()))
                             .with_message("Reported here"),
                     )
                     .with_note(format!("Expecting a closing {} character", "#\\\"".fg(SYNTAX)))
                     .finish(),
             ),
             Self::InvalidStringMnemonicEscape { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Invalid, or badly formed, mnemonic escape in string")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("Not a valid escape sequence"),
                     )
                     .with_note(
@@ -1044,11 +1438,11 @@ impl Error {
                     .finish()
             ),
             Self::InvalidStringHexEscape { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Invalid, or badly formed, hex escape in string")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("Not a valid escape sequence"),
                     )
                     .with_note(
@@ -1059,91 +1453,91 @@ impl Error {
                     .finish()
             ),
             Self::InvalidStringInput { span, source: _ } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Invalid, or badly formed, string input")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("Not a valid string"),
                     )
                     .finish(),
             ),
             // --------------------------------------------------------------
             Self::InvalidNumericInput { span, source: _ } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Invalid, or badly formed, numeric input")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("Not a valid numeric value"),
                     )
                     .finish(),
             ),
             // --------------------------------------------------------------
             Self::IncompleteList { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Incomplete list")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("List starts here"),
                     )
                     .with_note(format!("Expecting a closing {}", "#\\)".fg(SYNTAX)))
                     .finish(),
             ),
             Self::IncompletePair { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Incomplete pair")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("List starts here"),
                     )
                     .with_note(format!("Expecting a closing {}", "#\\)".fg(SYNTAX)))
                     .finish(),
             ),
             Self::PairMissingCar { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message(format!("Pair is missing a {} value", "car".fg(FUNCTIONS)))
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("need a datum left of this dot"),
                     )
                     .finish(),
             ),
             Self::PairMissingCdr { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message(format!("Pair is missing a {} value", "cdr".fg(FUNCTIONS)))
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("need a datum right of this dot"),
                     )
                     .finish(),
             ),
             Self::PairAdditionalCdr { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message(format!("Pair already has a {} value", "cdr".fg(FUNCTIONS)))
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("too many values right of this dot"),
                     )
                     .finish(),
             ),
             Self::InvalidPairInput { span, source: _ } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Invalid, or badly formed, pair")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("Could not construct a pair from this"),
                     )
                     .finish(),
             ),
             Self::CannotAppendToImproperPair { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message(
                         format!(
@@ -1152,7 +1546,7 @@ impl Error {
                             "append".fg(FUNCTIONS)
                         ))
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("This pair"),
                     )
                     .with_note(format!("Expecting a {} value", "list?".fg(TYPES)))
@@ -1160,11 +1554,11 @@ impl Error {
             ),
             // --------------------------------------------------------------
             Self::IncompleteVector { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Incomplete list")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("Vector starts here"),
                     )
                     .with_note(format!("Expecting a closing {}", "#\\)".fg(SYNTAX)))
@@ -1172,33 +1566,33 @@ impl Error {
             ),
             // --------------------------------------------------------------
             Self::InvalidByteVectorPrefix { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Too many datum values right of dot")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("This is not a valid byte vector prefix"),
                     )
                     .with_note(format!("Expecting the prefix {}", "#u8(".fg(SYNTAX)))
                     .finish(),
             ),
             Self::IncompleteByteVector { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Incomplete list")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("Byte vector starts here"),
                     )
                     .with_note(format!("Expecting a closing {}", ")".fg(SYNTAX)))
                     .finish(),
             ),
             Self::InvalidByteInput { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Invalid value in byte-vector")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("This is not a valid byte"),
                     )
                     .with_note("Expecting an exact integer in the range 0..=255")
@@ -1206,11 +1600,11 @@ impl Error {
             ),
             // --------------------------------------------------------------
              Self::IncompleteQuote { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                      .with_message("Incomplete quote form")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message(
                                 format!(
                                     "The quote symbol {} was not followed by a datum",
@@ -1220,11 +1614,11 @@ impl Error {
                    .finish(),
             ),
              Self::IncompleteQuasiQuote { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                      .with_message("Incomplete quasiquote form")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message(
                                 format!(
                                     "The quasi-quote symbol {} was not followed by a datum",
@@ -1234,11 +1628,11 @@ impl Error {
                    .finish(),
             ),
              Self::IncompleteUnquote { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                      .with_message("Incomplete unquote form")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message(
                                 format!(
                                     "The unquote symbol {} was not followed by a datum",
@@ -1248,11 +1642,11 @@ impl Error {
                    .finish(),
             ),
              Self::IncompleteUnquoteSplicing { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                      .with_message("Incomplete unquote splicing form")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message(
                                 format!(
                                     "The unquote-splicing symbol {} was not followed by a datum",
@@ -1263,22 +1657,22 @@ impl Error {
             ),
              // --------------------------------------------------------------
             Self::IncompleteBlockComment { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Incomplete block comment")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message("Comment starts here"),
                     )
                     .with_note(format!("Expecting a closing {}", "|#".fg(SYNTAX)))
                     .finish(),
             ),
             Self::IncompleteDatumComment { span } => Some(
-                Report::build(ReportKind::Error, (), span.start())
+                Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                     .with_message("Incomplete datum comment")
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message(
                             format!(
                                     "The datum quote form {} was not followed by a datum",
@@ -1287,23 +1681,81 @@ impl Error {
                     )
                     .finish(),
             ),
+            Self::InvalidCommentInput { span } => Some(
+                Report::build(ReportKind::Error, id.clone(), span.start())
+                    .with_code(self.code())
+                    .with_message("Invalid, or badly formed, comment input")
+                    .with_label(
+                        Label::new(at(span.as_range()))
+                            .with_message("Not a single line, block, or datum comment"),
+                    )
+                    .finish(),
+            ),
            // --------------------------------------------------------------
              Self::UnexpectedToken { token, span, within, } => {
-                let mut report = Report::build(ReportKind::Error, (), span.start())
+                let mut report = Report::build(ReportKind::Error, id.clone(), span.start())
                     .with_code(self.code())
                      .with_message(format!("A {} token was not expected here", token))
                     .with_label(
-                        Label::new(span.as_range())
+                        Label::new(at(span.as_range()))
                             .with_message(format!("This {}", token)),
                     );
                 if let Some(within) = within {
                     report = report.with_label(
-                        Label::new(within.as_range())
+                        Label::new(at(within.as_range()))
                             .with_message("Within this context"),
                     );
                 }
                  Some(report.finish())
              }
+            // --------------------------------------------------------------
+            Self::Custom {
+                message,
+                span: Some(span),
+            } => Some(
+                Report::build(ReportKind::Error, id.clone(), span.start())
+                    .with_code(self.code())
+                    .with_message(message)
+                    .with_label(
+                        Label::new(at(span.as_range()))
+                            .with_message("In this datum"),
+                    )
+                    .finish(),
+            ),
+            // --------------------------------------------------------------
+            Self::DatumConversion { conversion, span } => Some(
+                Report::build(ReportKind::Error, id.clone(), span.start())
+                    .with_code(self.code())
+                    .with_message(format!("Could not convert datum to {conversion}"))
+                    .with_label(
+                        Label::new(at(span.as_range()))
+                            .with_message(&conversion.message),
+                    )
+                    .finish(),
+            ),
+            // --------------------------------------------------------------
+            Self::InvalidPattern {
+                message,
+                span: Some(span),
+            } => Some(
+                Report::build(ReportKind::Error, id.clone(), span.start())
+                    .with_code(self.code())
+                    .with_message(format!("Invalid pattern, {message}"))
+                    .with_label(
+                        Label::new(at(span.as_range())).with_message("In this pattern"),
+                    )
+                    .finish(),
+            ),
+            // --------------------------------------------------------------
+            Self::InvalidQuery { message, span } => Some(
+                Report::build(ReportKind::Error, id.clone(), span.start())
+                    .with_code(self.code())
+                    .with_message(format!("Invalid query, {message}"))
+                    .with_label(
+                        Label::new(at(span.as_range())).with_message("In this query"),
+                    )
+                    .finish(),
+            ),
            _ => None,
         }
     }
@@ -1328,6 +1780,101 @@ impl Error {
         }
     }
 
+    ///
+    /// Print this error using the file its span refers to in `sources`, which provides the
+    /// name and text of the file.
+    ///
+    pub fn print_with(&self, sources: &SourceMap) {
+        let input = self
+            .span()
+            .and_then(|span| span.file())
+            .and_then(|file| sources.get(file));
+
+        if let Some(err_source) = self.self_source() {
+            err_source.print_with(sources);
+        }
+
+        if let Some(input) = input {
+            let name = input.source_id().to_string();
+            if let Some(report) = self.report_for(name.clone()) {
+                report
+                    .print((name, Source::from(input.source_str())))
+                    .expect("Could not write error as report");
+                return;
+            }
+        }
+
+        error!("{}", self);
+    }
+
+    fn span_mut(&mut self) -> Option<&mut Span> {
+        match self {
+            Self::IoError { source: _ } => None,
+            Self::IncompleteSpecial { span }
+            | Self::UnexpectedCharacter { span, character: _ }
+            | Self::InvalidDirectiveInput { span }
+            | Self::UnknownDirectiveName { span, name: _ }
+            | Self::InvalidDatumLabel { span }
+            | Self::DuplicateDatumLabel { span, label: _ }
+            | Self::UnknownDatumLabel { span, label: _ }
+            | Self::IncompleteDatumAssignment { span, label: _ }
+            | Self::IncompleteIdentifier { span }
+            | Self::InvalidIdentifierMnemonicEscape { span }
+            | Self::InvalidIdentifierHexEscape { span }
+            | Self::InvalidIdentifierInput { span, source: _ }
+            | Self::InvalidBooleanInput { span }
+            | Self::UnknownCharName { span, name: _ }
+            | Self::InvalidUnicodeValue { span }
+            | Self::InvalidCharInput { span, source: _ }
+            | Self::IncompleteString { span }
+            | Self::InvalidStringMnemonicEscape { span }
+            | Self::InvalidStringHexEscape { span }
+            | Self::InvalidStringInput { span, source: _ }
+            | Self::InvalidNumericInput { span, source: _ }
+            | Self::IncompleteList { span }
+            | Self::IncompletePair { span }
+            | Self::PairMissingCar { span }
+            | Self::PairMissingCdr { span }
+            | Self::PairAdditionalCdr { span }
+            | Self::InvalidPairInput { span, source: _ }
+            | Self::CannotAppendToImproperPair { span }
+            | Self::IncompleteVector { span }
+            | Self::InvalidByteVectorPrefix { span }
+            | Self::IncompleteByteVector { span }
+            | Self::InvalidByteInput { span }
+            | Self::IncompleteQuote { span }
+            | Self::IncompleteQuasiQuote { span }
+            | Self::IncompleteUnquote { span }
+            | Self::IncompleteUnquoteSplicing { span }
+            | Self::IncompleteBlockComment { span }
+            | Self::IncompleteDatumComment { span }
+            | Self::InvalidCommentInput { span }
+            | Self::UnexpectedToken {
+                span,
+                token: _,
+                within: _,
+            } => Some(span),
+            Self::Custom { message: _, span } => span.as_mut(),
+            Self::DatumConversion {
+                conversion: _,
+                span,
+            } => Some(span),
+            Self::InvalidPattern { message: _, span } => span.as_mut(),
+            Self::InvalidQuery { message: _, span } => Some(span),
+        }
+    }
+
+    fn self_source_mut(&mut self) -> Option<&mut Option<Box<Self>>> {
+        match self {
+            Self::InvalidIdentifierInput { span: _, source } => Some(source),
+            Self::InvalidCharInput { span: _, source } => Some(source),
+            Self::InvalidStringInput { span: _, source } => Some(source),
+            Self::InvalidNumericInput { span: _, source } => Some(source),
+            Self::InvalidPairInput { span: _, source } => Some(source),
+            _ => None,
+        }
+    }
+
     fn self_source(&self) -> &Option<Box<Self>> {
         match self {
             Self::InvalidIdentifierInput { span: _, source } => source,
@@ -0,0 +1,183 @@
+/*!
+Provides an input source that reads from any [`Read`] implementation a chunk at a time,
+rather than reading the entire source into memory before lexing.
+
+A [`ChunkedInput`] holds only the text that has been read but not yet consumed. As each
+top-level datum is read it is removed from the front of the buffer, so the memory used is
+bounded by the size of the largest top-level form rather than the size of the whole input.
+Indices reported by the lexer and reader remain relative to the start of the complete input.
+
+# Example
+
+```rust
+use ffsr::input::chunked_input_from_reader;
+use ffsr::reader::stream::DatumStream;
+use ffsr::SourceId;
+
+let data = "(define x 1) (define y 2)";
+let stream = DatumStream::from(chunked_input_from_reader(data.as_bytes(), SourceId::String));
+
+assert_eq!(stream.filter_map(Result::ok).count(), 2);
+```
+
+*/
+
+use crate::error::Error;
+use crate::input::indices::Index;
+use crate::input::Input;
+use crate::SourceId;
+use std::io::{ErrorKind, Read};
+use tracing::trace;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+pub const DEFAULT_CHUNK_SIZE: usize = 8 * 1024;
+
+#[derive(Debug)]
+pub struct ChunkedInput<R> {
+    id: SourceId,
+    reader: R,
+    chunk_size: usize,
+    buffer: String,
+    buffer_chars: usize,
+    undecoded: Vec<u8>,
+    offset: Index,
+    eof: bool,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl<R: Read> ChunkedInput<R> {
+    pub fn new(reader: R, id: SourceId, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0);
+        Self {
+            id,
+            reader,
+            chunk_size,
+            buffer: Default::default(),
+            buffer_chars: 0,
+            undecoded: Default::default(),
+            offset: Default::default(),
+            eof: false,
+        }
+    }
+
+    #[inline(always)]
+    pub fn source_id(&self) -> &SourceId {
+        &self.id
+    }
+
+    ///
+    /// Returns `true` once the underlying reader has reported end of input; no more text
+    /// will be added to the buffer.
+    ///
+    #[inline(always)]
+    pub fn is_eof(&self) -> bool {
+        self.eof
+    }
+
+    ///
+    /// The text read but not yet consumed.
+    ///
+    #[inline(always)]
+    pub fn buffered_str(&self) -> &str {
+        &self.buffer
+    }
+
+    ///
+    /// The index, within the complete input, of the first character in the buffer.
+    ///
+    #[inline(always)]
+    pub fn start_index(&self) -> Index {
+        self.offset
+    }
+
+    ///
+    /// The index, within the complete input, just past the last character in the buffer.
+    ///
+    pub fn end_index(&self) -> Index {
+        self.offset + Index::new(self.buffer.len(), self.buffer_chars)
+    }
+
+    ///
+    /// Returns an [`Input`] over the current buffer contents, positioned at the buffer's
+    /// offset within the complete input.
+    ///
+    pub fn input(&self) -> Input<'_> {
+        Input::new_at(self.id.clone(), &self.buffer, self.offset)
+    }
+
+    ///
+    /// Read more text from the underlying reader. The amount requested grows with the size of
+    /// the buffer so that repeatedly re-scanning a very large form remains linear overall.
+    /// Returns the number of bytes read, which is zero at end of input.
+    ///
+    pub fn fill(&mut self) -> Result<usize, Error> {
+        if self.eof {
+            return Ok(0);
+        }
+
+        let mut chunk = vec![0u8; self.chunk_size.max(self.buffer.len())];
+        let read = loop {
+            match self.reader.read(&mut chunk) {
+                Ok(read) => break read,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        };
+        trace!("read {read} bytes from {:?}", self.id);
+
+        if read == 0 {
+            self.eof = true;
+            if !self.undecoded.is_empty() {
+                return Err(invalid_utf8());
+            }
+        } else {
+            self.undecoded.extend_from_slice(&chunk[..read]);
+            self.decode()?;
+        }
+        Ok(read)
+    }
+
+    ///
+    /// Remove all text before `index` from the buffer; `index` is relative to the complete
+    /// input and must fall on a character boundary within the buffer.
+    ///
+    pub fn consume_to(&mut self, index: Index) {
+        assert!(index >= self.offset);
+        let byte = index.byte() - self.offset.byte();
+        trace!("consuming {byte} bytes from buffer");
+        let _ = self.buffer.drain(..byte);
+        self.buffer_chars -= index.character() - self.offset.character();
+        self.offset = index;
+    }
+
+    fn decode(&mut self) -> Result<(), Error> {
+        let decoded = match std::str::from_utf8(&self.undecoded) {
+            Ok(s) => s,
+            // a multi-byte character was split across reads, keep the tail for next time.
+            Err(e) if e.error_len().is_none() => {
+                std::str::from_utf8(&self.undecoded[..e.valid_up_to()]).unwrap()
+            }
+            Err(_) => return Err(invalid_utf8()),
+        };
+        self.buffer.push_str(decoded);
+        self.buffer_chars += decoded.chars().count();
+        let valid_up_to = decoded.len();
+        let _ = self.undecoded.drain(..valid_up_to);
+        Ok(())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+#[inline(always)]
+fn invalid_utf8() -> Error {
+    std::io::Error::new(ErrorKind::InvalidData, "stream did not contain valid UTF-8").into()
+}
@@ -0,0 +1,191 @@
+/*!
+Provides detection of byte order marks and transcoding of source text into UTF-8.
+
+All inputs are lexed as UTF-8 text; files in other encodings are transcoded when read. Each
+supported encoding maps one source character to exactly one Unicode scalar value, so
+character indices in the transcoded text are the same as character indices in the
+original file (ignoring any byte order mark) and diagnostics report the same lines and columns
+an editor shows for the original. [`Encoding::original_byte_offset`] converts an index back
+into a byte offset within the original file.
+
+# Example
+
+```rust
+use ffsr::input::encoding::{decode, Encoding};
+
+let (text, encoding, bom) = decode(b"\xFF\xFEa\x00b\x00", None).unwrap();
+assert_eq!(text, "ab");
+assert_eq!(encoding, Encoding::Utf16Le);
+assert!(bom);
+
+let (text, _, _) = decode(b"caf\xE9", Some(Encoding::Latin1)).unwrap();
+assert_eq!(text, "café");
+```
+
+*/
+
+use crate::error::Error;
+use crate::input::indices::Index;
+use std::fmt::Display;
+use std::io::ErrorKind;
+use tracing::trace;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    /// ISO-8859-1, each byte is the Unicode code point of the same value.
+    Latin1,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Decode `bytes` into a UTF-8 string. If `encoding` is `None` the encoding is determined from
+/// a byte order mark, defaulting to UTF-8. Any byte order mark matching the chosen encoding is
+/// removed. Returns the decoded text, the encoding used, and whether a byte order mark was
+/// removed.
+///
+pub fn decode(bytes: &[u8], encoding: Option<Encoding>) -> Result<(String, Encoding, bool), Error> {
+    let detected = Encoding::from_byte_order_mark(bytes);
+    let encoding = match (encoding, detected) {
+        (Some(encoding), _) => encoding,
+        (None, Some(detected)) => detected,
+        (None, None) => Encoding::Utf8,
+    };
+    let has_bom = detected == Some(encoding);
+    let bytes = if has_bom {
+        &bytes[encoding.byte_order_mark().len()..]
+    } else {
+        bytes
+    };
+    trace!(
+        "decoding {} bytes as {encoding}, BOM: {has_bom}",
+        bytes.len()
+    );
+
+    let text = match encoding {
+        Encoding::Utf8 => std::str::from_utf8(bytes)
+            .map_err(|e| invalid_data(encoding, e))?
+            .to_string(),
+        Encoding::Utf16Le => decode_utf16(bytes, encoding, u16::from_le_bytes)?,
+        Encoding::Utf16Be => decode_utf16(bytes, encoding, u16::from_be_bytes)?,
+        Encoding::Latin1 => bytes.iter().map(|b| char::from(*b)).collect(),
+    };
+    Ok((text, encoding, has_bom))
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Encoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Utf8 => "UTF-8",
+                Self::Utf16Le => "UTF-16LE",
+                Self::Utf16Be => "UTF-16BE",
+                Self::Latin1 => "ISO-8859-1",
+            }
+        )
+    }
+}
+
+impl Encoding {
+    pub const UTF8_BOM: &'static [u8] = &[0xEF, 0xBB, 0xBF];
+    pub const UTF16LE_BOM: &'static [u8] = &[0xFF, 0xFE];
+    pub const UTF16BE_BOM: &'static [u8] = &[0xFE, 0xFF];
+
+    pub fn from_byte_order_mark(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(Self::UTF8_BOM) {
+            Some(Self::Utf8)
+        } else if bytes.starts_with(Self::UTF16LE_BOM) {
+            Some(Self::Utf16Le)
+        } else if bytes.starts_with(Self::UTF16BE_BOM) {
+            Some(Self::Utf16Be)
+        } else {
+            None
+        }
+    }
+
+    pub fn byte_order_mark(&self) -> &'static [u8] {
+        match self {
+            Self::Utf8 => Self::UTF8_BOM,
+            Self::Utf16Le => Self::UTF16LE_BOM,
+            Self::Utf16Be => Self::UTF16BE_BOM,
+            Self::Latin1 => &[],
+        }
+    }
+
+    ///
+    /// The number of bytes `c` occupies when encoded in this encoding.
+    ///
+    pub fn encoded_len(&self, c: char) -> usize {
+        match self {
+            Self::Utf8 => c.len_utf8(),
+            Self::Utf16Le | Self::Utf16Be => c.len_utf16() * 2,
+            Self::Latin1 => 1,
+        }
+    }
+
+    ///
+    /// Convert `index`, a position within `decoded` which is the text produced by [`decode`],
+    /// into a byte offset within the original, encoded, bytes.
+    ///
+    pub fn original_byte_offset(&self, decoded: &str, index: Index, has_bom: bool) -> usize {
+        let bom_len = if has_bom {
+            self.byte_order_mark().len()
+        } else {
+            0
+        };
+        bom_len
+            + match self {
+                Self::Utf8 => index.byte(),
+                Self::Latin1 => index.character(),
+                Self::Utf16Le | Self::Utf16Be => decoded[..index.byte()]
+                    .chars()
+                    .map(|c| self.encoded_len(c))
+                    .sum(),
+            }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn decode_utf16(
+    bytes: &[u8],
+    encoding: Encoding,
+    to_unit: fn([u8; 2]) -> u16,
+) -> Result<String, Error> {
+    if !bytes.len().is_multiple_of(2) {
+        return Err(invalid_data(encoding, "odd number of bytes"));
+    }
+    char::decode_utf16(
+        bytes
+            .chunks_exact(2)
+            .map(|pair| to_unit([pair[0], pair[1]])),
+    )
+    .collect::<Result<String, _>>()
+    .map_err(|e| invalid_data(encoding, e))
+}
+
+fn invalid_data<E: Display>(encoding: Encoding, e: E) -> Error {
+    std::io::Error::new(
+        ErrorKind::InvalidData,
+        format!("input is not valid {encoding}; {e}"),
+    )
+    .into()
+}
@@ -11,6 +11,7 @@ YYYYY
 
 use crate::{
     input::indices::{CharIndex, Index},
+    source_map::FileId,
     SourceId, Sourced,
 };
 use std::str::CharIndices as ActualCharIndices;
@@ -23,8 +24,12 @@ use std::str::CharIndices as ActualCharIndices;
 pub struct CharIndices<'a> {
     id: &'a SourceId,
     source: &'a str,
+    offset: Index,
+    file_id: Option<FileId>,
     iter: ActualCharIndices<'a>,
+    iter_starts_at: usize,
     current_index: Index,
+    next_index: Index,
     pushback_stack: Vec<CharIndex>,
 }
 
@@ -38,9 +43,13 @@ impl Iterator for CharIndices<'_> {
     fn next(&mut self) -> Option<Self::Item> {
         if self.pushback_stack.is_empty() {
             if let Some((i, c)) = self.iter.next() {
-                self.current_index.set_byte(i);
+                self.current_index.set_byte(self.iter_starts_at + i);
                 let char_index = self.current_index.to_char_index(c);
                 self.current_index.increment_character();
+                self.next_index = Index::new(
+                    char_index.byte_index() + c.len_utf8(),
+                    char_index.char_index() + 1,
+                );
                 Some(char_index)
             } else {
                 None
@@ -61,25 +70,65 @@ impl Sourced for CharIndices<'_> {
     fn source_str(&self) -> &str {
         self.source
     }
+
+    #[inline(always)]
+    fn source_offset(&self) -> Index {
+        self.offset
+    }
+
+    #[inline(always)]
+    fn file_id(&self) -> Option<FileId> {
+        self.file_id
+    }
 }
 
 impl<'a> CharIndices<'a> {
     pub fn new(id: &'a SourceId, source: &'a str) -> Self {
+        Self::new_at(id, source, Index::default())
+    }
+
+    ///
+    /// As `source_str`, but borrowed for the lifetime of the input rather than this iterator.
+    ///
+    #[inline(always)]
+    pub(crate) fn input_str(&self) -> &'a str {
+        self.source
+    }
+
+    ///
+    /// Create a new iterator over `source`, where `source` is a fragment of some larger
+    /// input and its first character is at `offset` within that input. All indices returned
+    /// by this iterator are relative to the larger input.
+    ///
+    pub fn new_at(id: &'a SourceId, source: &'a str, offset: Index) -> Self {
         Self {
             id,
             source,
+            offset,
+            file_id: None,
             iter: source.char_indices(),
-            current_index: Default::default(),
+            iter_starts_at: offset.byte(),
+            current_index: offset,
+            next_index: offset,
             pushback_stack: Default::default(),
         }
     }
 
+    #[inline(always)]
+    pub(crate) fn with_file_id(self, file_id: Option<FileId>) -> Self {
+        Self { file_id, ..self }
+    }
+
     pub fn from(&self, starts_from: Index) -> Self {
         Self {
             id: self.id,
             source: self.source,
-            iter: self.source[starts_from.byte()..].char_indices(),
+            offset: self.offset,
+            file_id: self.file_id,
+            iter: self.source[starts_from.byte() - self.offset.byte()..].char_indices(),
+            iter_starts_at: starts_from.byte(),
             current_index: starts_from,
+            next_index: starts_from,
             pushback_stack: Default::default(),
         }
     }
@@ -103,4 +152,16 @@ impl<'a> CharIndices<'a> {
     pub fn current_index(&self) -> Index {
         self.current_index
     }
+
+    ///
+    /// The index of the next character this iterator will return, taking any pushed-back
+    /// characters into account. Everything before this index has been consumed.
+    ///
+    #[inline(always)]
+    pub fn consumed_index(&self) -> Index {
+        self.pushback_stack
+            .last()
+            .map(|char_index| char_index.index())
+            .unwrap_or(self.next_index)
+    }
 }
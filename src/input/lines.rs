@@ -0,0 +1,339 @@
+/*!
+Provides a line index over some source text, converting between byte offsets, character
+offsets, line/column positions, and UTF-16 code-unit offsets.
+
+The index is built once by scanning the source, after which every conversion is a binary
+search. Lines are terminated by `"\n"`, `"\r\n"`, or a lone `"\r"`; the terminator is not
+part of the line's columns. Lines and columns are zero-based.
+
+# Example
+
+```rust
+use ffsr::input::lines::{LineIndex, Position};
+
+let index = LineIndex::new("(a\r\n λ 𝄞)");
+
+assert_eq!(index.line_count(), 2);
+assert_eq!(index.position(8), Position::new(1, 4));
+assert_eq!(index.position_utf16(8), Position::new(1, 5));
+assert_eq!(index.char_to_byte(8), 12);
+assert_eq!(index.byte_to_char(12), 8);
+assert_eq!(index.char_offset_utf16(Position::new(1, 5)), Some(8));
+```
+
+*/
+
+use crate::input::indices::Index;
+use crate::lexer::token::Span;
+use std::fmt::Display;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A zero-based line and column; whether the column counts characters or UTF-16 code units
+/// depends on the [`LineIndex`] method that produced it.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    line: usize,
+    column: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LineIndex {
+    /// The character offset of the start of each line.
+    line_starts: Vec<usize>,
+    /// The character offset of the end of each line, before any terminator.
+    line_ends: Vec<usize>,
+    /// Every character that is not a single byte, in source order.
+    wide_chars: Vec<WideChar>,
+    len: Index,
+    len_utf16: usize,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct WideChar {
+    character: usize,
+    byte: usize,
+    utf16: usize,
+    len_utf8: usize,
+    len_utf16: usize,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line + 1, self.column + 1)
+    }
+}
+
+impl From<(usize, usize)> for Position {
+    fn from(v: (usize, usize)) -> Self {
+        Self::new(v.0, v.1)
+    }
+}
+
+impl Position {
+    #[inline(always)]
+    pub fn new(line: usize, column: usize) -> Self {
+        Self { line, column }
+    }
+
+    #[inline(always)]
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    #[inline(always)]
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl From<&str> for LineIndex {
+    fn from(source: &str) -> Self {
+        Self::new(source)
+    }
+}
+
+impl LineIndex {
+    pub fn new(source: &str) -> Self {
+        let mut line_starts = vec![0];
+        let mut line_ends = Vec::new();
+        let mut wide_chars = Vec::new();
+        let mut utf16 = 0;
+        let mut character = 0;
+        let mut chars = source.char_indices().peekable();
+
+        while let Some((byte, c)) = chars.next() {
+            if c.len_utf8() > 1 {
+                wide_chars.push(WideChar {
+                    character,
+                    byte,
+                    utf16,
+                    len_utf8: c.len_utf8(),
+                    len_utf16: c.len_utf16(),
+                });
+            }
+            utf16 += c.len_utf16();
+            character += 1;
+            if c == '\n' || (c == '\r' && !matches!(chars.peek(), Some((_, '\n')))) {
+                let terminator = if c == '\n' && source[..byte].ends_with('\r') {
+                    2
+                } else {
+                    1
+                };
+                line_ends.push(character - terminator);
+                line_starts.push(character);
+            }
+        }
+        line_ends.push(character);
+
+        Self {
+            line_starts,
+            line_ends,
+            wide_chars,
+            len: Index::new(source.len(), character),
+            len_utf16: utf16,
+        }
+    }
+
+    #[inline(always)]
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    ///
+    /// The length of the indexed source in bytes and characters.
+    ///
+    #[inline(always)]
+    pub fn len(&self) -> Index {
+        self.len
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len.byte() == 0
+    }
+
+    #[inline(always)]
+    pub fn len_utf16(&self) -> usize {
+        self.len_utf16
+    }
+
+    ///
+    /// The character span of `line`, not including any line terminator.
+    ///
+    pub fn line_span(&self, line: usize) -> Option<Span> {
+        if line < self.line_count() {
+            Some(Span::new(self.line_starts[line], self.line_ends[line]))
+        } else {
+            None
+        }
+    }
+
+    // --------------------------------------------------------------------------------------------
+
+    ///
+    /// Convert a character offset into a byte offset; offsets past the end are clamped.
+    ///
+    pub fn char_to_byte(&self, character: usize) -> usize {
+        let character = character.min(self.len.character());
+        match self.wide_char_before(character) {
+            None => character,
+            Some(wide) => wide.byte + wide.len_utf8 + (character - wide.character - 1),
+        }
+    }
+
+    ///
+    /// Convert a byte offset into a character offset; a byte offset within a multi-byte
+    /// character returns that character's offset, and offsets past the end are clamped.
+    ///
+    pub fn byte_to_char(&self, byte: usize) -> usize {
+        let byte = byte.min(self.len.byte());
+        let i = self.wide_chars.partition_point(|wide| wide.byte < byte);
+        if i == 0 {
+            byte
+        } else {
+            let wide = &self.wide_chars[i - 1];
+            if byte < wide.byte + wide.len_utf8 {
+                wide.character
+            } else {
+                wide.character + 1 + (byte - wide.byte - wide.len_utf8)
+            }
+        }
+    }
+
+    ///
+    /// Convert a character offset into an [`Index`] carrying both byte and character offsets.
+    ///
+    #[inline(always)]
+    pub fn index(&self, character: usize) -> Index {
+        Index::new(self.char_to_byte(character), character)
+    }
+
+    ///
+    /// Convert a character offset into a UTF-16 code-unit offset; offsets past the end are
+    /// clamped.
+    ///
+    pub fn char_to_utf16(&self, character: usize) -> usize {
+        let character = character.min(self.len.character());
+        match self.wide_char_before(character) {
+            None => character,
+            Some(wide) => wide.utf16 + wide.len_utf16 + (character - wide.character - 1),
+        }
+    }
+
+    ///
+    /// Convert a UTF-16 code-unit offset into a character offset; an offset between the two
+    /// halves of a surrogate pair returns that character's offset, and offsets past the end
+    /// are clamped.
+    ///
+    pub fn utf16_to_char(&self, utf16: usize) -> usize {
+        let utf16 = utf16.min(self.len_utf16);
+        let i = self.wide_chars.partition_point(|wide| wide.utf16 < utf16);
+        if i == 0 {
+            utf16
+        } else {
+            let wide = &self.wide_chars[i - 1];
+            if utf16 < wide.utf16 + wide.len_utf16 {
+                wide.character
+            } else {
+                wide.character + 1 + (utf16 - wide.utf16 - wide.len_utf16)
+            }
+        }
+    }
+
+    // --------------------------------------------------------------------------------------------
+
+    ///
+    /// Convert a character offset into a line and character column. A line terminator
+    /// belongs to the line it terminates.
+    ///
+    pub fn position(&self, character: usize) -> Position {
+        let character = character.min(self.len.character());
+        let line = self.line_of(character);
+        Position::new(line, character - self.line_starts[line])
+    }
+
+    ///
+    /// Convert a character offset into a line and UTF-16 code-unit column, as used by the
+    /// Language Server Protocol.
+    ///
+    pub fn position_utf16(&self, character: usize) -> Position {
+        let character = character.min(self.len.character());
+        let line = self.line_of(character);
+        Position::new(
+            line,
+            self.char_to_utf16(character) - self.char_to_utf16(self.line_starts[line]),
+        )
+    }
+
+    ///
+    /// Convert a line and character column into a character offset. Returns `None` if the
+    /// line does not exist or the column is past the end of the line.
+    ///
+    pub fn char_offset(&self, position: Position) -> Option<usize> {
+        let span = self.line_span(position.line)?;
+        let character = span.start() + position.column;
+        if character <= span.end() {
+            Some(character)
+        } else {
+            None
+        }
+    }
+
+    ///
+    /// Convert a line and UTF-16 code-unit column into a character offset. Returns `None` if
+    /// the line does not exist or the column is past the end of the line.
+    ///
+    pub fn char_offset_utf16(&self, position: Position) -> Option<usize> {
+        let span = self.line_span(position.line)?;
+        let utf16 = self.char_to_utf16(span.start()) + position.column;
+        if utf16 <= self.char_to_utf16(span.end()) {
+            Some(self.utf16_to_char(utf16))
+        } else {
+            None
+        }
+    }
+
+    ///
+    /// Convert a character span into its start and end positions, see [`Self::position`].
+    ///
+    #[inline(always)]
+    pub fn span_positions(&self, span: Span) -> (Position, Position) {
+        (self.position(span.start()), self.position(span.end()))
+    }
+
+    // --------------------------------------------------------------------------------------------
+
+    #[inline(always)]
+    fn line_of(&self, character: usize) -> usize {
+        self.line_starts
+            .partition_point(|start| *start <= character)
+            - 1
+    }
+
+    #[inline(always)]
+    fn wide_char_before(&self, character: usize) -> Option<&WideChar> {
+        let i = self
+            .wide_chars
+            .partition_point(|wide| wide.character < character);
+        if i == 0 {
+            None
+        } else {
+            Some(&self.wide_chars[i - 1])
+        }
+    }
+}
@@ -0,0 +1,73 @@
+/*!
+Provides a read-only, memory-mapped file whose content has been validated as UTF-8.
+
+This module, and the `unsafe` function [`input_from_mapped_file`] that uses it, are the only
+code in the crate that uses `unsafe`, and only when the `mmap` feature is enabled.
+
+[`input_from_mapped_file`]: crate::input::input_from_mapped_file
+
+*/
+
+use crate::error::Error;
+use crate::input::encoding::Encoding;
+use memmap2::Mmap;
+use std::fs::File;
+use std::io::ErrorKind;
+use std::path::Path;
+use tracing::trace;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug)]
+pub(crate) struct MappedFile {
+    map: Mmap,
+    start: usize,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl MappedFile {
+    ///
+    /// Map the file at `path` and validate its content as UTF-8.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified while the returned value is alive, as the content is not
+    /// validated again by [`MappedFile::as_str`].
+    ///
+    #[allow(unsafe_code)]
+    pub(crate) unsafe fn open(path: &Path) -> Result<Self, Error> {
+        let file = File::open(path)?;
+        // SAFETY: the map is read-only, and the caller guarantees the file is not modified
+        // while it is mapped.
+        let map = unsafe { Mmap::map(&file)? };
+        trace!("mapped {} bytes from {:?}", map.len(), path);
+
+        let start = if map.starts_with(Encoding::UTF8_BOM) {
+            Encoding::UTF8_BOM.len()
+        } else {
+            0
+        };
+        if let Err(e) = std::str::from_utf8(&map[start..]) {
+            return Err(std::io::Error::new(ErrorKind::InvalidData, e).into());
+        }
+
+        Ok(Self { map, start })
+    }
+
+    #[allow(unsafe_code)]
+    #[inline(always)]
+    pub(crate) fn as_str(&self) -> &str {
+        // SAFETY: the content was validated as UTF-8 in `open`, and the map is read-only.
+        unsafe { std::str::from_utf8_unchecked(&self.map[self.start..]) }
+    }
+
+    #[inline(always)]
+    pub(crate) fn has_bom(&self) -> bool {
+        self.start > 0
+    }
+}
@@ -11,10 +11,21 @@ YYYYY
 
 use crate::error::Error;
 use crate::Sourced;
-use crate::{input::iter::CharIndices, SourceId};
+use crate::{
+    input::{
+        chunked::{ChunkedInput, DEFAULT_CHUNK_SIZE},
+        encoding::{decode, Encoding},
+        indices::Index,
+        iter::CharIndices,
+        lines::LineIndex,
+    },
+    source_map::FileId,
+    SourceId,
+};
 use std::borrow::{Borrow, Cow};
+use std::cell::OnceCell;
 use std::fs::File;
-use std::io::Read;
+use std::io::{Read, Stdin};
 use std::path::PathBuf;
 use tracing::trace;
 
@@ -25,7 +36,12 @@ use tracing::trace;
 #[derive(Debug)]
 pub struct Input<'a> {
     id: SourceId,
-    source: Cow<'a, str>,
+    source: InputSource<'a>,
+    offset: Index,
+    encoding: Encoding,
+    has_bom: bool,
+    line_index: OnceCell<LineIndex>,
+    file_id: Option<FileId>,
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -41,16 +57,94 @@ pub fn input_from_string<'a>(s: String) -> Input<'a> {
 }
 
 pub fn input_from_stdin<'a>() -> Result<Input<'a>, Error> {
-    input_from_reader(std::io::stdin(), SourceId::StdIn)
+    input_from_reader(std::io::stdin(), SourceId::StdIn, None)
 }
 
+///
+/// Read the file at `path`, detecting its encoding from any byte order mark and defaulting
+/// to UTF-8. The byte order mark is not part of the resulting input.
+///
 pub fn input_from_file<'a, P>(path: P) -> Result<Input<'a>, Error>
 where
     P: Into<PathBuf>,
 {
     let path = path.into();
     let file = File::open(&path)?;
-    input_from_reader(file, SourceId::File(path))
+    input_from_reader(file, SourceId::File(path), None)
+}
+
+///
+/// Read the file at `path`, transcoding from `encoding` into UTF-8. A byte order mark is
+/// removed only if it matches `encoding`.
+///
+pub fn input_from_file_with_encoding<'a, P>(path: P, encoding: Encoding) -> Result<Input<'a>, Error>
+where
+    P: Into<PathBuf>,
+{
+    let path = path.into();
+    let file = File::open(&path)?;
+    input_from_reader(file, SourceId::File(path), Some(encoding))
+}
+
+///
+/// Create an input backed by a read-only memory map of the file at `path`. The file is
+/// validated as UTF-8 once, when mapped, and is not copied; a UTF-8 byte order mark is
+/// skipped. Files in other encodings must be read with [`input_from_file_with_encoding`].
+///
+/// # Safety
+///
+/// The file must not be modified, or truncated, by this or any other process while the
+/// returned input, or anything read from it, is alive. The content is only validated as UTF-8
+/// when the file is mapped, and a change to the file afterwards is visible through the map;
+/// the behavior is then undefined.
+///
+#[cfg(feature = "mmap")]
+#[allow(unsafe_code)]
+pub unsafe fn input_from_mapped_file<'a, P>(path: P) -> Result<Input<'a>, Error>
+where
+    P: Into<PathBuf>,
+{
+    let path = path.into();
+    // SAFETY: the caller upholds the requirements of this function, which are the same as
+    // those of `MappedFile::open`.
+    let mapped = unsafe { MappedFile::open(&path)? };
+    Ok(Input {
+        id: SourceId::File(path),
+        has_bom: mapped.has_bom(),
+        line_index: Default::default(),
+        file_id: None,
+        source: InputSource::Mapped(mapped),
+        offset: Default::default(),
+        encoding: Encoding::Utf8,
+    })
+}
+
+pub fn chunked_input_from_reader<R: Read>(reader: R, id: SourceId) -> ChunkedInput<R> {
+    ChunkedInput::new(reader, id, DEFAULT_CHUNK_SIZE)
+}
+
+pub fn chunked_input_from_stdin() -> ChunkedInput<Stdin> {
+    chunked_input_from_reader(std::io::stdin(), SourceId::StdIn)
+}
+
+pub fn chunked_input_from_file<P>(path: P) -> Result<ChunkedInput<File>, Error>
+where
+    P: Into<PathBuf>,
+{
+    let path = path.into();
+    let file = File::open(&path)?;
+    Ok(chunked_input_from_reader(file, SourceId::File(path)))
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug)]
+enum InputSource<'a> {
+    Text(Cow<'a, str>),
+    #[cfg(feature = "mmap")]
+    Mapped(MappedFile),
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -61,7 +155,12 @@ impl<'a> From<&'a str> for Input<'a> {
     fn from(s: &'a str) -> Self {
         Self {
             id: SourceId::String,
-            source: Cow::Borrowed(s),
+            source: InputSource::Text(Cow::Borrowed(s)),
+            offset: Default::default(),
+            encoding: Default::default(),
+            has_bom: false,
+            line_index: Default::default(),
+            file_id: None,
         }
     }
 }
@@ -71,7 +170,12 @@ impl From<String> for Input<'_> {
         trace!("input source {:?}; length: {}", s, s.len());
         Self {
             id: SourceId::String,
-            source: Cow::Owned(s),
+            source: InputSource::Text(Cow::Owned(s)),
+            offset: Default::default(),
+            encoding: Default::default(),
+            has_bom: false,
+            line_index: Default::default(),
+            file_id: None,
         }
     }
 }
@@ -84,14 +188,111 @@ impl Sourced for Input<'_> {
 
     #[inline(always)]
     fn source_str(&self) -> &str {
-        self.source.borrow()
+        match &self.source {
+            InputSource::Text(source) => source.borrow(),
+            #[cfg(feature = "mmap")]
+            InputSource::Mapped(source) => source.as_str(),
+        }
+    }
+
+    #[inline(always)]
+    fn source_offset(&self) -> Index {
+        self.offset
+    }
+
+    #[inline(always)]
+    fn file_id(&self) -> Option<FileId> {
+        self.file_id
     }
 }
 
 impl<'a> Input<'a> {
+    ///
+    /// Create an input for `source`, a part of a larger text that starts at `offset` within
+    /// it, so that the spans of what is read from it are positions in the larger text.
+    ///
+    pub fn new_at(id: SourceId, source: &'a str, offset: Index) -> Self {
+        Self {
+            id,
+            source: InputSource::Text(Cow::Borrowed(source)),
+            offset,
+            encoding: Default::default(),
+            has_bom: false,
+            line_index: Default::default(),
+            file_id: None,
+        }
+    }
+
+    ///
+    /// Returns this input with its source identifier replaced by `id`.
+    ///
+    #[inline(always)]
+    pub fn with_source_id(self, id: SourceId) -> Self {
+        Self { id, ..self }
+    }
+
+    ///
+    /// The encoding of the original source, the text of this input is always UTF-8.
+    ///
+    #[inline(always)]
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    ///
+    /// Returns `true` if a byte order mark was removed from the start of the original source.
+    ///
+    #[inline(always)]
+    pub fn has_byte_order_mark(&self) -> bool {
+        self.has_bom
+    }
+
+    ///
+    /// Convert `index`, a position within this input, into a byte offset within the original
+    /// source, taking into account any byte order mark and transcoding.
+    ///
+    pub fn original_byte_offset(&self, index: Index) -> usize {
+        let relative = Index::new(
+            index.byte() - self.offset.byte(),
+            index.character() - self.offset.character(),
+        );
+        self.encoding
+            .original_byte_offset(self.source_str(), relative, self.has_bom)
+    }
+
+    ///
+    /// The line index for this input, built the first time it is requested. Offsets in the
+    /// index are relative to the start of this input's text.
+    ///
+    #[inline(always)]
+    pub fn line_index(&self) -> &LineIndex {
+        self.line_index
+            .get_or_init(|| LineIndex::new(self.source_str()))
+    }
+
     #[inline(always)]
     pub fn char_indices(&'a self) -> CharIndices<'a> {
-        CharIndices::new(&self.id, &self.source)
+        CharIndices::new_at(&self.id, self.source_str(), self.offset).with_file_id(self.file_id)
+    }
+
+    #[inline(always)]
+    pub(crate) fn set_file_id(&mut self, file_id: FileId) {
+        self.file_id = Some(file_id);
+    }
+
+    ///
+    /// Returns a new input that borrows the text of this one, and shares its identity.
+    ///
+    pub(crate) fn borrowed(&self) -> Input<'_> {
+        Input {
+            id: self.id.clone(),
+            source: InputSource::Text(Cow::Borrowed(self.source_str())),
+            offset: self.offset,
+            encoding: self.encoding,
+            has_bom: self.has_bom,
+            line_index: self.line_index.clone(),
+            file_id: self.file_id,
+        }
     }
 }
 
@@ -99,12 +300,22 @@ impl<'a> Input<'a> {
 // Private Functions
 // ------------------------------------------------------------------------------------------------
 
-fn input_from_reader<'a, R: Read>(mut reader: R, id: SourceId) -> Result<Input<'a>, Error> {
-    let mut buffer = String::new();
-    reader.read_to_string(&mut buffer)?;
+fn input_from_reader<'a, R: Read>(
+    mut reader: R,
+    id: SourceId,
+    encoding: Option<Encoding>,
+) -> Result<Input<'a>, Error> {
+    let mut buffer = Vec::new();
+    reader.read_to_end(&mut buffer)?;
+    let (text, encoding, has_bom) = decode(&buffer, encoding)?;
     Ok(Input {
         id,
-        source: Cow::Owned(buffer),
+        source: InputSource::Text(Cow::Owned(text)),
+        offset: Default::default(),
+        encoding,
+        has_bom,
+        line_index: Default::default(),
+        file_id: None,
     })
 }
 
@@ -112,6 +323,17 @@ fn input_from_reader<'a, R: Read>(mut reader: R, id: SourceId) -> Result<Input<'
 // Modules
 // ------------------------------------------------------------------------------------------------
 
+pub mod chunked;
+
+pub mod encoding;
+
 pub mod indices;
 
+#[cfg(feature = "mmap")]
+mod mapped;
+#[cfg(feature = "mmap")]
+use mapped::MappedFile;
+
 pub mod iter;
+
+pub mod lines;
@@ -12,12 +12,13 @@ YYYYY
 use crate::error::{
     incomplete_block_comment, incomplete_identifier, incomplete_special, incomplete_string,
     invalid_byte_vector_prefix, invalid_char_input, invalid_datum_label, invalid_directive_input,
-    Error,
+    invalid_identifier_mnemonic_escape, invalid_numeric_input, unexpected_character, Error,
 };
-use crate::input::indices::CharIndex;
+use crate::input::indices::{CharIndex, Index};
 use crate::input::iter::CharIndices;
 use crate::lexer::internals::{IteratorState, State};
 use crate::lexer::token::{Span, Token, TokenKind};
+use crate::source_map::FileId;
 use crate::syntax::{
     IDENTIFIER_WRAPPER, PAIR_END, PAIR_START, QUASI_QUOTE_ABBREV, QUOTE_ABBREV,
     SPECIAL_PREFIX_CHAR, STRING_QUOTE, UNQUOTE_ABBREV, UNQUOTE_SPLICING_ABBREV,
@@ -34,6 +35,7 @@ use unicode_categories::UnicodeCategories;
 pub struct TokenIter<'a> {
     source: CharIndices<'a>,
     state_stack: Vec<IteratorState>,
+    recover: bool,
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -158,6 +160,17 @@ macro_rules! return_error {
     };
 }
 
+macro_rules! return_error_and_recover {
+    ($me:expr, $current_state:expr, $char_index:expr, $error_fn:ident $(, $arg:expr)?) => {
+        state_change!($current_state => Nothing);
+        let start = $current_state.token_starts_at().character();
+        let end = $me.skip_to_resume_point($char_index).max(start);
+        let err = Some($error_fn(Span::new(start, end) $(, $arg)?));
+        error!("return error {err:?}");
+        return err;
+    };
+}
+
 // ------------------------------------------------------------------------------------------------
 // Implementations
 // ------------------------------------------------------------------------------------------------
@@ -167,6 +180,7 @@ impl<'a> From<CharIndices<'a>> for TokenIter<'a> {
         Self {
             state_stack: Vec::default(),
             source,
+            recover: false,
         }
     }
 }
@@ -181,6 +195,16 @@ impl Sourced for TokenIter<'_> {
     fn source_str(&self) -> &str {
         self.source.source_str()
     }
+
+    #[inline(always)]
+    fn source_offset(&self) -> Index {
+        self.source.source_offset()
+    }
+
+    #[inline(always)]
+    fn file_id(&self) -> Option<FileId> {
+        self.source.file_id()
+    }
 }
 
 #[inline(always)]
@@ -199,18 +223,36 @@ fn is_exponent_char(c: char, radix: u32) -> bool {
     c == '^' || if radix == 16 { c == 'l' } else { c == 'e' }
 }
 
+///
+/// A character at which tokenizing can safely restart after an error.
+///
+#[inline(always)]
+fn is_resume_point(c: char) -> bool {
+    c.is_whitespace() || [PAIR_START, PAIR_END, STRING_QUOTE, ';'].contains(&c)
+}
+
 impl Iterator for TokenIter<'_> {
     type Item = Result<Token, Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        let next = self.next_token();
+        match self.source.file_id() {
+            Some(file_id) => next.map(|result| {
+                result
+                    .map(|token| token.with_file(file_id))
+                    .map_err(|e| e.with_file(file_id))
+            }),
+            None => next,
+        }
+    }
+}
+
+impl TokenIter<'_> {
+    fn next_token(&mut self) -> Option<Result<Token, Error>> {
         let span = trace_span!("next-token", ?self.state_stack);
         let _scope = span.enter();
 
-        let mut current_state = if let Some(state) = self.state_stack.pop() {
-            state
-        } else {
-            IteratorState::default()
-        };
+        let mut current_state = self.state_stack.pop().unwrap_or_default();
 
         let mut last_char_index = CharIndex::new(0, 0, '\u{00}');
         let mut number_radix: u32 = 10;
@@ -401,6 +443,7 @@ impl Iterator for TokenIter<'_> {
                     return_token_and_add_char!(current_state, char_index, Number => Nothing);
                 }
                 (State::InMaybeInf(_), _) => {
+                    self.push_back_char(char_index);
                     state_change!(current_state => State::InIdentifier);
                 }
                 (State::InMaybeNan(0), 'a') => {
@@ -416,6 +459,7 @@ impl Iterator for TokenIter<'_> {
                     return_token_and_add_char!(current_state, char_index, Number => Nothing);
                 }
                 (State::InMaybeNan(_), _) => {
+                    self.push_back_char(char_index);
                     state_change!(current_state => State::InIdentifier);
                 }
                 (State::Nothing | State::InWhitespace, c) if is_radix_char(c, number_radix) => {
@@ -456,6 +500,9 @@ impl Iterator for TokenIter<'_> {
                 (State::InNumberPostPrefix, SPECIAL_PREFIX_CHAR) => {
                     state_change!(current_state => InNumberPrefix);
                 }
+                (State::InComplex, 'i') => {
+                    return_token_and_add_char!(current_state, char_index, Number => Nothing);
+                }
                 (State::InNumberPostPrefix | State::InComplex, c)
                     if c == '+' || c == '-' || is_radix_char(c, number_radix) =>
                 {
@@ -519,7 +566,12 @@ impl Iterator for TokenIter<'_> {
                     return_token_and_add_char!(current_state, char_index, OpenByteVector => Nothing);
                 }
                 (State::InOpenByteVector(_), _) => {
-                    return_error!(current_state, char_index, invalid_byte_vector_prefix);
+                    return_error_and_recover!(
+                        self,
+                        current_state,
+                        char_index,
+                        invalid_byte_vector_prefix
+                    );
                 }
                 // --------------------------------------------------------------------------------
                 // Directives
@@ -530,7 +582,12 @@ impl Iterator for TokenIter<'_> {
                     state_change!(current_state => InDirectiveText);
                 }
                 (State::InDirective, _) => {
-                    return_error!(current_state, char_index, invalid_directive_input);
+                    return_error_and_recover!(
+                        self,
+                        current_state,
+                        char_index,
+                        invalid_directive_input
+                    );
                 }
                 (State::InDirectiveText, c) if is_directive(c) => {}
                 (State::InDirectiveText, _) => {
@@ -550,7 +607,7 @@ impl Iterator for TokenIter<'_> {
                     return_token_and_add_char!(current_state, char_index, DatumRef => Nothing);
                 }
                 (State::InDatumRef, _) => {
-                    return_error!(current_state, char_index, invalid_datum_label);
+                    return_error_and_recover!(self, current_state, char_index, invalid_datum_label);
                 }
                 // --------------------------------------------------------------------------------
                 // Comment Forms
@@ -582,11 +639,47 @@ impl Iterator for TokenIter<'_> {
                 (State::InSpecial, c) => {
                     // push back?
                     error!("Found char {c:?} at {char_index:?}, which doesn't belong in a special");
-                    return_error!(current_state, char_index, incomplete_special);
+                    return_error_and_recover!(self, current_state, char_index, incomplete_special);
                 }
                 // --------------------------------------------------------------------------------
+                // Malformed tokens
+                (
+                    State::InNumberPrefix
+                    | State::InNumberPostPrefix
+                    | State::InNumberExponentMark
+                    | State::InComplex,
+                    _,
+                ) => {
+                    return_error_and_recover!(
+                        self,
+                        current_state,
+                        char_index,
+                        invalid_numeric_input
+                    );
+                }
+                (State::InVBarIdentifierEscape, _) => {
+                    return_error_and_recover!(
+                        self,
+                        current_state,
+                        char_index,
+                        invalid_identifier_mnemonic_escape
+                    );
+                }
+                (State::InCharacterXNum, _) => {
+                    return_error_and_recover!(self, current_state, char_index, invalid_char_input);
+                }
                 (s, c) => {
-                    unreachable!("Unexpected input; state: {:?}, char: {:?}", s, c);
+                    if matches!(s, State::Nothing | State::InWhitespace) {
+                        current_state.set_token_start(&char_index);
+                    }
+                    error!("Found char {c:?} at {char_index:?}, which is not expected in {s:?}");
+                    return_error_and_recover!(
+                        self,
+                        current_state,
+                        char_index,
+                        unexpected_character,
+                        c
+                    );
                 }
             }
         }
@@ -595,7 +688,7 @@ impl Iterator for TokenIter<'_> {
             panic!("State Stack: {:#?}", self.state_stack);
         }
 
-        last_char_index.set_byte_index(self.source_len());
+        last_char_index.set_byte_index(self.source_offset().byte() + self.source_len());
 
         match current_state.state() {
             // ***** Safe Cases *****
@@ -614,11 +707,17 @@ impl Iterator for TokenIter<'_> {
             State::InLineComment => {
                 return_token!(current_state, last_char_index, LineComment);
             }
+            State::InDotNumberOrIdentifier => {
+                return_token!(current_state, last_char_index, Dot);
+            }
+            State::InMaybeInf(_) | State::InMaybeNan(_) => {
+                return_token!(current_state, last_char_index, Identifier);
+            }
             State::InCharacterName | State::InCharacterX => {
                 return_token!(current_state, last_char_index, Character);
             }
             // ***** Error Cases *****
-            State::InVBarIdentifier => {
+            State::InVBarIdentifier | State::InVBarIdentifierEscape => {
                 return_error!(current_state, last_char_index, incomplete_identifier);
             }
             State::InSpecial => {
@@ -627,10 +726,10 @@ impl Iterator for TokenIter<'_> {
             State::InDirective => {
                 return_error!(current_state, last_char_index, invalid_directive_input);
             }
-            State::InString => {
+            State::InString | State::InStringEscape => {
                 return_error!(current_state, last_char_index, incomplete_string);
             }
-            State::InBlockComment => {
+            State::InBlockComment | State::InBlockCommentBar => {
                 return_error!(current_state, last_char_index, incomplete_block_comment);
             }
             State::InOpenByteVector(_) => {
@@ -639,12 +738,89 @@ impl Iterator for TokenIter<'_> {
             State::InCharacter | State::InCharacterXNum => {
                 return_error!(current_state, last_char_index, invalid_char_input);
             }
+            State::InNumberPrefix
+            | State::InNumberPostPrefix
+            | State::InNumberExponentMark
+            | State::InComplex => {
+                return_error!(current_state, last_char_index, invalid_numeric_input);
+            }
+            State::InDatumRef => {
+                return_error!(current_state, last_char_index, invalid_datum_label);
+            }
             _ => None,
         }
     }
 }
 
-impl TokenIter<'_> {
+impl<'a> TokenIter<'a> {
+    ///
+    /// Returns this iterator in recovering mode. After an error the text up to the next white
+    /// space or delimiter is skipped, and included in the error's span, and tokenizing then
+    /// continues, so that all the lexical errors in a source can be reported in one pass.
+    ///
+    /// The `Err` item takes the place of an error token in the stream: its span covers the
+    /// bad text and every character skipped, up to the start of the next item, and it keeps
+    /// the error's code and message that a token kind could not. Taken in order, the tokens
+    /// and errors of one pass cover the source without overlap.
+    ///
+    pub fn with_recovery(self) -> Self {
+        Self {
+            recover: true,
+            ..self
+        }
+    }
+
+    #[inline(always)]
+    pub fn is_recovering(&self) -> bool {
+        self.recover
+    }
+
+    #[inline(always)]
+    pub fn token_str(&self, token: &Token) -> &str {
+        self.get(token.source_range(self.source_offset())).unwrap()
+    }
+
+    ///
+    /// As `token_str`, but borrowed for the lifetime of the input rather than this iterator.
+    ///
+    #[inline(always)]
+    pub(crate) fn input_token_str(&self, token: &Token) -> &'a str {
+        self.source
+            .input_str()
+            .get(token.source_range(self.source_offset()))
+            .unwrap()
+    }
+
+    #[inline(always)]
+    pub(crate) fn consumed_index(&self) -> Index {
+        self.source.consumed_index()
+    }
+
+    ///
+    /// Called with the character that caused an error, returns the index of the last character
+    /// covered by the error. In recovering mode this skips to the next resume point, leaving a
+    /// resume point to start the next token.
+    ///
+    fn skip_to_resume_point(&mut self, char_index: CharIndex) -> usize {
+        if !self.recover {
+            return char_index.index().character();
+        }
+        if is_resume_point(char_index.character()) {
+            self.push_back_char(char_index);
+            return char_index.index().character().saturating_sub(1);
+        }
+        let mut end = char_index.index().character();
+        while let Some(next) = self.peek_next_char() {
+            if is_resume_point(next.character()) {
+                break;
+            }
+            end = next.index().character();
+            let _ = self.next_char();
+        }
+        trace!(end, "skipped to resume point");
+        end
+    }
+
     #[inline(always)]
     fn next_char(&mut self) -> Option<CharIndex> {
         self.source.next()
@@ -659,9 +835,4 @@ impl TokenIter<'_> {
     fn push_back_char(&mut self, index: CharIndex) {
         self.source.push_back(index)
     }
-
-    #[inline(always)]
-    pub fn token_str(&self, token: &Token) -> &str {
-        self.get(token.byte_span().as_range()).unwrap()
-    }
 }
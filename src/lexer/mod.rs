@@ -9,9 +9,12 @@ YYYYY
 
 */
 
+use crate::input::indices::Index;
+use crate::input::lines::LineIndex;
 use crate::input::Input;
 use crate::lexer::iter::TokenIter;
 use crate::lexer::token::Token;
+use crate::source_map::FileId;
 use crate::Sourced;
 
 // ------------------------------------------------------------------------------------------------
@@ -55,6 +58,16 @@ impl Sourced for Lexer<'_> {
     fn source_str(&self) -> &str {
         self.source.source_str()
     }
+
+    #[inline(always)]
+    fn source_offset(&self) -> Index {
+        self.source.source_offset()
+    }
+
+    #[inline(always)]
+    fn file_id(&self) -> Option<FileId> {
+        self.source.file_id()
+    }
 }
 
 impl<'a> Lexer<'a> {
@@ -62,9 +75,21 @@ impl<'a> Lexer<'a> {
         TokenIter::from(self.source.char_indices())
     }
 
+    ///
+    /// Returns the tokens of this source in recovering mode, see [`TokenIter::with_recovery`].
+    ///
+    pub fn tokens_with_recovery(&'a self) -> TokenIter<'a> {
+        self.tokens().with_recovery()
+    }
+
+    #[inline(always)]
+    pub fn line_index(&self) -> &LineIndex {
+        self.source.line_index()
+    }
+
     #[inline(always)]
     pub fn token_str(&self, token: &Token) -> &str {
-        self.get(token.byte_span().as_range()).unwrap()
+        self.get(token.source_range(self.source_offset())).unwrap()
     }
 }
 
@@ -10,6 +10,7 @@ YYYYY
 */
 
 use crate::input::indices::{CharIndex, Index};
+use crate::source_map::FileId;
 use std::{
     fmt::Display,
     ops::{Range, RangeInclusive},
@@ -23,6 +24,7 @@ use std::{
 pub struct Span {
     start: usize,
     end: usize,
+    file: Option<FileId>,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -82,7 +84,23 @@ impl Span {
     #[inline(always)]
     pub fn new(start: usize, end: usize) -> Self {
         assert!(start <= end);
-        Self { start, end }
+        Self {
+            start,
+            end,
+            file: None,
+        }
+    }
+
+    ///
+    /// Returns a copy of this span that records the file, in a
+    /// [`SourceMap`](crate::source_map::SourceMap), that it refers to.
+    ///
+    #[inline(always)]
+    pub fn with_file(&self, file: FileId) -> Self {
+        Self {
+            file: Some(file),
+            ..*self
+        }
     }
 
     #[inline(always)]
@@ -95,19 +113,45 @@ impl Span {
         Self::new(0, s.chars().count())
     }
 
+    ///
+    /// Returns this span moved `delta` characters toward the end, or start if negative, of
+    /// the source.
+    ///
+    #[inline(always)]
+    pub(crate) fn shifted(&self, delta: isize) -> Self {
+        Self {
+            start: self.start.checked_add_signed(delta).unwrap(),
+            end: self.end.checked_add_signed(delta).unwrap(),
+            ..*self
+        }
+    }
+
+    ///
+    /// Returns this span moved `delta` characters, or `None` if that would move it before the
+    /// start of the source.
+    ///
+    #[inline(always)]
+    pub(crate) fn checked_shifted(&self, delta: isize) -> Option<Self> {
+        Some(Self {
+            start: self.start.checked_add_signed(delta)?,
+            end: self.end.checked_add_signed(delta)?,
+            ..*self
+        })
+    }
+
     #[inline(always)]
     pub fn with_start_from(&self, other: Self) -> Self {
         Self {
             start: other.start,
-            end: self.end,
+            ..*self
         }
     }
 
     #[inline(always)]
     pub fn with_end_from(&self, other: Self) -> Self {
         Self {
-            start: self.start,
             end: other.end,
+            ..*self
         }
     }
 
@@ -121,6 +165,11 @@ impl Span {
         self.end
     }
 
+    #[inline(always)]
+    pub fn file(&self) -> Option<FileId> {
+        self.file
+    }
+
     #[inline(always)]
     pub fn is_empty(&self) -> bool {
         self.start == self.end
@@ -181,6 +230,15 @@ impl Token {
         }
     }
 
+    #[inline(always)]
+    pub(crate) fn with_file(&self, file: FileId) -> Self {
+        Self {
+            character_span: self.character_span.with_file(file),
+            byte_span: self.byte_span.with_file(file),
+            ..*self
+        }
+    }
+
     #[inline(always)]
     pub fn kind(&self) -> TokenKind {
         self.kind
@@ -192,10 +250,16 @@ impl Token {
     }
 
     #[inline(always)]
-    pub(super) fn byte_span(&self) -> Span {
+    pub(crate) fn byte_span(&self) -> Span {
         self.byte_span
     }
 
+    #[inline(always)]
+    pub(super) fn source_range(&self, offset: Index) -> Range<usize> {
+        let byte_span = self.byte_span();
+        byte_span.start() - offset.byte()..byte_span.end() - offset.byte()
+    }
+
     #[inline(always)]
     pub fn is_empty(&self) -> bool {
         self.character_span.start == self.character_span.end
@@ -239,6 +303,11 @@ impl Token {
         (datum_reference, TokenKind::DatumRef),
         (directive, TokenKind::Directive)
     );
+
+    #[inline(always)]
+    pub fn is_comment(&self) -> bool {
+        self.is_line_comment() || self.is_block_comment() || self.is_datum_comment()
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -40,7 +40,8 @@ YYYYY
 #![deny(
     // ---------- Public
     exported_private_dependencies,
-    private_in_public,
+    private_interfaces,
+    private_bounds,
     // ---------- Deprecated
     anonymous_parameters,
     bare_trait_objects,
@@ -50,9 +51,12 @@ YYYYY
     drop_bounds,
     dyn_drop,
 )]
-#![forbid(unsafe_code)]
+#![cfg_attr(not(feature = "mmap"), forbid(unsafe_code))]
+#![cfg_attr(feature = "mmap", deny(unsafe_code))]
 
-use std::{path::PathBuf, slice::SliceIndex};
+use crate::input::indices::Index;
+use crate::source_map::FileId;
+use std::{fmt::Display, path::PathBuf, slice::SliceIndex};
 
 // ------------------------------------------------------------------------------------------------
 // Public Types
@@ -63,6 +67,8 @@ pub enum SourceId {
     File(PathBuf),
     StdIn,
     String,
+    /// A virtual source, such as a REPL cell, generated code, or a URL.
+    Named(String),
 }
 
 pub trait Sourced {
@@ -70,6 +76,23 @@ pub trait Sourced {
 
     fn source_str(&self) -> &str;
 
+    ///
+    /// The index, within the complete input, of the first character of `source_str`. This is
+    /// only non-zero when the source is a fragment of a larger input, such as a chunk read
+    /// from a stream.
+    ///
+    fn source_offset(&self) -> Index {
+        Index::default()
+    }
+
+    ///
+    /// The identifier of this source within a [`SourceMap`](crate::source_map::SourceMap),
+    /// if it was added to one.
+    ///
+    fn file_id(&self) -> Option<FileId> {
+        None
+    }
+
     fn source_len(&self) -> usize {
         self.source_str().len()
     }
@@ -90,6 +113,17 @@ pub trait Sourced {
 // Implementations
 // ------------------------------------------------------------------------------------------------
 
+impl Display for SourceId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::StdIn => write!(f, "<stdin>"),
+            Self::String => write!(f, "<string>"),
+            Self::Named(name) => write!(f, "{name}"),
+        }
+    }
+}
+
 // ------------------------------------------------------------------------------------------------
 // Modules
 // ------------------------------------------------------------------------------------------------
@@ -99,10 +133,28 @@ mod macros;
 
 mod syntax;
 
+pub mod convert;
+
 pub mod error;
 
 pub mod input;
 
 pub mod lexer;
 
+pub mod pattern;
+
+pub mod query;
+
 pub mod reader;
+
+pub mod source_map;
+
+pub mod visit;
+
+pub mod writer;
+
+#[cfg(feature = "serde")]
+pub mod de;
+
+#[cfg(feature = "serde")]
+pub mod ser;
@@ -0,0 +1,666 @@
+/*!
+Provides structural patterns over datums, compiled from a datum or its text and matched
+following the rules of R7RS `syntax-rules`.
+
+Within a pattern:
+
+* an identifier listed as a literal matches only an identifier of the same name,
+* `_` matches any datum, and binds nothing,
+* any other identifier is a pattern variable that matches any datum, and binds to it,
+* a pattern followed by the ellipsis, `...`, matches zero or more elements of a list or vector;
+  there may be one ellipsis in each list or vector, followed by any number of patterns,
+* a list pattern may have a dotted tail, `(a b . rest)`; without an ellipsis the tail matches
+  the rest of the list after the elements before it, with an ellipsis it matches the final
+  `cdr`, which is `()` for a proper list,
+* a vector pattern, `#(...)`, matches a vector in the same way as a list,
+* any other datum matches an equal datum.
+
+A variable within `n` ellipses binds to a [`Binding`] nested `n` levels deep, one for each
+repetition. Unlike `syntax-rules` the first element of a list pattern is not ignored; use `_`
+there to ignore the keyword of a form. A shared datum, from a datum label, is matched by the
+datum it refers to, and a variable binds to the shared datum itself. A circular list is never
+a proper list, and so is only matched by a list pattern with a dotted tail.
+
+# Example
+
+```rust
+use ffsr::pattern::Pattern;
+use std::str::FromStr;
+
+let pattern = Pattern::from_str("(define (name . args) body ...)").unwrap();
+
+let form = ffsr::reader::Reader::from(ffsr::lexer::Lexer::from(
+    "(define (square x) (display x) (* x x))",
+))
+.iter()
+.next()
+.unwrap()
+.unwrap();
+
+let bindings = pattern.matches(&form).unwrap();
+assert_eq!(bindings.get("name").unwrap().to_string(), "square");
+assert_eq!(bindings.get("args").unwrap().to_string(), "(x)");
+assert_eq!(
+    bindings.get("body").unwrap().to_string(),
+    "[(display x) (* x x)]"
+);
+```
+
+*/
+
+use crate::error::{invalid_pattern, Error};
+use crate::lexer::token::Span;
+use crate::lexer::Lexer;
+use crate::reader::datum::{Datum, SIdentifier, SList};
+use crate::reader::Reader;
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::str::FromStr;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Compiles patterns with a set of literals, and an ellipsis identifier.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PatternCompiler {
+    literals: Vec<String>,
+    ellipsis: String,
+}
+
+///
+/// A compiled pattern.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pattern {
+    root: Node,
+    variables: Vec<Variable>,
+}
+
+///
+/// The datums bound to a single pattern variable.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum Binding {
+    /// The datum matched by a variable not within an ellipsis.
+    One(Datum),
+    /// One binding for each repetition of the ellipsis that the variable is within.
+    Many(Vec<Binding>),
+}
+
+///
+/// The binding of each variable in a pattern, from a successful match.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bindings {
+    values: Vec<(String, Binding)>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq)]
+struct Variable {
+    name: String,
+    depth: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum QuoteKind {
+    Quote,
+    QuasiQuote,
+    Unquote,
+    UnquoteSplicing,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Any,
+    Variable(usize),
+    Literal(String),
+    Constant(Datum),
+    Quote(QuoteKind, Box<Node>),
+    List {
+        before: Vec<Node>,
+        repeat: Option<Repeat>,
+        tail: Option<Box<Node>>,
+    },
+    Vector {
+        before: Vec<Node>,
+        repeat: Option<Repeat>,
+    },
+}
+
+///
+/// A pattern followed by an ellipsis, the patterns after it, and the variables within it.
+///
+#[derive(Clone, Debug, PartialEq)]
+struct Repeat {
+    node: Box<Node>,
+    after: Vec<Node>,
+    variables: Vec<usize>,
+}
+
+struct Compiler<'a> {
+    options: &'a PatternCompiler,
+    variables: Vec<Variable>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Values
+// ------------------------------------------------------------------------------------------------
+
+pub const DEFAULT_ELLIPSIS: &str = "...";
+
+pub const UNDERSCORE: &str = "_";
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Default for PatternCompiler {
+    fn default() -> Self {
+        Self {
+            literals: Vec::new(),
+            ellipsis: DEFAULT_ELLIPSIS.to_string(),
+        }
+    }
+}
+
+impl PatternCompiler {
+    pub fn with_literals<I, S>(self, literals: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            literals: literals.into_iter().map(Into::into).collect(),
+            ..self
+        }
+    }
+
+    pub fn with_ellipsis<S>(self, ellipsis: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            ellipsis: ellipsis.into(),
+            ..self
+        }
+    }
+
+    #[inline(always)]
+    pub fn literals(&self) -> &[String] {
+        &self.literals
+    }
+
+    #[inline(always)]
+    pub fn ellipsis(&self) -> &str {
+        &self.ellipsis
+    }
+
+    pub fn compile(&self, datum: &Datum) -> Result<Pattern, Error> {
+        self.compile_in_span(datum, None)
+    }
+
+    ///
+    /// Compile the single datum in `s`; any error has the span of that datum.
+    ///
+    pub fn compile_str(&self, s: &str) -> Result<Pattern, Error> {
+        let reader = Reader::from(Lexer::from(s));
+        let mut iter = reader.syntax_iter();
+        let datum = match iter.next() {
+            Some(datum) => datum?,
+            None => return invalid_pattern(None, "expecting a datum, found none"),
+        };
+        if let Some(next) = iter.next() {
+            return invalid_pattern(Some(next?.span()), "expecting a single datum");
+        }
+        self.compile_in_span(&datum.to_datum(), Some(datum.span()))
+    }
+
+    fn compile_in_span(&self, datum: &Datum, span: Option<Span>) -> Result<Pattern, Error> {
+        let mut compiler = Compiler {
+            options: self,
+            variables: Vec::new(),
+        };
+        match compiler.node(datum, 0) {
+            Ok(root) => Ok(Pattern {
+                root,
+                variables: compiler.variables,
+            }),
+            Err(message) => invalid_pattern(span, message),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl FromStr for Pattern {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PatternCompiler::default().compile_str(s)
+    }
+}
+
+impl TryFrom<&Datum> for Pattern {
+    type Error = Error;
+
+    fn try_from(datum: &Datum) -> Result<Self, Self::Error> {
+        PatternCompiler::default().compile(datum)
+    }
+}
+
+impl Pattern {
+    ///
+    /// The name of each pattern variable, and the number of ellipses it is within, in the
+    /// order they appear in the pattern.
+    ///
+    pub fn variables(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.variables
+            .iter()
+            .map(|variable| (variable.name.as_str(), variable.depth))
+    }
+
+    ///
+    /// Match `datum` against this pattern, returning the binding of each variable if it
+    /// matches.
+    ///
+    pub fn matches(&self, datum: &Datum) -> Option<Bindings> {
+        let mut values = vec![None; self.variables.len()];
+        if !match_node(&self.root, datum, &mut values) {
+            return None;
+        }
+        Some(Bindings {
+            values: self
+                .variables
+                .iter()
+                .zip(values)
+                .map(|(variable, value)| (variable.name.clone(), value.unwrap()))
+                .collect(),
+        })
+    }
+
+    #[inline(always)]
+    pub fn is_match(&self, datum: &Datum) -> bool {
+        self.matches(datum).is_some()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A single binding is written as its datum, a repeated binding as its bindings between
+/// `[` and `]`.
+///
+impl Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::One(datum) => write!(f, "{datum}"),
+            Self::Many(bindings) => write!(
+                f,
+                "[{}]",
+                bindings
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
+        }
+    }
+}
+
+impl Binding {
+    #[inline(always)]
+    pub fn as_datum(&self) -> Option<&Datum> {
+        match self {
+            Self::One(datum) => Some(datum),
+            Self::Many(_) => None,
+        }
+    }
+
+    #[inline(always)]
+    pub fn as_many(&self) -> Option<&[Binding]> {
+        match self {
+            Self::One(_) => None,
+            Self::Many(bindings) => Some(bindings),
+        }
+    }
+
+    ///
+    /// Returns every datum in this binding, in the order they were matched.
+    ///
+    pub fn datums(&self) -> Vec<&Datum> {
+        match self {
+            Self::One(datum) => vec![datum],
+            Self::Many(bindings) => bindings.iter().flat_map(Binding::datums).collect(),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Bindings {
+    pub fn get(&self, name: &str) -> Option<&Binding> {
+        self.values
+            .iter()
+            .find(|(variable, _)| variable == name)
+            .map(|(_, binding)| binding)
+    }
+
+    ///
+    /// The datum bound to `name`, if it is not within an ellipsis.
+    ///
+    pub fn datum(&self, name: &str) -> Option<&Datum> {
+        self.get(name).and_then(Binding::as_datum)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Binding)> {
+        self.values
+            .iter()
+            .map(|(name, binding)| (name.as_str(), binding))
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Compiler<'_> {
+    fn node(&mut self, datum: &Datum, depth: usize) -> Result<Node, String> {
+        Ok(match datum {
+            Datum::Identifier(identifier) => self.identifier(identifier, depth)?,
+            Datum::Quote(v) => Node::Quote(QuoteKind::Quote, Box::new(self.node(v, depth)?)),
+            Datum::QuasiQuote(v) => {
+                Node::Quote(QuoteKind::QuasiQuote, Box::new(self.node(v, depth)?))
+            }
+            Datum::Unquote(v) => Node::Quote(QuoteKind::Unquote, Box::new(self.node(v, depth)?)),
+            Datum::UnquoteSplicing(v) => {
+                Node::Quote(QuoteKind::UnquoteSplicing, Box::new(self.node(v, depth)?))
+            }
+            Datum::List(list) => {
+                let (elements, tail) = list_parts(list);
+                let (before, repeat) = self.sequence(&elements, depth)?;
+                let tail = match tail {
+                    Datum::List(list) if list.is_empty() => None,
+                    tail if self.is_ellipsis(&tail) => {
+                        return Err("an ellipsis cannot be the tail of a list".to_string())
+                    }
+                    tail => Some(Box::new(self.node(&tail, depth)?)),
+                };
+                Node::List {
+                    before,
+                    repeat,
+                    tail,
+                }
+            }
+            Datum::Vector(vector) => {
+                let elements: Vec<Datum> = vector.iter().cloned().collect();
+                let (before, repeat) = self.sequence(&elements, depth)?;
+                Node::Vector { before, repeat }
+            }
+            Datum::Boolean(_)
+            | Datum::Char(_)
+            | Datum::Number(_)
+            | Datum::String(_)
+            | Datum::ByteVector(_) => Node::Constant(datum.clone()),
+            Datum::Comment(_) | Datum::Directive(_) | Datum::Shared(_) | Datum::Error(_) => {
+                return Err(format!("a {} cannot be a pattern", datum.type_string()))
+            }
+        })
+    }
+
+    fn identifier(&mut self, identifier: &SIdentifier, depth: usize) -> Result<Node, String> {
+        let name = identifier.as_str();
+        if self.options.literals.iter().any(|literal| literal == name) {
+            Ok(Node::Literal(name.to_string()))
+        } else if name == self.options.ellipsis {
+            Err("an ellipsis must follow a pattern within a list or vector".to_string())
+        } else if name == UNDERSCORE {
+            Ok(Node::Any)
+        } else if self.variables.iter().any(|variable| variable.name == name) {
+            Err(format!(
+                "the pattern variable `{name}` appears more than once"
+            ))
+        } else {
+            self.variables.push(Variable {
+                name: name.to_string(),
+                depth,
+            });
+            Ok(Node::Variable(self.variables.len() - 1))
+        }
+    }
+
+    ///
+    /// Compile the elements of a list or vector, splitting them at the ellipsis, if any.
+    ///
+    fn sequence(
+        &mut self,
+        elements: &[Datum],
+        depth: usize,
+    ) -> Result<(Vec<Node>, Option<Repeat>), String> {
+        let position = elements
+            .iter()
+            .position(|element| self.is_ellipsis(element));
+        let position = match position {
+            None => {
+                let before = elements
+                    .iter()
+                    .map(|element| self.node(element, depth))
+                    .collect::<Result<_, _>>()?;
+                return Ok((before, None));
+            }
+            Some(0) => {
+                return Err("an ellipsis must follow a pattern within a list or vector".to_string())
+            }
+            Some(position) => position,
+        };
+        let before = elements[..position - 1]
+            .iter()
+            .map(|element| self.node(element, depth))
+            .collect::<Result<_, _>>()?;
+        let first = self.variables.len();
+        let node = Box::new(self.node(&elements[position - 1], depth + 1)?);
+        let variables = (first..self.variables.len()).collect();
+        let rest = &elements[position + 1..];
+        if rest.iter().any(|element| self.is_ellipsis(element)) {
+            return Err("there may only be one ellipsis in a list or vector".to_string());
+        }
+        let after = rest
+            .iter()
+            .map(|element| self.node(element, depth))
+            .collect::<Result<_, _>>()?;
+        Ok((
+            before,
+            Some(Repeat {
+                node,
+                after,
+                variables,
+            }),
+        ))
+    }
+
+    fn is_ellipsis(&self, datum: &Datum) -> bool {
+        match datum {
+            Datum::Identifier(identifier) => {
+                let name = identifier.as_str();
+                name == self.options.ellipsis
+                    && !self.options.literals.iter().any(|literal| literal == name)
+            }
+            _ => false,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn match_node(node: &Node, datum: &Datum, values: &mut [Option<Binding>]) -> bool {
+    match (node, datum) {
+        (Node::Any, _) => true,
+        (Node::Variable(index), _) => {
+            values[*index] = Some(Binding::One(datum.clone()));
+            true
+        }
+        (_, Datum::Shared(shared)) => match shared.get() {
+            Some(datum) => match_node(node, &datum, values),
+            None => false,
+        },
+        (Node::Literal(name), Datum::Identifier(identifier)) => identifier.as_str() == name,
+        (Node::Constant(constant), _) => constant == datum,
+        (Node::Quote(kind, node), datum) => match (kind, datum) {
+            (QuoteKind::Quote, Datum::Quote(v))
+            | (QuoteKind::QuasiQuote, Datum::QuasiQuote(v))
+            | (QuoteKind::Unquote, Datum::Unquote(v))
+            | (QuoteKind::UnquoteSplicing, Datum::UnquoteSplicing(v)) => {
+                match_node(node, v, values)
+            }
+            _ => false,
+        },
+        (
+            Node::List {
+                before,
+                repeat,
+                tail,
+            },
+            Datum::List(list),
+        ) => match_list(before, repeat.as_ref(), tail.as_deref(), list, values),
+        (Node::Vector { before, repeat }, Datum::Vector(vector)) => {
+            let elements: Vec<Datum> = vector.iter().cloned().collect();
+            match_sequence(before, repeat.as_ref(), &elements, values)
+        }
+        _ => false,
+    }
+}
+
+fn match_list(
+    before: &[Node],
+    repeat: Option<&Repeat>,
+    tail: Option<&Node>,
+    list: &SList,
+    values: &mut [Option<Binding>],
+) -> bool {
+    match (repeat, tail) {
+        (None, Some(tail)) => {
+            // the tail matches whatever follows the elements before it.
+            let mut rest = Datum::List(list.clone());
+            for node in before {
+                if let Datum::Shared(shared) = &rest {
+                    match shared.get() {
+                        Some(datum) => rest = datum.as_ref().clone(),
+                        None => return false,
+                    }
+                }
+                let pair = match &rest {
+                    Datum::List(list) => match list.as_pair() {
+                        Some(pair) => pair.clone(),
+                        None => return false,
+                    },
+                    _ => return false,
+                };
+                if !match_node(node, pair.car(), values) {
+                    return false;
+                }
+                rest = pair.cdr().as_ref().clone();
+            }
+            match_node(tail, &rest, values)
+        }
+        (repeat, tail) => {
+            let (elements, rest) = list_parts(list);
+            let tail_matches = match tail {
+                Some(tail) => match_node(tail, &rest, values),
+                None => matches!(&rest, Datum::List(list) if list.is_empty()),
+            };
+            tail_matches && match_sequence(before, repeat, &elements, values)
+        }
+    }
+}
+
+fn match_sequence(
+    before: &[Node],
+    repeat: Option<&Repeat>,
+    elements: &[Datum],
+    values: &mut [Option<Binding>],
+) -> bool {
+    let repeat = match repeat {
+        None => {
+            return before.len() == elements.len()
+                && before
+                    .iter()
+                    .zip(elements)
+                    .all(|(node, element)| match_node(node, element, values))
+        }
+        Some(repeat) => repeat,
+    };
+    if elements.len() < before.len() + repeat.after.len() {
+        return false;
+    }
+    let repeated_end = elements.len() - repeat.after.len();
+    if !before
+        .iter()
+        .zip(elements)
+        .all(|(node, element)| match_node(node, element, values))
+    {
+        return false;
+    }
+    if !repeat
+        .after
+        .iter()
+        .zip(&elements[repeated_end..])
+        .all(|(node, element)| match_node(node, element, values))
+    {
+        return false;
+    }
+    let mut repetitions: Vec<Vec<Binding>> = vec![Vec::new(); repeat.variables.len()];
+    for element in &elements[before.len()..repeated_end] {
+        if !match_node(&repeat.node, element, values) {
+            return false;
+        }
+        for (repetition, index) in repetitions.iter_mut().zip(&repeat.variables) {
+            repetition.push(values[*index].take().unwrap());
+        }
+    }
+    for (repetition, index) in repetitions.into_iter().zip(&repeat.variables) {
+        values[*index] = Some(Binding::Many(repetition));
+    }
+    true
+}
+
+///
+/// The elements of `list`, and the final `cdr`, which is the empty list for a proper list. A
+/// shared `cdr` is followed, unless it has been followed already, in which case the list is
+/// circular and the shared `cdr` is returned as its tail.
+///
+fn list_parts(list: &SList) -> (Vec<Datum>, Datum) {
+    let mut elements = Vec::new();
+    let mut visited = HashSet::new();
+    let mut list = list.clone();
+    loop {
+        let pair = match list.as_pair() {
+            Some(pair) => pair.clone(),
+            None => return (elements, SList::empty().into()),
+        };
+        elements.push(pair.car().as_ref().clone());
+        match pair.cdr().as_ref() {
+            Datum::List(next) => list = next.clone(),
+            Datum::Shared(shared) if visited.insert(shared.id()) => match shared.get().as_deref() {
+                Some(Datum::List(next)) => list = next.clone(),
+                _ => return (elements, pair.cdr().as_ref().clone()),
+            },
+            tail => return (elements, tail.clone()),
+        }
+    }
+}
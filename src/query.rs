@@ -0,0 +1,450 @@
+/*!
+Provides a path query language for selecting datums within a tree of datums, or of
+[`SyntaxDatum`]s when the spans of the selected datums are needed.
+
+A query is a sequence of steps, each selecting from the children of the datums selected by
+the step before it; the first step selects from the datums given to [`Query::select`]. The
+children of a list are its elements, not including the tail of an improper list, the children
+of a vector are its elements, and the only child of a quoted datum is the datum quoted. A
+labelled datum, `#n=`, has the children of the datum it labels, while a reference to it from
+within itself, `#n#`, has none, so that a query on circular structure terminates.
+
+| Step       | Selects                                                                    |
+|------------|----------------------------------------------------------------------------|
+| `/name`    | each child that is a list starting with the symbol `name`                  |
+| `/N`       | the child at index `N`, from zero; a negative index counts from the end    |
+| `/` `*`    | every child                                                                |
+| `//step`   | as `/step`, from the children of the datums and all of their descendants   |
+| `step[t]`  | only the datums selected by `step` whose [`type_string`] is `t`            |
+
+The `/` before the first step may be omitted, and a name may be written between `|`, as in
+`|a/b|`, if it contains a character used by a query. Selected datums are returned once, in
+the order they are found, depth first.
+
+[`type_string`]: Datum::type_string
+
+# Example
+
+```rust
+use ffsr::lexer::Lexer;
+use ffsr::query::Query;
+use ffsr::reader::Reader;
+use std::str::FromStr;
+
+let source = r#"
+(define-library (example)
+  (import (scheme base) (only (scheme write) display))
+  (export run))
+"#;
+let forms: Vec<_> = Reader::from(Lexer::from(source))
+    .syntax_iter()
+    .collect::<Result<_, _>>()
+    .unwrap();
+
+// the second argument of every import in a library
+let query = Query::from_str("define-library/import/2").unwrap();
+let selected = query.select(&forms);
+assert_eq!(selected.len(), 1);
+assert_eq!(selected[0].to_datum().to_string(), "(only (scheme write) display)");
+assert_eq!(selected[0].span().as_range(), 51..80);
+
+// the symbol at index 1 of any list within an import, `base` and `write`
+let query = Query::from_str("define-library/import//1[identifier]").unwrap();
+assert_eq!(query.select(&forms).len(), 2);
+```
+
+*/
+
+use crate::error::{invalid_query, Error};
+use crate::lexer::token::Span;
+use crate::reader::datum::Datum;
+use crate::reader::syntax::{SyntaxDatum, SyntaxValue};
+use crate::syntax::IDENTIFIER_WRAPPER;
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::str::FromStr;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A tree of datums that a [`Query`] can select from.
+///
+pub trait Queryable {
+    fn children(&self) -> Vec<&Self>;
+
+    ///
+    /// The name of the symbol that starts this list, without any `|` around it.
+    ///
+    fn head_symbol(&self) -> Option<&str>;
+
+    fn type_string(&self) -> &'static str;
+}
+
+///
+/// A parsed query.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Query {
+    steps: Vec<Step>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Step {
+    descendants: bool,
+    selector: Selector,
+    types: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Selector {
+    All,
+    Index(isize),
+    Head(String),
+}
+
+struct Parser {
+    chars: Vec<char>,
+    index: usize,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Values
+// ------------------------------------------------------------------------------------------------
+
+const SEPARATOR: char = '/';
+const ALL: char = '*';
+const FILTER_START: char = '[';
+const FILTER_END: char = ']';
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Queryable for Datum {
+    fn children(&self) -> Vec<&Self> {
+        match self {
+            Self::Quote(v) | Self::QuasiQuote(v) | Self::Unquote(v) | Self::UnquoteSplicing(v) => {
+                vec![v.as_ref()]
+            }
+            Self::List(list) => {
+                let mut children = Vec::new();
+                let mut pair = list.as_pair();
+                while let Some(current) = pair {
+                    children.push(current.car().as_ref());
+                    pair = match current.cdr().as_ref() {
+                        Self::List(next) => next.as_pair(),
+                        _ => None,
+                    };
+                }
+                children
+            }
+            Self::Vector(vector) => vector.iter().collect(),
+            Self::Shared(shared) => shared.get_ref().map_or_else(Vec::new, Self::children),
+            _ => Vec::new(),
+        }
+    }
+
+    fn head_symbol(&self) -> Option<&str> {
+        match self {
+            Self::List(list) => match list.as_pair()?.car().as_ref() {
+                Self::Identifier(name) => Some(unwrap_name(name.as_str())),
+                _ => None,
+            },
+            Self::Shared(shared) => shared.get_ref()?.head_symbol(),
+            _ => None,
+        }
+    }
+
+    #[inline(always)]
+    fn type_string(&self) -> &'static str {
+        Datum::type_string(self)
+    }
+}
+
+impl Queryable for SyntaxDatum {
+    fn children(&self) -> Vec<&Self> {
+        match self.value() {
+            SyntaxValue::Quote(v)
+            | SyntaxValue::QuasiQuote(v)
+            | SyntaxValue::Unquote(v)
+            | SyntaxValue::UnquoteSplicing(v) => vec![v.as_ref()],
+            SyntaxValue::List(elements, _) | SyntaxValue::Vector(elements) => {
+                elements.iter().collect()
+            }
+            SyntaxValue::Atom(_) | SyntaxValue::ByteVector(_) => Vec::new(),
+        }
+    }
+
+    fn head_symbol(&self) -> Option<&str> {
+        match self.value() {
+            SyntaxValue::List(elements, _) => match elements.first()?.value() {
+                SyntaxValue::Atom(Datum::Identifier(name)) => Some(unwrap_name(name.as_str())),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn type_string(&self) -> &'static str {
+        match self.value() {
+            SyntaxValue::Quote(_) => "quote",
+            SyntaxValue::QuasiQuote(_) => "quasiquote",
+            SyntaxValue::Unquote(_) => "unquote",
+            SyntaxValue::UnquoteSplicing(_) => "unquote-splicing",
+            SyntaxValue::Atom(atom) => atom.type_string(),
+            SyntaxValue::List(_, _) => "pair-or-list",
+            SyntaxValue::Vector(_) => "vector",
+            SyntaxValue::ByteVector(_) => "byte-vector",
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Query {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for step in &self.steps {
+            write!(f, "{}", SEPARATOR)?;
+            if step.descendants {
+                write!(f, "{}", SEPARATOR)?;
+            }
+            match &step.selector {
+                Selector::All => write!(f, "{}", ALL)?,
+                Selector::Index(index) => write!(f, "{}", index)?,
+                Selector::Head(name) if needs_bars(name) => {
+                    write!(f, "{}{}{}", IDENTIFIER_WRAPPER, name, IDENTIFIER_WRAPPER)?
+                }
+                Selector::Head(name) => write!(f, "{}", name)?,
+            }
+            for type_string in &step.types {
+                write!(f, "{}{}{}", FILTER_START, type_string, FILTER_END)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Query {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Parser {
+            chars: s.chars().collect(),
+            index: 0,
+        }
+        .query()
+    }
+}
+
+impl Query {
+    ///
+    /// Returns the datums selected by this query, starting from `roots`, such as the datums
+    /// read from a source file.
+    ///
+    pub fn select<'a, N, I>(&self, roots: I) -> Vec<&'a N>
+    where
+        N: Queryable + 'a,
+        I: IntoIterator<Item = &'a N>,
+    {
+        let mut parents: Vec<Vec<&'a N>> = vec![roots.into_iter().collect()];
+        let mut selected = Vec::new();
+        for step in &self.steps {
+            let mut seen = HashSet::new();
+            let mut entered = HashSet::new();
+            selected.clear();
+            for children in &parents {
+                step.select(children, &mut seen, &mut entered, &mut selected);
+            }
+            parents = selected.iter().map(|node| node.children()).collect();
+        }
+        selected
+    }
+
+    ///
+    /// Returns the first datum selected by this query, if any.
+    ///
+    pub fn select_first<'a, N, I>(&self, roots: I) -> Option<&'a N>
+    where
+        N: Queryable + 'a,
+        I: IntoIterator<Item = &'a N>,
+    {
+        self.select(roots).into_iter().next()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Step {
+    ///
+    /// Add the matching nodes from `children`, or from `children` and their descendants, to
+    /// `selected` in preorder, skipping any already `seen`. The descendants of a node are
+    /// only searched the first time it is `entered`, as a shared datum is reached from more
+    /// than one place.
+    ///
+    fn select<'a, N: Queryable>(
+        &self,
+        children: &[&'a N],
+        seen: &mut HashSet<*const N>,
+        entered: &mut HashSet<*const N>,
+        selected: &mut Vec<&'a N>,
+    ) {
+        for (index, child) in children.iter().copied().enumerate() {
+            if self.is_match(child, index, children.len()) && seen.insert(std::ptr::from_ref(child))
+            {
+                selected.push(child);
+            }
+            if self.descendants && entered.insert(std::ptr::from_ref(child)) {
+                self.select(&child.children(), seen, entered, selected);
+            }
+        }
+    }
+
+    fn is_match<N: Queryable>(&self, node: &N, index: usize, siblings: usize) -> bool {
+        let selected = match &self.selector {
+            Selector::All => true,
+            Selector::Index(expected) if *expected < 0 => {
+                siblings.checked_sub(expected.unsigned_abs()) == Some(index)
+            }
+            Selector::Index(expected) => *expected as usize == index,
+            Selector::Head(name) => node.head_symbol() == Some(name.as_str()),
+        };
+        selected
+            && self
+                .types
+                .iter()
+                .all(|type_string| node.type_string() == type_string)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Parser {
+    fn query(mut self) -> Result<Query, Error> {
+        let mut steps = Vec::new();
+        while self.index < self.chars.len() || steps.is_empty() {
+            let descendants = if self.eat(SEPARATOR) {
+                self.eat(SEPARATOR)
+            } else if steps.is_empty() {
+                false
+            } else {
+                return self.error(0, "expecting `/` before a step");
+            };
+            let selector = self.selector()?;
+            let mut types = Vec::new();
+            while self.eat(FILTER_START) {
+                let start = self.index;
+                while self.peek().is_some_and(|c| c != FILTER_END) {
+                    self.index += 1;
+                }
+                let type_string: String = self.chars[start..self.index].iter().collect();
+                if !self.eat(FILTER_END) {
+                    return self.error(0, "expecting `]` to end a type filter");
+                }
+                if type_string.trim().is_empty() {
+                    return self.error(2, "expecting a type name between `[` and `]`");
+                }
+                types.push(type_string.trim().to_string());
+            }
+            steps.push(Step {
+                descendants,
+                selector,
+                types,
+            });
+        }
+        Ok(Query { steps })
+    }
+
+    fn selector(&mut self) -> Result<Selector, Error> {
+        let start = self.index;
+        if self.eat(IDENTIFIER_WRAPPER) {
+            while self.peek().is_some_and(|c| c != IDENTIFIER_WRAPPER) {
+                self.index += 1;
+            }
+            if !self.eat(IDENTIFIER_WRAPPER) {
+                return self.error(self.index - start, "expecting `|` to end a name");
+            }
+            let name: String = self.chars[start + 1..self.index - 1].iter().collect();
+            return Ok(Selector::Head(name));
+        }
+        while self
+            .peek()
+            .is_some_and(|c| !is_reserved(c) && !c.is_whitespace())
+        {
+            self.index += 1;
+        }
+        let text: String = self.chars[start..self.index].iter().collect();
+        if text.is_empty() {
+            return self.error(0, "expecting a name, an index, or `*`");
+        }
+        if text == ALL.to_string() {
+            Ok(Selector::All)
+        } else if is_index(&text) {
+            match text.parse() {
+                Ok(index) => Ok(Selector::Index(index)),
+                Err(_) => self.error(text.chars().count(), "the index is too large"),
+            }
+        } else {
+            Ok(Selector::Head(text))
+        }
+    }
+
+    #[inline(always)]
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    ///
+    /// An error for the `len` characters before the current one, or the current character if
+    /// `len` is zero.
+    ///
+    fn error<T>(&self, len: usize, message: &str) -> Result<T, Error> {
+        let span = if len == 0 {
+            Span::new(self.index, (self.index + 1).min(self.chars.len()))
+        } else {
+            Span::new(self.index.saturating_sub(len), self.index)
+        };
+        invalid_query(span, message)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn unwrap_name(name: &str) -> &str {
+    name.strip_prefix(IDENTIFIER_WRAPPER)
+        .and_then(|name| name.strip_suffix(IDENTIFIER_WRAPPER))
+        .unwrap_or(name)
+}
+
+#[inline(always)]
+fn is_reserved(c: char) -> bool {
+    c == SEPARATOR || c == FILTER_START || c == FILTER_END || c == IDENTIFIER_WRAPPER
+}
+
+fn is_index(s: &str) -> bool {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+fn needs_bars(name: &str) -> bool {
+    name.is_empty()
+        || name == ALL.to_string()
+        || is_index(name)
+        || name.chars().any(|c| is_reserved(c) || c.is_whitespace())
+}
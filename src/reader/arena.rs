@@ -0,0 +1,477 @@
+/*!
+Provides datums allocated in a single bump [`Arena`], read from the tokens of a [`TokenIter`].
+
+Where [`DatumIter`](crate::reader::iter::DatumIter) allocates an `Rc` for every pair and a box
+for every quote, an [`ArenaIter`] places every node of the datums it reads in the arena it is
+given and returns [`ArenaDatum`] values that refer to each other by reference. No value in the
+arena needs to be dropped, so the whole tree is freed at once, and without recursion, when the
+arena is dropped or [reset](Arena::reset). The text of identifiers and strings is copied into
+the arena, and identifiers are only interned when a datum is copied out of it. A labelled compound datum, `#n=`, is held once in an
+[`ArenaShared`] node that every reference to it, `#n#`, shares, so circular structure, as in
+`#0=(a . #0#)`, is kept.
+
+This module requires the `arena` feature.
+
+# Example
+
+```rust
+use ffsr::lexer::Lexer;
+use ffsr::reader::arena::{Arena, ArenaDatum};
+use ffsr::reader::Reader;
+
+let arena = Arena::new();
+let reader = Reader::from(Lexer::from(r#"(define (greet) (display "hello")) #0=(a) #0#"#));
+let data: Vec<ArenaDatum<'_>> = reader
+    .arena_iter(&arena)
+    .collect::<Result<_, _>>()
+    .unwrap();
+
+let elements = data[0].elements().unwrap();
+assert_eq!(elements[0].as_identifier(), Some("define"));
+assert_eq!(data[0].to_datum().to_string(), r#"(define (greet) (display hello))"#);
+
+// a label and its references share the same nodes.
+assert!(std::ptr::eq(data[1].elements().unwrap(), data[2].elements().unwrap()));
+assert!(arena.allocated_bytes() > 0);
+```
+
+*/
+
+use crate::error::Error;
+use crate::lexer::iter::TokenIter;
+use crate::lexer::token::Span;
+use crate::reader::datum::{
+    Datum, SBoolean, SByteVector, SChar, SDirective, SIdentifier, SList, SNumber, SShared, SString,
+    SVector,
+};
+use crate::reader::internals::{byte_value, Builder, QuoteKind};
+use crate::reader::iter::BuildIter;
+use bumpalo::Bump;
+use std::borrow::Cow;
+use std::cell::OnceCell;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::rc::Rc;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A bump arena that holds the nodes of the datums read by an [`ArenaIter`].
+///
+#[derive(Debug, Default)]
+pub struct Arena {
+    bump: Bump,
+}
+
+///
+/// A datum whose nodes are allocated in an [`Arena`].
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum ArenaDatum<'arena> {
+    Quote(&'arena ArenaDatum<'arena>),
+    QuasiQuote(&'arena ArenaDatum<'arena>),
+    Unquote(&'arena ArenaDatum<'arena>),
+    UnquoteSplicing(&'arena ArenaDatum<'arena>),
+    Identifier(&'arena str),
+    Boolean(SBoolean),
+    Char(SChar),
+    Number(SNumber),
+    String(&'arena str),
+    /// The elements of a list, and the tail if the list is improper.
+    List(
+        &'arena [ArenaDatum<'arena>],
+        Option<&'arena ArenaDatum<'arena>>,
+    ),
+    Vector(&'arena [ArenaDatum<'arena>]),
+    ByteVector(&'arena [u8]),
+    Directive(SDirective),
+    /// A labelled compound datum, or a reference to one.
+    Shared(&'arena ArenaShared<'arena>),
+    /// A placeholder for a malformed datum.
+    Error(Span),
+}
+
+///
+/// The datum for a label, `#n=`, shared by every reference to it.
+///
+pub struct ArenaShared<'arena> {
+    label: u16,
+    datum: OnceCell<ArenaDatum<'arena>>,
+}
+
+#[derive(Debug)]
+pub struct ArenaIter<'a, 'arena> {
+    inner: BuildIter<'a, ArenaBuilder<'arena>>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug)]
+struct ArenaBuilder<'arena> {
+    arena: &'arena Arena,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Values
+// ------------------------------------------------------------------------------------------------
+
+// The arena never runs destructors, so no datum may own memory outside of it.
+const _: () = assert!(!std::mem::needs_drop::<ArenaDatum<'static>>());
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Arena {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            bump: Bump::with_capacity(capacity),
+        }
+    }
+
+    ///
+    /// The number of bytes allocated for the arena, including any space not yet used.
+    ///
+    #[inline(always)]
+    pub fn allocated_bytes(&self) -> usize {
+        self.bump.allocated_bytes()
+    }
+
+    ///
+    /// Free every datum in the arena at once, keeping its largest block of memory to reuse.
+    ///
+    #[inline(always)]
+    pub fn reset(&mut self) {
+        self.bump.reset()
+    }
+
+    #[inline(always)]
+    fn alloc<'arena>(&'arena self, datum: ArenaDatum<'arena>) -> &'arena ArenaDatum<'arena> {
+        self.bump.alloc(datum)
+    }
+
+    #[inline(always)]
+    fn alloc_shared<'arena>(&'arena self, label: u16) -> &'arena ArenaShared<'arena> {
+        self.bump.alloc(ArenaShared {
+            label,
+            datum: OnceCell::new(),
+        })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl From<&ArenaDatum<'_>> for Datum {
+    fn from(v: &ArenaDatum<'_>) -> Self {
+        v.to_datum()
+    }
+}
+
+impl<'arena> ArenaDatum<'arena> {
+    pub fn as_identifier(&self) -> Option<&'arena str> {
+        match self {
+            Self::Identifier(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_string(&self) -> Option<&'arena str> {
+        match self {
+            Self::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    ///
+    /// The elements of a list or vector, not including the tail of an improper list.
+    ///
+    pub fn elements(&self) -> Option<&'arena [ArenaDatum<'arena>]> {
+        match self {
+            Self::List(elements, _) | Self::Vector(elements) => Some(elements),
+            Self::Shared(v) => v.get().and_then(ArenaDatum::elements),
+            _ => None,
+        }
+    }
+
+    pub fn type_string(&self) -> &'static str {
+        match self {
+            Self::Quote(_) => "quote",
+            Self::QuasiQuote(_) => "quasiquote",
+            Self::Unquote(_) => "unquote",
+            Self::UnquoteSplicing(_) => "unquote-splicing",
+            Self::Identifier(_) => "identifier",
+            Self::Boolean(_) => "boolean",
+            Self::Char(_) => "char",
+            Self::Number(v) => v.type_string(),
+            Self::String(_) => "string",
+            Self::List(_, _) => "pair-or-list",
+            Self::Vector(_) => "vector",
+            Self::ByteVector(_) => "byte-vector",
+            Self::Directive(_) => "directive",
+            Self::Shared(v) => v.get().map_or("shared", ArenaDatum::type_string),
+            Self::Error(_) => "error",
+        }
+    }
+
+    ///
+    /// Returns the plain datum for this value, the value [`DatumIter`] would have returned,
+    /// copied out of the arena.
+    ///
+    /// [`DatumIter`]: crate::reader::iter::DatumIter
+    ///
+    pub fn to_datum(&self) -> Datum {
+        self.to_datum_sharing(&mut HashMap::new())
+    }
+
+    ///
+    /// Returns the plain datum, with one [`SShared`] in `shared` for each shared node.
+    ///
+    fn to_datum_sharing(&self, shared: &mut HashMap<*const ArenaShared<'arena>, SShared>) -> Datum {
+        match self {
+            Self::Quote(v) => v.to_datum_sharing(shared).quote(),
+            Self::QuasiQuote(v) => v.to_datum_sharing(shared).quasiquote(),
+            Self::Unquote(v) => v.to_datum_sharing(shared).unquote(),
+            Self::UnquoteSplicing(v) => v.to_datum_sharing(shared).unquote_splicing(),
+            Self::Identifier(v) => SIdentifier::intern(v).into(),
+            Self::Boolean(v) => (*v).into(),
+            Self::Char(v) => (*v).into(),
+            Self::Number(v) => v.clone().into(),
+            Self::String(v) => SString::from(v.to_string()).into(),
+            Self::List(elements, tail) => {
+                let mut list =
+                    SList::from_iter(elements.iter().map(|v| v.to_datum_sharing(shared)));
+                if let Some(tail) = tail {
+                    list.append_improper(Rc::new(tail.to_datum_sharing(shared)), None)
+                        .expect("a list with a tail has elements");
+                }
+                list.into()
+            }
+            Self::Vector(elements) => {
+                SVector::from_iter(elements.iter().map(|v| v.to_datum_sharing(shared))).into()
+            }
+            Self::ByteVector(v) => {
+                let mut bytes = SByteVector::default();
+                v.iter().for_each(|byte| bytes.append(*byte));
+                bytes.into()
+            }
+            Self::Directive(v) => (*v).into(),
+            Self::Shared(v) => v.to_datum_sharing(shared),
+            Self::Error(span) => Datum::Error(*span),
+        }
+    }
+
+    ///
+    /// Returns `true` if this is an object with an identity worth sharing.
+    ///
+    fn is_compound(&self) -> bool {
+        match self {
+            Self::List(elements, _) => !elements.is_empty(),
+            Self::Quote(_)
+            | Self::QuasiQuote(_)
+            | Self::Unquote(_)
+            | Self::UnquoteSplicing(_)
+            | Self::Vector(_) => true,
+            _ => false,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Debug for ArenaShared<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // the datum may contain this node, so only the label is written.
+        f.debug_struct("ArenaShared")
+            .field("label", &self.label)
+            .finish_non_exhaustive()
+    }
+}
+
+///
+/// Shared nodes are equal if they are the same node, or if their datums have the same
+/// structure.
+///
+impl PartialEq for ArenaShared<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+            || self.to_datum_sharing(&mut HashMap::new())
+                == other.to_datum_sharing(&mut HashMap::new())
+    }
+}
+
+impl<'arena> ArenaShared<'arena> {
+    #[inline(always)]
+    pub fn label(&self) -> u16 {
+        self.label
+    }
+
+    ///
+    /// Returns the shared datum, or `None` while it is still being read.
+    ///
+    #[inline(always)]
+    pub fn get(&self) -> Option<&ArenaDatum<'arena>> {
+        self.datum.get()
+    }
+
+    fn to_datum_sharing(&self, shared: &mut HashMap<*const ArenaShared<'arena>, SShared>) -> Datum {
+        let key = std::ptr::from_ref(self);
+        if let Some(handle) = shared.get(&key) {
+            // weak while the shared datum itself is still being copied.
+            return handle.reference();
+        }
+        let handle = SShared::new(self.label);
+        shared.insert(key, handle.clone());
+        match self.get() {
+            Some(datum) => handle.assign(datum.to_datum_sharing(shared)),
+            None => Datum::Shared(handle),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<'arena> Iterator for ArenaIter<'_, 'arena> {
+    type Item = Result<ArenaDatum<'arena>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+impl<'a, 'arena> ArenaIter<'a, 'arena> {
+    pub fn new(source: TokenIter<'a>, arena: &'arena Arena) -> Self {
+        Self {
+            inner: BuildIter::new(source, ArenaBuilder { arena }),
+        }
+    }
+
+    ///
+    /// Set whether identifiers and character names are case folded before any `#!fold-case`
+    /// or `#!no-fold-case` directive is read; the default is `false`.
+    ///
+    pub fn with_fold_case(self, fold_case: bool) -> Self {
+        Self {
+            inner: self.inner.with_fold_case(fold_case),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<'a, 'arena> Builder<'a> for ArenaBuilder<'arena> {
+    type Datum = ArenaDatum<'arena>;
+    type Label = &'arena ArenaShared<'arena>;
+
+    fn identifier(&mut self, text: Cow<'a, str>, _: Span) -> ArenaDatum<'arena> {
+        ArenaDatum::Identifier(self.arena.bump.alloc_str(&text))
+    }
+
+    fn string(&mut self, text: Cow<'a, str>, _: Span) -> ArenaDatum<'arena> {
+        ArenaDatum::String(self.arena.bump.alloc_str(&text))
+    }
+
+    fn boolean(&mut self, value: SBoolean, _: Span) -> ArenaDatum<'arena> {
+        ArenaDatum::Boolean(value)
+    }
+
+    fn character(&mut self, value: SChar, _: Span) -> ArenaDatum<'arena> {
+        ArenaDatum::Char(value)
+    }
+
+    fn number(&mut self, value: SNumber, _: Span) -> ArenaDatum<'arena> {
+        ArenaDatum::Number(value)
+    }
+
+    fn directive(&mut self, value: SDirective, _: Span) -> ArenaDatum<'arena> {
+        ArenaDatum::Directive(value)
+    }
+
+    fn error(&mut self, span: Span) -> ArenaDatum<'arena> {
+        ArenaDatum::Error(span)
+    }
+
+    fn byte(datum: &ArenaDatum<'arena>) -> Option<u8> {
+        match datum {
+            ArenaDatum::Number(number) => byte_value(number),
+            _ => None,
+        }
+    }
+
+    fn quote(&mut self, kind: QuoteKind, _: Span, datum: ArenaDatum<'arena>) -> ArenaDatum<'arena> {
+        let inner = self.arena.alloc(datum);
+        match kind {
+            QuoteKind::Quote => ArenaDatum::Quote(inner),
+            QuoteKind::QuasiQuote => ArenaDatum::QuasiQuote(inner),
+            QuoteKind::Unquote => ArenaDatum::Unquote(inner),
+            QuoteKind::UnquoteSplicing => ArenaDatum::UnquoteSplicing(inner),
+        }
+    }
+
+    fn list(
+        &mut self,
+        _: Span,
+        elements: Vec<ArenaDatum<'arena>>,
+        tail: Option<ArenaDatum<'arena>>,
+    ) -> ArenaDatum<'arena> {
+        ArenaDatum::List(
+            self.arena.bump.alloc_slice_fill_iter(elements),
+            tail.map(|tail| self.arena.alloc(tail)),
+        )
+    }
+
+    fn vector(&mut self, _: Span, elements: Vec<ArenaDatum<'arena>>) -> ArenaDatum<'arena> {
+        ArenaDatum::Vector(self.arena.bump.alloc_slice_fill_iter(elements))
+    }
+
+    fn byte_vector(
+        &mut self,
+        _: Span,
+        _: Vec<ArenaDatum<'arena>>,
+        bytes: Vec<u8>,
+    ) -> ArenaDatum<'arena> {
+        ArenaDatum::ByteVector(self.arena.bump.alloc_slice_copy(&bytes))
+    }
+
+    fn label(&mut self, label: u16) -> &'arena ArenaShared<'arena> {
+        self.arena.alloc_shared(label)
+    }
+
+    fn reference(&mut self, shared: &&'arena ArenaShared<'arena>, _: Span) -> ArenaDatum<'arena> {
+        match shared.get() {
+            // a simple datum has no identity to keep, and is copied.
+            Some(datum) if !datum.is_compound() => datum.clone(),
+            _ => ArenaDatum::Shared(shared),
+        }
+    }
+
+    fn assign(
+        &mut self,
+        shared: &mut &'arena ArenaShared<'arena>,
+        datum: ArenaDatum<'arena>,
+    ) -> Option<ArenaDatum<'arena>> {
+        match datum {
+            ArenaDatum::Shared(other) if std::ptr::eq(other, *shared) => None,
+            ArenaDatum::Shared(other) => {
+                // an alias for a label already read, such as `#1=#0#`.
+                *shared = other;
+                Some(datum)
+            }
+            datum if datum.is_compound() => {
+                let _ = shared.datum.set(datum);
+                Some(ArenaDatum::Shared(shared))
+            }
+            datum => {
+                let _ = shared.datum.set(datum.clone());
+                Some(datum)
+            }
+        }
+    }
+}
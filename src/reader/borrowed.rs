@@ -0,0 +1,354 @@
+/*!
+Provides datums that borrow the text of strings and identifiers from the input they are read
+from, read from the tokens of a [`TokenIter`].
+
+Where [`DatumIter`](crate::reader::iter::DatumIter) allocates the text of every string and
+identifier, a [`BorrowedIter`] returns [`BorrowedDatum`] values whose strings and identifiers
+are a [`Cow`] that borrows from the input, and is only owned when escapes, such as `\x41;` or
+`\n`, or case folding change the text. A value is detached from the input with
+[`BorrowedDatum::into_owned`], or converted to a plain [`Datum`]. A labelled compound datum,
+`#n=`, and each reference to it, `#n#`, is held as the same [`SShared`] that `DatumIter`
+would have returned, so shared and circular structure is kept.
+
+# Example
+
+```rust
+use ffsr::lexer::Lexer;
+use ffsr::reader::borrowed::BorrowedDatum;
+use ffsr::reader::datum::Datum;
+use ffsr::reader::Reader;
+
+let reader = Reader::from(Lexer::from(r#"(display "hello" "a\x42;c")"#));
+let datum = reader.borrowed_iter().next().unwrap().unwrap();
+
+let elements = datum.elements().unwrap();
+assert_eq!(elements[0].as_identifier(), Some("display"));
+assert!(elements[1].is_borrowed());
+assert_eq!(elements[2].as_string(), Some("aBc"));
+assert!(!elements[2].is_borrowed());
+
+let owned: BorrowedDatum<'static> = datum.clone().into_owned();
+assert_eq!(owned, datum);
+assert_eq!(Datum::from(owned).to_string(), "(display hello aBc)");
+```
+
+*/
+
+use crate::error::Error;
+use crate::lexer::iter::TokenIter;
+use crate::lexer::token::Span;
+use crate::reader::datum::{
+    Datum, SBoolean, SByteVector, SChar, SDirective, SIdentifier, SList, SNumber, SShared, SString,
+    SVector,
+};
+use crate::reader::internals::{byte_value, Builder, QuoteKind};
+use crate::reader::iter::BuildIter;
+use std::borrow::Cow;
+use std::rc::Rc;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A datum whose strings and identifiers may borrow their text from the input.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum BorrowedDatum<'a> {
+    Quote(Box<BorrowedDatum<'a>>),
+    QuasiQuote(Box<BorrowedDatum<'a>>),
+    Unquote(Box<BorrowedDatum<'a>>),
+    UnquoteSplicing(Box<BorrowedDatum<'a>>),
+    /// The text of an identifier, as returned by [`SIdentifier::as_str`].
+    Identifier(Cow<'a, str>),
+    String(Cow<'a, str>),
+    /// Any other value without children: booleans, characters, numbers, and directives.
+    Atom(Datum),
+    /// The elements of a list, and the tail if the list is improper.
+    List(Vec<BorrowedDatum<'a>>, Option<Box<BorrowedDatum<'a>>>),
+    Vector(Vec<BorrowedDatum<'a>>),
+    ByteVector(SByteVector),
+    /// A labelled compound datum, or a reference to one.
+    Shared(SShared),
+}
+
+#[derive(Debug)]
+pub struct BorrowedIter<'a> {
+    inner: BuildIter<'a, BorrowedBuilder>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug)]
+struct BorrowedBuilder;
+
+#[derive(Debug)]
+struct BorrowedLabel<'a> {
+    shared: SShared,
+    /// The datum to use for a reference, once the labelled datum has been read.
+    datum: Option<BorrowedDatum<'a>>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl From<BorrowedDatum<'_>> for Datum {
+    fn from(v: BorrowedDatum<'_>) -> Self {
+        v.to_datum()
+    }
+}
+
+impl<'a> BorrowedDatum<'a> {
+    ///
+    /// Returns this datum with all of its text owned, detached from the input; only text that
+    /// is still borrowed is copied.
+    ///
+    pub fn into_owned(self) -> BorrowedDatum<'static> {
+        let owned = |text: Cow<'a, str>| Cow::Owned(text.into_owned());
+        let all = |data: Vec<Self>| data.into_iter().map(Self::into_owned).collect();
+        match self {
+            Self::Quote(v) => BorrowedDatum::Quote(Box::new(v.into_owned())),
+            Self::QuasiQuote(v) => BorrowedDatum::QuasiQuote(Box::new(v.into_owned())),
+            Self::Unquote(v) => BorrowedDatum::Unquote(Box::new(v.into_owned())),
+            Self::UnquoteSplicing(v) => BorrowedDatum::UnquoteSplicing(Box::new(v.into_owned())),
+            Self::Identifier(v) => BorrowedDatum::Identifier(owned(v)),
+            Self::String(v) => BorrowedDatum::String(owned(v)),
+            Self::Atom(v) => BorrowedDatum::Atom(v),
+            Self::List(elements, tail) => {
+                BorrowedDatum::List(all(elements), tail.map(|v| Box::new(v.into_owned())))
+            }
+            Self::Vector(elements) => BorrowedDatum::Vector(all(elements)),
+            Self::ByteVector(v) => BorrowedDatum::ByteVector(v),
+            Self::Shared(v) => BorrowedDatum::Shared(v),
+        }
+    }
+
+    ///
+    /// Returns `true` if this is a string or identifier whose text is borrowed from the input.
+    ///
+    pub fn is_borrowed(&self) -> bool {
+        matches!(
+            self,
+            Self::Identifier(Cow::Borrowed(_)) | Self::String(Cow::Borrowed(_))
+        )
+    }
+
+    pub fn as_identifier(&self) -> Option<&str> {
+        match self {
+            Self::Identifier(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_string(&self) -> Option<&str> {
+        match self {
+            Self::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    ///
+    /// The elements of a list or vector, not including the tail of an improper list.
+    ///
+    pub fn elements(&self) -> Option<&[BorrowedDatum<'a>]> {
+        match self {
+            Self::List(elements, _) | Self::Vector(elements) => Some(elements),
+            _ => None,
+        }
+    }
+
+    pub fn type_string(&self) -> &'static str {
+        match self {
+            Self::Quote(_) => "quote",
+            Self::QuasiQuote(_) => "quasiquote",
+            Self::Unquote(_) => "unquote",
+            Self::UnquoteSplicing(_) => "unquote-splicing",
+            Self::Identifier(_) => "identifier",
+            Self::String(_) => "string",
+            Self::Atom(atom) => atom.type_string(),
+            Self::List(_, _) => "pair-or-list",
+            Self::Vector(_) => "vector",
+            Self::ByteVector(_) => "byte-vector",
+            Self::Shared(v) => Datum::Shared(v.clone()).type_string(),
+        }
+    }
+
+    ///
+    /// Returns the plain datum for this value, the value [`DatumIter`] would have returned.
+    ///
+    /// [`DatumIter`]: crate::reader::iter::DatumIter
+    ///
+    pub fn to_datum(&self) -> Datum {
+        match self {
+            Self::Quote(v) => v.to_datum().quote(),
+            Self::QuasiQuote(v) => v.to_datum().quasiquote(),
+            Self::Unquote(v) => v.to_datum().unquote(),
+            Self::UnquoteSplicing(v) => v.to_datum().unquote_splicing(),
+            Self::Identifier(v) => SIdentifier::intern(v).into(),
+            Self::String(v) => SString::from(v.to_string()).into(),
+            Self::Atom(v) => v.clone(),
+            Self::List(elements, tail) => {
+                let mut list = SList::from_iter(elements.iter().map(Self::to_datum));
+                if let Some(tail) = tail {
+                    list.append_improper(Rc::new(tail.to_datum()), None)
+                        .expect("a list with a tail has elements");
+                }
+                list.into()
+            }
+            Self::Vector(elements) => {
+                SVector::from_iter(elements.iter().map(Self::to_datum)).into()
+            }
+            Self::ByteVector(v) => v.clone().into(),
+            Self::Shared(v) => Datum::Shared(v.clone()),
+        }
+    }
+
+    fn quote(self, kind: QuoteKind) -> Self {
+        let inner = Box::new(self);
+        match kind {
+            QuoteKind::Quote => Self::Quote(inner),
+            QuoteKind::QuasiQuote => Self::QuasiQuote(inner),
+            QuoteKind::Unquote => Self::Unquote(inner),
+            QuoteKind::UnquoteSplicing => Self::UnquoteSplicing(inner),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<'a> From<TokenIter<'a>> for BorrowedIter<'a> {
+    fn from(source: TokenIter<'a>) -> Self {
+        Self {
+            inner: BuildIter::new(source, BorrowedBuilder),
+        }
+    }
+}
+
+impl<'a> Iterator for BorrowedIter<'a> {
+    type Item = Result<BorrowedDatum<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+impl BorrowedIter<'_> {
+    ///
+    /// Set whether identifiers and character names are case folded before any `#!fold-case`
+    /// or `#!no-fold-case` directive is read; the default is `false`.
+    ///
+    pub fn with_fold_case(self, fold_case: bool) -> Self {
+        Self {
+            inner: self.inner.with_fold_case(fold_case),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<'a> Builder<'a> for BorrowedBuilder {
+    type Datum = BorrowedDatum<'a>;
+    type Label = BorrowedLabel<'a>;
+
+    fn identifier(&mut self, text: Cow<'a, str>, _: Span) -> BorrowedDatum<'a> {
+        BorrowedDatum::Identifier(text)
+    }
+
+    fn string(&mut self, text: Cow<'a, str>, _: Span) -> BorrowedDatum<'a> {
+        BorrowedDatum::String(text)
+    }
+
+    fn boolean(&mut self, value: SBoolean, _: Span) -> BorrowedDatum<'a> {
+        BorrowedDatum::Atom(value.into())
+    }
+
+    fn character(&mut self, value: SChar, _: Span) -> BorrowedDatum<'a> {
+        BorrowedDatum::Atom(value.into())
+    }
+
+    fn number(&mut self, value: SNumber, _: Span) -> BorrowedDatum<'a> {
+        BorrowedDatum::Atom(value.into())
+    }
+
+    fn directive(&mut self, value: SDirective, _: Span) -> BorrowedDatum<'a> {
+        BorrowedDatum::Atom(value.into())
+    }
+
+    fn error(&mut self, span: Span) -> BorrowedDatum<'a> {
+        BorrowedDatum::Atom(Datum::Error(span))
+    }
+
+    fn byte(datum: &BorrowedDatum<'a>) -> Option<u8> {
+        match datum {
+            BorrowedDatum::Atom(Datum::Number(number)) => byte_value(number),
+            _ => None,
+        }
+    }
+
+    fn quote(&mut self, kind: QuoteKind, _: Span, datum: BorrowedDatum<'a>) -> BorrowedDatum<'a> {
+        datum.quote(kind)
+    }
+
+    fn list(
+        &mut self,
+        _: Span,
+        elements: Vec<BorrowedDatum<'a>>,
+        tail: Option<BorrowedDatum<'a>>,
+    ) -> BorrowedDatum<'a> {
+        BorrowedDatum::List(elements, tail.map(Box::new))
+    }
+
+    fn vector(&mut self, _: Span, elements: Vec<BorrowedDatum<'a>>) -> BorrowedDatum<'a> {
+        BorrowedDatum::Vector(elements)
+    }
+
+    fn byte_vector(
+        &mut self,
+        _: Span,
+        _: Vec<BorrowedDatum<'a>>,
+        bytes: Vec<u8>,
+    ) -> BorrowedDatum<'a> {
+        let mut byte_vector = SByteVector::default();
+        bytes.into_iter().for_each(|byte| byte_vector.append(byte));
+        BorrowedDatum::ByteVector(byte_vector)
+    }
+
+    fn label(&mut self, label: u16) -> BorrowedLabel<'a> {
+        BorrowedLabel {
+            shared: SShared::new(label),
+            datum: None,
+        }
+    }
+
+    fn reference(&mut self, label: &BorrowedLabel<'a>, _: Span) -> BorrowedDatum<'a> {
+        match &label.datum {
+            Some(datum) => datum.clone(),
+            None => BorrowedDatum::Shared(label.shared.downgrade()),
+        }
+    }
+
+    fn assign(
+        &mut self,
+        label: &mut BorrowedLabel<'a>,
+        datum: BorrowedDatum<'a>,
+    ) -> Option<BorrowedDatum<'a>> {
+        let datum = match datum {
+            BorrowedDatum::Shared(other) if other.ptr_eq(&label.shared) => return None,
+            BorrowedDatum::Shared(other) => {
+                // an alias for a label already read, such as `#1=#0#`.
+                label.shared = other.clone();
+                BorrowedDatum::Shared(other)
+            }
+            datum => match label.shared.assign(datum.to_datum()) {
+                Datum::Shared(shared) => BorrowedDatum::Shared(shared),
+                // a simple datum is copied, keeping any borrowed text.
+                _ => datum,
+            },
+        };
+        label.datum = Some(datum.clone());
+        Some(datum)
+    }
+}
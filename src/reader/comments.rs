@@ -0,0 +1,403 @@
+/*!
+Provides the attachment of comments to the forms they describe, built on the [`SyntaxTree`]
+of some source.
+
+Each comment in the tree ends up in exactly one place:
+
+* a comment that follows a datum on the same line is one of that datum's
+  [trailing](Commented::trailing) comments,
+* a run of comments on the lines directly before a datum, with no empty line between them or
+  between the last of them and the datum, are that datum's [leading](Commented::leading)
+  comments, and so its documentation,
+* any other comment, including one within, or before, a datum comment (`#;`), is
+  [detached](CommentedTree::detached).
+
+Each [`Comment`] records the [`CommentStyle`] of its marker, and its text with the markers
+removed: the semicolons, and one following space, from each line comment; and the `#|` and
+`|#` from a block comment, along with the indentation common to its lines.
+
+# Example
+
+```rust
+use ffsr::lexer::Lexer;
+use ffsr::reader::comments::{CommentStyle, CommentedTree};
+use ffsr::reader::cst::SyntaxTree;
+
+let source = ";;; Squares
+
+;; Returns the square
+;; of x.
+(define (square x)
+  (* x x)) ; no checks
+";
+let tree = SyntaxTree::parse(Lexer::from(source).tokens()).unwrap();
+let commented = CommentedTree::from(&tree);
+
+let define = &commented.forms()[0];
+assert_eq!(define.documentation().unwrap(), "Returns the square\nof x.");
+assert_eq!(define.leading()[0].style(), CommentStyle::Double);
+assert_eq!(define.trailing()[0].text(), "no checks");
+assert_eq!(commented.detached()[0].style(), CommentStyle::Triple);
+assert_eq!(commented.detached()[0].text(), "Squares");
+```
+
+*/
+
+use crate::error::Error;
+use crate::lexer::token::Span;
+use crate::reader::cst::{Node, NodeKind, SyntaxTree, Trivia, TriviaKind};
+use crate::reader::datum::Datum;
+use crate::syntax::{COMMENT_BLOCK_END, COMMENT_BLOCK_START, COMMENT_LINE_START};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The marker that starts a comment; by convention the number of semicolons says what a line
+/// comment describes.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CommentStyle {
+    /// `;`, usually a comment at the end of a line of code.
+    Single,
+    /// `;;`, usually a comment on its own line describing the code that follows.
+    Double,
+    /// `;;;`, or more semicolons, usually a heading for a section of a source file.
+    Triple,
+    /// `#| ... |#`
+    Block,
+}
+
+///
+/// A single comment, with its markers removed.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Comment {
+    style: CommentStyle,
+    text: String,
+    span: Span,
+}
+
+///
+/// A node of a [`SyntaxTree`] with the comments attached to it, and to its children.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Commented<'a> {
+    node: &'a Node,
+    leading: Vec<Comment>,
+    trailing: Vec<Comment>,
+    children: Vec<Commented<'a>>,
+}
+
+///
+/// The top-level nodes of a [`SyntaxTree`] with their attached comments, and every comment
+/// not attached to any node.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommentedTree<'a> {
+    forms: Vec<Commented<'a>>,
+    detached: Vec<Comment>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Comment {
+    fn from_trivia(trivia: &Trivia) -> Option<Self> {
+        let text = trivia.text();
+        let (style, text) = match trivia.kind() {
+            TriviaKind::LineComment => {
+                let content = text.trim_start_matches(COMMENT_LINE_START);
+                let style = match text.len() - content.len() {
+                    1 => CommentStyle::Single,
+                    2 => CommentStyle::Double,
+                    _ => CommentStyle::Triple,
+                };
+                let content = content.strip_prefix(' ').unwrap_or(content);
+                (style, content.trim_end().to_string())
+            }
+            TriviaKind::BlockComment => (CommentStyle::Block, block_text(text)),
+            TriviaKind::Whitespace | TriviaKind::Newline | TriviaKind::DatumComment => return None,
+        };
+        Some(Self {
+            style,
+            text,
+            span: trivia.span(),
+        })
+    }
+
+    #[inline(always)]
+    pub fn style(&self) -> CommentStyle {
+        self.style
+    }
+
+    ///
+    /// The text of this comment without its markers; the lines of a block comment are
+    /// separated by `"\n"`.
+    ///
+    #[inline(always)]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    #[inline(always)]
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<'a> Commented<'a> {
+    #[inline(always)]
+    pub fn node(&self) -> &'a Node {
+        self.node
+    }
+
+    #[inline(always)]
+    pub fn span(&self) -> Span {
+        self.node.span()
+    }
+
+    ///
+    /// Read the node as a datum, see [`Node::to_datum`].
+    ///
+    #[inline(always)]
+    pub fn datum(&self) -> Option<Result<Datum, Error>> {
+        self.node.to_datum()
+    }
+
+    ///
+    /// The comments on the lines directly before the node, in source order.
+    ///
+    #[inline(always)]
+    pub fn leading(&self) -> &[Comment] {
+        &self.leading
+    }
+
+    ///
+    /// The comments after the node on the line where it ends, in source order.
+    ///
+    #[inline(always)]
+    pub fn trailing(&self) -> &[Comment] {
+        &self.trailing
+    }
+
+    ///
+    /// The children of the node, see [`Node::children`], with their comments; datum comments
+    /// are not included.
+    ///
+    #[inline(always)]
+    pub fn children(&self) -> &[Commented<'a>] {
+        &self.children
+    }
+
+    ///
+    /// The text of the leading comments joined by `"\n"`, or `None` if there are none.
+    ///
+    pub fn documentation(&self) -> Option<String> {
+        (!self.leading.is_empty()).then(|| {
+            self.leading
+                .iter()
+                .map(Comment::text)
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+    }
+
+    ///
+    /// The leading comments with the marker `style`.
+    ///
+    pub fn leading_with_style(&self, style: CommentStyle) -> impl Iterator<Item = &Comment> {
+        self.leading.iter().filter(move |c| c.style == style)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<'a> From<&'a SyntaxTree> for CommentedTree<'a> {
+    fn from(tree: &'a SyntaxTree) -> Self {
+        let mut detached = Vec::new();
+        let forms = attach(tree.nodes(), tree.trailing(), &mut detached);
+        detached.sort_by_key(|comment| comment.span.start());
+        Self { forms, detached }
+    }
+}
+
+impl<'a> CommentedTree<'a> {
+    #[inline(always)]
+    pub fn forms(&self) -> &[Commented<'a>] {
+        &self.forms
+    }
+
+    ///
+    /// The comments not attached to any node, in source order.
+    ///
+    #[inline(always)]
+    pub fn detached(&self) -> &[Comment] {
+        &self.detached
+    }
+
+    ///
+    /// Returns each node, at any depth, with leading comments, in source order.
+    ///
+    pub fn documented(&self) -> Vec<&Commented<'a>> {
+        fn walk<'b, 'a>(forms: &'b [Commented<'a>], result: &mut Vec<&'b Commented<'a>>) {
+            for form in forms {
+                if !form.leading.is_empty() {
+                    result.push(form);
+                }
+                walk(&form.children, result);
+            }
+        }
+        let mut result = Vec::new();
+        walk(&self.forms, &mut result);
+        result
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Attach the comments in the leading trivia of `nodes`, and in the trivia `end` that follows
+/// them, to the nodes; any not attached are added to `detached`.
+///
+fn attach<'a>(
+    nodes: &'a [Node],
+    end: &[Trivia],
+    detached: &mut Vec<Comment>,
+) -> Vec<Commented<'a>> {
+    let mut result: Vec<Commented<'a>> = Vec::new();
+    let mut after_datum_comment = false;
+    for node in nodes {
+        let rest = attach_trailing(&mut result, after_datum_comment, node.leading(), detached);
+        if node.is_datum_comment() {
+            detached.extend(rest.iter().filter_map(Comment::from_trivia));
+            detach(node.children(), detached);
+            after_datum_comment = true;
+            continue;
+        }
+        let leading = attach_leading(rest, detached);
+        let children = match node.kind() {
+            NodeKind::Delimited {
+                open: _,
+                nodes,
+                close_leading,
+                close: _,
+            } => attach(nodes, close_leading, detached),
+            _ => attach(node.children(), &[], detached),
+        };
+        result.push(Commented {
+            node,
+            leading,
+            trailing: Vec::new(),
+            children,
+        });
+        after_datum_comment = false;
+    }
+    let rest = attach_trailing(&mut result, after_datum_comment, end, detached);
+    detached.extend(rest.iter().filter_map(Comment::from_trivia));
+    result
+}
+
+///
+/// Add the comments in `trivia` before its first newline to the last of `previous`, or to
+/// `detached` if they follow a datum comment, returning the trivia not examined.
+///
+fn attach_trailing<'t>(
+    previous: &mut [Commented<'_>],
+    after_datum_comment: bool,
+    trivia: &'t [Trivia],
+    detached: &mut Vec<Comment>,
+) -> &'t [Trivia] {
+    let target = match previous.last_mut() {
+        _ if after_datum_comment => detached,
+        Some(previous) => &mut previous.trailing,
+        None => return trivia,
+    };
+    let line_end = trivia
+        .iter()
+        .position(|t| t.kind() == TriviaKind::Newline)
+        .unwrap_or(trivia.len());
+    target.extend(trivia[..line_end].iter().filter_map(Comment::from_trivia));
+    &trivia[line_end..]
+}
+
+///
+/// Returns the last run of comments in `trivia` with no empty line between them, or after
+/// them; earlier comments are added to `detached`.
+///
+fn attach_leading(trivia: &[Trivia], detached: &mut Vec<Comment>) -> Vec<Comment> {
+    let mut run = Vec::new();
+    let mut newlines = 0;
+    for trivia in trivia {
+        if trivia.kind() == TriviaKind::Newline {
+            newlines += 1;
+        } else if let Some(comment) = Comment::from_trivia(trivia) {
+            if newlines > 1 {
+                detached.append(&mut run);
+            }
+            run.push(comment);
+            newlines = 0;
+        }
+    }
+    if newlines > 1 {
+        detached.append(&mut run);
+    }
+    run
+}
+
+///
+/// Add every comment within `nodes` to `detached`.
+///
+fn detach(nodes: &[Node], detached: &mut Vec<Comment>) {
+    for node in nodes {
+        detached.extend(node.leading().iter().filter_map(Comment::from_trivia));
+        if let NodeKind::Delimited {
+            open: _,
+            nodes: _,
+            close_leading,
+            close: _,
+        } = node.kind()
+        {
+            detached.extend(close_leading.iter().filter_map(Comment::from_trivia));
+        }
+        detach(node.children(), detached);
+    }
+}
+
+///
+/// The text of a block comment without its markers, leading and trailing empty lines, or the
+/// indentation common to the lines after the first.
+///
+fn block_text(text: &str) -> String {
+    let content = text
+        .strip_prefix(COMMENT_BLOCK_START)
+        .and_then(|content| content.strip_suffix(COMMENT_BLOCK_END))
+        .unwrap_or(text);
+    let mut lines: Vec<&str> = content.lines().map(str::trim_end).collect();
+    if let Some(first) = lines.first_mut() {
+        *first = first.trim_start();
+    }
+    let indent = lines
+        .iter()
+        .skip(1)
+        .filter(|line| !line.is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    for line in lines.iter_mut().skip(1) {
+        *line = line.get(indent..).unwrap_or_default();
+    }
+    while lines.first().is_some_and(|line| line.is_empty()) {
+        lines.remove(0);
+    }
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,598 @@
+/*!
+Provides a lossless concrete syntax tree, built from the tokens of a [`TokenIter`].
+
+Unlike [`DatumIter`](crate::reader::iter::DatumIter) the tree keeps everything in the
+source: white space, newlines, and comments are kept as [`Trivia`] attached to the node that
+follows them, or to the end of the enclosing list, or to the end of the tree. Writing the
+tree, with `Display`, returns the original text byte for byte.
+
+Each [`Node`] can be converted into the [`Datum`] that the reader would return for the same
+text.
+
+# Example
+
+```rust
+use ffsr::lexer::Lexer;
+use ffsr::reader::cst::SyntaxTree;
+
+let source = "; greeting\n(display  \"hello\") ; done\n";
+let tree = SyntaxTree::parse(Lexer::from(source).tokens()).unwrap();
+
+assert_eq!(tree.to_string(), source);
+assert_eq!(tree.nodes().len(), 1);
+assert_eq!(tree.nodes()[0].leading().len(), 2);
+assert_eq!(tree.nodes()[0].text(), "(display  \"hello\")");
+assert_eq!(tree.nodes()[0].to_datum().unwrap().unwrap().to_string(), "(display hello)");
+```
+
+*/
+
+use crate::error::{
+    incomplete_byte_vector, incomplete_datum_assignment, incomplete_datum_comment, incomplete_list,
+    incomplete_quasi_quote, incomplete_quote, incomplete_unquote, incomplete_unquote_splicing,
+    incomplete_vector, invalid_datum_label, unexpected_token, Error,
+};
+use crate::input::indices::Index;
+use crate::input::Input;
+use crate::lexer::iter::TokenIter;
+use crate::lexer::token::{Span, TokenKind};
+use crate::lexer::Lexer;
+use crate::reader::datum::{Datum, SComment};
+use crate::reader::Reader;
+use crate::{SourceId, Sourced};
+use std::fmt::{Display, Write};
+use tracing::{trace, trace_span};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TriviaKind {
+    Whitespace,
+    /// One of `"\n"`, `"\r\n"`, or `"\r"`.
+    Newline,
+    /// The text of a line comment, not including the newline that ends it.
+    LineComment,
+    BlockComment,
+    /// A datum comment, `#;` and the datum it comments out, between a prefix and the datum
+    /// the prefix applies to, as in `'#;a b`.
+    DatumComment,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trivia {
+    kind: TriviaKind,
+    text: String,
+    span: Span,
+}
+
+///
+/// A single token within the tree.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Leaf {
+    kind: TokenKind,
+    text: String,
+    span: Span,
+    start: Index,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NodeKind {
+    /// A simple datum, a datum reference, a directive, or the `.` within a pair.
+    Atom(Leaf),
+    /// A quote, quasi-quote, unquote, unquote-splicing, datum label assignment, or datum
+    /// comment and the node it applies to.
+    Prefixed { prefix: Leaf, node: Box<Node> },
+    /// A list, vector, or byte vector; `close_leading` is the trivia before the closing
+    /// parenthesis.
+    Delimited {
+        open: Leaf,
+        nodes: Vec<Node>,
+        close_leading: Vec<Trivia>,
+        close: Leaf,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Node {
+    leading: Vec<Trivia>,
+    kind: NodeKind,
+    span: Span,
+}
+
+///
+/// The complete tree for some source, the top-level nodes followed by any trivia at the end
+/// of the source.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyntaxTree {
+    nodes: Vec<Node>,
+    trailing: Vec<Trivia>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug)]
+enum Frame {
+    Prefixed {
+        leading: Vec<Trivia>,
+        prefix: Leaf,
+    },
+    Delimited {
+        leading: Vec<Trivia>,
+        open: Leaf,
+        nodes: Vec<Node>,
+    },
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Trivia {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl Trivia {
+    fn new(kind: TriviaKind, text: &str, start: usize) -> Self {
+        Self {
+            kind,
+            text: text.to_string(),
+            span: Span::new(start, start + text.chars().count()),
+        }
+    }
+
+    #[inline(always)]
+    pub fn kind(&self) -> TriviaKind {
+        self.kind
+    }
+
+    #[inline(always)]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    ///
+    /// The span of this trivia, the end is the character following it.
+    ///
+    #[inline(always)]
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    #[inline(always)]
+    pub fn is_comment(&self) -> bool {
+        matches!(
+            self.kind,
+            TriviaKind::LineComment | TriviaKind::BlockComment
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Leaf {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl Leaf {
+    #[inline(always)]
+    pub fn kind(&self) -> TokenKind {
+        self.kind
+    }
+
+    #[inline(always)]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    #[inline(always)]
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Node {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for trivia in &self.leading {
+            write!(f, "{trivia}")?;
+        }
+        self.write_text(f)
+    }
+}
+
+impl Node {
+    ///
+    /// The trivia, white space and comments, that precede this node.
+    ///
+    #[inline(always)]
+    pub fn leading(&self) -> &[Trivia] {
+        &self.leading
+    }
+
+    #[inline(always)]
+    pub fn kind(&self) -> &NodeKind {
+        &self.kind
+    }
+
+    ///
+    /// The span of this node, not including its leading trivia.
+    ///
+    #[inline(always)]
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    ///
+    /// The child nodes of a list, vector, or byte vector; or the single node following a
+    /// prefix.
+    ///
+    pub fn children(&self) -> &[Node] {
+        match &self.kind {
+            NodeKind::Atom(_) => &[],
+            NodeKind::Prefixed { prefix: _, node } => std::slice::from_ref(node),
+            NodeKind::Delimited {
+                open: _,
+                nodes,
+                close_leading: _,
+                close: _,
+            } => nodes,
+        }
+    }
+
+    #[inline(always)]
+    pub fn is_datum_comment(&self) -> bool {
+        matches!(&self.kind, NodeKind::Prefixed { prefix, node: _ } if prefix.kind == TokenKind::DatumComment)
+    }
+
+    ///
+    /// The text of this node, not including its leading trivia.
+    ///
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        self.write_text(&mut text).unwrap();
+        text
+    }
+
+    ///
+    /// Read this node as a datum; a datum comment is returned as a [`SComment::Datum`], and
+    /// datum comments within this node are skipped, as the reader does. Returns `None` for
+    /// the `.` within a pair, which is not a datum.
+    ///
+    pub fn to_datum(&self) -> Option<Result<Datum, Error>> {
+        match &self.kind {
+            NodeKind::Atom(leaf) if leaf.kind == TokenKind::Dot => None,
+            NodeKind::Prefixed { prefix, node } if prefix.kind == TokenKind::DatumComment => {
+                Some(node.to_datum()?.map(|datum| SComment::from(datum).into()))
+            }
+            _ => {
+                let text = self.text();
+                let reader = Reader::from(Lexer::from(Input::new_at(
+                    SourceId::String,
+                    &text,
+                    self.start(),
+                )));
+                let mut iter = reader.iter();
+                iter.next()
+            }
+        }
+    }
+
+    fn start(&self) -> Index {
+        match &self.kind {
+            NodeKind::Atom(leaf) => leaf.start,
+            NodeKind::Prefixed { prefix, node: _ } => prefix.start,
+            NodeKind::Delimited {
+                open,
+                nodes: _,
+                close_leading: _,
+                close: _,
+            } => open.start,
+        }
+    }
+
+    fn write_text<W: Write>(&self, f: &mut W) -> std::fmt::Result {
+        match &self.kind {
+            NodeKind::Atom(leaf) => write!(f, "{leaf}"),
+            NodeKind::Prefixed { prefix, node } => write!(f, "{prefix}{node}"),
+            NodeKind::Delimited {
+                open,
+                nodes,
+                close_leading,
+                close,
+            } => {
+                write!(f, "{open}")?;
+                for node in nodes {
+                    write!(f, "{node}")?;
+                }
+                for trivia in close_leading {
+                    write!(f, "{trivia}")?;
+                }
+                write!(f, "{close}")
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for SyntaxTree {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for node in &self.nodes {
+            write!(f, "{node}")?;
+        }
+        for trivia in &self.trailing {
+            write!(f, "{trivia}")?;
+        }
+        Ok(())
+    }
+}
+
+impl SyntaxTree {
+    ///
+    /// Build a tree from all the tokens in `tokens`, returning the first error from the lexer
+    /// or any unbalanced or incomplete form.
+    ///
+    pub fn parse(mut tokens: TokenIter<'_>) -> Result<Self, Error> {
+        let _span = trace_span!("parse-syntax-tree");
+        let _scope = _span.enter();
+
+        let offset = tokens.source_offset();
+        let mut last = offset;
+        let mut pending: Vec<Trivia> = Default::default();
+        let mut stack: Vec<Frame> = Default::default();
+        let mut top_level: Vec<Node> = Default::default();
+
+        while let Some(token) = tokens.next() {
+            let token = token?;
+            let start = Index::new(token.byte_span().start(), token.start());
+            push_gap_trivia(&mut pending, tokens.source_str(), offset, last, start);
+            let leaf = Leaf {
+                kind: token.kind(),
+                text: tokens.token_str(&token).to_string(),
+                span: token.span(),
+                start,
+            };
+            // token character spans differ in whether their end is inclusive, so the end is
+            // taken from the token's text.
+            last = Index::new(
+                token.byte_span().end(),
+                start.character() + leaf.text.chars().count(),
+            );
+            trace!(?leaf, frames = stack.len(), "next leaf");
+
+            match token.kind() {
+                TokenKind::LineComment => push_line_comment(&mut pending, leaf),
+                TokenKind::BlockComment => pending.push(Trivia::new(
+                    TriviaKind::BlockComment,
+                    &leaf.text,
+                    start.character(),
+                )),
+                TokenKind::OpenParenthesis | TokenKind::OpenVector | TokenKind::OpenByteVector => {
+                    stack.push(Frame::Delimited {
+                        leading: std::mem::take(&mut pending),
+                        open: leaf,
+                        nodes: Default::default(),
+                    })
+                }
+                TokenKind::Quote
+                | TokenKind::QuasiQuote
+                | TokenKind::Unquote
+                | TokenKind::UnquoteSplicing
+                | TokenKind::DatumAssign
+                | TokenKind::DatumComment => stack.push(Frame::Prefixed {
+                    leading: std::mem::take(&mut pending),
+                    prefix: leaf,
+                }),
+                TokenKind::CloseParenthesis => match stack.pop() {
+                    Some(Frame::Delimited {
+                        leading,
+                        open,
+                        nodes,
+                    }) => {
+                        let node = Node {
+                            leading,
+                            span: open.span.with_end_from(leaf.span),
+                            kind: NodeKind::Delimited {
+                                open,
+                                nodes,
+                                close_leading: std::mem::take(&mut pending),
+                                close: leaf,
+                            },
+                        };
+                        complete(&mut stack, &mut top_level, &mut pending, node);
+                    }
+                    _ => return unexpected_token(token.span(), token.kind()),
+                },
+                _ => {
+                    let node = Node {
+                        leading: std::mem::take(&mut pending),
+                        span: leaf.span,
+                        kind: NodeKind::Atom(leaf),
+                    };
+                    complete(&mut stack, &mut top_level, &mut pending, node);
+                }
+            }
+        }
+
+        let source = tokens.source_str();
+        let end = Index::new(offset.byte() + source.len(), last.character());
+        push_gap_trivia(&mut pending, source, offset, last, end);
+
+        if let Some(frame) = stack.pop() {
+            return incomplete_frame(frame, last.character());
+        }
+
+        Ok(Self {
+            nodes: top_level,
+            trailing: pending,
+        })
+    }
+
+    #[inline(always)]
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    ///
+    /// The trivia following the last top-level node.
+    ///
+    #[inline(always)]
+    pub fn trailing(&self) -> &[Trivia] {
+        &self.trailing
+    }
+
+    ///
+    /// Read all the top-level nodes as datums, this is the same as reading the text of the
+    /// tree with [`Reader::iter`], and so datum labels may refer across top-level nodes.
+    ///
+    pub fn to_datums(&self) -> Vec<Result<Datum, Error>> {
+        let text = self.to_string();
+        let reader = Reader::from(Lexer::from(text.as_str()));
+        let datums = reader.iter().collect();
+        datums
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Add a completed node to the innermost open frame. A datum comment completed within a
+/// prefix, which applies to the datum after it, is added to `pending` as trivia instead.
+///
+fn complete(
+    stack: &mut Vec<Frame>,
+    top_level: &mut Vec<Node>,
+    pending: &mut Vec<Trivia>,
+    mut node: Node,
+) {
+    loop {
+        if node.is_datum_comment() && matches!(stack.last(), Some(Frame::Prefixed { .. })) {
+            let text = node.text();
+            pending.append(&mut node.leading);
+            pending.push(Trivia::new(
+                TriviaKind::DatumComment,
+                &text,
+                node.start().character(),
+            ));
+            return;
+        }
+        match stack.pop() {
+            Some(Frame::Prefixed { leading, prefix }) => {
+                node = Node {
+                    leading,
+                    span: prefix.span.with_end_from(node.span),
+                    kind: NodeKind::Prefixed {
+                        prefix,
+                        node: Box::new(node),
+                    },
+                };
+            }
+            Some(Frame::Delimited {
+                leading,
+                open,
+                mut nodes,
+            }) => {
+                nodes.push(node);
+                stack.push(Frame::Delimited {
+                    leading,
+                    open,
+                    nodes,
+                });
+                return;
+            }
+            None => {
+                top_level.push(node);
+                return;
+            }
+        }
+    }
+}
+
+fn incomplete_frame<T>(frame: Frame, end: usize) -> Result<T, Error> {
+    match frame {
+        Frame::Delimited {
+            leading: _,
+            open,
+            nodes: _,
+        } => {
+            let span = open.span.with_end_from(Span::from(end));
+            match open.kind {
+                TokenKind::OpenVector => incomplete_vector(span),
+                TokenKind::OpenByteVector => incomplete_byte_vector(span),
+                _ => incomplete_list(span),
+            }
+        }
+        Frame::Prefixed { leading: _, prefix } => {
+            let span = prefix.span.with_end_from(Span::from(end));
+            match prefix.kind {
+                TokenKind::QuasiQuote => incomplete_quasi_quote(span),
+                TokenKind::Unquote => incomplete_unquote(span),
+                TokenKind::UnquoteSplicing => incomplete_unquote_splicing(span),
+                TokenKind::DatumComment => incomplete_datum_comment(span),
+                TokenKind::DatumAssign => {
+                    match prefix.text[1..prefix.text.len() - 1].parse::<u16>() {
+                        Ok(label) => incomplete_datum_assignment(span, label),
+                        Err(_) => invalid_datum_label(prefix.span),
+                    }
+                }
+                _ => incomplete_quote(span),
+            }
+        }
+    }
+}
+
+///
+/// Split the white space between `from` and `to` into whitespace and newline trivia.
+///
+fn push_gap_trivia(pending: &mut Vec<Trivia>, source: &str, offset: Index, from: Index, to: Index) {
+    let gap = &source[from.byte() - offset.byte()..to.byte() - offset.byte()];
+    let mut character = from.character();
+    let mut rest = gap;
+    while !rest.is_empty() {
+        let (kind, len) = if rest.starts_with("\r\n") {
+            (TriviaKind::Newline, 2)
+        } else if rest.starts_with(['\n', '\r']) {
+            (TriviaKind::Newline, 1)
+        } else {
+            let len = rest.find(['\n', '\r']).unwrap_or(rest.len());
+            (TriviaKind::Whitespace, len)
+        };
+        let trivia = Trivia::new(kind, &rest[..len], character);
+        character = trivia.span.end();
+        pending.push(trivia);
+        rest = &rest[len..];
+    }
+}
+
+///
+/// A line comment token includes the newline that ends it, which is split into its own trivia.
+///
+fn push_line_comment(pending: &mut Vec<Trivia>, leaf: Leaf) {
+    let newline = if leaf.text.ends_with("\r\n") {
+        2
+    } else if leaf.text.ends_with(['\n', '\r']) {
+        1
+    } else {
+        0
+    };
+    let (comment, newline_text) = leaf.text.split_at(leaf.text.len() - newline);
+    let comment = Trivia::new(TriviaKind::LineComment, comment, leaf.start.character());
+    let comment_end = comment.span.end();
+    pending.push(comment);
+    if newline > 0 {
+        pending.push(Trivia::new(TriviaKind::Newline, newline_text, comment_end));
+    }
+}
@@ -47,10 +47,30 @@ pub struct EscapeDefault {
     state: EscapeDefaultState,
 }
 
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Returns the full case folding of `s`, as `string-foldcase`. This uses the lowercase mapping
+/// of each character, except for those characters where Unicode case folding differs, such as
+/// the final sigma, `ß` which folds to `ss`, and Cherokee which folds to upper case.
+///
+pub fn fold_case_str(s: &str) -> String {
+    s.chars().flat_map(FoldCase::new).collect()
+}
+
 // ------------------------------------------------------------------------------------------------
 // Private Types
 // ------------------------------------------------------------------------------------------------
 
+#[derive(Clone, Debug)]
+enum FoldCase {
+    Lower(std::char::ToLowercase),
+    Chars(&'static [char]),
+    Char(Option<char>),
+}
+
 #[derive(Clone, Debug)]
 enum EscapeUnicodeState {
     Done,
@@ -180,6 +200,19 @@ impl SChar {
         self.escape_unicode().collect()
     }
 
+    ///
+    /// Returns the simple case folding of this character, as `char-foldcase`; characters with
+    /// no single character folding are returned unchanged.
+    ///
+    pub fn fold_case(&self) -> Self {
+        let mut folded = FoldCase::new(self.0);
+        match (folded.next(), folded.next()) {
+            (Some(c), None) => Self(c),
+            _ if self.0 == '\u{1E9E}' => Self('\u{DF}'),
+            _ => *self,
+        }
+    }
+
     pub fn is_non_printing(&self) -> bool {
         matches!(self.0,
             '\u{0000}'..='\u{0008}'
@@ -195,7 +228,7 @@ impl SChar {
     pub fn is_valid(cp: u32) -> bool {
         match cp {
             // Surrogate codepoint values
-            0xD800..=0xDBFF | 0xDC00..=0xDFFF => false,
+            0xD800..=0xDFFF => false,
             // Supplementary Private Use codepoint values
             0x100000..=0x10FFFF => false,
             _ => true,
@@ -374,3 +407,47 @@ impl ExactSizeIterator for EscapeUnicode {
 }
 
 impl FusedIterator for EscapeUnicode {}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Iterator for FoldCase {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        match self {
+            Self::Lower(lower) => lower.next(),
+            Self::Chars(chars) => {
+                let (first, rest) = chars.split_first()?;
+                *chars = rest;
+                Some(*first)
+            }
+            Self::Char(c) => c.take(),
+        }
+    }
+}
+
+impl FoldCase {
+    fn new(c: char) -> Self {
+        let folded = match c {
+            '\u{DF}' | '\u{1E9E}' => return Self::Chars(&['s', 's']),
+            '\u{B5}' => '\u{3BC}',
+            '\u{17F}' => 's',
+            '\u{345}' | '\u{1FBE}' => '\u{3B9}',
+            '\u{3C2}' => '\u{3C3}',
+            '\u{3D0}' => '\u{3B2}',
+            '\u{3D1}' => '\u{3B8}',
+            '\u{3D5}' => '\u{3C6}',
+            '\u{3D6}' => '\u{3C0}',
+            '\u{3F0}' => '\u{3BA}',
+            '\u{3F1}' => '\u{3C1}',
+            '\u{3F5}' => '\u{3B5}',
+            '\u{1E9B}' => '\u{1E61}',
+            // Cherokee folds to the upper case letters
+            '\u{13A0}'..='\u{13F5}' => c,
+            '\u{13F8}'..='\u{13FD}' => char::from_u32(c as u32 - 8).unwrap(),
+            '\u{AB70}'..='\u{ABBF}' => char::from_u32(c as u32 - 0xAB70 + 0x13A0).unwrap(),
+            _ => return Self::Lower(c.to_lowercase()),
+        };
+        Self::Char(Some(folded))
+    }
+}
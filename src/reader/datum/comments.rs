@@ -11,8 +11,9 @@ YYYYY
 
 use super::{Datum, SimpleDatumValue};
 use crate::{
-    error::Error,
-    lexer::token::Span,
+    error::{invalid_comment_input, Error},
+    lexer::{token::Span, Lexer},
+    reader::Reader,
     syntax::{COMMENT_BLOCK_END, COMMENT_BLOCK_START, COMMENT_DATUM_START, COMMENT_LINE_START},
 };
 use std::fmt::{Debug, Display};
@@ -70,10 +71,10 @@ impl SimpleDatumValue for SComment {
             Ok(Self::Block(s[2..s.len() - 2].trim().to_string()))
         } else if s.starts_with(COMMENT_DATUM_START) {
             trace!("Datum comment");
-            unimplemented!()
+            datum_comment_from_str_in_span(s, span)
         } else {
             error!("No clue");
-            unimplemented!()
+            invalid_comment_input(span)
         }
     }
 }
@@ -87,3 +88,22 @@ impl SComment {
         }
     }
 }
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Read `s` as a single datum comment, `#;` followed by exactly one datum; any error from
+/// reading the datum has its spans moved to be within `span`.
+///
+fn datum_comment_from_str_in_span(s: &str, span: Span) -> Result<SComment, Error> {
+    let reader = Reader::from(Lexer::from(s));
+    let mut iter = reader.iter_with_comments();
+    let shift = |e: Error| e.map_spans(|inner| inner.shifted(span.start() as isize));
+    match (iter.next(), iter.next()) {
+        (Some(Ok(Datum::Comment(comment @ SComment::Datum(_)))), None) => Ok(comment),
+        (Some(Err(e)), _) | (Some(Ok(_)), Some(Err(e))) => Err(shift(e)),
+        _ => invalid_comment_input(span),
+    }
+}
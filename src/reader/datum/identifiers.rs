@@ -32,10 +32,16 @@ use crate::lexer::iter::{
     is_dot_subsequent, is_identifier_initial, is_identifier_subsequent, is_sign_subsequent,
 };
 use crate::lexer::token::Span;
-use crate::reader::datum::SChar;
+use crate::reader::datum::{fold_case_str, SChar};
 use crate::reader::datum::{SString, SimpleDatumValue};
 use crate::syntax::IDENTIFIER_WRAPPER;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashSet;
 use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, OnceLock, RwLock};
 use std::{fmt::Display, str::FromStr};
 use tracing::{error, trace};
 use unicode_categories::UnicodeCategories;
@@ -48,8 +54,18 @@ use unicode_categories::UnicodeCategories;
 // Public Types
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct SIdentifier(String);
+///
+/// An identifier, or symbol, interned as a reference-counted handle to its text; identifiers
+/// are equal, and hash, by their handle rather than by comparing text, and are ordered by their
+/// text. An identifier is `Clone` but not `Copy`, a clone only adds to the count of the text.
+///
+/// The text of every distinct identifier is held in a global table, behind a lock that each
+/// identifier read takes once, so that it is allocated once however many times the identifier
+/// is read. Text no longer held by any identifier is released before the table grows, or by
+/// [`SIdentifier::release_unused`].
+///
+#[derive(Clone)]
+pub struct SIdentifier(Arc<str>);
 
 // ------------------------------------------------------------------------------------------------
 // Public Functions
@@ -90,10 +106,56 @@ macro_rules! save_and_change_state {
     };
 }
 
+// ------------------------------------------------------------------------------------------------
+// Private Values
+// ------------------------------------------------------------------------------------------------
+
+static SYMBOLS: OnceLock<RwLock<HashSet<Arc<str>>>> = OnceLock::new();
+
+thread_local! {
+    static BUFFER: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
 // ------------------------------------------------------------------------------------------------
 // Implementations
 // ------------------------------------------------------------------------------------------------
 
+impl Default for SIdentifier {
+    fn default() -> Self {
+        Self::intern("")
+    }
+}
+
+impl PartialEq for SIdentifier {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for SIdentifier {}
+
+impl PartialOrd for SIdentifier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SIdentifier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self == other {
+            Ordering::Equal
+        } else {
+            self.0.cmp(&other.0)
+        }
+    }
+}
+
+impl Hash for SIdentifier {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::ptr::hash(Arc::as_ptr(&self.0), state)
+    }
+}
+
 impl Display for SIdentifier {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
@@ -106,7 +168,13 @@ impl Debug for SIdentifier {
     }
 }
 
-impl_datum_value!(Identifier, SIdentifier, String);
+impl_datum_value!(Identifier, SIdentifier);
+
+impl From<SIdentifier> for String {
+    fn from(v: SIdentifier) -> Self {
+        v.0.to_string()
+    }
+}
 
 impl_simple_datum_from_str!(Identifier, SIdentifier);
 
@@ -134,9 +202,20 @@ impl AsRef<str> for SIdentifier {
 
 impl SimpleDatumValue for SIdentifier {
     fn from_str_in_span(s: &str, span: Span) -> Result<Self, Error> {
-        let _span = ::tracing::trace_span!("from_str_in_span", s, ?span);
+        Self::text_in_span(s, span).map(|text| Self::intern(&text))
+    }
+}
+
+impl SIdentifier {
+    ///
+    /// Returns the text of the identifier `s` as it would be interned, borrowing `s`, or part
+    /// of it, unless escapes change the text.
+    ///
+    pub(crate) fn text_in_span(s: &str, span: Span) -> Result<Cow<'_, str>, Error> {
+        let _span = ::tracing::trace_span!("text_in_span", s, ?span);
         let _scope = _span.enter();
 
+        let text = s;
         let s = if s.starts_with(IDENTIFIER_WRAPPER) && s.ends_with(IDENTIFIER_WRAPPER) {
             &s[1..s.len() - 1]
         } else {
@@ -145,11 +224,13 @@ impl SimpleDatumValue for SIdentifier {
 
         if s.is_empty() {
             trace!("Identifier is an empty string");
-            return Ok(SIdentifier("||".to_string()));
+            return Ok(Cow::Borrowed("||"));
         }
 
+        // The text is built in a reused buffer, as it is only copied if escapes change it.
         let mut requires_escape = false;
-        let mut buffer = String::with_capacity(s.len());
+        let mut buffer = BUFFER.take();
+        buffer.clear();
         let mut current_state = ParseState::Start;
         let mut mark: usize = 0;
 
@@ -254,28 +335,39 @@ impl SimpleDatumValue for SIdentifier {
                 }
             }
         }
-        if current_state == ParseState::InNumber {
+        let result = if current_state == ParseState::InNumber {
             error!("identifier cannot be a number");
             invalid_identifier_input(span)
         } else if current_state == ParseState::InEscape || current_state == ParseState::InHexEscape
         {
             error!("incomplete escape sequence, in {current_state:?}");
             incomplete_string(span)
+        } else if requires_escape && s.len() < text.len() && buffer == s {
+            Ok(Cow::Borrowed(text))
         } else if requires_escape {
-            Ok(SIdentifier(format!(
+            Ok(Cow::Owned(format!(
                 "{IDENTIFIER_WRAPPER}{buffer}{IDENTIFIER_WRAPPER}"
             )))
+        } else if buffer == s {
+            Ok(Cow::Borrowed(s))
         } else {
-            Ok(SIdentifier(buffer))
-        }
+            Ok(Cow::Owned(buffer.clone()))
+        };
+        BUFFER.set(buffer);
+        result
     }
-}
 
-impl SIdentifier {
     pub fn as_str(&self) -> &str {
         self.as_ref()
     }
 
+    ///
+    /// Returns the case folded form of this identifier, as `string-foldcase`.
+    ///
+    pub fn fold_case(&self) -> Self {
+        Self::intern(&fold_case_str(&self.0))
+    }
+
     pub fn chars(&self) -> impl Iterator<Item = SChar> + '_ {
         self.0.chars().map(SChar::from)
     }
@@ -306,8 +398,61 @@ impl SIdentifier {
     {
         Self::from_str(s.as_ref()).is_ok()
     }
+
+    ///
+    /// Returns the number of distinct identifiers interned so far.
+    ///
+    pub fn interned_count() -> usize {
+        symbols().read().unwrap().len()
+    }
+
+    ///
+    /// Release the text of every interned identifier no longer held by any handle, returning
+    /// the number released.
+    ///
+    pub fn release_unused() -> usize {
+        release_unused(&mut symbols().write().unwrap())
+    }
+
+    ///
+    /// Returns the handle for `s`, which must already be in the form of a valid identifier,
+    /// adding it to the table of identifiers if it is not yet present.
+    ///
+    pub(crate) fn intern(s: &str) -> Self {
+        if let Some(symbol) = symbols().read().unwrap().get(s) {
+            return Self(symbol.clone());
+        }
+        let mut symbols = symbols().write().unwrap();
+        if let Some(symbol) = symbols.get(s) {
+            return Self(symbol.clone());
+        }
+        if symbols.len() == symbols.capacity() {
+            // unused text is released before the table grows, so that it stays in proportion
+            // to the identifiers in use.
+            release_unused(&mut symbols);
+        }
+        let symbol: Arc<str> = Arc::from(s);
+        symbols.insert(symbol.clone());
+        Self(symbol)
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
 // Private Functions
 // ------------------------------------------------------------------------------------------------
+
+#[inline(always)]
+fn symbols() -> &'static RwLock<HashSet<Arc<str>>> {
+    SYMBOLS.get_or_init(Default::default)
+}
+
+///
+/// Remove the text held by no handle but the table's own from `symbols`, returning the number
+/// removed. A handle is only made while holding the table's lock, so none can be made for text
+/// as it is removed.
+///
+fn release_unused(symbols: &mut HashSet<Arc<str>>) -> usize {
+    let count = symbols.len();
+    symbols.retain(|symbol| Arc::strong_count(symbol) > 1);
+    count - symbols.len()
+}
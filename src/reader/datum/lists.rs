@@ -102,6 +102,16 @@ impl SList {
         matches!(self, Self(None))
     }
 
+    #[inline(always)]
+    pub fn as_pair(&self) -> Option<&SPair> {
+        self.0.as_ref()
+    }
+
+    #[inline(always)]
+    pub fn as_pair_mut(&mut self) -> Option<&mut SPair> {
+        self.0.as_mut()
+    }
+
     pub fn is_improper_list(&self) -> bool {
         match &self.0 {
             None => false,
@@ -242,6 +252,13 @@ impl SPair {
         self.cdr = datum
     }
 
+    ///
+    /// Returns both the car and cdr, so that each may be changed independently.
+    ///
+    pub fn car_cdr_mut(&mut self) -> (&mut Rc<Datum>, &mut Rc<Datum>) {
+        (&mut self.car, &mut self.cdr)
+    }
+
     fn inner_fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.car())?;
 
@@ -71,7 +71,7 @@ macro_rules! impl_simple_datum_from_str {
 // Public Types
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone)]
 pub enum Datum {
     Quote(Box<Datum>),
     QuasiQuote(Box<Datum>),
@@ -87,6 +87,12 @@ pub enum Datum {
     ByteVector(SByteVector),
     Comment(SComment),
     Directive(SDirective),
+    Shared(SShared),
+    ///
+    /// A placeholder, covering the given span, for a datum that could not be read; only
+    /// returned by a reader that is recovering from errors.
+    ///
+    Error(Span),
 }
 
 pub trait DatumValue: Display + Debug + Into<Datum> {}
@@ -101,51 +107,50 @@ pub trait SimpleDatumValue: DatumValue + FromStr {
 
 impl Display for Datum {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Self::Quote(v) => format!("'{}", v),
-                Self::QuasiQuote(v) => format!("`{}", v),
-                Self::Unquote(v) => format!(",{}", v),
-                Self::UnquoteSplicing(v) => format!(",@{}", v),
-                Self::Identifier(v) => v.to_string(),
-                Self::Boolean(v) => v.to_string(),
-                Self::Char(v) => v.to_string(),
-                Self::Number(v) => v.to_string(),
-                Self::String(v) => v.to_string(),
-                Self::List(v) => v.to_string(),
-                Self::Vector(v) => v.to_string(),
-                Self::ByteVector(v) => v.to_string(),
-                Self::Comment(v) => v.to_string(),
-                Self::Directive(v) => v.to_string(),
-            }
-        )
+        shared::write_scope(|| self.write_display(f))
     }
 }
 
+///
+/// The alternate form, `{:#?}`, is the external representation of the datum laid out over
+/// multiple lines by the [pretty printer](crate::writer::pretty).
+///
 impl Debug for Datum {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Self::Quote(v) => format!("(quote {:?})", v),
-                Self::QuasiQuote(v) => format!("(quasiquote {:?})", v),
-                Self::Unquote(v) => format!("(unquote {:?})", v),
-                Self::UnquoteSplicing(v) => format!("(unquote-splicing {:?})", v),
-                Self::Identifier(v) => format!("{:?}", v),
-                Self::Boolean(v) => format!("{:?}", v),
-                Self::Char(v) => format!("{:?}", v),
-                Self::Number(v) => format!("{:?}", v),
-                Self::String(v) => format!("{:?}", v),
-                Self::List(v) => format!("{:?}", v),
-                Self::Vector(v) => format!("{:?}", v),
-                Self::ByteVector(v) => format!("{:?}", v),
-                Self::Comment(v) => format!("{:?}", v),
-                Self::Directive(v) => format!("{:?}", v),
-            }
-        )
+        if f.alternate() {
+            return write!(f, "{}", crate::writer::pretty::pretty(self));
+        }
+        shared::write_scope(|| self.write_debug(f))
+    }
+}
+
+///
+/// Datums are equal if they have the same structure; a shared datum is compared by its
+/// content, so that it is equal to an unshared copy, and circular structure is compared
+/// without looping.
+///
+impl PartialEq for Datum {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Shared(v), other) => v.eq_datum(other),
+            (this, Self::Shared(v)) => v.eq_datum(this),
+            (Self::Quote(v1), Self::Quote(v2)) => v1 == v2,
+            (Self::QuasiQuote(v1), Self::QuasiQuote(v2)) => v1 == v2,
+            (Self::Unquote(v1), Self::Unquote(v2)) => v1 == v2,
+            (Self::UnquoteSplicing(v1), Self::UnquoteSplicing(v2)) => v1 == v2,
+            (Self::Identifier(v1), Self::Identifier(v2)) => v1 == v2,
+            (Self::Boolean(v1), Self::Boolean(v2)) => v1 == v2,
+            (Self::Char(v1), Self::Char(v2)) => v1 == v2,
+            (Self::Number(v1), Self::Number(v2)) => v1 == v2,
+            (Self::String(v1), Self::String(v2)) => v1 == v2,
+            (Self::List(v1), Self::List(v2)) => v1 == v2,
+            (Self::Vector(v1), Self::Vector(v2)) => v1 == v2,
+            (Self::ByteVector(v1), Self::ByteVector(v2)) => v1 == v2,
+            (Self::Comment(v1), Self::Comment(v2)) => v1 == v2,
+            (Self::Directive(v1), Self::Directive(v2)) => v1 == v2,
+            (Self::Error(v1), Self::Error(v2)) => v1 == v2,
+            _ => false,
+        }
     }
 }
 
@@ -179,9 +184,15 @@ impl Datum {
         (list, List, SList),
         (vector, Vector, SVector),
         (byte_vector, ByteVector, SByteVector),
-        (comment, Comment, SComment)
+        (comment, Comment, SComment),
+        (shared, Shared, SShared)
     );
 
+    #[inline(always)]
+    pub fn is_error(&self) -> bool {
+        matches!(self, Self::Error(_))
+    }
+
     pub fn is_empty_list(&self) -> bool {
         self.as_list()
             .map(|list| list.is_empty())
@@ -201,7 +212,8 @@ impl Datum {
         (list, List, SList),
         (vector, Vector, SVector),
         (byte_vector, ByteVector, SByteVector),
-        (comment, Comment, SComment)
+        (comment, Comment, SComment),
+        (shared, Shared, SShared)
     );
 
     pub fn type_string(&self) -> &'static str {
@@ -220,7 +232,59 @@ impl Datum {
             (Self::Vector(_) => "vector"),
             (Self::ByteVector(_) => "byte-vector"),
             (Self::Comment(v) => v.type_string()),
-            (Self::Directive(_) => "directive")
+            (Self::Directive(_) => "directive"),
+            (Self::Shared(v) => v.get().map_or("shared", |datum| datum.type_string())),
+            (Self::Error(_) => "error")
+        )
+    }
+
+    fn write_display(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Quote(v) => format!("'{}", v),
+                Self::QuasiQuote(v) => format!("`{}", v),
+                Self::Unquote(v) => format!(",{}", v),
+                Self::UnquoteSplicing(v) => format!(",@{}", v),
+                Self::Identifier(v) => v.to_string(),
+                Self::Boolean(v) => v.to_string(),
+                Self::Char(v) => v.to_string(),
+                Self::Number(v) => v.to_string(),
+                Self::String(v) => v.to_string(),
+                Self::List(v) => v.to_string(),
+                Self::Vector(v) => v.to_string(),
+                Self::ByteVector(v) => v.to_string(),
+                Self::Comment(v) => v.to_string(),
+                Self::Directive(v) => v.to_string(),
+                Self::Shared(v) => v.to_string(),
+                Self::Error(_) => "#<error>".to_string(),
+            }
+        )
+    }
+
+    fn write_debug(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Quote(v) => format!("(quote {:?})", v),
+                Self::QuasiQuote(v) => format!("(quasiquote {:?})", v),
+                Self::Unquote(v) => format!("(unquote {:?})", v),
+                Self::UnquoteSplicing(v) => format!("(unquote-splicing {:?})", v),
+                Self::Identifier(v) => format!("{:?}", v),
+                Self::Boolean(v) => format!("{:?}", v),
+                Self::Char(v) => format!("{:?}", v),
+                Self::Number(v) => format!("{:?}", v),
+                Self::String(v) => format!("{:?}", v),
+                Self::List(v) => format!("{:?}", v),
+                Self::Vector(v) => format!("{:?}", v),
+                Self::ByteVector(v) => format!("{:?}", v),
+                Self::Comment(v) => format!("{:?}", v),
+                Self::Directive(v) => format!("{:?}", v),
+                Self::Shared(v) => format!("{:?}", v),
+                Self::Error(span) => format!("#<error {}>", span),
+            }
         )
     }
 }
@@ -233,7 +297,7 @@ mod booleans;
 pub use booleans::SBoolean;
 
 mod chars;
-pub use chars::{EscapeDefault, EscapeUnicode, SChar};
+pub use chars::{fold_case_str, EscapeDefault, EscapeUnicode, SChar};
 
 mod comments;
 pub use comments::SComment;
@@ -250,6 +314,9 @@ pub use lists::{SList, SPair, EMPTY_LIST};
 pub mod numbers;
 pub use numbers::{Complexnum, Fixnum, Flonum, Ratnum, SNumber};
 
+mod shared;
+pub use shared::SShared;
+
 mod strings;
 pub use strings::SString;
 
@@ -217,6 +217,7 @@ const REGEX_TEMPLATE_STR: &str = r##"
     $
     "##;
 
+#[allow(dead_code)]
 const REGEX_ANY_STR: &str = concatcp!(REGEX_FLAG_STR, REGEX_PREFIX_STR, REGEX_TEMPLATE_STR);
 
 const REGEX_BINARY_STR: &str = concatcp!(
@@ -0,0 +1,354 @@
+/*!
+Provides shared, and possibly circular, structure for datums read with datum labels.
+
+The R7RS datum label syntax, `#n=` and `#n#`, denotes a single object that appears in more
+than one place, possibly within itself as in `#0=(a . #0#)`. An [`SShared`] value is a handle
+to such an object: every handle created for a label refers to the same underlying datum, so
+identity is kept, see [`SShared::ptr_eq`].
+
+A reference that appears within the datum it refers to is held weakly, so that circular
+structure does not leak. Such a reference only resolves while some handle from outside the
+cycle, such as the datum returned by the reader, is alive.
+
+Equality, `Debug`, and `Display` are all safe on circular structure. Two datums are equal if
+they have the same structure, whether or not either is shared, and a cycle is written using
+datum labels, `#0=(a . #0#)`.
+
+# Example
+
+```rust
+use ffsr::lexer::Lexer;
+use ffsr::reader::Reader;
+
+let reader = Reader::from(Lexer::from("#0=(a b . #0#)"));
+let datum = reader.iter().next().unwrap().unwrap();
+
+assert!(datum.is_shared());
+assert_eq!(datum.to_string(), "#0=(a b . #0#)");
+assert_eq!(datum, datum.clone());
+```
+
+*/
+
+use super::Datum;
+use crate::syntax::{DATUM_ASSIGN_END, DATUM_LABEL_PREFIX, DATUM_REF_END};
+use std::cell::{OnceCell, RefCell};
+use std::collections::HashSet;
+use std::fmt::{Debug, Display};
+use std::rc::{Rc, Weak};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A handle to a datum that may be referenced from more than one place.
+///
+#[derive(Clone)]
+pub struct SShared {
+    label: u16,
+    target: Target,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+type Cell = OnceCell<Rc<Datum>>;
+
+#[derive(Clone)]
+enum Target {
+    Strong(Rc<Cell>),
+    Weak(Weak<Cell>),
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Values
+// ------------------------------------------------------------------------------------------------
+
+thread_local! {
+    /// Shared datums whose label has been written, `#n=`, and the write depth so that the
+    /// set is cleared when the outermost datum has been written.
+    static WRITING: RefCell<(usize, HashSet<usize>)> = RefCell::new((0, HashSet::new()));
+
+    /// Pairs of shared datum and datum assumed equal while comparing, and the comparison
+    /// depth so that the set is cleared when the outermost comparison completes.
+    static COMPARING: RefCell<(usize, HashSet<(usize, usize)>)> =
+        RefCell::new((0, HashSet::new()));
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for SShared {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write(f, |datum, f| write!(f, "{datum}"))
+    }
+}
+
+impl Debug for SShared {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write(f, |datum, f| write!(f, "{datum:?}"))
+    }
+}
+
+impl PartialEq for SShared {
+    fn eq(&self, other: &Self) -> bool {
+        self.eq_datum(&Datum::Shared(other.clone()))
+    }
+}
+
+impl_datum_value!(Shared, SShared);
+
+impl SShared {
+    ///
+    /// Create a new handle for `label`, the datum itself is set later with `resolve`.
+    ///
+    pub(crate) fn new(label: u16) -> Self {
+        Self {
+            label,
+            target: Target::Strong(Default::default()),
+        }
+    }
+
+    ///
+    /// Create a new handle for `label` that refers to `datum`.
+    ///
+    pub fn from_datum(label: u16, datum: Datum) -> Self {
+        let shared = Self::new(label);
+        shared.resolve(datum);
+        shared
+    }
+
+    #[inline(always)]
+    pub fn label(&self) -> u16 {
+        self.label
+    }
+
+    ///
+    /// Returns the shared datum, or `None` if this is a reference within a datum that has
+    /// since been dropped.
+    ///
+    pub fn get(&self) -> Option<Rc<Datum>> {
+        match &self.target {
+            Target::Strong(cell) => cell.get().cloned(),
+            Target::Weak(cell) => cell.upgrade().and_then(|cell| cell.get().cloned()),
+        }
+    }
+
+    ///
+    /// Returns the shared datum by reference, or `None` if this is a reference within the
+    /// datum itself, which only holds it weakly.
+    ///
+    pub(crate) fn get_ref(&self) -> Option<&Datum> {
+        match &self.target {
+            Target::Strong(cell) => cell.get().map(Rc::as_ref),
+            Target::Weak(_) => None,
+        }
+    }
+
+    ///
+    /// Returns `true` if both handles refer to the same datum.
+    ///
+    #[inline(always)]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        self.id() == other.id()
+    }
+
+    ///
+    /// Returns `true` if the shared datum contains a reference to itself.
+    ///
+    pub fn is_cyclic(&self) -> bool {
+        match self.get() {
+            Some(datum) => reaches(&datum, self.id(), &mut HashSet::new()),
+            None => false,
+        }
+    }
+
+    pub(crate) fn resolve(&self, datum: Datum) {
+        if let Target::Strong(cell) = &self.target {
+            let _ = cell.set(Rc::new(datum));
+        }
+    }
+
+    ///
+    /// Returns the datum to use for a reference, `#n#`, to this label. A shared compound
+    /// datum is referenced by handle; a simple datum, which has no identity to keep, is
+    /// copied. A reference within the datum itself is weak.
+    ///
+    pub(crate) fn reference(&self) -> Datum {
+        match self.get() {
+            Some(datum) if is_compound(&datum) => Datum::Shared(self.clone()),
+            Some(datum) => datum.as_ref().clone(),
+            None => Datum::Shared(self.downgrade()),
+        }
+    }
+
+    ///
+    /// Set the datum for this label, returning the datum to use in its place: either a
+    /// handle to it, if it is compound, or the datum itself.
+    ///
+    pub(crate) fn assign(&self, datum: Datum) -> Datum {
+        if is_compound(&datum) {
+            self.resolve(datum);
+            Datum::Shared(self.clone())
+        } else {
+            self.resolve(datum.clone());
+            datum
+        }
+    }
+
+    ///
+    /// Returns a weak handle, for use as a reference within the shared datum itself.
+    ///
+    pub(crate) fn downgrade(&self) -> Self {
+        Self {
+            label: self.label,
+            target: match &self.target {
+                Target::Strong(cell) => Target::Weak(Rc::downgrade(cell)),
+                Target::Weak(cell) => Target::Weak(cell.clone()),
+            },
+        }
+    }
+
+    ///
+    /// Compare the shared datum with `other`, assuming a pair already being compared is equal
+    /// so that the comparison of circular structure terminates.
+    ///
+    pub(crate) fn eq_datum(&self, other: &Datum) -> bool {
+        let other_shared = other.as_shared();
+        if let Some(other) = other_shared {
+            if self.ptr_eq(other) {
+                return true;
+            }
+        }
+        let key = (
+            self.id(),
+            other_shared.map_or(std::ptr::from_ref(other) as usize, SShared::id),
+        );
+        let assumed = COMPARING.with(|comparing| {
+            let mut comparing = comparing.borrow_mut();
+            comparing.0 += 1;
+            !comparing.1.insert(key)
+        });
+        let result = assumed
+            || match (self.get(), other_shared) {
+                (Some(datum), Some(other)) => match other.get() {
+                    Some(other) => *datum == *other,
+                    None => false,
+                },
+                (Some(datum), None) => *datum == *other,
+                (None, _) => false,
+            };
+        COMPARING.with(|comparing| {
+            let mut comparing = comparing.borrow_mut();
+            comparing.0 -= 1;
+            if comparing.0 == 0 {
+                comparing.1.clear();
+            }
+        });
+        result
+    }
+
+    #[inline(always)]
+    pub(crate) fn id(&self) -> usize {
+        match &self.target {
+            Target::Strong(cell) => Rc::as_ptr(cell) as usize,
+            Target::Weak(cell) => Weak::as_ptr(cell) as usize,
+        }
+    }
+
+    fn write(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        write_datum: impl Fn(&Datum, &mut std::fmt::Formatter<'_>) -> std::fmt::Result,
+    ) -> std::fmt::Result {
+        let id = self.id();
+        let datum = match self.get() {
+            Some(datum) if !WRITING.with(|writing| writing.borrow().1.contains(&id)) => datum,
+            _ => return write!(f, "{DATUM_LABEL_PREFIX}{}{DATUM_REF_END}", self.label),
+        };
+        write_scope(|| {
+            // a datum is labelled before it is written, so each back reference and each later
+            // occurrence is written as a reference to the label.
+            if self.is_cyclic() {
+                WRITING.with(|writing| writing.borrow_mut().1.insert(id));
+                write!(f, "{DATUM_LABEL_PREFIX}{}{DATUM_ASSIGN_END}", self.label)?;
+            }
+            write_datum(&datum, f)
+        })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Run `write` as part of writing a datum; the labels written within it are forgotten once
+/// the outermost datum has been written.
+///
+pub(crate) fn write_scope<T>(write: impl FnOnce() -> T) -> T {
+    WRITING.with(|writing| writing.borrow_mut().0 += 1);
+    let result = write();
+    WRITING.with(|writing| {
+        let mut writing = writing.borrow_mut();
+        writing.0 -= 1;
+        if writing.0 == 0 {
+            writing.1.clear();
+        }
+    });
+    result
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Returns `true` if `datum` is an object with an identity worth sharing, and that may
+/// contain references.
+///
+fn is_compound(datum: &Datum) -> bool {
+    match datum {
+        Datum::List(list) => !list.is_empty(),
+        Datum::Quote(_)
+        | Datum::QuasiQuote(_)
+        | Datum::Unquote(_)
+        | Datum::UnquoteSplicing(_)
+        | Datum::Vector(_) => true,
+        _ => false,
+    }
+}
+
+///
+/// Returns `true` if `datum` contains the shared datum identified by `id`; `visited` holds the
+/// shared datums already searched.
+///
+fn reaches(datum: &Datum, id: usize, visited: &mut HashSet<usize>) -> bool {
+    match datum {
+        Datum::Quote(v) | Datum::QuasiQuote(v) | Datum::Unquote(v) | Datum::UnquoteSplicing(v) => {
+            reaches(v, id, visited)
+        }
+        Datum::List(list) => {
+            let mut next = list.as_pair();
+            while let Some(pair) = next {
+                if reaches(pair.car(), id, visited) {
+                    return true;
+                }
+                match pair.cdr().as_ref() {
+                    Datum::List(list) => next = list.as_pair(),
+                    cdr => return reaches(cdr, id, visited),
+                }
+            }
+            false
+        }
+        Datum::Vector(vector) => vector.iter().any(|datum| reaches(datum, id, visited)),
+        Datum::Shared(shared) if shared.id() == id => true,
+        Datum::Shared(shared) if visited.insert(shared.id()) => shared
+            .get()
+            .is_some_and(|datum| reaches(&datum, id, visited)),
+        _ => false,
+    }
+}
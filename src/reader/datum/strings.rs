@@ -15,6 +15,7 @@ use crate::{
     lexer::token::Span,
     syntax::STRING_QUOTE,
 };
+use std::borrow::Cow;
 use std::fmt::{Debug, Display};
 use std::str::FromStr;
 use tracing::trace;
@@ -86,7 +87,7 @@ impl Debug for SString {
     }
 }
 
-impl_datum_value!(String, SString, String);
+impl_datum_value!(String, SString, infallible String);
 
 impl AsRef<str> for SString {
     fn as_ref(&self) -> &str {
@@ -182,6 +183,23 @@ impl SString {
         self.as_ref()
     }
 
+    ///
+    /// Returns the text of the string `s`, borrowing it without the quotes unless it contains
+    /// escapes.
+    ///
+    pub(crate) fn text_in_span(s: &str, span: Span) -> Result<Cow<'_, str>, Error> {
+        let text = if s.starts_with(STRING_QUOTE) && s.ends_with(STRING_QUOTE) && s.len() > 1 {
+            &s[1..s.len() - 1]
+        } else {
+            s
+        };
+        if text.contains('\\') {
+            Ok(Cow::Owned(Self::from_str_in_span(s, span)?.0))
+        } else {
+            Ok(Cow::Borrowed(text))
+        }
+    }
+
     pub fn chars(&self) -> impl Iterator<Item = SChar> + '_ {
         self.0.chars().map(SChar::from)
     }
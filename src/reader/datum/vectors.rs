@@ -87,6 +87,16 @@ impl SVector {
     pub fn append(&mut self, datum: Datum) {
         self.0.push(datum)
     }
+
+    #[inline(always)]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Datum> {
+        self.0.iter()
+    }
+
+    #[inline(always)]
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut Datum> {
+        self.0.iter_mut()
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -140,42 +150,26 @@ impl FromIterator<Fixnum> for SByteVector {
 }
 
 impl SByteVector {
-    pub(crate) fn try_append_datum(&mut self, datum: Datum, span: Span) -> Result<(), Error> {
-        let number = if let Some(number) = datum.as_number() {
-            number
-        } else {
-            error!(
-                "Invalid datum type {}, expecting fixnum",
-                datum.type_string()
-            );
-            return invalid_byte_input(span);
-        };
-
-        if let Some(fixnum) = number.as_fixnum() {
-            self.try_append(fixnum.clone())
-        } else {
-            error!(
-                "Invalid numeric type {}, expecting fixnum",
-                number.type_string()
-            );
-            invalid_byte_input(span)
-        }
-    }
-
     pub fn try_append(&mut self, fixnum: Fixnum) -> Result<(), Error> {
         if fixnum.deref() >= &Integer::from(0) && fixnum.deref() <= &Integer::from(255) {
             self.0.push(fixnum);
+            Ok(())
         } else {
-            panic!(
+            error!(
                 "Not a valid fixnum value, {NUMERIC_PREFIX_EXACT}{}..{NUMERIC_PREFIX_EXACT}{}",
                 u8::MIN,
                 u8::MAX
             );
+            invalid_byte_input(Span::default())
         }
-        Ok(())
     }
 
     pub fn append(&mut self, byte: u8) {
         self.0.push(byte.into());
     }
+
+    #[inline(always)]
+    pub fn iter(&self) -> impl Iterator<Item = &Fixnum> {
+        self.0.iter()
+    }
 }
@@ -0,0 +1,415 @@
+/*!
+Provides a [`Document`], the text of a source along with its top-level datums, which can be
+updated by a [`TextEdit`] without reading the entire text again.
+
+When an edit is applied, the datums that end before the edit are kept and reading restarts
+from the end of the last of them. Reading stops as soon as it reaches the start of a datum
+that follows the edit; that datum, and all those after it, are kept with their spans moved
+to account for the change in length of the text. The result is always the same as reading
+the edited text from the start.
+
+Datum labels (`#n=`/`#n#`) may refer to datums in earlier top-level forms, and a
+`#!fold-case` directive changes how all following datums are read, so while the text contains
+a label assignment or a fold-case directive every edit reads the entire text.
+
+# Example
+
+```rust
+use ffsr::reader::incremental::{Document, TextEdit};
+use ffsr::SourceId;
+
+let mut document = Document::new(SourceId::String, "(define a 1)\n(define b 2)\n(define c 3)");
+assert_eq!(document.datums().len(), 3);
+
+document.apply(TextEdit::replace(23, 24, "22"));
+
+assert_eq!(document.text(), "(define a 1)\n(define b 22)\n(define c 3)");
+assert_eq!(document.reused(), 2);
+assert_eq!(document.datums()[1].datum().unwrap().to_string(), "(define b 22)");
+assert_eq!(document.datums()[2].span().start(), 27);
+```
+
+*/
+
+use crate::error::Error;
+use crate::input::indices::Index;
+use crate::input::Input;
+use crate::lexer::token::Span;
+use crate::lexer::Lexer;
+use crate::reader::datum::Datum;
+use crate::reader::Reader;
+use crate::syntax::{DIRECTIVE_FOLD_CASE, DIRECTIVE_PREFIX_STR};
+use crate::{SourceId, Sourced};
+use std::ops::Range;
+use tracing::{trace, trace_span};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// An edit to the text of a [`Document`], replacing a byte range with new text.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextEdit {
+    range: Range<usize>,
+    text: String,
+}
+
+///
+/// A top-level datum, or error, read from a [`Document`].
+///
+#[derive(Debug)]
+pub struct TopLevelDatum {
+    start: Index,
+    end: Index,
+    span: Span,
+    result: Result<Datum, Error>,
+    at_top_level: bool,
+}
+
+#[derive(Debug)]
+pub struct Document {
+    id: SourceId,
+    text: String,
+    datums: Vec<TopLevelDatum>,
+    reused: usize,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl TextEdit {
+    ///
+    /// Replace the bytes in `range` with `text`.
+    ///
+    pub fn new<S>(range: Range<usize>, text: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            range,
+            text: text.into(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn replace<S>(start: usize, end: usize, text: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self::new(start..end, text)
+    }
+
+    #[inline(always)]
+    pub fn insert<S>(at: usize, text: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self::new(at..at, text)
+    }
+
+    #[inline(always)]
+    pub fn delete(range: Range<usize>) -> Self {
+        Self::new(range, String::new())
+    }
+
+    #[inline(always)]
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    #[inline(always)]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl TopLevelDatum {
+    ///
+    /// The span of the datum, not including any preceding comments, or the span of the error.
+    ///
+    #[inline(always)]
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    ///
+    /// The index of the start of the text read for this datum, including any preceding white
+    /// space and comments.
+    ///
+    #[inline(always)]
+    pub fn start_index(&self) -> Index {
+        self.start
+    }
+
+    ///
+    /// The index of the end of the text read for this datum.
+    ///
+    #[inline(always)]
+    pub fn end_index(&self) -> Index {
+        self.end
+    }
+
+    #[inline(always)]
+    pub fn result(&self) -> &Result<Datum, Error> {
+        &self.result
+    }
+
+    #[inline(always)]
+    pub fn datum(&self) -> Option<&Datum> {
+        self.result.as_ref().ok()
+    }
+
+    #[inline(always)]
+    pub fn error(&self) -> Option<&Error> {
+        self.result.as_ref().err()
+    }
+
+    #[inline(always)]
+    pub fn is_ok(&self) -> bool {
+        self.result.is_ok()
+    }
+
+    ///
+    /// Returns this datum moved by the given deltas, or `None` if that would move it before the
+    /// start of the source.
+    ///
+    fn shifted(self, bytes: isize, characters: isize) -> Option<Self> {
+        let shift = |index: Index| {
+            Some(Index::new(
+                index.byte().checked_add_signed(bytes)?,
+                index.character().checked_add_signed(characters)?,
+            ))
+        };
+        let start = shift(self.start)?;
+        let end = shift(self.end)?;
+        let span = self.span.checked_shifted(characters)?;
+        // error spans lie within the datum, so they cannot move before its start.
+        let result = self
+            .result
+            .map_err(|e| e.map_spans(|span| span.checked_shifted(characters).unwrap_or(span)));
+        Some(Self {
+            start,
+            end,
+            span,
+            result,
+            at_top_level: self.at_top_level,
+        })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl From<&str> for Document {
+    fn from(s: &str) -> Self {
+        Self::new(SourceId::String, s)
+    }
+}
+
+impl From<String> for Document {
+    fn from(s: String) -> Self {
+        Self::new(SourceId::String, s)
+    }
+}
+
+impl Sourced for Document {
+    #[inline(always)]
+    fn source_id(&self) -> &SourceId {
+        &self.id
+    }
+
+    #[inline(always)]
+    fn source_str(&self) -> &str {
+        &self.text
+    }
+}
+
+impl Document {
+    pub fn new<S>(id: SourceId, text: S) -> Self
+    where
+        S: Into<String>,
+    {
+        let text = text.into();
+        let (datums, _) = read_from(&id, &text, Index::default(), |_| None);
+        Self {
+            id,
+            text,
+            datums,
+            reused: 0,
+        }
+    }
+
+    #[inline(always)]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    #[inline(always)]
+    pub fn datums(&self) -> &[TopLevelDatum] {
+        &self.datums
+    }
+
+    ///
+    /// The number of datums kept, rather than read again, by the most recent edit.
+    ///
+    #[inline(always)]
+    pub fn reused(&self) -> usize {
+        self.reused
+    }
+
+    ///
+    /// Apply `edit` to the text of this document and update its datums.
+    ///
+    /// # Panics
+    ///
+    /// If the range of `edit` is out of bounds, or does not lie on character boundaries.
+    ///
+    pub fn apply(&mut self, edit: TextEdit) {
+        let _span = trace_span!("apply-edit", ?edit);
+        let _scope = _span.enter();
+
+        let TextEdit { range, text } = edit;
+        let byte_delta = text.len() as isize - range.len() as isize;
+        let char_delta =
+            text.chars().count() as isize - self.text[range.clone()].chars().count() as isize;
+        self.text.replace_range(range.clone(), &text);
+
+        if has_datum_assignment(&self.text) || has_fold_case_directive(&self.text) {
+            trace!("text contains a datum label or fold-case directive, reading all");
+            self.read_all();
+            return;
+        }
+
+        // keep the datums that end before the edit, up to one after which reading can restart.
+        let mut keep = self
+            .datums
+            .iter()
+            .take_while(|datum| datum.end.byte() < range.start)
+            .count();
+        while keep > 0 && !self.datums[keep - 1].at_top_level {
+            keep -= 1;
+        }
+        let mut following = self.datums.split_off(keep);
+        let restart = self
+            .datums
+            .last()
+            .map(|datum| datum.end)
+            .unwrap_or_default();
+
+        // the datums after the edit that reading can stop at, keyed by their new start.
+        let resync_at: Vec<(usize, usize)> = (1..following.len())
+            .filter(|i| following[*i].start.byte() >= range.end && following[i - 1].at_top_level)
+            .filter_map(|i| Some((following[i].start.byte().checked_add_signed(byte_delta)?, i)))
+            .collect();
+
+        let (read, resync) = read_from(&self.id, &self.text, restart, |index| {
+            resync_at
+                .iter()
+                .find(|(start, _)| *start == index.byte())
+                .map(|(_, i)| *i)
+        });
+        trace!(kept = keep, read = read.len(), ?resync, "re-read");
+
+        self.datums.extend(read);
+        self.reused = keep;
+        if let Some(i) = resync {
+            let shifted: Option<Vec<_>> = following
+                .drain(i..)
+                .map(|datum| datum.shifted(byte_delta, char_delta))
+                .collect();
+            match shifted {
+                Some(shifted) => {
+                    self.reused += shifted.len();
+                    self.datums.extend(shifted);
+                }
+                None => {
+                    trace!("edit moves a following datum before the start, reading all");
+                    self.read_all();
+                }
+            }
+        }
+    }
+
+    fn read_all(&mut self) {
+        self.datums = read_from(&self.id, &self.text, Index::default(), |_| None).0;
+        self.reused = 0;
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Read top-level datums from `text`, starting at `start`, until the end of the text or until
+/// `resync` returns a value for the end index of a datum after which the reader is between
+/// top-level datums.
+///
+fn read_from<F>(
+    id: &SourceId,
+    text: &str,
+    start: Index,
+    mut resync: F,
+) -> (Vec<TopLevelDatum>, Option<usize>)
+where
+    F: FnMut(Index) -> Option<usize>,
+{
+    let reader = Reader::from(Lexer::from(Input::new_at(
+        id.clone(),
+        &text[start.byte()..],
+        start,
+    )));
+    let mut iter = reader.iter();
+    let mut datums = Vec::new();
+    let mut datum_start = start;
+
+    while let Some(result) = iter.next() {
+        let end = iter.consumed_index();
+        let at_top_level = result.is_ok() && iter.is_at_top_level();
+        let span = match &result {
+            Ok(_) => iter.last_datum_span(),
+            Err(e) => e.span(),
+        }
+        .unwrap_or_else(|| Span::new(datum_start.character(), end.character()));
+        datums.push(TopLevelDatum {
+            start: datum_start,
+            end,
+            span,
+            result,
+            at_top_level,
+        });
+        datum_start = end;
+
+        if at_top_level {
+            if let Some(resync) = resync(end) {
+                return (datums, Some(resync));
+            }
+        }
+    }
+    (datums, None)
+}
+
+///
+/// Returns `true` if `text` may contain a datum label assignment, `#` followed by digits and
+/// `=`; this may be a false positive, such as within a string.
+///
+fn has_datum_assignment(text: &str) -> bool {
+    text.match_indices('#').any(|(i, _)| {
+        let rest = &text[i + 1..];
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        digits > 0 && rest[digits..].starts_with('=')
+    })
+}
+
+///
+/// Returns `true` if `text` may contain a `#!fold-case` directive; this may be a false
+/// positive, such as within a string.
+///
+fn has_fold_case_directive(text: &str) -> bool {
+    text.match_indices(DIRECTIVE_PREFIX_STR)
+        .any(|(i, prefix)| text[i + prefix.len()..].starts_with(DIRECTIVE_FOLD_CASE))
+}
@@ -1,24 +1,28 @@
-use crate::error::Error;
-use crate::lexer::token::Span;
-use crate::reader::datum::{Datum, SByteVector, SList, SVector};
+use crate::lexer::token::{Span, TokenKind};
+use crate::reader::datum::numbers::Number;
+use crate::reader::datum::{SBoolean, SChar, SComment, SDirective, SNumber};
+use std::borrow::Cow;
+use std::fmt::Debug;
 
 // ------------------------------------------------------------------------------------------------
 // Public Types
 // ------------------------------------------------------------------------------------------------
 
 #[derive(Debug, Default)]
-pub(crate) enum State {
+pub(crate) enum State<D> {
     #[default]
     TopLevel,
     Quote(Span, QuoteKind),
     DatumComment(Span),
     DatumAssign(Span, u16),
-    List(Span, SList),
-    Dot(Span, Option<Datum>),
-    Vector(Span, SVector),
-    ByteVector(Span, SByteVector),
-    #[allow(dead_code)]
-    FastForward(Error),
+    List(Span, Vec<D>),
+    Dot(Span, Option<D>),
+    Vector(Span, Vec<D>),
+    /// The elements of an open byte vector, and their values.
+    ByteVector(Span, Vec<D>, Vec<u8>),
+    /// Skipping the remainder of the form opened at span, by the token kind, after an
+    /// error; the count is of nested forms still open.
+    FastForward(Span, TokenKind, usize),
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -29,40 +33,139 @@ pub(crate) enum QuoteKind {
     UnquoteSplicing,
 }
 
+///
+/// Makes the values returned by a reader. The reader itself handles the structure of the
+/// input, datum labels, errors, and recovery, and calls a builder to make each value once it
+/// is complete. Spans passed to a builder are ranges of characters with an exclusive end.
+///
+pub(crate) trait Builder<'a> {
+    type Datum: Clone + Debug;
+
+    /// The state kept for a datum label, from its `#n=` to the end of the input.
+    type Label: Debug;
+
+    fn identifier(&mut self, text: Cow<'a, str>, span: Span) -> Self::Datum;
+
+    fn string(&mut self, text: Cow<'a, str>, span: Span) -> Self::Datum;
+
+    fn boolean(&mut self, value: SBoolean, span: Span) -> Self::Datum;
+
+    fn character(&mut self, value: SChar, span: Span) -> Self::Datum;
+
+    fn number(&mut self, value: SNumber, span: Span) -> Self::Datum;
+
+    fn directive(&mut self, value: SDirective, span: Span) -> Self::Datum;
+
+    /// A placeholder for a malformed datum, when the reader is recovering from errors.
+    fn error(&mut self, span: Span) -> Self::Datum;
+
+    /// A line or block comment, or `None` if this builder does not keep comments.
+    fn comment(&mut self, _comment: SComment, _span: Span) -> Option<Self::Datum> {
+        None
+    }
+
+    /// The datum following `#;`, or `None` if this builder does not keep comments.
+    fn datum_comment(&mut self, _datum: Self::Datum) -> Option<Self::Datum> {
+        None
+    }
+
+    /// The value of `datum` if it is a number that is a valid byte.
+    fn byte(datum: &Self::Datum) -> Option<u8>;
+
+    /// The span is of the quote token only.
+    fn quote(&mut self, kind: QuoteKind, span: Span, datum: Self::Datum) -> Self::Datum;
+
+    /// A list that is improper if it has a tail; the reader only passes a tail along with at
+    /// least one element.
+    fn list(
+        &mut self,
+        span: Span,
+        elements: Vec<Self::Datum>,
+        tail: Option<Self::Datum>,
+    ) -> Self::Datum;
+
+    fn vector(&mut self, span: Span, elements: Vec<Self::Datum>) -> Self::Datum;
+
+    /// A byte vector, with the elements it was read from and their values.
+    fn byte_vector(
+        &mut self,
+        span: Span,
+        elements: Vec<Self::Datum>,
+        bytes: Vec<u8>,
+    ) -> Self::Datum;
+
+    /// The state for the label `#n=` just read, before its datum is.
+    fn label(&mut self, label: u16) -> Self::Label;
+
+    /// A reference, `#n#`, which may be to a label whose datum is still being read.
+    fn reference(&mut self, label: &Self::Label, span: Span) -> Self::Datum;
+
+    /// Set the datum for a label, returning the datum to use in its place, or `None` if the
+    /// datum is only a reference to the label itself, as in `#0=#0#`.
+    fn assign(&mut self, label: &mut Self::Label, datum: Self::Datum) -> Option<Self::Datum>;
+}
+
 // ------------------------------------------------------------------------------------------------
 // Implementations
 // ------------------------------------------------------------------------------------------------
 
-impl State {
+impl<D> State<D> {
     #[inline(always)]
-    pub(crate) fn into_list(self) -> SList {
+    pub(crate) fn into_list(self) -> Vec<D> {
         match self {
-            State::List(_, list) => list,
+            State::List(_, elements) => elements,
             _ => panic!(),
         }
     }
 
     #[inline(always)]
-    pub(crate) fn into_vector(self) -> SVector {
+    pub(crate) fn into_vector(self) -> Vec<D> {
         match self {
-            State::Vector(_, vector) => vector,
+            State::Vector(_, elements) => elements,
             _ => panic!(),
         }
     }
 
     #[inline(always)]
-    pub(crate) fn into_byte_vector(self) -> SByteVector {
+    pub(crate) fn into_byte_vector(self) -> (Vec<D>, Vec<u8>) {
         match self {
-            State::ByteVector(_, byte_vector) => byte_vector,
+            State::ByteVector(_, elements, bytes) => (elements, bytes),
             _ => panic!(),
         }
     }
 
     #[inline(always)]
-    pub(crate) fn into_error(self) -> Error {
+    pub(crate) fn into_cdr(self) -> Option<D> {
         match self {
-            State::FastForward(err) => err,
+            State::Dot(_, cdr) => cdr,
             _ => panic!(),
         }
     }
+
+    ///
+    /// The span of this state if it is an open list, vector, or byte vector.
+    ///
+    #[inline(always)]
+    pub(crate) fn form_span(&self) -> Option<Span> {
+        match self {
+            State::List(span, _)
+            | State::Vector(span, _)
+            | State::ByteVector(span, _, _)
+            | State::FastForward(span, _, _) => Some(*span),
+            _ => None,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The value of `number` if it is an exact integer that is a valid byte.
+///
+pub(crate) fn byte_value(number: &SNumber) -> Option<u8> {
+    number
+        .as_fixnum()
+        .and_then(|fixnum| u8::try_from(*fixnum.value()).ok())
 }
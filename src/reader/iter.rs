@@ -12,17 +12,24 @@ YYYYY
 use crate::error::{
     duplicate_datum_label, incomplete_byte_vector, incomplete_datum_assignment,
     incomplete_datum_comment, incomplete_list, incomplete_quasi_quote, incomplete_quote,
-    incomplete_unquote, incomplete_unquote_splicing, incomplete_vector, invalid_datum_label,
-    pair_missing_cdr, pair_too_many_cdr, unexpected_token, unknown_datum_label, Error,
+    incomplete_unquote, incomplete_unquote_splicing, incomplete_vector, invalid_byte_input,
+    invalid_datum_label, pair_missing_car, pair_missing_cdr, pair_too_many_cdr, unexpected_token,
+    unexpected_token_within, unknown_datum_label, Error,
 };
+use crate::input::indices::Index;
+use crate::input::lines::{LineIndex, Position};
 use crate::lexer::iter::TokenIter;
-use crate::lexer::token::{Span, TokenKind};
+use crate::lexer::token::{Span, Token, TokenKind};
 use crate::reader::datum::{
-    Datum, SBoolean, SChar, SComment, SDirective, SIdentifier, SList, SNumber, SString,
-    SimpleDatumValue,
+    fold_case_str, Datum, SBoolean, SByteVector, SChar, SComment, SDirective, SIdentifier, SList,
+    SNumber, SPair, SShared, SString, SVector, SimpleDatumValue, EMPTY_LIST,
 };
-use crate::reader::internals::{QuoteKind, State};
-use crate::Sourced;
+use crate::reader::internals::{byte_value, Builder, QuoteKind, State};
+use crate::reader::syntax::SyntaxValue;
+use crate::syntax::{CHAR_PREFIX_STR, IDENTIFIER_WRAPPER};
+use crate::{SourceId, Sourced};
+use std::borrow::Cow;
+use std::cell::OnceCell;
 use std::collections::HashMap;
 use std::ops::{Range, RangeInclusive};
 use std::rc::Rc;
@@ -35,19 +42,49 @@ use tracing::{debug, error, trace, trace_span};
 
 #[derive(Debug)]
 pub struct DatumIter<'a> {
-    source: TokenIter<'a>,
-    return_comments: bool,
-    state_stack: Vec<State>,
-    ref_table: HashMap<u16, Datum>,
+    inner: BuildIter<'a, DatumBuilder>,
+}
+
+///
+/// A value read from source, with the span and source it was read from. Spans are ranges of
+/// characters with an exclusive end. Compound values hold annotated children, see
+/// [`SyntaxIter`](crate::reader::syntax::SyntaxIter).
+///
+#[derive(Clone, Debug)]
+pub struct SyntaxDatum {
+    span: Span,
+    source_id: Rc<SourceId>,
+    value: SyntaxValue,
+    /// The handle for a labelled compound datum, and each reference to it.
+    shared: Option<SShared>,
+    datum: OnceCell<Datum>,
 }
 
+///
+/// The state machine shared by all readers: it reads the structure of the input from the
+/// tokens of a [`TokenIter`] and calls a [`Builder`] to make each value.
+///
 #[derive(Debug)]
-pub struct SyntaxDatum<'a> {
+pub(crate) struct BuildIter<'a, B: Builder<'a>> {
     source: TokenIter<'a>,
-    span: Span,
-    datum: Datum,
+    builder: B,
+    return_comments: bool,
+    return_directives: bool,
+    fold_case: bool,
+    recover: bool,
+    pending: Option<B::Datum>,
+    state_stack: Vec<State<B::Datum>>,
+    ref_table: HashMap<u16, B::Label>,
+    datum_span: Option<Span>,
 }
 
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug, Default)]
+struct DatumBuilder;
+
 // ------------------------------------------------------------------------------------------------
 // Private Macros
 // ------------------------------------------------------------------------------------------------
@@ -68,12 +105,12 @@ macro_rules! pop_state {
 }
 
 macro_rules! handle_error {
-    ($fallible:expr) => {
+    ($me:expr, $current_state:expr, $span:expr, $fallible:expr) => {
         match $fallible {
             Ok(ok) => ok,
             Err(e) => {
                 error!("handle_error: {}", e);
-                return Some(Err(e));
+                return $me.recover_element($current_state, $span, e);
             }
         }
     };
@@ -82,43 +119,42 @@ macro_rules! handle_datum {
     ($self:expr, $current_state:expr, $datum:expr) => {
         let (datum, state) = match $self.handle_datum($current_state, $datum) {
             Ok(result) => result,
-            Err(e) => {
-                return Some(Err(e));
+            Err((e, state)) => {
+                error!("handle_datum: {}", e);
+                return $self.recover_structure(state, None, e);
             }
         };
         $current_state = state;
         if let Some(datum) = datum {
+            $self.suspend($current_state);
             return Some(Ok(datum));
         }
     };
 }
 
 macro_rules! handle_datum_from_str {
-    ($datum_type:ty, $token:expr => $me:expr, $current_state:expr) => {
-        let datum = handle_error!(<$datum_type>::from_str_in_span(
-            $me.source.token_str(&$token),
-            $token.span()
-        ));
-        handle_datum!($me, $current_state, Datum::from(datum.clone()));
+    ($datum_type:ty, $token:expr => $me:expr, $current_state:expr, $build:ident) => {
+        let datum = handle_error!(
+            $me,
+            $current_state,
+            $token.span(),
+            <$datum_type>::from_str_in_span($me.source.token_str(&$token), $token.span())
+        );
+        let span = $me.token_span(&$token);
+        let datum = $me.builder.$build(datum, span);
+        handle_datum!($me, $current_state, datum);
     };
 }
 
 macro_rules! return_error {
-    ($span:expr => $error_fn:expr) => {
-        return_error!("", $span => $error_fn);
-    };
-    ($message:expr, $span:expr => $error_fn:expr) => {
-        let err = $error_fn($span);
-        error!("{}{:?}", $message, err);
-        return Some(err);
-    };
-    ($span:expr => $error_fn:expr, $second:expr ) => {
-        return_error!("", $span => $error_fn, $second );
-    };
-    ($message:expr, $span:expr => $error_fn:expr, $second:expr ) => {
-        let err = $error_fn($span, $second);
-        error!("{}{:?}", $message, err);
-        return Some(err);
+    (
+        $me:expr, $current_state:expr, $span:expr => $error_fn:expr $(, $arg:expr)* ;
+        $recovery:ident $(, $recovery_arg:expr)*
+    ) => {
+        let result: Result<(), Error> = $error_fn($span $(, $arg)*);
+        let err = result.unwrap_err();
+        error!("{:?}", err);
+        return $me.$recovery($current_state $(, $recovery_arg)*, err);
     };
 }
 
@@ -126,31 +162,123 @@ macro_rules! return_error {
 // Implementations
 // ------------------------------------------------------------------------------------------------
 
-impl From<SyntaxDatum<'_>> for Datum {
-    fn from(v: SyntaxDatum<'_>) -> Self {
-        v.datum
+impl<'a> From<TokenIter<'a>> for DatumIter<'a> {
+    fn from(source: TokenIter<'a>) -> Self {
+        Self {
+            inner: BuildIter::new(source, DatumBuilder),
+        }
     }
 }
 
-impl Sourced for SyntaxDatum<'_> {
+impl Iterator for DatumIter<'_> {
+    type Item = Result<Datum, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+impl DatumIter<'_> {
+    pub(crate) fn with_comments(self) -> Self {
+        Self {
+            inner: self.inner.with_comments(),
+        }
+    }
+
+    ///
+    /// Set whether identifiers and character names are case folded before any `#!fold-case`
+    /// or `#!no-fold-case` directive is read; the default is `false`.
+    ///
+    pub fn with_fold_case(self, fold_case: bool) -> Self {
+        Self {
+            inner: self.inner.with_fold_case(fold_case),
+        }
+    }
+
+    ///
+    /// Set whether directives are returned as datums, the default, or only change the state
+    /// of this iterator.
+    ///
+    pub fn with_directives(self, return_directives: bool) -> Self {
+        Self {
+            inner: self.inner.with_directives(return_directives),
+        }
+    }
+
+    ///
+    /// Returns this iterator in recovering mode, with its tokens also read in recovering mode.
+    /// After an error the iterator continues reading: a malformed element is replaced by an
+    /// [`Datum::Error`] placeholder, and after a structural error, such as an unexpected `)`
+    /// or `.`, the rest of the enclosing form is skipped up to its balanced close and the
+    /// whole form is replaced by a placeholder. Every error is returned, followed by any
+    /// top-level placeholder, so all the diagnostics for a source can be collected in one pass.
+    ///
+    pub fn with_recovery(self) -> Self {
+        Self {
+            inner: self.inner.with_recovery(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn is_recovering(&self) -> bool {
+        self.inner.recover
+    }
+
+    ///
+    /// Returns `true` if identifiers and character names are currently being case folded.
+    ///
     #[inline(always)]
-    fn source_id(&self) -> &crate::SourceId {
-        self.source.source_id()
+    pub fn is_folding_case(&self) -> bool {
+        self.inner.fold_case
     }
 
     #[inline(always)]
-    fn source_str(&self) -> &str {
-        self.source.source_str()
+    pub(crate) fn consumed_index(&self) -> Index {
+        self.inner.source.consumed_index()
+    }
+
+    ///
+    /// The span of the top-level datum most recently returned, from its first token to its
+    /// last, not including any preceding comments, with an exclusive end.
+    ///
+    #[inline(always)]
+    pub fn last_datum_span(&self) -> Option<Span> {
+        self.inner.datum_span
+    }
+
+    ///
+    /// Returns `true` if this iterator is between top-level datums, with no partially read
+    /// datum. Reading may restart at such a point without changing the result.
+    ///
+    #[inline(always)]
+    pub(crate) fn is_at_top_level(&self) -> bool {
+        self.inner.state_stack.is_empty()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl From<SyntaxDatum> for Datum {
+    fn from(v: SyntaxDatum) -> Self {
+        v.to_datum()
     }
 }
 
-impl<'a> SyntaxDatum<'a> {
+impl PartialEq for SyntaxDatum {
+    fn eq(&self, other: &Self) -> bool {
+        self.span == other.span && self.source_id == other.source_id && self.value == other.value
+    }
+}
+
+impl SyntaxDatum {
     #[inline(always)]
-    pub fn new(source: TokenIter<'a>, span: Span, datum: Datum) -> Self {
+    pub fn new(span: Span, source_id: Rc<SourceId>, value: SyntaxValue) -> Self {
         Self {
-            source,
             span,
-            datum,
+            source_id,
+            value,
+            shared: None,
+            datum: OnceCell::new(),
         }
     }
 
@@ -185,47 +313,339 @@ impl<'a> SyntaxDatum<'a> {
     }
 
     #[inline(always)]
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    #[inline(always)]
+    pub fn source_id(&self) -> &SourceId {
+        &self.source_id
+    }
+
+    #[inline(always)]
+    pub fn start_position(&self, lines: &LineIndex) -> Position {
+        lines.position(self.start())
+    }
+
+    #[inline(always)]
+    pub fn end_position(&self, lines: &LineIndex) -> Position {
+        lines.position(self.end())
+    }
+
+    #[inline(always)]
+    pub fn value(&self) -> &SyntaxValue {
+        &self.value
+    }
+
+    #[inline(always)]
+    pub fn into_value(self) -> SyntaxValue {
+        self.value
+    }
+
+    ///
+    /// The plain datum for this value, stripped of annotations and made once on first use;
+    /// see [`SyntaxDatum::to_datum`].
+    ///
     pub fn datum(&self) -> &Datum {
-        &self.datum
+        self.datum.get_or_init(|| self.to_datum())
+    }
+
+    ///
+    /// The annotated children of a list, vector, or byte vector, not including the tail of an
+    /// improper list; empty for any other value.
+    ///
+    pub fn elements(&self) -> &[SyntaxDatum] {
+        match &self.value {
+            SyntaxValue::List(elements, _)
+            | SyntaxValue::Vector(elements)
+            | SyntaxValue::ByteVector(elements) => elements,
+            _ => &[],
+        }
+    }
+
+    ///
+    /// The tail of an improper list.
+    ///
+    pub fn tail(&self) -> Option<&SyntaxDatum> {
+        match &self.value {
+            SyntaxValue::List(_, tail) => tail.as_deref(),
+            _ => None,
+        }
+    }
+
+    ///
+    /// The datum wrapped by a quote, quasi-quote, unquote, or unquote-splicing.
+    ///
+    pub fn quoted(&self) -> Option<&SyntaxDatum> {
+        match &self.value {
+            SyntaxValue::Quote(v)
+            | SyntaxValue::QuasiQuote(v)
+            | SyntaxValue::Unquote(v)
+            | SyntaxValue::UnquoteSplicing(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Strip all annotations, returning the datum the reader would return for the same text.
+    /// A labelled datum, and each reference to it, is returned as the same shared datum.
+    ///
+    pub fn to_datum(&self) -> Datum {
+        if let Some(shared) = &self.shared {
+            return Datum::Shared(shared.clone());
+        }
+        match &self.value {
+            SyntaxValue::Quote(v) => v.to_datum().quote(),
+            SyntaxValue::QuasiQuote(v) => v.to_datum().quasiquote(),
+            SyntaxValue::Unquote(v) => v.to_datum().unquote(),
+            SyntaxValue::UnquoteSplicing(v) => v.to_datum().unquote_splicing(),
+            SyntaxValue::Atom(v) => v.clone(),
+            SyntaxValue::List(elements, tail) => list_datum(
+                elements.iter().map(SyntaxDatum::to_datum),
+                tail.as_ref().map(|tail| tail.to_datum()),
+            ),
+            SyntaxValue::Vector(elements) => elements
+                .iter()
+                .map(SyntaxDatum::to_datum)
+                .collect::<SVector>()
+                .into(),
+            SyntaxValue::ByteVector(elements) => elements
+                .iter()
+                .filter_map(|element| match &element.value {
+                    SyntaxValue::Atom(Datum::Number(number)) => byte_value(number),
+                    _ => None,
+                })
+                .fold(SByteVector::default(), |mut bytes, byte| {
+                    bytes.append(byte);
+                    bytes
+                })
+                .into(),
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn shared(&self) -> Option<&SShared> {
+        self.shared.as_ref()
+    }
+
+    #[inline(always)]
+    pub(crate) fn with_shared(self, shared: SShared) -> Self {
+        Self {
+            shared: Some(shared),
+            datum: OnceCell::new(),
+            ..self
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn with_span(self, span: Span) -> Self {
+        Self { span, ..self }
     }
 }
 
 // ------------------------------------------------------------------------------------------------
 
-impl<'a> From<TokenIter<'a>> for DatumIter<'a> {
-    fn from(source: TokenIter<'a>) -> Self {
+impl<'a> Builder<'a> for DatumBuilder {
+    type Datum = Datum;
+    type Label = SShared;
+
+    fn identifier(&mut self, text: Cow<'a, str>, _: Span) -> Datum {
+        SIdentifier::intern(&text).into()
+    }
+
+    fn string(&mut self, text: Cow<'a, str>, _: Span) -> Datum {
+        SString::from(text.into_owned()).into()
+    }
+
+    fn boolean(&mut self, value: SBoolean, _: Span) -> Datum {
+        value.into()
+    }
+
+    fn character(&mut self, value: SChar, _: Span) -> Datum {
+        value.into()
+    }
+
+    fn number(&mut self, value: SNumber, _: Span) -> Datum {
+        value.into()
+    }
+
+    fn directive(&mut self, value: SDirective, _: Span) -> Datum {
+        value.into()
+    }
+
+    fn error(&mut self, span: Span) -> Datum {
+        Datum::Error(span)
+    }
+
+    fn comment(&mut self, comment: SComment, _: Span) -> Option<Datum> {
+        Some(comment.into())
+    }
+
+    fn datum_comment(&mut self, datum: Datum) -> Option<Datum> {
+        Some(SComment::from(datum).into())
+    }
+
+    fn byte(datum: &Datum) -> Option<u8> {
+        datum.as_number().and_then(byte_value)
+    }
+
+    fn quote(&mut self, kind: QuoteKind, _: Span, datum: Datum) -> Datum {
+        match kind {
+            QuoteKind::Quote => datum.quote(),
+            QuoteKind::QuasiQuote => datum.quasiquote(),
+            QuoteKind::Unquote => datum.unquote(),
+            QuoteKind::UnquoteSplicing => datum.unquote_splicing(),
+        }
+    }
+
+    fn list(&mut self, _: Span, elements: Vec<Datum>, tail: Option<Datum>) -> Datum {
+        list_datum(elements, tail)
+    }
+
+    fn vector(&mut self, _: Span, elements: Vec<Datum>) -> Datum {
+        SVector::from(elements).into()
+    }
+
+    fn byte_vector(&mut self, _: Span, _: Vec<Datum>, bytes: Vec<u8>) -> Datum {
+        let mut byte_vector = SByteVector::default();
+        bytes.into_iter().for_each(|byte| byte_vector.append(byte));
+        byte_vector.into()
+    }
+
+    fn label(&mut self, label: u16) -> SShared {
+        SShared::new(label)
+    }
+
+    fn reference(&mut self, shared: &SShared, _: Span) -> Datum {
+        shared.reference()
+    }
+
+    fn assign(&mut self, shared: &mut SShared, datum: Datum) -> Option<Datum> {
+        match datum {
+            Datum::Shared(other) if other.ptr_eq(shared) => None,
+            Datum::Shared(other) => {
+                // an alias for a label already read, such as `#1=#0#`.
+                *shared = other.clone();
+                Some(Datum::Shared(other))
+            }
+            datum => Some(shared.assign(datum)),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<'a, B: Builder<'a>> Iterator for BuildIter<'a, B> {
+    type Item = Result<B::Datum, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.next_datum();
+        match self.source.file_id() {
+            Some(file_id) => next.map(|result| result.map_err(|e| e.with_file(file_id))),
+            None => next,
+        }
+    }
+}
+
+impl<'a, B: Builder<'a>> BuildIter<'a, B> {
+    pub(crate) fn new(source: TokenIter<'a>, builder: B) -> Self {
         Self {
             source,
+            builder,
             return_comments: false,
+            return_directives: true,
+            fold_case: false,
+            recover: false,
+            pending: None,
             state_stack: Default::default(),
             ref_table: Default::default(),
+            datum_span: None,
         }
     }
-}
 
-impl Iterator for DatumIter<'_> {
-    type Item = Result<Datum, Error>;
+    pub(crate) fn with_comments(self) -> Self {
+        Self {
+            return_comments: true,
+            ..self
+        }
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
+    pub(crate) fn with_fold_case(self, fold_case: bool) -> Self {
+        Self { fold_case, ..self }
+    }
+
+    pub(crate) fn with_directives(self, return_directives: bool) -> Self {
+        Self {
+            return_directives,
+            ..self
+        }
+    }
+
+    pub(crate) fn with_recovery(self) -> Self {
+        Self {
+            source: self.source.with_recovery(),
+            recover: true,
+            ..self
+        }
+    }
+
+    fn next_datum(&mut self) -> Option<Result<B::Datum, Error>> {
         let _span = trace_span!("next-datum", ?self.state_stack);
         let _scope = _span.enter();
 
-        let mut current_state = if let Some(state) = self.state_stack.pop() {
-            state
-        } else {
-            State::default()
-        };
+        if let Some(datum) = self.pending.take() {
+            trace!(datum = ?datum, "return placeholder");
+            return Some(Ok(datum));
+        }
+        if self.state_stack.is_empty() {
+            self.datum_span = None;
+        }
+        let mut current_state = self.state_stack.pop().unwrap_or_default();
 
         while let Some(token) = self.source.next() {
             let token = match token {
                 Ok(t) => t,
                 Err(e) => {
-                    return Some(Err(e));
+                    let span = e.span().unwrap_or_default();
+                    return self.recover_element(current_state, span, e);
                 }
             };
 
             trace!(current_state = ?current_state, token = ?token, "match");
 
+            self.datum_span = match self.datum_span {
+                Some(span) => Some(span.with_end_from(self.token_span(&token))),
+                None if self.state_stack.is_empty()
+                    && matches!(current_state, State::TopLevel)
+                    && (self.return_comments || !token.is_comment())
+                    && (self.return_directives || !token.is_directive()) =>
+                {
+                    Some(self.token_span(&token))
+                }
+                None => None,
+            };
+
+            if let State::FastForward(span, _, depth) = &mut current_state {
+                match token.kind() {
+                    TokenKind::OpenParenthesis
+                    | TokenKind::OpenVector
+                    | TokenKind::OpenByteVector => *depth += 1,
+                    TokenKind::CloseParenthesis if *depth > 0 => *depth -= 1,
+                    TokenKind::CloseParenthesis => {
+                        let placeholder = self.builder.error(span.with_end_from(token.span()));
+                        trace!(token = ?token, "fast forwarded");
+                        pop_state!(self, current_state);
+                        current_state = self.place(current_state, placeholder);
+                        if let Some(datum) = self.pending.take() {
+                            self.suspend(current_state);
+                            return Some(Ok(datum));
+                        }
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
             match (&current_state, token.kind()) {
                 // --------------------------------------------------------------------------------
                 (_, TokenKind::Quote) => {
@@ -258,96 +678,174 @@ impl Iterator for DatumIter<'_> {
                 }
                 // --------------------------------------------------------------------------------
                 (_, TokenKind::Identifier) => {
-                    handle_datum_from_str!(SIdentifier, token => self, current_state);
+                    let token_str = self.source.input_token_str(&token);
+                    let text = handle_error!(
+                        self,
+                        current_state,
+                        token.span(),
+                        SIdentifier::text_in_span(token_str, token.span())
+                    );
+                    let text = if self.fold_case && !token_str.starts_with(IDENTIFIER_WRAPPER) {
+                        match fold_case_str(&text) {
+                            folded if folded == text => text,
+                            folded => Cow::Owned(folded),
+                        }
+                    } else {
+                        text
+                    };
+                    let span = self.token_span(&token);
+                    let datum = self.builder.identifier(text, span);
+                    handle_datum!(self, current_state, datum);
                 }
                 (_, TokenKind::Boolean) => {
-                    handle_datum_from_str!(SBoolean, token => self, current_state);
+                    handle_datum_from_str!(SBoolean, token => self, current_state, boolean);
+                }
+                (_, TokenKind::Character) if self.fold_case => {
+                    let token_str = fold_char_name(self.source.token_str(&token));
+                    let datum = handle_error!(
+                        self,
+                        current_state,
+                        token.span(),
+                        SChar::from_str_in_span(&token_str, token.span())
+                    );
+                    let span = self.token_span(&token);
+                    let datum = self.builder.character(datum, span);
+                    handle_datum!(self, current_state, datum);
                 }
                 (_, TokenKind::Character) => {
-                    handle_datum_from_str!(SChar, token => self, current_state);
+                    handle_datum_from_str!(SChar, token => self, current_state, character);
                 }
                 (_, TokenKind::String) => {
-                    handle_datum_from_str!(SString, token => self, current_state);
+                    let text = handle_error!(
+                        self,
+                        current_state,
+                        token.span(),
+                        SString::text_in_span(self.source.input_token_str(&token), token.span())
+                    );
+                    let span = self.token_span(&token);
+                    let datum = self.builder.string(text, span);
+                    handle_datum!(self, current_state, datum);
                 }
                 (_, TokenKind::Number) => {
-                    handle_datum_from_str!(SNumber, token => self, current_state);
+                    handle_datum_from_str!(SNumber, token => self, current_state, number);
                 }
                 (_, TokenKind::Directive) => {
-                    handle_datum_from_str!(SDirective, token => self, current_state);
-                }
-                // --------------------------------------------------------------------------------
-                (State::FastForward(_), TokenKind::OpenParenthesis) => {
-                    trace!(token = ?token, "fast forwarded");
-                    let err = current_state.into_error();
-                    pop_state!(self, current_state);
-                    return Some(Err(err));
+                    let directive = match SDirective::from_str_in_span(
+                        self.source.token_str(&token),
+                        token.span(),
+                    ) {
+                        Ok(directive) => directive,
+                        Err(e) => {
+                            error!("handle_error: {}", e);
+                            return self.recover_skip(current_state, e);
+                        }
+                    };
+                    match directive {
+                        SDirective::FoldCase(fold_case) => {
+                            trace!(fold_case, "fold-case directive");
+                            self.fold_case = fold_case;
+                        }
+                    }
+                    if self.return_directives {
+                        let span = self.token_span(&token);
+                        let datum = self.builder.directive(directive, span);
+                        handle_datum!(self, current_state, datum);
+                    }
                 }
                 // --------------------------------------------------------------------------------
                 (_, TokenKind::OpenParenthesis) => {
-                    push_new_state!(
-                        self,
-                        current_state,
-                        State::List(token.span(), SList::empty())
-                    );
+                    push_new_state!(self, current_state, State::List(token.span(), Vec::new()));
                 }
-                (State::List(_, _), TokenKind::CloseParenthesis) => {
-                    let datum = current_state.into_list();
+                (State::List(span, _), TokenKind::CloseParenthesis) => {
+                    let span = span.with_end_from(self.token_span(&token));
+                    let elements = current_state.into_list();
                     pop_state!(self, current_state);
-                    handle_datum!(self, current_state, datum.into());
+                    let datum = self.builder.list(span, elements, None);
+                    handle_datum!(self, current_state, datum);
                 }
                 (State::List(_, _), TokenKind::Dot) => {
                     push_new_state!(self, current_state, State::Dot(token.span(), None));
                 }
                 (State::Dot(span, None), TokenKind::CloseParenthesis) => {
-                    return_error!(*span => pair_missing_cdr);
+                    let span = *span;
+                    return_error!(
+                        self, current_state, span => pair_missing_cdr;
+                        recover_structure, Some(token.span())
+                    );
                 }
-                (State::Dot(span, Some(cdr)), TokenKind::CloseParenthesis) => {
+                (State::Dot(span, Some(_)), TokenKind::CloseParenthesis) => {
                     let span = *span;
-                    let cdr = cdr.clone();
+                    let cdr = current_state.into_cdr();
                     pop_state!(self, current_state);
-                    let mut datum = current_state.into_list();
+                    let list_span = current_state.form_span().unwrap_or(span);
+                    let elements = current_state.into_list();
                     pop_state!(self, current_state);
-                    handle_error!(datum.append_improper(cdr.into(), Some(span)));
-                    handle_datum!(self, current_state, datum.into());
+                    if elements.is_empty() {
+                        handle_error!(
+                            self,
+                            current_state,
+                            list_span.with_end_from(token.span()),
+                            pair_missing_car::<()>(span)
+                        );
+                    }
+                    let datum = self.builder.list(
+                        list_span.with_end_from(self.token_span(&token)),
+                        elements,
+                        cdr,
+                    );
+                    handle_datum!(self, current_state, datum);
                 }
                 (State::Dot(span, _), TokenKind::Dot) => {
-                    return_error!(*span => pair_too_many_cdr);
+                    let span = *span;
+                    return_error!(
+                        self, current_state, span => pair_too_many_cdr;
+                        recover_structure, None
+                    );
                 }
                 // --------------------------------------------------------------------------------
                 (_, TokenKind::OpenVector) => {
-                    push_new_state!(
-                        self,
-                        current_state,
-                        State::Vector(token.span(), Default::default())
-                    );
+                    push_new_state!(self, current_state, State::Vector(token.span(), Vec::new()));
                 }
-                (State::Vector(_, _), TokenKind::CloseParenthesis) => {
-                    let datum = current_state.into_vector();
+                (State::Vector(span, _), TokenKind::CloseParenthesis) => {
+                    let span = span.with_end_from(self.token_span(&token));
+                    let elements = current_state.into_vector();
                     pop_state!(self, current_state);
-                    handle_datum!(self, current_state, datum.into());
+                    let datum = self.builder.vector(span, elements);
+                    handle_datum!(self, current_state, datum);
                 }
                 // --------------------------------------------------------------------------------
                 (_, TokenKind::OpenByteVector) => {
                     push_new_state!(
                         self,
                         current_state,
-                        State::ByteVector(token.span(), Default::default())
+                        State::ByteVector(token.span(), Vec::new(), Vec::new())
                     );
                 }
-                (State::ByteVector(_, _), TokenKind::CloseParenthesis) => {
-                    let datum = current_state.into_byte_vector();
+                (State::ByteVector(span, _, _), TokenKind::CloseParenthesis) => {
+                    let span = span.with_end_from(self.token_span(&token));
+                    let (elements, bytes) = current_state.into_byte_vector();
                     pop_state!(self, current_state);
-                    handle_datum!(self, current_state, datum.into());
+                    let datum = self.builder.byte_vector(span, elements, bytes);
+                    handle_datum!(self, current_state, datum);
                 }
                 // --------------------------------------------------------------------------------
                 (_, TokenKind::BlockComment) if self.return_comments => {
                     let content = self.source.token_str(&token);
                     let content = content[2..content.len() - 2].trim().to_string();
-                    return Some(Ok(SComment::Block(content).into()));
+                    let span = self.token_span(&token);
+                    if let Some(comment) = self.builder.comment(SComment::Block(content), span) {
+                        self.suspend(current_state);
+                        return Some(Ok(comment));
+                    }
                 }
                 (_, TokenKind::LineComment) if self.return_comments => {
                     let content = self.source.token_str(&token);
-                    return Some(Ok(SComment::Line(content.into()).into()));
+                    let comment = SComment::Line(content.into());
+                    let span = self.token_span(&token);
+                    if let Some(comment) = self.builder.comment(comment, span) {
+                        self.suspend(current_state);
+                        return Some(Ok(comment));
+                    }
                 }
                 (_, TokenKind::BlockComment | TokenKind::LineComment) => {}
                 // --------------------------------------------------------------------------------
@@ -366,12 +864,20 @@ impl Iterator for DatumIter<'_> {
                         Ok(label) => label,
                         Err(e) => {
                             error!("error making label from {label_str:?}, error: {e}");
-                            return_error!(token.span() => invalid_datum_label);
+                            return_error!(
+                                self, current_state, token.span() => invalid_datum_label;
+                                recover_skip
+                            );
                         }
                     };
                     if self.ref_table.contains_key(&label) {
-                        return_error!(token.span() => duplicate_datum_label, label);
+                        return_error!(
+                            self, current_state, token.span() => duplicate_datum_label, label;
+                            recover_skip
+                        );
                     }
+                    let shared = self.builder.label(label);
+                    self.ref_table.insert(label, shared);
                     push_new_state!(self, current_state, State::DatumAssign(token.span(), label));
                 }
                 (_, TokenKind::DatumRef) => {
@@ -381,26 +887,55 @@ impl Iterator for DatumIter<'_> {
                         Ok(label) => label,
                         Err(e) => {
                             error!("error making label from {label_str:?}, error: {e}");
-                            return_error!(token.span() => invalid_datum_label);
+                            return_error!(
+                                self, current_state, token.span() => invalid_datum_label;
+                                recover_element, token.span()
+                            );
                         }
                     };
-                    if let Some(datum) = self.ref_table.get(&label).cloned() {
+                    if let Some(shared) = self.ref_table.get(&label) {
+                        let span = self.token_span(&token);
+                        let datum = self.builder.reference(shared, span);
                         handle_datum!(self, current_state, datum);
                     } else {
-                        return_error!(token.span() => unknown_datum_label, label);
+                        return_error!(
+                            self, current_state, token.span() => unknown_datum_label, label;
+                            recover_element, token.span()
+                        );
                     }
                 }
                 // --------------------------------------------------------------------------------
                 (state, kind) => {
                     debug!(state = ?state, token = ?token, "unexpected");
-                    return_error!(token.span() => unexpected_token, kind);
+                    let close = (kind == TokenKind::CloseParenthesis).then(|| token.span());
+                    match self.enclosing_form_span(state) {
+                        Some(within) => {
+                            let within = within.with_end_from(token.span());
+                            return_error!(
+                                self, current_state,
+                                token.span() => unexpected_token_within, kind, within;
+                                recover_structure, close
+                            );
+                        }
+                        None => {
+                            return_error!(
+                                self, current_state, token.span() => unexpected_token, kind;
+                                recover_structure, close
+                            );
+                        }
+                    }
                 }
             }
         }
         match current_state {
             State::List(span, _) => Some(incomplete_list(span)),
             State::Vector(span, _) => Some(incomplete_vector(span)),
-            State::ByteVector(span, _) => Some(incomplete_byte_vector(span)),
+            State::ByteVector(span, _, _) => Some(incomplete_byte_vector(span)),
+            State::FastForward(span, open, _) => match open {
+                TokenKind::OpenVector => Some(incomplete_vector(span)),
+                TokenKind::OpenByteVector => Some(incomplete_byte_vector(span)),
+                _ => Some(incomplete_list(span)),
+            },
             State::DatumAssign(span, label) => Some(incomplete_datum_assignment(span, label)),
             State::DatumComment(span) => Some(incomplete_datum_comment(span)),
             State::Quote(span, kind) => match kind {
@@ -412,40 +947,56 @@ impl Iterator for DatumIter<'_> {
             _ => None,
         }
     }
-}
 
-impl DatumIter<'_> {
-    pub(crate) fn with_comments(self) -> Self {
-        Self {
-            return_comments: true,
-            ..self
-        }
+    ///
+    /// The character span of the token, always with an exclusive end.
+    ///
+    fn token_span(&self, token: &Token) -> Span {
+        let span = token.span();
+        let length = self.source.token_str(token).chars().count();
+        span.with_end_from(Span::from(span.start() + length))
     }
 
+    #[allow(clippy::type_complexity, clippy::result_large_err)]
     fn handle_datum(
         &mut self,
-        mut current_state: State,
-        mut datum: Datum,
-    ) -> Result<(Option<Datum>, State), Error> {
-        // do this before deciding what to do with the datum
-        if let State::DatumAssign(_, label) = current_state {
-            trace!(datum = ?datum, "assigning datum to label {label:?}");
-            pop_state!(self, current_state);
-            self.ref_table.insert(label, datum.clone());
-        }
-
-        while let State::Quote(_, q) = current_state {
-            datum = match q {
-                QuoteKind::Quote => datum.quote(),
-                QuoteKind::QuasiQuote => datum.quasiquote(),
-                QuoteKind::Unquote => datum.unquote(),
-                QuoteKind::UnquoteSplicing => datum.unquote_splicing(),
-            };
-            trace!(datum = ?datum, "quoted datum");
-            pop_state!(self, current_state);
+        mut current_state: State<B::Datum>,
+        mut datum: B::Datum,
+    ) -> Result<(Option<B::Datum>, State<B::Datum>), (Error, State<B::Datum>)> {
+        // a datum completes the labels and quotes before it, innermost first, so that
+        // `#0='a` labels the quote and `'#0=a` quotes the labelled datum.
+        loop {
+            match current_state {
+                State::DatumAssign(span, label) => {
+                    trace!(datum = ?datum, "assigning datum to label {label:?}");
+                    pop_state!(self, current_state);
+                    let assigned = self
+                        .ref_table
+                        .get_mut(&label)
+                        .and_then(|shared| self.builder.assign(shared, datum));
+                    datum = match assigned {
+                        Some(datum) => datum,
+                        None => {
+                            error!("datum label {label} assigned only to itself");
+                            return invalid_datum_label(span).map_err(|e| (e, current_state));
+                        }
+                    };
+                }
+                State::Quote(span, kind) => {
+                    datum = self.builder.quote(kind, span, datum);
+                    trace!(datum = ?datum, "quoted datum");
+                    pop_state!(self, current_state);
+                }
+                _ => break,
+            }
         }
 
         match current_state {
+            State::DatumComment(_) if self.return_comments => {
+                trace!(datum = ?datum, "return datum comment");
+                pop_state!(self, current_state);
+                Ok((self.builder.datum_comment(datum), current_state))
+            }
             State::DatumComment(_) => {
                 trace!(datum = ?datum, "ignoring datum");
                 pop_state!(self, current_state);
@@ -455,21 +1006,30 @@ impl DatumIter<'_> {
                 trace!(datum = ?datum, "adding datum to cdr of pair");
                 Ok((None, State::Dot(span, Some(datum))))
             }
-            State::Dot(span, Some(_)) => pair_too_many_cdr(span),
-            State::List(span, ref mut pair) => {
+            State::Dot(span, Some(_)) => pair_too_many_cdr(span).map_err(|e| (e, current_state)),
+            State::List(_, ref mut elements) => {
                 trace!(datum = ?datum, "adding datum to open list");
-                pair.append(Rc::new(datum), Some(span))?;
+                elements.push(datum);
                 Ok((None, current_state))
             }
-            State::Vector(_, ref mut vector) => {
+            State::Vector(_, ref mut elements) => {
                 trace!(datum = ?datum, "adding datum to open vector");
-                vector.append(datum);
+                elements.push(datum);
                 Ok((None, current_state))
             }
-            State::ByteVector(span, ref mut byte_vector) => {
+            State::ByteVector(span, ref mut elements, ref mut bytes) => {
                 trace!(datum = ?datum, "adding datum to open byte vector");
-                byte_vector.try_append_datum(datum, span)?;
-                Ok((None, current_state))
+                match B::byte(&datum) {
+                    Some(byte) => {
+                        elements.push(datum);
+                        bytes.push(byte);
+                        Ok((None, current_state))
+                    }
+                    None => {
+                        error!("Invalid datum {datum:?}, expecting a byte");
+                        invalid_byte_input(span).map_err(|e| (e, current_state))
+                    }
+                }
             }
             _ => {
                 trace!(datum = ?datum, "return datum");
@@ -477,12 +1037,155 @@ impl DatumIter<'_> {
             }
         }
     }
+
+    ///
+    /// The span of the innermost list, vector, or byte vector open in, or below, `state`.
+    ///
+    fn enclosing_form_span(&self, state: &State<B::Datum>) -> Option<Span> {
+        state
+            .form_span()
+            .or_else(|| self.state_stack.iter().rev().find_map(State::form_span))
+    }
+
+    ///
+    /// Save `state` so that the next call to `next_datum` resumes from it.
+    ///
+    fn suspend(&mut self, state: State<B::Datum>) {
+        if !matches!(state, State::TopLevel) {
+            self.state_stack.push(state);
+        }
+    }
+
+    ///
+    /// Recover from an error in a single element by putting a placeholder in its place.
+    ///
+    fn recover_element(
+        &mut self,
+        current_state: State<B::Datum>,
+        span: Span,
+        err: Error,
+    ) -> Option<Result<B::Datum, Error>> {
+        if self.recover {
+            let state = if matches!(current_state, State::FastForward(_, _, _)) {
+                current_state
+            } else {
+                let placeholder = self.builder.error(span);
+                self.place(current_state, placeholder)
+            };
+            self.suspend(state);
+        }
+        Some(Err(err))
+    }
+
+    ///
+    /// Recover from an error in the structure of a form by skipping the rest of the
+    /// innermost enclosing form; `close` is the span of the token that caused the error if
+    /// that token was itself the form's close.
+    ///
+    fn recover_structure(
+        &mut self,
+        current_state: State<B::Datum>,
+        close: Option<Span>,
+        err: Error,
+    ) -> Option<Result<B::Datum, Error>> {
+        if self.recover {
+            let state = self.resync(current_state, close);
+            self.suspend(state);
+        }
+        Some(Err(err))
+    }
+
+    ///
+    /// Recover from an error in a token that does not produce a datum by ignoring it.
+    ///
+    fn recover_skip(
+        &mut self,
+        current_state: State<B::Datum>,
+        err: Error,
+    ) -> Option<Result<B::Datum, Error>> {
+        if self.recover {
+            self.suspend(current_state);
+        }
+        Some(Err(err))
+    }
+
+    fn place(&mut self, current_state: State<B::Datum>, placeholder: B::Datum) -> State<B::Datum> {
+        match self.handle_datum(current_state, placeholder) {
+            Ok((Some(datum), state)) => {
+                self.pending = Some(datum);
+                state
+            }
+            Ok((None, state)) => state,
+            Err((e, state)) => {
+                debug!("placeholder not accepted: {}", e);
+                self.resync(state, None)
+            }
+        }
+    }
+
+    fn resync(
+        &mut self,
+        mut current_state: State<B::Datum>,
+        close: Option<Span>,
+    ) -> State<B::Datum> {
+        loop {
+            let open = match current_state {
+                State::TopLevel | State::FastForward(_, _, _) => return current_state,
+                State::List(_, _) => TokenKind::OpenParenthesis,
+                State::Vector(_, _) => TokenKind::OpenVector,
+                State::ByteVector(_, _, _) => TokenKind::OpenByteVector,
+                _ => {
+                    pop_state!(self, current_state);
+                    continue;
+                }
+            };
+            let span = current_state.form_span().unwrap();
+            trace!(?span, ?close, "resync");
+            return match close {
+                Some(close) => {
+                    pop_state!(self, current_state);
+                    let placeholder = self.builder.error(span.with_end_from(close));
+                    self.place(current_state, placeholder)
+                }
+                None => State::FastForward(span, open, 0),
+            };
+        }
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
 // Private Functions
 // ------------------------------------------------------------------------------------------------
 
+///
+/// Case fold the name in a character token, `#\NewLine` for example, leaving a single
+/// character, `#\A`, unchanged.
+///
+pub(crate) fn fold_char_name(token_str: &str) -> Cow<'_, str> {
+    match token_str.strip_prefix(CHAR_PREFIX_STR) {
+        Some(name) if name.chars().nth(1).is_some() => {
+            Cow::Owned(format!("{CHAR_PREFIX_STR}{}", fold_case_str(name)))
+        }
+        _ => Cow::Borrowed(token_str),
+    }
+}
+
+///
+/// The list of `elements`, improper if it has a `tail`.
+///
+fn list_datum<I>(elements: I, tail: Option<Datum>) -> Datum
+where
+    I: IntoIterator<Item = Datum>,
+    I::IntoIter: DoubleEndedIterator,
+{
+    elements
+        .into_iter()
+        .rev()
+        .fold(tail.unwrap_or(EMPTY_LIST), |cdr, car| {
+            SList::from(SPair::cons(Rc::new(car), Rc::new(cdr))).into()
+        })
+}
+
 #[inline(always)]
 fn is_datum(token: TokenKind) -> bool {
     matches!(
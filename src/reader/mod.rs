@@ -9,8 +9,11 @@ YYYYY
 
 */
 
+use crate::input::indices::Index;
+use crate::input::lines::LineIndex;
 use crate::lexer::Lexer;
 use crate::reader::iter::DatumIter;
+use crate::source_map::FileId;
 use crate::Sourced;
 
 // ------------------------------------------------------------------------------------------------
@@ -42,6 +45,16 @@ impl Sourced for Reader<'_> {
     fn source_str(&self) -> &str {
         self.source.source_str()
     }
+
+    #[inline(always)]
+    fn source_offset(&self) -> Index {
+        self.source.source_offset()
+    }
+
+    #[inline(always)]
+    fn file_id(&self) -> Option<FileId> {
+        self.source.file_id()
+    }
 }
 
 impl<'a> Reader<'a> {
@@ -50,10 +63,60 @@ impl<'a> Reader<'a> {
         DatumIter::from(self.source.tokens())
     }
 
+    ///
+    /// Returns the datums of this source along with its comments, each as a
+    /// [`Datum::Comment`](datum::Datum::Comment); the datum following `#;` is read in full and
+    /// returned as a [`SComment::Datum`](datum::SComment::Datum). A comment within a list or
+    /// vector is returned before the datum that contains it.
+    ///
     #[inline(always)]
     pub fn iter_with_comments(&'a self) -> DatumIter<'a> {
         DatumIter::from(self.source.tokens()).with_comments()
     }
+
+    ///
+    /// Returns the datums of this source in recovering mode, see [`DatumIter::with_recovery`].
+    ///
+    #[inline(always)]
+    pub fn iter_with_recovery(&'a self) -> DatumIter<'a> {
+        DatumIter::from(self.source.tokens()).with_recovery()
+    }
+
+    ///
+    /// Returns the datums of this source as span-annotated syntax, see
+    /// [`SyntaxIter`](syntax::SyntaxIter).
+    ///
+    #[inline(always)]
+    pub fn syntax_iter(&'a self) -> syntax::SyntaxIter<'a> {
+        syntax::SyntaxIter::from(self.source.tokens())
+    }
+
+    ///
+    /// Returns the datums of this source with the text of strings and identifiers borrowed
+    /// from it where possible, see [`BorrowedIter`](borrowed::BorrowedIter).
+    ///
+    #[inline(always)]
+    pub fn borrowed_iter(&'a self) -> borrowed::BorrowedIter<'a> {
+        borrowed::BorrowedIter::from(self.source.tokens())
+    }
+
+    ///
+    /// Returns the datums of this source with all of their nodes allocated in `arena`, see
+    /// [`ArenaIter`](arena::ArenaIter).
+    ///
+    #[cfg(feature = "arena")]
+    #[inline(always)]
+    pub fn arena_iter<'arena>(
+        &'a self,
+        arena: &'arena arena::Arena,
+    ) -> arena::ArenaIter<'a, 'arena> {
+        arena::ArenaIter::new(self.source.tokens(), arena)
+    }
+
+    #[inline(always)]
+    pub fn line_index(&self) -> &LineIndex {
+        self.source.line_index()
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -62,6 +125,21 @@ impl<'a> Reader<'a> {
 
 mod internals;
 
+#[cfg(feature = "arena")]
+pub mod arena;
+
+pub mod borrowed;
+
+pub mod comments;
+
+pub mod cst;
+
 pub mod datum;
 
+pub mod incremental;
+
 pub mod iter;
+
+pub mod stream;
+
+pub mod syntax;
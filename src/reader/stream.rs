@@ -0,0 +1,161 @@
+/*!
+Provides a datum iterator over a [`ChunkedInput`], returning each top-level datum as soon as
+its text has been read.
+
+Each call to `next` lexes and reads the buffered text; if reading ran into the end of the
+buffer before the underlying reader reached end of input the form may be incomplete, so more
+text is read and the form is read again. Once a datum is returned its text is removed from
+the buffer.
+
+Note that datum labels (`#n=`/`#n#`) are scoped to a single top-level datum.
+
+# Example
+
+```rust
+use ffsr::input::chunked_input_from_reader;
+use ffsr::reader::stream::DatumStream;
+use ffsr::SourceId;
+
+let data = "#t ; a comment\n(1 2 3)";
+let mut stream = DatumStream::from(chunked_input_from_reader(data.as_bytes(), SourceId::String));
+
+assert_eq!(stream.next().unwrap().unwrap().to_string(), "#t");
+assert_eq!(stream.next().unwrap().unwrap().to_string(), "(1 2 3)");
+assert!(stream.next().is_none());
+```
+
+*/
+
+use crate::error::Error;
+use crate::input::chunked::ChunkedInput;
+use crate::lexer::Lexer;
+use crate::reader::datum::Datum;
+use crate::reader::Reader;
+use std::io::Read;
+use tracing::{trace, trace_span};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug)]
+pub struct DatumStream<R> {
+    source: ChunkedInput<R>,
+    return_comments: bool,
+    return_directives: bool,
+    fold_case: bool,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl<R: Read> From<ChunkedInput<R>> for DatumStream<R> {
+    fn from(source: ChunkedInput<R>) -> Self {
+        Self {
+            source,
+            return_comments: false,
+            return_directives: true,
+            fold_case: false,
+        }
+    }
+}
+
+impl<R: Read> Iterator for DatumStream<R> {
+    type Item = Result<Datum, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let _span = trace_span!("next-streamed-datum", start = ?self.source.start_index());
+        let _scope = _span.enter();
+
+        loop {
+            let (result, consumed, fold_case) = {
+                let reader = Reader::from(Lexer::from(self.source.input()));
+                let iter = if self.return_comments {
+                    reader.iter_with_comments()
+                } else {
+                    reader.iter()
+                };
+                let mut iter = iter
+                    .with_fold_case(self.fold_case)
+                    .with_directives(self.return_directives);
+                let result = iter.next();
+                (result, iter.consumed_index(), iter.is_folding_case())
+            };
+
+            let end = self.source.end_index();
+            let eof = self.source.is_eof();
+            // if reading stopped at the end of the buffer the form may continue in text not
+            // yet read, e.g. `(a b` or `123` followed by `45`.
+            let maybe_truncated = consumed >= end && !eof;
+            trace!(
+                ?result,
+                ?consumed,
+                ?end,
+                eof,
+                maybe_truncated,
+                "read from buffer"
+            );
+
+            match result {
+                Some(Ok(datum)) if !maybe_truncated => {
+                    self.fold_case = fold_case;
+                    self.source.consume_to(consumed);
+                    return Some(Ok(datum));
+                }
+                Some(Err(e)) if !maybe_truncated => {
+                    self.fold_case = fold_case;
+                    if consumed > self.source.start_index() {
+                        self.source.consume_to(consumed);
+                    } else {
+                        self.source.consume_to(end);
+                    }
+                    return Some(Err(e));
+                }
+                None if eof => {
+                    self.source.consume_to(end);
+                    return None;
+                }
+                _ => {
+                    if let Err(e) = self.source.fill() {
+                        return Some(Err(e));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<R> DatumStream<R> {
+    pub fn with_comments(self) -> Self {
+        Self {
+            return_comments: true,
+            ..self
+        }
+    }
+
+    ///
+    /// Set whether identifiers and character names are case folded before any `#!fold-case`
+    /// or `#!no-fold-case` directive is read, see
+    /// [`DatumIter::with_fold_case`](crate::reader::iter::DatumIter::with_fold_case).
+    ///
+    pub fn with_fold_case(self, fold_case: bool) -> Self {
+        Self { fold_case, ..self }
+    }
+
+    ///
+    /// Set whether directives are returned as datums, see
+    /// [`DatumIter::with_directives`](crate::reader::iter::DatumIter::with_directives).
+    ///
+    pub fn with_directives(self, return_directives: bool) -> Self {
+        Self {
+            return_directives,
+            ..self
+        }
+    }
+
+    #[inline(always)]
+    pub fn source(&self) -> &ChunkedInput<R> {
+        &self.source
+    }
+}
@@ -0,0 +1,237 @@
+/*!
+Provides a span-annotated syntax tree, read from the tokens of a [`TokenIter`].
+
+Where [`DatumIter`](crate::reader::iter::DatumIter) returns bare [`Datum`] values, a
+[`SyntaxIter`] returns [`SyntaxDatum`] nodes in which every value, including each list and
+vector element, each quote wrapper, and the tail of an improper list, records the [`Span`] and
+[`SourceId`] it was read from. Any node can be stripped back to the plain [`Datum`] the reader
+would have returned.
+
+# Example
+
+```rust
+use ffsr::lexer::Lexer;
+use ffsr::reader::Reader;
+
+let reader = Reader::from(Lexer::from("(define x '(1 . 2))"));
+let syntax = reader.syntax_iter().next().unwrap().unwrap();
+
+let quoted = &syntax.elements()[2];
+assert_eq!(quoted.span().as_range(), 10..18);
+assert_eq!(quoted.to_datum().to_string(), "'(1 . 2)");
+assert_eq!(syntax.to_datum().to_string(), "(define x '(1 . 2))");
+```
+
+*/
+
+use crate::error::Error;
+use crate::lexer::iter::TokenIter;
+use crate::lexer::token::Span;
+use crate::reader::datum::{
+    Datum, SBoolean, SChar, SDirective, SIdentifier, SNumber, SShared, SString,
+};
+use crate::reader::internals::{byte_value, Builder, QuoteKind};
+use crate::reader::iter::BuildIter;
+use crate::{SourceId, Sourced};
+use std::borrow::Cow;
+use std::rc::Rc;
+
+pub use crate::reader::iter::SyntaxDatum;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The value of a [`SyntaxDatum`]; compound values hold annotated children.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum SyntaxValue {
+    Quote(Box<SyntaxDatum>),
+    QuasiQuote(Box<SyntaxDatum>),
+    Unquote(Box<SyntaxDatum>),
+    UnquoteSplicing(Box<SyntaxDatum>),
+    /// Any value without children: identifiers, booleans, characters, numbers, strings, and
+    /// directives. A reference to a labelled datum from within that datum, as in
+    /// `#0=(a . #0#)`, is also an atom holding a [`Datum::Shared`].
+    Atom(Datum),
+    /// The elements of a list, and the tail if the list is improper.
+    List(Vec<SyntaxDatum>, Option<Box<SyntaxDatum>>),
+    Vector(Vec<SyntaxDatum>),
+    ByteVector(Vec<SyntaxDatum>),
+}
+
+#[derive(Debug)]
+pub struct SyntaxIter<'a> {
+    inner: BuildIter<'a, SyntaxBuilder>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug)]
+struct SyntaxBuilder {
+    source_id: Rc<SourceId>,
+}
+
+#[derive(Debug)]
+struct SyntaxLabel {
+    shared: SShared,
+    /// The labelled node, once it has been read.
+    node: Option<SyntaxDatum>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl<'a> From<TokenIter<'a>> for SyntaxIter<'a> {
+    fn from(source: TokenIter<'a>) -> Self {
+        let source_id = Rc::new(source.source_id().clone());
+        Self {
+            inner: BuildIter::new(source, SyntaxBuilder { source_id }),
+        }
+    }
+}
+
+impl Iterator for SyntaxIter<'_> {
+    type Item = Result<SyntaxDatum, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+impl SyntaxIter<'_> {
+    ///
+    /// Set whether identifiers and character names are case folded before any `#!fold-case`
+    /// or `#!no-fold-case` directive is read; the default is `false`.
+    ///
+    pub fn with_fold_case(self, fold_case: bool) -> Self {
+        Self {
+            inner: self.inner.with_fold_case(fold_case),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl<'a> Builder<'a> for SyntaxBuilder {
+    type Datum = SyntaxDatum;
+    type Label = SyntaxLabel;
+
+    fn identifier(&mut self, text: Cow<'a, str>, span: Span) -> SyntaxDatum {
+        self.atom(SIdentifier::intern(&text).into(), span)
+    }
+
+    fn string(&mut self, text: Cow<'a, str>, span: Span) -> SyntaxDatum {
+        self.atom(SString::from(text.into_owned()).into(), span)
+    }
+
+    fn boolean(&mut self, value: SBoolean, span: Span) -> SyntaxDatum {
+        self.atom(value.into(), span)
+    }
+
+    fn character(&mut self, value: SChar, span: Span) -> SyntaxDatum {
+        self.atom(value.into(), span)
+    }
+
+    fn number(&mut self, value: SNumber, span: Span) -> SyntaxDatum {
+        self.atom(value.into(), span)
+    }
+
+    fn directive(&mut self, value: SDirective, span: Span) -> SyntaxDatum {
+        self.atom(value.into(), span)
+    }
+
+    fn error(&mut self, span: Span) -> SyntaxDatum {
+        self.atom(Datum::Error(span), span)
+    }
+
+    fn byte(syntax: &SyntaxDatum) -> Option<u8> {
+        match syntax.value() {
+            SyntaxValue::Atom(Datum::Number(number)) => byte_value(number),
+            _ => None,
+        }
+    }
+
+    fn quote(&mut self, kind: QuoteKind, span: Span, syntax: SyntaxDatum) -> SyntaxDatum {
+        let span = span.with_end_from(syntax.span());
+        let inner = Box::new(syntax);
+        self.node(
+            span,
+            match kind {
+                QuoteKind::Quote => SyntaxValue::Quote(inner),
+                QuoteKind::QuasiQuote => SyntaxValue::QuasiQuote(inner),
+                QuoteKind::Unquote => SyntaxValue::Unquote(inner),
+                QuoteKind::UnquoteSplicing => SyntaxValue::UnquoteSplicing(inner),
+            },
+        )
+    }
+
+    fn list(
+        &mut self,
+        span: Span,
+        elements: Vec<SyntaxDatum>,
+        tail: Option<SyntaxDatum>,
+    ) -> SyntaxDatum {
+        self.node(span, SyntaxValue::List(elements, tail.map(Box::new)))
+    }
+
+    fn vector(&mut self, span: Span, elements: Vec<SyntaxDatum>) -> SyntaxDatum {
+        self.node(span, SyntaxValue::Vector(elements))
+    }
+
+    fn byte_vector(&mut self, span: Span, elements: Vec<SyntaxDatum>, _: Vec<u8>) -> SyntaxDatum {
+        self.node(span, SyntaxValue::ByteVector(elements))
+    }
+
+    fn label(&mut self, label: u16) -> SyntaxLabel {
+        SyntaxLabel {
+            shared: SShared::new(label),
+            node: None,
+        }
+    }
+
+    fn reference(&mut self, label: &SyntaxLabel, span: Span) -> SyntaxDatum {
+        match &label.node {
+            // the reference takes the span of its own token, the children keep theirs.
+            Some(node) => node.clone().with_span(span),
+            None => {
+                let shared = label.shared.downgrade();
+                self.atom(Datum::Shared(shared.clone()), span)
+                    .with_shared(shared)
+            }
+        }
+    }
+
+    fn assign(&mut self, label: &mut SyntaxLabel, syntax: SyntaxDatum) -> Option<SyntaxDatum> {
+        let syntax = match syntax.shared() {
+            Some(other) if other.ptr_eq(&label.shared) => return None,
+            Some(other) => {
+                // an alias for a label already read, such as `#1=#0#`.
+                label.shared = other.clone();
+                syntax
+            }
+            None => match label.shared.assign(syntax.to_datum()) {
+                Datum::Shared(shared) => syntax.with_shared(shared),
+                _ => syntax,
+            },
+        };
+        label.node = Some(syntax.clone());
+        Some(syntax)
+    }
+}
+
+impl SyntaxBuilder {
+    #[inline(always)]
+    fn node(&self, span: Span, value: SyntaxValue) -> SyntaxDatum {
+        SyntaxDatum::new(span, self.source_id.clone(), value)
+    }
+
+    #[inline(always)]
+    fn atom(&self, datum: Datum, span: Span) -> SyntaxDatum {
+        self.node(span, SyntaxValue::Atom(datum))
+    }
+}
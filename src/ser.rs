@@ -0,0 +1,603 @@
+/*!
+Provides a serde [`Serializer`] that converts Rust values into S-expressions, enabled by the
+`serde` feature.
+
+Values are converted into [`Datum`] values as follows:
+
+| serde data model                  | Datum                                          |
+|-----------------------------------|------------------------------------------------|
+| `bool`                            | `#t` or `#f`                                   |
+| integers                          | an exact integer, an error if not within `i64` |
+| `f32`, `f64`                      | an inexact number                              |
+| `char`                            | a character                                    |
+| string                            | a string                                       |
+| bytes                             | a byte vector, `#u8(...)`                      |
+| `None`, unit, unit struct         | the empty list, `()`                           |
+| `Some(v)`, newtype struct         | the datum for `v`                              |
+| sequence                          | a list, or a vector, see [`SequenceStyle`]     |
+| tuple, tuple struct               | a list                                         |
+| map                               | an association list, `((k . v) ...)`           |
+| struct                            | an association list, or a keyword list, see [`StructStyle`] |
+| unit variant                      | a symbol, `Name`                               |
+| newtype, tuple, or struct variant | a list tagged with the variant, `(Name ...)`   |
+
+The reverse conversion is provided by [`crate::de`].
+
+# Example
+
+```rust
+use ffsr::ser::{to_string, Serializer, StructStyle};
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct Server {
+    host: String,
+    ports: Vec<u16>,
+}
+
+let server = Server { host: "localhost".into(), ports: vec![80, 443] };
+assert_eq!(
+    to_string(&server).unwrap(),
+    "((host . \"localhost\") (ports 80 443))"
+);
+assert_eq!(
+    Serializer::default()
+        .with_struct_style(StructStyle::KeywordList)
+        .to_string(&server)
+        .unwrap(),
+    "(host: \"localhost\" ports: (80 443))"
+);
+```
+
+*/
+
+use crate::error::{custom_error, Error};
+use crate::reader::datum::{
+    Datum, Fixnum, SByteVector, SIdentifier, SList, SPair, SString, SVector,
+};
+use crate::writer::write;
+use serde::ser::{self, Serialize};
+use std::fmt::Display;
+use std::rc::Rc;
+use std::str::FromStr;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// How a struct, or struct variant, is written.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum StructStyle {
+    /// A list of pairs, one for each field, `((name . value) ...)`.
+    #[default]
+    AssociationList,
+    /// A list of each field's keyword followed by its value, `(name: value ...)`.
+    KeywordList,
+}
+
+///
+/// How a sequence, such as a `Vec`, is written.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SequenceStyle {
+    #[default]
+    List,
+    Vector,
+}
+
+///
+/// The options for converting values, and the serde serializer that returns a [`Datum`].
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Serializer {
+    structs: StructStyle,
+    sequences: SequenceStyle,
+}
+
+#[derive(Debug)]
+pub struct SerializeSeq {
+    serializer: Serializer,
+    tag: Option<&'static str>,
+    vector: bool,
+    items: Vec<Datum>,
+}
+
+#[derive(Debug)]
+pub struct SerializeMap {
+    serializer: Serializer,
+    key: Option<Datum>,
+    items: Vec<Datum>,
+}
+
+#[derive(Debug)]
+pub struct SerializeStruct {
+    serializer: Serializer,
+    tag: Option<&'static str>,
+    items: Vec<Datum>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Convert `value` into a datum with the default options.
+///
+#[inline(always)]
+pub fn to_datum<T>(value: &T) -> Result<Datum, Error>
+where
+    T: Serialize + ?Sized,
+{
+    Serializer::default().to_datum(value)
+}
+
+///
+/// Convert `value` into the text of a datum with the default options.
+///
+#[inline(always)]
+pub fn to_string<T>(value: &T) -> Result<String, Error>
+where
+    T: Serialize + ?Sized,
+{
+    Serializer::default().to_string(value)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        custom_error::<(), _>(None, msg).unwrap_err()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Serializer {
+    pub fn with_struct_style(self, structs: StructStyle) -> Self {
+        Self { structs, ..self }
+    }
+
+    pub fn with_sequence_style(self, sequences: SequenceStyle) -> Self {
+        Self { sequences, ..self }
+    }
+
+    #[inline(always)]
+    pub fn struct_style(&self) -> StructStyle {
+        self.structs
+    }
+
+    #[inline(always)]
+    pub fn sequence_style(&self) -> SequenceStyle {
+        self.sequences
+    }
+
+    pub fn to_datum<T>(&self, value: &T) -> Result<Datum, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(*self)
+    }
+
+    ///
+    /// Convert `value` into the text of a datum, as written by [`write`](crate::writer::write()).
+    ///
+    pub fn to_string<T>(&self, value: &T) -> Result<String, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(write(&self.to_datum(value)?).to_string())
+    }
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = Datum;
+    type Error = Error;
+    type SerializeSeq = SerializeSeq;
+    type SerializeTuple = SerializeSeq;
+    type SerializeTupleStruct = SerializeSeq;
+    type SerializeTupleVariant = SerializeSeq;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeStruct;
+    type SerializeStructVariant = SerializeStruct;
+
+    fn serialize_bool(self, v: bool) -> Result<Datum, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Datum, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Datum, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Datum, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Datum, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Datum, Error> {
+        integer(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Datum, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Datum, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Datum, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Datum, Error> {
+        integer(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Datum, Error> {
+        integer(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Datum, Error> {
+        Ok(f64::from(v).into())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Datum, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_char(self, v: char) -> Result<Datum, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Datum, Error> {
+        Ok(SString::from(v.to_string()).into())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Datum, Error> {
+        Ok(v.iter()
+            .copied()
+            .map(Fixnum::from)
+            .collect::<SByteVector>()
+            .into())
+    }
+
+    fn serialize_none(self) -> Result<Datum, Error> {
+        Ok(SList::empty().into())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Datum, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Datum, Error> {
+        Ok(SList::empty().into())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Datum, Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Datum, Error> {
+        symbol(variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Datum, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Datum, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(SList::from(vec![symbol(variant)?, value.serialize(self)?]).into())
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeSeq, Error> {
+        Ok(SerializeSeq::new(
+            self,
+            None,
+            self.sequences == SequenceStyle::Vector,
+            len,
+        ))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeSeq, Error> {
+        Ok(SerializeSeq::new(self, None, false, Some(len)))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeSeq, Error> {
+        Ok(SerializeSeq::new(self, None, false, Some(len)))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeSeq, Error> {
+        Ok(SerializeSeq::new(self, Some(variant), false, Some(len)))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeMap, Error> {
+        Ok(SerializeMap {
+            serializer: self,
+            key: None,
+            items: Vec::with_capacity(len.unwrap_or_default()),
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeStruct, Error> {
+        Ok(SerializeStruct::new(self, None, len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeStruct, Error> {
+        Ok(SerializeStruct::new(self, Some(variant), len))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl SerializeSeq {
+    fn new(
+        serializer: Serializer,
+        tag: Option<&'static str>,
+        vector: bool,
+        len: Option<usize>,
+    ) -> Self {
+        Self {
+            serializer,
+            tag,
+            vector,
+            items: Vec::with_capacity(len.unwrap_or_default() + 1),
+        }
+    }
+
+    fn push<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.items.push(value.serialize(self.serializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Datum, Error> {
+        match self.tag {
+            Some(tag) => tagged(tag, self.items),
+            None if self.vector => Ok(SVector::from(self.items).into()),
+            None => Ok(SList::from(self.items).into()),
+        }
+    }
+}
+
+impl ser::SerializeSeq for SerializeSeq {
+    type Ok = Datum;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Datum, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SerializeSeq {
+    type Ok = Datum;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Datum, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeSeq {
+    type Ok = Datum;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Datum, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeSeq {
+    type Ok = Datum;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Datum, Error> {
+        self.finish()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = Datum;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.key = Some(key.serialize(self.serializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = match self.key.take() {
+            Some(key) => key,
+            None => return custom_error(None, "map value serialized before its key"),
+        };
+        self.items
+            .push(pair(key, value.serialize(self.serializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Datum, Error> {
+        Ok(SList::from(self.items).into())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl SerializeStruct {
+    fn new(serializer: Serializer, tag: Option<&'static str>, len: usize) -> Self {
+        Self {
+            serializer,
+            tag,
+            items: Vec::with_capacity(len * 2 + 1),
+        }
+    }
+
+    fn push<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let value = value.serialize(self.serializer)?;
+        match self.serializer.structs {
+            StructStyle::AssociationList => self.items.push(pair(symbol(key)?, value)),
+            StructStyle::KeywordList => {
+                self.items.push(symbol(&format!("{key}:"))?);
+                self.items.push(value);
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Datum, Error> {
+        match self.tag {
+            Some(tag) => tagged(tag, self.items),
+            None => Ok(SList::from(self.items).into()),
+        }
+    }
+}
+
+impl ser::SerializeStruct for SerializeStruct {
+    type Ok = Datum;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Datum, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeStruct {
+    type Ok = Datum;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Datum, Error> {
+        self.finish()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn integer<T>(v: T) -> Result<Datum, Error>
+where
+    T: TryInto<i64> + Display + Copy,
+{
+    match v.try_into() {
+        Ok(v) => Ok(v.into()),
+        Err(_) => custom_error(None, format!("the integer {v} is too large for a fixnum")),
+    }
+}
+
+///
+/// The symbol `name`, written between `|` if it is not a valid identifier as it is.
+///
+fn symbol(name: &str) -> Result<Datum, Error> {
+    SIdentifier::from_str(name)
+        .or_else(|_| SIdentifier::from_str(&format!("|{name}|")))
+        .map(Datum::from)
+}
+
+fn pair(car: Datum, cdr: Datum) -> Datum {
+    SList::from(SPair::cons(Rc::new(car), Rc::new(cdr))).into()
+}
+
+fn tagged(tag: &str, mut items: Vec<Datum>) -> Result<Datum, Error> {
+    items.insert(0, symbol(tag)?);
+    Ok(SList::from(items).into())
+}
@@ -0,0 +1,168 @@
+/*!
+Provides a registry of all the sources read by a program, each identified by a compact
+[`FileId`].
+
+An input added to a [`SourceMap`] is stamped with its `FileId`, and every token, and every
+error, produced from that input carries the `FileId` in its [`Span`](crate::lexer::token::Span).
+This allows an error to be reported against the correct file, using
+[`Error::print_with`](crate::error::Error::print_with), without the caller having to keep
+track of which text it came from.
+
+# Example
+
+```rust
+use ffsr::lexer::Lexer;
+use ffsr::reader::Reader;
+use ffsr::source_map::SourceMap;
+use ffsr::Sourced;
+
+let mut sources = SourceMap::default();
+let first = sources.add_named("repl-1", "(define x 1)");
+let second = sources.add_named("repl-2", "(display x");
+
+let reader = Reader::from(Lexer::from(sources.input(second).unwrap()));
+let error = reader.iter().find_map(|datum| datum.err()).unwrap();
+
+assert_eq!(error.span().unwrap().file(), Some(second));
+assert_ne!(first, second);
+assert_eq!(sources.get(second).unwrap().source_id().to_string(), "repl-2");
+```
+
+*/
+
+use crate::error::Error;
+use crate::input::encoding::Encoding;
+use crate::input::{input_from_file, input_from_file_with_encoding, Input};
+use crate::{SourceId, Sourced};
+use std::fmt::Display;
+use std::path::PathBuf;
+use tracing::trace;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// A compact identifier for a source within a [`SourceMap`].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileId(u32);
+
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    sources: Vec<Input<'static>>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for FileId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+impl From<FileId> for u32 {
+    fn from(v: FileId) -> Self {
+        v.0
+    }
+}
+
+impl FileId {
+    #[inline(always)]
+    fn index(&self) -> usize {
+        self.0 as usize
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl SourceMap {
+    ///
+    /// Add `input` to this map, returning its new identifier.
+    ///
+    pub fn add(&mut self, mut input: Input<'static>) -> FileId {
+        let file_id =
+            FileId(u32::try_from(self.sources.len()).expect("too many sources for a source map"));
+        trace!("adding source {:?} as {file_id}", input.source_id());
+        input.set_file_id(file_id);
+        self.sources.push(input);
+        file_id
+    }
+
+    ///
+    /// Read the file at `path` with [`input_from_file`] and add it to this map.
+    ///
+    pub fn add_file<P>(&mut self, path: P) -> Result<FileId, Error>
+    where
+        P: Into<PathBuf>,
+    {
+        Ok(self.add(input_from_file(path)?))
+    }
+
+    ///
+    /// Read the file at `path` with [`input_from_file_with_encoding`] and add it to this map.
+    ///
+    pub fn add_file_with_encoding<P>(
+        &mut self,
+        path: P,
+        encoding: Encoding,
+    ) -> Result<FileId, Error>
+    where
+        P: Into<PathBuf>,
+    {
+        Ok(self.add(input_from_file_with_encoding(path, encoding)?))
+    }
+
+    ///
+    /// Add a virtual source, such as a REPL cell, generated code, or the content of a URL,
+    /// identified by `name`.
+    ///
+    pub fn add_named<N, S>(&mut self, name: N, text: S) -> FileId
+    where
+        N: Into<String>,
+        S: Into<String>,
+    {
+        self.add(Input::from(text.into()).with_source_id(SourceId::Named(name.into())))
+    }
+
+    #[inline(always)]
+    pub fn get(&self, file_id: FileId) -> Option<&Input<'static>> {
+        self.sources.get(file_id.index())
+    }
+
+    ///
+    /// Returns a new input, borrowing the text of the source `file_id`, to be lexed. Tokens,
+    /// and errors, from this input carry `file_id` in their spans.
+    ///
+    pub fn input(&self, file_id: FileId) -> Option<Input<'_>> {
+        self.get(file_id).map(|input| input.borrowed())
+    }
+
+    ///
+    /// Find the identifier of the first source added with `source_id`.
+    ///
+    pub fn find(&self, source_id: &SourceId) -> Option<FileId> {
+        self.iter()
+            .find(|(_, input)| input.source_id() == source_id)
+            .map(|(file_id, _)| file_id)
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.sources.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (FileId, &Input<'static>)> {
+        self.sources
+            .iter()
+            .enumerate()
+            .map(|(i, input)| (FileId(i as u32), input))
+    }
+}
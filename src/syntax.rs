@@ -121,6 +121,14 @@ pub(crate) const QUASI_QUOTE_ABBREV: char = '`';
 pub(crate) const UNQUOTE_ABBREV: char = ',';
 pub(crate) const UNQUOTE_SPLICING_ABBREV: char = '@';
 
+// ------------------------------------------------------------------------------------------------
+// Datum Labels
+// ------------------------------------------------------------------------------------------------
+
+pub(crate) const DATUM_LABEL_PREFIX: char = '#';
+pub(crate) const DATUM_ASSIGN_END: char = '=';
+pub(crate) const DATUM_REF_END: char = '#';
+
 // ------------------------------------------------------------------------------------------------
 // Directives
 // ------------------------------------------------------------------------------------------------
@@ -0,0 +1,728 @@
+/*!
+Provides traversal of a tree of datums, either to inspect it with a [`Visit`] implementation,
+to change it in place with a [`VisitMut`] implementation, or to compute a value from it or
+rebuild it bottom-up with [`fold`] and [`transform`].
+
+Each walk covers the datum quoted by a quote, quasi-quote, unquote, or unquote-splicing, the
+datum within a datum comment, every pair of a list along with its car, the tail of an
+improper list, the elements of a vector, and the bytes of a byte vector. The walks keep their
+own stack rather than recursing, so that deeply nested input cannot overflow the call stack.
+
+The target of a shared datum, from a datum label, is walked by [`walk`] the first time it is
+reached, so that circular structure is walked once; it is treated as a single value by
+[`walk_mut`], [`fold`], and [`transform`].
+
+# Example
+
+```rust
+use ffsr::lexer::Lexer;
+use ffsr::reader::datum::{Datum, SIdentifier};
+use ffsr::reader::Reader;
+use ffsr::visit::{transform, walk, Visit, Walk};
+use std::str::FromStr;
+
+#[derive(Default)]
+struct Identifiers(Vec<String>);
+
+impl Visit for Identifiers {
+    fn visit_identifier(&mut self, identifier: &SIdentifier) -> Walk {
+        self.0.push(identifier.to_string());
+        Walk::Continue
+    }
+}
+
+let reader = Reader::from(Lexer::from("(define (f x) '(g . x))"));
+let datum = reader.iter().next().unwrap().unwrap();
+
+let mut identifiers = Identifiers::default();
+walk(&mut identifiers, &datum);
+assert_eq!(identifiers.0, ["define", "f", "x", "g", "x"]);
+
+let renamed = transform(&datum, |datum| {
+    (datum.as_identifier().map(|v| v.as_str()) == Some("x"))
+        .then(|| SIdentifier::from_str("y").unwrap().into())
+});
+assert_eq!(renamed.to_string(), "(define (f y) '(g . y))");
+```
+
+*/
+
+use crate::lexer::token::Span;
+use crate::reader::datum::{
+    Datum, Fixnum, SBoolean, SByteVector, SChar, SComment, SDirective, SIdentifier, SList, SNumber,
+    SPair, SShared, SString, SVector, EMPTY_LIST,
+};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Returned by each visitor method to direct the walk.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Walk {
+    /// Continue the walk, including the content of the current value.
+    #[default]
+    Continue,
+    /// Continue the walk, but not into the content of the current value.
+    Skip,
+    /// End the walk.
+    Stop,
+}
+
+///
+/// A visitor over a tree of datums, driven by [`walk`]. Every method has a default that
+/// continues the walk, so an implementation only provides the methods it needs.
+///
+/// For each datum [`enter_datum`](Visit::enter_datum) is called first, then the method for its
+/// type, then its content is walked, and finally [`leave_datum`](Visit::leave_datum) is called.
+///
+pub trait Visit {
+    ///
+    /// Called before any other method for a datum; returning [`Walk::Skip`] skips the datum
+    /// entirely, including the call to `leave_datum`.
+    ///
+    fn enter_datum(&mut self, _datum: &Datum) -> Walk {
+        Walk::Continue
+    }
+
+    ///
+    /// Called once the content of a datum has been walked.
+    ///
+    fn leave_datum(&mut self, _datum: &Datum) -> Walk {
+        Walk::Continue
+    }
+
+    ///
+    /// Called for a quote, quasi-quote, unquote, or unquote-splicing datum, before the datum
+    /// it quotes.
+    ///
+    fn visit_quote(&mut self, _datum: &Datum) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_identifier(&mut self, _identifier: &SIdentifier) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_boolean(&mut self, _boolean: &SBoolean) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_char(&mut self, _char: &SChar) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_number(&mut self, _number: &SNumber) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_string(&mut self, _string: &SString) -> Walk {
+        Walk::Continue
+    }
+
+    ///
+    /// Called for every list, including the empty list, before its first pair.
+    ///
+    fn visit_list(&mut self, _list: &SList) -> Walk {
+        Walk::Continue
+    }
+
+    ///
+    /// Called for each pair of a list in turn, before its car; returning [`Walk::Skip`] skips
+    /// the car and the rest of the list.
+    ///
+    fn visit_pair(&mut self, _pair: &SPair) -> Walk {
+        Walk::Continue
+    }
+
+    ///
+    /// Called for the final cdr of an improper list, before it is walked as a datum.
+    ///
+    fn visit_improper_tail(&mut self, _tail: &Datum) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_vector(&mut self, _vector: &SVector) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_byte_vector(&mut self, _byte_vector: &SByteVector) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_byte(&mut self, _byte: &Fixnum) -> Walk {
+        Walk::Continue
+    }
+
+    ///
+    /// Called for every comment, before the datum within a datum comment.
+    ///
+    fn visit_comment(&mut self, _comment: &SComment) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_directive(&mut self, _directive: &SDirective) -> Walk {
+        Walk::Continue
+    }
+
+    ///
+    /// Called for every shared datum, before its target is walked the first time it is
+    /// reached.
+    ///
+    fn visit_shared(&mut self, _shared: &SShared) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_error(&mut self, _span: Span) -> Walk {
+        Walk::Continue
+    }
+}
+
+///
+/// A visitor that may change a tree of datums in place, driven by [`walk_mut`]. Every method
+/// has a default that continues the walk.
+///
+/// For each datum [`enter_datum_mut`](VisitMut::enter_datum_mut) is called first, which may
+/// replace the datum, then the method for its type, and then the content of the datum, as it
+/// is after these calls, is walked. The values held in an [`Rc`] by a pair are copied, by
+/// [`Rc::make_mut`], before they are changed if they are also held elsewhere.
+///
+pub trait VisitMut {
+    fn enter_datum_mut(&mut self, _datum: &mut Datum) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_quote_mut(&mut self, _datum: &mut Datum) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_identifier_mut(&mut self, _identifier: &mut SIdentifier) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_boolean_mut(&mut self, _boolean: &mut SBoolean) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_char_mut(&mut self, _char: &mut SChar) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_number_mut(&mut self, _number: &mut SNumber) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_string_mut(&mut self, _string: &mut SString) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_list_mut(&mut self, _list: &mut SList) -> Walk {
+        Walk::Continue
+    }
+
+    ///
+    /// Called for each pair of a list in turn, before its car; the pair's cdr may be replaced
+    /// to change the rest of the list.
+    ///
+    fn visit_pair_mut(&mut self, _pair: &mut SPair) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_improper_tail_mut(&mut self, _tail: &mut Datum) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_vector_mut(&mut self, _vector: &mut SVector) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_byte_vector_mut(&mut self, _byte_vector: &mut SByteVector) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_comment_mut(&mut self, _comment: &mut SComment) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_directive_mut(&mut self, _directive: &mut SDirective) -> Walk {
+        Walk::Continue
+    }
+
+    ///
+    /// Called for every shared datum; the target of a shared datum cannot be changed in
+    /// place, although the shared datum may be replaced by `enter_datum_mut`.
+    ///
+    fn visit_shared_mut(&mut self, _shared: &mut SShared) -> Walk {
+        Walk::Continue
+    }
+
+    fn visit_error_mut(&mut self, _span: &mut Span) -> Walk {
+        Walk::Continue
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+enum Task<'a> {
+    Enter(&'a Datum),
+    Leave(&'a Datum),
+    Pair(&'a SPair),
+    Tail(&'a Datum),
+}
+
+enum TaskMut<'a> {
+    Enter(&'a mut Datum),
+    Pair(&'a mut SPair),
+    Tail(&'a mut Datum),
+}
+
+///
+/// A child of a datum, with the [`Rc`] that holds it if it is held by a pair.
+///
+type Child<'a> = (&'a Datum, Option<&'a Rc<Datum>>);
+
+enum Frame<'a> {
+    Enter(Child<'a>),
+    Exit(Child<'a>, usize),
+}
+
+#[derive(Default)]
+struct Walker {
+    shared: HashSet<*const Datum>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Walk `datum` and its content with `visitor`, returning [`Walk::Stop`] if the visitor
+/// ended the walk.
+///
+pub fn walk<V: Visit + ?Sized>(visitor: &mut V, datum: &Datum) -> Walk {
+    Walker::default().walk(visitor, datum)
+}
+
+///
+/// Walk `datum` and its content with `visitor`, which may change them, returning
+/// [`Walk::Stop`] if the visitor ended the walk.
+///
+pub fn walk_mut<V: VisitMut + ?Sized>(visitor: &mut V, datum: &mut Datum) -> Walk {
+    let mut tasks = vec![TaskMut::Enter(datum)];
+    while let Some(task) = tasks.pop() {
+        let next = match task {
+            TaskMut::Enter(datum) => match visitor.enter_datum_mut(datum) {
+                Walk::Continue => enter_mut(visitor, datum, &mut tasks),
+                walk => walk,
+            },
+            TaskMut::Pair(pair) => {
+                let next = visitor.visit_pair_mut(pair);
+                if next == Walk::Continue {
+                    let (car, cdr) = pair.car_cdr_mut();
+                    match cdr.as_ref() {
+                        Datum::List(list) if list.is_empty() => {}
+                        Datum::List(_) => {
+                            if let Some(pair) =
+                                Rc::make_mut(cdr).as_list_mut().unwrap().as_pair_mut()
+                            {
+                                tasks.push(TaskMut::Pair(pair));
+                            }
+                        }
+                        _ => tasks.push(TaskMut::Tail(Rc::make_mut(cdr))),
+                    }
+                    tasks.push(TaskMut::Enter(Rc::make_mut(car)));
+                }
+                next
+            }
+            TaskMut::Tail(tail) => {
+                let next = visitor.visit_improper_tail_mut(tail);
+                if next == Walk::Continue {
+                    tasks.push(TaskMut::Enter(tail));
+                }
+                next
+            }
+        };
+        if next == Walk::Stop {
+            return Walk::Stop;
+        }
+    }
+    Walk::Continue
+}
+
+///
+/// Compute a value from `datum` bottom-up; `f` is called for each datum with the values
+/// computed for its children, in order. The children of a datum are the datum it quotes, the
+/// elements of a list followed by the tail if it is improper, the elements of a vector, and
+/// the datum within a datum comment.
+///
+/// ```rust
+/// use ffsr::reader::datum::Datum;
+/// use ffsr::visit::fold;
+///
+/// let datum = ffsr::convert::from_str::<Datum>("(a (b c) #(d (e)))").unwrap();
+/// let depth = fold(&datum, |_, children: Vec<usize>| {
+///     children.into_iter().max().map_or(0, |depth| depth + 1)
+/// });
+/// assert_eq!(depth, 3);
+/// ```
+///
+pub fn fold<T, F>(datum: &Datum, mut f: F) -> T
+where
+    F: FnMut(&Datum, Vec<T>) -> T,
+{
+    let mut frames = vec![Frame::Enter((datum, None))];
+    let mut values = Vec::new();
+    while let Some(frame) = frames.pop() {
+        match frame {
+            Frame::Enter(child) => {
+                let children = children(child.0);
+                frames.push(Frame::Exit(child, children.len()));
+                frames.extend(children.into_iter().rev().map(Frame::Enter));
+            }
+            Frame::Exit((datum, _), count) => {
+                let children = values.split_off(values.len() - count);
+                values.push(f(datum, children));
+            }
+        }
+    }
+    values.pop().unwrap()
+}
+
+///
+/// Rebuild `datum` bottom-up; `f` is called for each datum, once its children have been
+/// rebuilt, and returns a replacement or `None` to keep it. Only the datums that change, and
+/// those containing them, are copied, and a value held by more than one pair is rebuilt once
+/// so that it stays shared.
+///
+pub fn transform<F>(datum: &Datum, f: F) -> Datum
+where
+    F: FnMut(&Datum) -> Option<Datum>,
+{
+    transform_from((datum, None), f)
+        .map(into_datum)
+        .unwrap_or_else(|| datum.clone())
+}
+
+///
+/// As [`transform`], returning `datum` itself if nothing changed.
+///
+pub fn transform_rc<F>(datum: &Rc<Datum>, f: F) -> Rc<Datum>
+where
+    F: FnMut(&Datum) -> Option<Datum>,
+{
+    transform_from((datum, Some(datum)), f).unwrap_or_else(|| datum.clone())
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Walker {
+    fn walk<V: Visit + ?Sized>(&mut self, visitor: &mut V, datum: &Datum) -> Walk {
+        let mut tasks = vec![Task::Enter(datum)];
+        while let Some(task) = tasks.pop() {
+            let next = match task {
+                Task::Enter(datum) => match visitor.enter_datum(datum) {
+                    Walk::Continue => {
+                        tasks.push(Task::Leave(datum));
+                        self.enter(visitor, datum, &mut tasks)
+                    }
+                    walk => walk,
+                },
+                Task::Leave(datum) => visitor.leave_datum(datum),
+                Task::Pair(pair) => {
+                    let next = visitor.visit_pair(pair);
+                    if next == Walk::Continue {
+                        match pair.cdr().as_ref() {
+                            Datum::List(list) => {
+                                if let Some(pair) = list.as_pair() {
+                                    tasks.push(Task::Pair(pair));
+                                }
+                            }
+                            tail => tasks.push(Task::Tail(tail)),
+                        }
+                        tasks.push(Task::Enter(pair.car()));
+                    }
+                    next
+                }
+                Task::Tail(tail) => {
+                    let next = visitor.visit_improper_tail(tail);
+                    if next == Walk::Continue {
+                        tasks.push(Task::Enter(tail));
+                    }
+                    next
+                }
+            };
+            if next == Walk::Stop {
+                return Walk::Stop;
+            }
+        }
+        Walk::Continue
+    }
+
+    fn enter<'a, V: Visit + ?Sized>(
+        &mut self,
+        visitor: &mut V,
+        datum: &'a Datum,
+        tasks: &mut Vec<Task<'a>>,
+    ) -> Walk {
+        match datum {
+            Datum::Quote(quoted)
+            | Datum::QuasiQuote(quoted)
+            | Datum::Unquote(quoted)
+            | Datum::UnquoteSplicing(quoted) => {
+                let next = visitor.visit_quote(datum);
+                if next == Walk::Continue {
+                    tasks.push(Task::Enter(quoted));
+                }
+                next
+            }
+            Datum::Identifier(v) => visitor.visit_identifier(v),
+            Datum::Boolean(v) => visitor.visit_boolean(v),
+            Datum::Char(v) => visitor.visit_char(v),
+            Datum::Number(v) => visitor.visit_number(v),
+            Datum::String(v) => visitor.visit_string(v),
+            Datum::List(v) => {
+                let next = visitor.visit_list(v);
+                if next == Walk::Continue {
+                    if let Some(pair) = v.as_pair() {
+                        tasks.push(Task::Pair(pair));
+                    }
+                }
+                next
+            }
+            Datum::Vector(v) => {
+                let next = visitor.visit_vector(v);
+                if next == Walk::Continue {
+                    tasks.extend(v.iter().rev().map(Task::Enter));
+                }
+                next
+            }
+            Datum::ByteVector(v) => {
+                let next = visitor.visit_byte_vector(v);
+                if next == Walk::Continue {
+                    for byte in v.iter() {
+                        if visitor.visit_byte(byte) == Walk::Stop {
+                            return Walk::Stop;
+                        }
+                    }
+                }
+                next
+            }
+            Datum::Comment(v) => {
+                let next = visitor.visit_comment(v);
+                if let (Walk::Continue, SComment::Datum(commented)) = (next, v) {
+                    tasks.push(Task::Enter(commented));
+                }
+                next
+            }
+            Datum::Directive(v) => visitor.visit_directive(v),
+            Datum::Shared(v) => {
+                let next = visitor.visit_shared(v);
+                if next == Walk::Continue {
+                    // The target is walked with a nested stack, so only the nesting of
+                    // distinct shared datums adds to the depth of the call stack.
+                    if let Some(target) = v.get() {
+                        if self.shared.insert(Rc::as_ptr(&target))
+                            && self.walk(visitor, &target) == Walk::Stop
+                        {
+                            return Walk::Stop;
+                        }
+                    }
+                }
+                next
+            }
+            Datum::Error(span) => visitor.visit_error(*span),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn enter_mut<'a, V: VisitMut + ?Sized>(
+    visitor: &mut V,
+    datum: &'a mut Datum,
+    tasks: &mut Vec<TaskMut<'a>>,
+) -> Walk {
+    match datum {
+        Datum::Identifier(v) => visitor.visit_identifier_mut(v),
+        Datum::Boolean(v) => visitor.visit_boolean_mut(v),
+        Datum::Char(v) => visitor.visit_char_mut(v),
+        Datum::Number(v) => visitor.visit_number_mut(v),
+        Datum::String(v) => visitor.visit_string_mut(v),
+        Datum::List(v) => {
+            let next = visitor.visit_list_mut(v);
+            if next == Walk::Continue {
+                if let Some(pair) = v.as_pair_mut() {
+                    tasks.push(TaskMut::Pair(pair));
+                }
+            }
+            next
+        }
+        Datum::Vector(v) => {
+            let next = visitor.visit_vector_mut(v);
+            if next == Walk::Continue {
+                tasks.extend(v.iter_mut().rev().map(TaskMut::Enter));
+            }
+            next
+        }
+        Datum::ByteVector(v) => visitor.visit_byte_vector_mut(v),
+        Datum::Comment(v) => {
+            let next = visitor.visit_comment_mut(v);
+            if let (Walk::Continue, SComment::Datum(commented)) = (next, v) {
+                tasks.push(TaskMut::Enter(commented));
+            }
+            next
+        }
+        Datum::Directive(v) => visitor.visit_directive_mut(v),
+        Datum::Shared(v) => visitor.visit_shared_mut(v),
+        Datum::Error(span) => visitor.visit_error_mut(span),
+        _ => {
+            let next = visitor.visit_quote_mut(datum);
+            if let (
+                Walk::Continue,
+                Datum::Quote(quoted)
+                | Datum::QuasiQuote(quoted)
+                | Datum::Unquote(quoted)
+                | Datum::UnquoteSplicing(quoted),
+            ) = (next, datum)
+            {
+                tasks.push(TaskMut::Enter(quoted));
+            }
+            next
+        }
+    }
+}
+
+fn children(datum: &Datum) -> Vec<Child<'_>> {
+    match datum {
+        Datum::Quote(quoted)
+        | Datum::QuasiQuote(quoted)
+        | Datum::Unquote(quoted)
+        | Datum::UnquoteSplicing(quoted) => vec![(quoted, None)],
+        Datum::List(list) => {
+            let mut children = Vec::new();
+            let mut next = list.as_pair();
+            while let Some(pair) = next {
+                children.push((pair.car().as_ref(), Some(pair.car())));
+                next = match pair.cdr().as_ref() {
+                    Datum::List(list) => list.as_pair(),
+                    tail => {
+                        children.push((tail, Some(pair.cdr())));
+                        None
+                    }
+                };
+            }
+            children
+        }
+        Datum::Vector(vector) => vector.iter().map(|v| (v, None)).collect(),
+        Datum::Comment(SComment::Datum(commented)) => vec![(commented, None)],
+        _ => Vec::new(),
+    }
+}
+
+///
+/// Returns the rebuilt datum, or `None` if it did not change.
+///
+fn transform_from<F>(root: Child<'_>, mut f: F) -> Option<Rc<Datum>>
+where
+    F: FnMut(&Datum) -> Option<Datum>,
+{
+    let mut rebuilt: HashMap<*const Datum, Option<Rc<Datum>>> = HashMap::new();
+    let mut frames = vec![Frame::Enter(root)];
+    let mut values: Vec<Option<Rc<Datum>>> = Vec::new();
+    while let Some(frame) = frames.pop() {
+        match frame {
+            Frame::Enter(child) => {
+                if let Some(value) = child.1.and_then(|rc| rebuilt.get(&Rc::as_ptr(rc))) {
+                    values.push(value.clone());
+                } else {
+                    let children = children(child.0);
+                    frames.push(Frame::Exit(child, children.len()));
+                    frames.extend(children.into_iter().rev().map(Frame::Enter));
+                }
+            }
+            Frame::Exit((datum, rc), count) => {
+                let children = values.split_off(values.len() - count);
+                let copy = if children.iter().all(Option::is_none) {
+                    None
+                } else {
+                    Some(rebuild(datum, children))
+                };
+                let value = match (f(copy.as_ref().unwrap_or(datum)), copy) {
+                    (Some(replacement), _) => Some(Rc::new(replacement)),
+                    (None, copy) => copy.map(Rc::new),
+                };
+                if let Some(rc) = rc {
+                    rebuilt.insert(Rc::as_ptr(rc), value.clone());
+                }
+                values.push(value);
+            }
+        }
+    }
+    values.pop().unwrap()
+}
+
+///
+/// Copy `datum` with each of its children replaced by the corresponding rebuilt child, if
+/// any, in the order given by [`children`].
+///
+fn rebuild(datum: &Datum, children: Vec<Option<Rc<Datum>>>) -> Datum {
+    let mut children = children.into_iter();
+    let mut next_datum = |original: &Datum| {
+        children
+            .next()
+            .flatten()
+            .map(into_datum)
+            .unwrap_or_else(|| original.clone())
+    };
+    match datum {
+        Datum::Quote(v) => Datum::Quote(Box::new(next_datum(v))),
+        Datum::QuasiQuote(v) => Datum::QuasiQuote(Box::new(next_datum(v))),
+        Datum::Unquote(v) => Datum::Unquote(Box::new(next_datum(v))),
+        Datum::UnquoteSplicing(v) => Datum::UnquoteSplicing(Box::new(next_datum(v))),
+        Datum::Vector(v) => Datum::Vector(v.iter().map(next_datum).collect()),
+        Datum::Comment(SComment::Datum(v)) => {
+            Datum::Comment(SComment::Datum(Box::new(next_datum(v))))
+        }
+        Datum::List(list) => {
+            let mut next_rc = |original: &Rc<Datum>| {
+                children
+                    .next()
+                    .flatten()
+                    .unwrap_or_else(|| original.clone())
+            };
+            let mut cars = Vec::new();
+            let mut tail = Rc::new(EMPTY_LIST);
+            let mut next = list.as_pair();
+            while let Some(pair) = next {
+                cars.push(next_rc(pair.car()));
+                next = match pair.cdr().as_ref() {
+                    Datum::List(list) => list.as_pair(),
+                    _ => {
+                        tail = next_rc(pair.cdr());
+                        None
+                    }
+                };
+            }
+            let list = cars.into_iter().rev().fold(tail, |cdr, car| {
+                Rc::new(Datum::List(SPair::cons(car, cdr).into()))
+            });
+            into_datum(list)
+        }
+        _ => datum.clone(),
+    }
+}
+
+#[inline(always)]
+fn into_datum(rc: Rc<Datum>) -> Datum {
+    Rc::try_unwrap(rc).unwrap_or_else(|rc| rc.as_ref().clone())
+}
@@ -0,0 +1,366 @@
+/*!
+Provides a formatter for Scheme source text, which re-indents and re-breaks forms while keeping
+every comment and directive.
+
+The formatter works on the [`SyntaxTree`] of the source, and lays out each form with the same
+rules as the [pretty printer](super::pretty). Unlike the pretty printer it writes each atom as
+it appears in the source, and keeps comments where they are:
+
+* a line comment that follows a datum on the same line stays at the end of that line,
+* any other comment, including a datum comment, stays before the datum that follows it, on
+  its own line if it was on its own line in the source,
+* empty lines between forms are kept, up to [`SourceFormatter::with_blank_lines`] of them.
+
+Formatting is idempotent, formatting text that is already formatted returns it unchanged.
+
+# Example
+
+```rust
+use ffsr::writer::format::SourceFormatter;
+
+let source = "; squares
+(define (square x)   ; the usual
+(* x x))
+
+
+(display (square 2))";
+
+let formatter = SourceFormatter::default().with_width(30);
+assert_eq!(
+    formatter.format_str(source).unwrap(),
+    "; squares
+(define (square x) ; the usual
+  (* x x))
+
+(display (square 2))
+"
+);
+```
+
+*/
+
+use super::pretty::{list_doc, Doc, Element, IndentStyle, Operator, PrettyPrinter};
+use crate::error::Error;
+use crate::lexer::token::TokenKind;
+use crate::lexer::Lexer;
+use crate::reader::cst::{Node, NodeKind, SyntaxTree, Trivia, TriviaKind};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The options for formatting source text.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SourceFormatter {
+    printer: PrettyPrinter,
+    blank_lines: usize,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Values
+// ------------------------------------------------------------------------------------------------
+
+const DEFAULT_BLANK_LINES: usize = 1;
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Format `source` with the default options.
+///
+#[inline(always)]
+pub fn format(source: &str) -> Result<String, Error> {
+    SourceFormatter::default().format_str(source)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Default for SourceFormatter {
+    fn default() -> Self {
+        Self {
+            printer: Default::default(),
+            blank_lines: DEFAULT_BLANK_LINES,
+        }
+    }
+}
+
+impl From<PrettyPrinter> for SourceFormatter {
+    fn from(printer: PrettyPrinter) -> Self {
+        Self {
+            printer,
+            ..Default::default()
+        }
+    }
+}
+
+impl SourceFormatter {
+    ///
+    /// Set the line width to fit to, see [`PrettyPrinter::with_width`].
+    ///
+    pub fn with_width(self, width: usize) -> Self {
+        Self {
+            printer: self.printer.with_width(width),
+            ..self
+        }
+    }
+
+    ///
+    /// Set the indentation of a body, see [`PrettyPrinter::with_indent`].
+    ///
+    pub fn with_indent(self, indent: usize) -> Self {
+        Self {
+            printer: self.printer.with_indent(indent),
+            ..self
+        }
+    }
+
+    pub fn with_style(self, style: IndentStyle) -> Self {
+        Self {
+            printer: self.printer.with_style(style),
+            ..self
+        }
+    }
+
+    ///
+    /// Set the largest number of consecutive empty lines kept from the source, the default is
+    /// 1. Empty lines at the start of a list, or of the source, are never kept.
+    ///
+    pub fn with_blank_lines(self, blank_lines: usize) -> Self {
+        Self {
+            blank_lines,
+            ..self
+        }
+    }
+
+    #[inline(always)]
+    pub fn printer(&self) -> &PrettyPrinter {
+        &self.printer
+    }
+
+    #[inline(always)]
+    pub fn blank_lines(&self) -> usize {
+        self.blank_lines
+    }
+
+    ///
+    /// Format `source`, returning an error if it is not well formed.
+    ///
+    pub fn format_str(&self, source: &str) -> Result<String, Error> {
+        let tree = SyntaxTree::parse(Lexer::from(source).tokens())?;
+        Ok(self.format_tree(&tree))
+    }
+
+    pub fn format_tree(&self, tree: &SyntaxTree) -> String {
+        let mut result = String::new();
+        self.write_to_fmt(tree, &mut result).unwrap();
+        result
+    }
+
+    ///
+    /// Write the formatted text of `tree` to `w`; any text ends with a newline.
+    ///
+    pub fn write_to_fmt<W: std::fmt::Write>(
+        &self,
+        tree: &SyntaxTree,
+        w: &mut W,
+    ) -> std::fmt::Result {
+        let elements = self.elements(tree.nodes(), tree.trailing());
+        let mut docs = Vec::new();
+        for element in elements {
+            docs.push(element.doc);
+            docs.push(Doc::HardLine);
+        }
+        self.printer.layout(&Doc::Concat(docs), w)
+    }
+
+    ///
+    /// Returns `true` if formatting `source` would leave it unchanged.
+    ///
+    pub fn is_formatted(&self, source: &str) -> Result<bool, Error> {
+        Ok(self.format_str(source)? == source)
+    }
+
+    fn node(&self, node: &Node) -> Doc {
+        match node.kind() {
+            NodeKind::Atom(leaf) => Doc::text(leaf.text()),
+            NodeKind::Prefixed { prefix, node } => {
+                let mut elements = self.elements(std::slice::from_ref(node.as_ref()), &[]);
+                Doc::Concat(vec![Doc::text(prefix.text()), elements.remove(0).doc])
+            }
+            NodeKind::Delimited {
+                open,
+                nodes,
+                close_leading,
+                close,
+            } => {
+                let operator = identifier(nodes.first())
+                    .filter(|_| open.kind() == TokenKind::OpenParenthesis)
+                    .map(|name| Operator {
+                        name,
+                        named: identifier(nodes.get(1)).is_some(),
+                    });
+                list_doc(
+                    &self.printer,
+                    Doc::text(open.text()),
+                    self.elements(nodes, close_leading),
+                    operator,
+                    Doc::text(close.text()),
+                )
+            }
+        }
+    }
+
+    ///
+    /// Returns the elements for `nodes`, each with the comments that precede it, followed by
+    /// an element for any comments in `trailing`. The `.` of a pair is joined to the node
+    /// that follows it.
+    ///
+    fn elements(&self, nodes: &[Node], trailing: &[Trivia]) -> Vec<Element> {
+        let mut elements: Vec<Element> = Vec::new();
+        let mut after_dot = false;
+        for node in nodes {
+            let (mut prefix, own_line) = self.comments(&mut elements, node.leading(), true);
+            prefix.push(self.node(node));
+            let element = Element {
+                doc: Doc::Concat(prefix),
+                own_line,
+                ends_line: false,
+            };
+            match elements.pop() {
+                Some(dot) if after_dot => {
+                    let separator = if dot.ends_line {
+                        Doc::HardLine
+                    } else {
+                        Doc::text(" ")
+                    };
+                    elements.push(Element {
+                        doc: Doc::Concat(vec![dot.doc, separator, element.doc]),
+                        own_line: dot.own_line,
+                        ends_line: false,
+                    });
+                }
+                previous => {
+                    elements.extend(previous);
+                    elements.push(element);
+                }
+            }
+            after_dot =
+                matches!(node.kind(), NodeKind::Atom(leaf) if leaf.kind() == TokenKind::Dot);
+        }
+
+        let (mut prefix, own_line) = self.comments(&mut elements, trailing, false);
+        if !prefix.is_empty() {
+            let ends_line = matches!(prefix.last(), Some(Doc::HardLine));
+            prefix.pop();
+            elements.push(Element {
+                doc: Doc::Concat(prefix),
+                own_line,
+                ends_line,
+            });
+        }
+        elements
+    }
+
+    ///
+    /// Returns the documents for the comments, and empty lines, in `leading`, and whether
+    /// they start on a new line. A line comment on the same line as the last of `elements` is
+    /// added to it instead. The documents returned end with a new line, or a space, to
+    /// separate them from what follows.
+    ///
+    fn comments(
+        &self,
+        elements: &mut [Element],
+        leading: &[Trivia],
+        before_node: bool,
+    ) -> (Vec<Doc>, bool) {
+        let mut prefix = Vec::new();
+        let mut own_line = false;
+        let mut newlines = 0;
+        for trivia in leading {
+            let is_line_comment = match trivia.kind() {
+                TriviaKind::Newline => {
+                    newlines += 1;
+                    continue;
+                }
+                TriviaKind::Whitespace => continue,
+                TriviaKind::LineComment => true,
+                TriviaKind::BlockComment | TriviaKind::DatumComment => false,
+            };
+            match elements.last_mut() {
+                Some(previous)
+                    if newlines == 0
+                        && is_line_comment
+                        && prefix.is_empty()
+                        && !previous.ends_line =>
+                {
+                    let doc = std::mem::replace(&mut previous.doc, Doc::Concat(Vec::new()));
+                    previous.doc = Doc::Concat(vec![
+                        doc,
+                        Doc::text(" "),
+                        Doc::text(trivia.text().trim_end()),
+                        Doc::BreakParent,
+                    ]);
+                    previous.ends_line = true;
+                }
+                previous => {
+                    if newlines > 0 {
+                        own_line |= prefix.is_empty();
+                        self.new_line(&mut prefix, newlines, previous.is_some());
+                    }
+                    if is_line_comment {
+                        prefix.push(Doc::text(trivia.text().trim_end()));
+                        prefix.push(Doc::HardLine);
+                    } else {
+                        prefix.push(Doc::text(trivia.text()));
+                        prefix.push(Doc::text(" "));
+                    }
+                }
+            }
+            newlines = 0;
+        }
+        if before_node && newlines > 0 {
+            let previous = !elements.is_empty();
+            if !prefix.is_empty() || (previous && newlines > 1) {
+                own_line |= prefix.is_empty();
+                self.new_line(&mut prefix, newlines, previous);
+            }
+        }
+        (prefix, own_line)
+    }
+
+    ///
+    /// Start a new line, after `newlines` in the source; the new line itself is written by
+    /// a [`Doc::HardLine`] if `prefix` has one at its end, or by the separator before the
+    /// element. Empty lines are kept unless this is the start of a list or the source.
+    ///
+    fn new_line(&self, prefix: &mut Vec<Doc>, newlines: usize, previous: bool) {
+        if matches!(prefix.last(), Some(Doc::Text(s)) if s == " ") {
+            prefix.pop();
+            prefix.push(Doc::HardLine);
+        }
+        if prefix.is_empty() {
+            prefix.push(Doc::BreakParent);
+        }
+        if previous || prefix.len() > 1 {
+            for _ in 1..newlines.min(self.blank_lines + 1) {
+                prefix.push(Doc::Blank);
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+fn identifier(node: Option<&Node>) -> Option<&str> {
+    match node.map(Node::kind) {
+        Some(NodeKind::Atom(leaf)) if leaf.kind() == TokenKind::Identifier => Some(leaf.text()),
+        _ => None,
+    }
+}
@@ -0,0 +1,374 @@
+/*!
+Provides a writer for datums, producing text that reads back into an equivalent datum.
+
+The `Display` implementation of [`Datum`] follows the Scheme `display` procedure, strings and
+characters are written without quotes or escapes and shared structure is written once for each
+place it appears. The functions in this module follow `write` and `write-shared` from R7RS, and
+SRFI 38, instead: every value is written in its external representation, and shared structure is
+marked with datum labels, `#n=` at its first appearance and `#n#` at each later one.
+
+* [`write()`] labels only the shared datums that are part of a cycle, any other shared datum is
+  written in full wherever it appears.
+* [`write_shared`] labels every shared datum that appears more than once.
+
+In both cases the text read back, by [`DatumIter`](crate::reader::iter::DatumIter), is a datum
+equal to the one written, and with the same cycles; with `write_shared` the same sharing is
+kept as well. Labels are numbered from zero in the order they are written.
+
+# Example
+
+```rust
+use ffsr::lexer::Lexer;
+use ffsr::reader::Reader;
+use ffsr::writer::{write, write_shared};
+
+let reader = Reader::from(Lexer::from("(#5=(\"x\") #5# #9=(#\\space . #9#))"));
+let datum = reader.iter().next().unwrap().unwrap();
+
+assert_eq!(write(&datum).to_string(), "((\"x\") (\"x\") #0=(#\\space . #0#))");
+assert_eq!(
+    write_shared(&datum).to_string(),
+    "(#0=(\"x\") #0# #1=(#\\space . #1#))"
+);
+```
+
+*/
+
+use crate::lexer::iter::is_vbar_identifier;
+use crate::lexer::token::Span;
+use crate::reader::datum::{Datum, SChar, SIdentifier, SList, SShared};
+use crate::syntax::{
+    CHAR_HEX_ESCAPE_END, CHAR_HEX_ESCAPE_START, CHAR_NAME_ALARM, CHAR_NAME_BACKSPACE,
+    CHAR_NAME_DELETE, CHAR_NAME_ESCAPE, CHAR_NAME_NEWLINE, CHAR_NAME_NULL, CHAR_NAME_RETURN,
+    CHAR_NAME_SPACE, CHAR_NAME_TAB, CHAR_PREFIX_STR, CHAR_VALUE_ALARM, CHAR_VALUE_BACKSLASH,
+    CHAR_VALUE_BACKSPACE, CHAR_VALUE_DELETE, CHAR_VALUE_ESCAPE, CHAR_VALUE_NEWLINE,
+    CHAR_VALUE_NULL, CHAR_VALUE_RETURN, CHAR_VALUE_SPACE, CHAR_VALUE_TAB, DATUM_ASSIGN_END,
+    DATUM_LABEL_PREFIX, DATUM_REF_END, IDENTIFIER_WRAPPER, PAIR_DOT, PAIR_END, PAIR_START,
+    QUASI_QUOTE_ABBREV, QUOTE_ABBREV, UNQUOTE_ABBREV, UNQUOTE_SPLICING_ABBREV, VECTOR_END,
+    VECTOR_START,
+};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter, Result};
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Which shared datums are written with labels.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WriteMode {
+    /// Label only the shared datums that are part of a cycle, as R7RS `write`.
+    #[default]
+    Cycles,
+    /// Label every shared datum that appears more than once, as R7RS `write-shared`.
+    Shared,
+}
+
+///
+/// A datum to be written, with `Display`, in its external representation.
+///
+#[derive(Debug)]
+pub struct Written<'a> {
+    datum: &'a Datum,
+    mode: WriteMode,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+struct Writer<'a, 'b> {
+    f: &'a mut Formatter<'b>,
+    labelled: HashSet<usize>,
+    labels: HashMap<usize, usize>,
+}
+
+#[derive(Default)]
+struct Scan {
+    mode: WriteMode,
+    visiting: HashSet<usize>,
+    visited: HashSet<usize>,
+    labelled: HashSet<usize>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Write `datum` as R7RS `write`, labelling only cycles.
+///
+#[inline(always)]
+pub fn write(datum: &Datum) -> Written<'_> {
+    Written::new(datum, WriteMode::Cycles)
+}
+
+///
+/// Write `datum` as R7RS `write-shared`, labelling all shared structure.
+///
+#[inline(always)]
+pub fn write_shared(datum: &Datum) -> Written<'_> {
+    Written::new(datum, WriteMode::Shared)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Written<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        Writer {
+            f,
+            labelled: labelled(self.datum, self.mode),
+            labels: Default::default(),
+        }
+        .datum(self.datum)
+    }
+}
+
+impl<'a> Written<'a> {
+    pub fn new(datum: &'a Datum, mode: WriteMode) -> Self {
+        Self { datum, mode }
+    }
+
+    #[inline(always)]
+    pub fn mode(&self) -> WriteMode {
+        self.mode
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Scan {
+    ///
+    /// Find the shared datums to label, by a depth-first search in which a shared datum that
+    /// is reached again while still being searched is part of a cycle.
+    ///
+    fn datum(&mut self, datum: &Datum) {
+        match datum {
+            Datum::Quote(v)
+            | Datum::QuasiQuote(v)
+            | Datum::Unquote(v)
+            | Datum::UnquoteSplicing(v) => self.datum(v),
+            Datum::List(list) => {
+                let mut next = list.as_pair();
+                while let Some(pair) = next {
+                    self.datum(pair.car());
+                    match pair.cdr().as_ref() {
+                        Datum::List(list) => next = list.as_pair(),
+                        cdr => {
+                            self.datum(cdr);
+                            next = None;
+                        }
+                    }
+                }
+            }
+            Datum::Vector(vector) => vector.iter().for_each(|datum| self.datum(datum)),
+            Datum::Shared(shared) => self.shared(shared),
+            _ => {}
+        }
+    }
+
+    fn shared(&mut self, shared: &SShared) {
+        let id = shared.id();
+        if self.visiting.contains(&id) {
+            self.labelled.insert(id);
+        } else if self.visited.contains(&id) {
+            if self.mode == WriteMode::Shared {
+                self.labelled.insert(id);
+            }
+        } else if let Some(datum) = shared.get() {
+            self.visiting.insert(id);
+            self.datum(&datum);
+            self.visiting.remove(&id);
+            self.visited.insert(id);
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Writer<'_, '_> {
+    fn datum(&mut self, datum: &Datum) -> Result {
+        match datum {
+            Datum::Quote(v) => {
+                write!(self.f, "{QUOTE_ABBREV}")?;
+                self.datum(v)
+            }
+            Datum::QuasiQuote(v) => {
+                write!(self.f, "{QUASI_QUOTE_ABBREV}")?;
+                self.datum(v)
+            }
+            Datum::Unquote(v) => {
+                write!(self.f, "{UNQUOTE_ABBREV}")?;
+                self.datum(v)
+            }
+            Datum::UnquoteSplicing(v) => {
+                write!(self.f, "{UNQUOTE_ABBREV}{UNQUOTE_SPLICING_ABBREV}")?;
+                self.datum(v)
+            }
+            Datum::List(list) => {
+                write!(self.f, "{PAIR_START}")?;
+                self.list_elements(list, true)?;
+                write!(self.f, "{PAIR_END}")
+            }
+            Datum::Vector(vector) => {
+                write!(self.f, "{VECTOR_START}")?;
+                for (i, datum) in vector.iter().enumerate() {
+                    if i > 0 {
+                        write!(self.f, " ")?;
+                    }
+                    self.datum(datum)?;
+                }
+                write!(self.f, "{VECTOR_END}")
+            }
+            Datum::Identifier(v) => write_identifier(self.f, v),
+            Datum::Char(v) => write_char(self.f, v),
+            Datum::String(v) => write!(self.f, "{v:?}"),
+            Datum::Directive(v) => write!(self.f, "{v:?}"),
+            Datum::Shared(shared) => self.shared(shared),
+            _ => write!(self.f, "{datum}"),
+        }
+    }
+
+    fn shared(&mut self, shared: &SShared) -> Result {
+        let id = shared.id();
+        if self.labelled.contains(&id) {
+            if let Some(label) = self.labels.get(&id) {
+                return write!(self.f, "{DATUM_LABEL_PREFIX}{label}{DATUM_REF_END}");
+            }
+            let label = self.labels.len();
+            self.labels.insert(id, label);
+            write!(self.f, "{DATUM_LABEL_PREFIX}{label}{DATUM_ASSIGN_END}")?;
+        }
+        match shared.get() {
+            Some(datum) => self.datum(&datum),
+            // a reference to a datum that no longer exists, nothing better can be written.
+            None => write!(
+                self.f,
+                "{DATUM_LABEL_PREFIX}{}{DATUM_REF_END}",
+                shared.label()
+            ),
+        }
+    }
+
+    ///
+    /// Write the elements of `list`, without its parentheses; an unlabelled shared tail is
+    /// written as more elements of the same list.
+    ///
+    fn list_elements(&mut self, list: &SList, first: bool) -> Result {
+        let mut next = list.as_pair();
+        let mut first = first;
+        while let Some(pair) = next {
+            if !first {
+                write!(self.f, " ")?;
+            }
+            first = false;
+            self.datum(pair.car())?;
+            next = None;
+            match pair.cdr().as_ref() {
+                Datum::List(list) => next = list.as_pair(),
+                Datum::Shared(shared) if !self.labelled.contains(&shared.id()) => {
+                    match shared.get() {
+                        Some(datum) if datum.is_list() => {
+                            return self.list_elements(datum.as_list().unwrap(), false)
+                        }
+                        _ => self.dotted_tail(pair.cdr())?,
+                    }
+                }
+                cdr => self.dotted_tail(cdr)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn dotted_tail(&mut self, cdr: &Datum) -> Result {
+        write!(self.f, " {PAIR_DOT} ")?;
+        self.datum(cdr)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Returns the identities of the shared datums within `datum` to be written with labels.
+///
+fn labelled(datum: &Datum, mode: WriteMode) -> HashSet<usize> {
+    let mut scan = Scan {
+        mode,
+        ..Default::default()
+    };
+    scan.datum(datum);
+    scan.labelled
+}
+
+///
+/// Write `identifier` bare if its text reads back as the same identifier, otherwise between
+/// `|`, with `\` escaped and any character not allowed there, such as `|`, as a hex escape.
+///
+fn write_identifier(f: &mut Formatter<'_>, identifier: &SIdentifier) -> Result {
+    let text = identifier.as_str();
+    let text = text
+        .strip_prefix(IDENTIFIER_WRAPPER)
+        .and_then(|text| text.strip_suffix(IDENTIFIER_WRAPPER))
+        .unwrap_or(text);
+    // a lone dot is read as the dot of a pair.
+    let bare = !(text.len() == 1 && text.starts_with(PAIR_DOT))
+        && matches!(
+            SIdentifier::text_in_span(text, Span::default()),
+            Ok(Cow::Borrowed(bare)) if bare == text
+        );
+    if bare {
+        return write!(f, "{text}");
+    }
+    write!(f, "{IDENTIFIER_WRAPPER}")?;
+    for c in text.chars() {
+        match c {
+            CHAR_VALUE_BACKSLASH => write!(f, "{CHAR_VALUE_BACKSLASH}{CHAR_VALUE_BACKSLASH}")?,
+            c if !is_vbar_identifier(c) => write!(
+                f,
+                "{CHAR_VALUE_BACKSLASH}{CHAR_HEX_ESCAPE_START}{:x}{CHAR_HEX_ESCAPE_END}",
+                c as u32
+            )?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    write!(f, "{IDENTIFIER_WRAPPER}")
+}
+
+fn write_char(f: &mut Formatter<'_>, c: &SChar) -> Result {
+    let c = char::from(*c);
+    let name = match c {
+        CHAR_VALUE_ALARM => CHAR_NAME_ALARM,
+        CHAR_VALUE_BACKSPACE => CHAR_NAME_BACKSPACE,
+        CHAR_VALUE_DELETE => CHAR_NAME_DELETE,
+        CHAR_VALUE_ESCAPE => CHAR_NAME_ESCAPE,
+        CHAR_VALUE_NEWLINE => CHAR_NAME_NEWLINE,
+        CHAR_VALUE_NULL => CHAR_NAME_NULL,
+        CHAR_VALUE_RETURN => CHAR_NAME_RETURN,
+        CHAR_VALUE_SPACE => CHAR_NAME_SPACE,
+        CHAR_VALUE_TAB => CHAR_NAME_TAB,
+        c if c.is_control() || c.is_whitespace() => {
+            return write!(
+                f,
+                "{CHAR_PREFIX_STR}{CHAR_HEX_ESCAPE_START}{:x}{CHAR_HEX_ESCAPE_END}",
+                c as u32
+            );
+        }
+        c => return write!(f, "{CHAR_PREFIX_STR}{c}"),
+    };
+    write!(f, "{CHAR_PREFIX_STR}{name}")
+}
+
+// ------------------------------------------------------------------------------------------------
+// Modules
+// ------------------------------------------------------------------------------------------------
+
+pub mod format;
+
+pub mod pretty;
@@ -0,0 +1,666 @@
+/*!
+Provides a width-aware pretty printer for datums.
+
+The printer writes the same external representation as [`write`](super::write), or
+[`write_shared`](super::write_shared), but breaks lists and vectors across lines so that, where
+possible, no line is longer than the configured width. Layout follows the Wadler/Lindig style: a
+form is written on one line if it fits, otherwise its elements are written one per line and
+each element is laid out in turn.
+
+Lists headed by one of the common Scheme binding and control forms, such as `define`, `let`,
+and `lambda`, keep their distinguished arguments on the first line and indent their body, while
+the clauses of `cond`, and the arguments of `if`, `and`, and `or`, are aligned, as in
+
+```scheme
+(define (sign x)
+  (cond ((> x 0) 'positive)
+        ((< x 0) 'negative)
+        (else 'zero)))
+```
+
+Other lists are written in one of the styles of [`IndentStyle`].
+
+# Example
+
+```rust
+use ffsr::lexer::Lexer;
+use ffsr::reader::Reader;
+use ffsr::writer::pretty::PrettyPrinter;
+
+let reader = Reader::from(Lexer::from("(let ((a 1) (b 2)) (display a) (display b))"));
+let datum = reader.iter().next().unwrap().unwrap();
+
+let printer = PrettyPrinter::default().with_width(16);
+assert_eq!(
+    printer.print(&datum).to_string(),
+    "(let ((a 1)
+      (b 2))
+  (display a)
+  (display b))"
+);
+```
+
+*/
+
+use super::{labelled, WriteMode, Written};
+use crate::reader::datum::{Datum, SList, SPair, SShared};
+use crate::syntax::{
+    DATUM_ASSIGN_END, DATUM_LABEL_PREFIX, DATUM_REF_END, PAIR_DOT, PAIR_END, PAIR_START,
+    QUASI_QUOTE_ABBREV, QUOTE_ABBREV, UNQUOTE_ABBREV, UNQUOTE_SPLICING_ABBREV, VECTOR_END,
+    VECTOR_START,
+};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+
+// ------------------------------------------------------------------------------------------------
+// Public Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// How the arguments of a list that is not a known Scheme form are laid out when the list does
+/// not fit on one line.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IndentStyle {
+    /// Keep the first argument on the line of the operator and align the remaining arguments
+    /// under it.
+    ///
+    /// ```scheme
+    /// (list alpha
+    ///       beta)
+    /// ```
+    #[default]
+    Aligned,
+    /// Write every argument on its own line, indented from the opening parenthesis.
+    ///
+    /// ```scheme
+    /// (list
+    ///   alpha
+    ///   beta)
+    /// ```
+    Block,
+}
+
+///
+/// The layout options for pretty printing.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PrettyPrinter {
+    width: usize,
+    indent: usize,
+    style: IndentStyle,
+    mode: WriteMode,
+}
+
+///
+/// A datum to be pretty printed, with `Display`.
+///
+#[derive(Debug)]
+pub struct Pretty<'a> {
+    printer: PrettyPrinter,
+    datum: &'a Datum,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Types
+// ------------------------------------------------------------------------------------------------
+
+///
+/// The document built from a datum, and then laid out to fit the width.
+///
+#[derive(Debug)]
+pub(super) enum Doc {
+    Text(String),
+    /// A space if the enclosing group fits on the line, otherwise a new line.
+    Line,
+    /// Always a new line, and so the enclosing groups never fit on one line.
+    HardLine,
+    /// An empty line; it is written before the new line that follows it, so that the empty
+    /// line is not indented.
+    Blank,
+    /// Nothing, but the enclosing groups never fit on one line.
+    BreakParent,
+    Concat(Vec<Doc>),
+    /// Indent new lines by the given number of columns more than the enclosing document.
+    Nest(usize, Box<Doc>),
+    /// Indent new lines to the column at which this document starts.
+    Align(Box<Doc>),
+    Group(Box<Doc>),
+}
+
+///
+/// An element of a list or vector, see [`list_doc`].
+///
+#[derive(Debug)]
+pub(super) struct Element {
+    pub(super) doc: Doc,
+    /// The element starts with a comment or an empty line, and so is not kept on the line
+    /// of the operator.
+    pub(super) own_line: bool,
+    /// The element ends with a line comment, and so the next element is not kept on its
+    /// line.
+    pub(super) ends_line: bool,
+}
+
+///
+/// The identifier that a list starts with; `named` is `true` if the next element is also an
+/// identifier, as in a named `let`.
+///
+#[derive(Clone, Copy, Debug)]
+pub(super) struct Operator<'a> {
+    pub(super) name: &'a str,
+    pub(super) named: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Layout {
+    /// Align all elements under the first.
+    Data,
+    /// Keep the first argument on the line of the operator and align the rest under it.
+    Aligned,
+    /// Keep the operator, and this number of elements in all, on the first line, and indent
+    /// the rest.
+    Body(usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+struct Builder {
+    printer: PrettyPrinter,
+    labelled: HashSet<usize>,
+    labels: HashMap<usize, usize>,
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Values
+// ------------------------------------------------------------------------------------------------
+
+const DEFAULT_WIDTH: usize = 80;
+
+const DEFAULT_INDENT: usize = 2;
+
+///
+/// Forms whose first _n_ arguments are kept on the line of the operator, with the rest of the
+/// form written as an indented body.
+///
+const BODY_FORMS: &[(&str, usize)] = &[
+    ("begin", 0),
+    ("case", 1),
+    ("case-lambda", 0),
+    ("define", 1),
+    ("define-library", 1),
+    ("define-record-type", 2),
+    ("define-syntax", 1),
+    ("define-values", 1),
+    ("do", 2),
+    ("guard", 1),
+    ("lambda", 1),
+    ("let", 1),
+    ("let*", 1),
+    ("let*-values", 1),
+    ("let-syntax", 1),
+    ("let-values", 1),
+    ("letrec", 1),
+    ("letrec*", 1),
+    ("letrec-syntax", 1),
+    ("parameterize", 1),
+    ("syntax-rules", 1),
+    ("unless", 1),
+    ("when", 1),
+];
+
+///
+/// Forms whose arguments are aligned under the first, whatever the [`IndentStyle`].
+///
+const ALIGNED_FORMS: &[&str] = &["and", "cond", "if", "or"];
+
+// ------------------------------------------------------------------------------------------------
+// Public Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Pretty print `datum` with the default options.
+///
+#[inline(always)]
+pub fn pretty(datum: &Datum) -> Pretty<'_> {
+    PrettyPrinter::default().print(datum)
+}
+
+// ------------------------------------------------------------------------------------------------
+// Implementations
+// ------------------------------------------------------------------------------------------------
+
+impl Default for PrettyPrinter {
+    fn default() -> Self {
+        Self {
+            width: DEFAULT_WIDTH,
+            indent: DEFAULT_INDENT,
+            style: Default::default(),
+            mode: Default::default(),
+        }
+    }
+}
+
+impl PrettyPrinter {
+    ///
+    /// Set the line width to fit to, the default is 80 columns. A datum that cannot be broken,
+    /// such as a long string, may still exceed it.
+    ///
+    pub fn with_width(self, width: usize) -> Self {
+        Self { width, ..self }
+    }
+
+    ///
+    /// Set the number of columns by which a body, or a [`IndentStyle::Block`] argument, is
+    /// indented, the default is 2.
+    ///
+    pub fn with_indent(self, indent: usize) -> Self {
+        Self { indent, ..self }
+    }
+
+    ///
+    /// Set how the arguments of a list that is not a known form are laid out, the default is
+    /// [`IndentStyle::Aligned`].
+    ///
+    pub fn with_style(self, style: IndentStyle) -> Self {
+        Self { style, ..self }
+    }
+
+    ///
+    /// Set which shared datums are written with labels, as for [`Written`].
+    ///
+    pub fn with_mode(self, mode: WriteMode) -> Self {
+        Self { mode, ..self }
+    }
+
+    #[inline(always)]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline(always)]
+    pub fn indent(&self) -> usize {
+        self.indent
+    }
+
+    #[inline(always)]
+    pub fn style(&self) -> IndentStyle {
+        self.style
+    }
+
+    #[inline(always)]
+    pub fn mode(&self) -> WriteMode {
+        self.mode
+    }
+
+    #[inline(always)]
+    pub fn print<'a>(&self, datum: &'a Datum) -> Pretty<'a> {
+        Pretty {
+            printer: *self,
+            datum,
+        }
+    }
+
+    ///
+    /// Pretty print `datum` to `w`.
+    ///
+    pub fn write_to_fmt<W: std::fmt::Write>(&self, datum: &Datum, w: &mut W) -> std::fmt::Result {
+        let doc = Builder::new(*self, datum).datum(datum);
+        self.layout(&doc, w)
+    }
+
+    ///
+    /// Pretty print `datum` to `w`.
+    ///
+    pub fn write_to_io<W: std::io::Write>(&self, datum: &Datum, w: &mut W) -> std::io::Result<()> {
+        write!(w, "{}", self.print(datum))
+    }
+
+    ///
+    /// Write `doc` to `w`; the indentation of a new line is only written once some text
+    /// follows it, so that no line ends in white space.
+    ///
+    pub(super) fn layout<W: std::fmt::Write>(&self, doc: &Doc, w: &mut W) -> std::fmt::Result {
+        let mut column = 0;
+        let mut pending = None;
+        let mut stack = vec![(0, Mode::Break, doc)];
+        while let Some((indent, mode, doc)) = stack.pop() {
+            match doc {
+                Doc::Text(s) => {
+                    if let Some(pending) = pending.take() {
+                        write!(w, "{:pending$}", "")?;
+                    }
+                    w.write_str(s)?;
+                    column = match s.rfind('\n') {
+                        Some(newline) => s[newline + 1..].chars().count(),
+                        None => column + s.chars().count(),
+                    };
+                }
+                Doc::Line if mode == Mode::Flat => {
+                    w.write_char(' ')?;
+                    column += 1;
+                }
+                Doc::Line | Doc::HardLine => {
+                    w.write_char('\n')?;
+                    pending = Some(indent);
+                    column = indent;
+                }
+                Doc::Blank => w.write_char('\n')?,
+                Doc::BreakParent => {}
+                Doc::Concat(docs) => stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc))),
+                Doc::Nest(nest, doc) => stack.push((indent + nest, mode, doc)),
+                Doc::Align(doc) => stack.push((column, mode, doc)),
+                Doc::Group(doc) => {
+                    let mode = if mode == Mode::Flat
+                        || fits(self.width.saturating_sub(column), doc, &stack)
+                    {
+                        Mode::Flat
+                    } else {
+                        Mode::Break
+                    };
+                    stack.push((indent, mode, doc));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Display for Pretty<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.printer.write_to_fmt(self.datum, f)
+    }
+}
+
+impl Pretty<'_> {
+    #[inline(always)]
+    pub fn printer(&self) -> &PrettyPrinter {
+        &self.printer
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl From<Doc> for Element {
+    fn from(doc: Doc) -> Self {
+        Self {
+            doc,
+            own_line: false,
+            ends_line: false,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Doc {
+    pub(super) fn text<S: Into<String>>(s: S) -> Self {
+        Self::Text(s.into())
+    }
+
+    pub(super) fn group(self) -> Self {
+        Self::Group(Box::new(self))
+    }
+
+    pub(super) fn nest(self, indent: usize) -> Self {
+        Self::Nest(indent, Box::new(self))
+    }
+
+    pub(super) fn align(self) -> Self {
+        Self::Align(Box::new(self))
+    }
+
+    pub(super) fn lines(docs: impl IntoIterator<Item = Doc>) -> Self {
+        let mut result = Vec::new();
+        for doc in docs {
+            if !result.is_empty() {
+                result.push(Self::Line);
+            }
+            result.push(doc);
+        }
+        Self::Concat(result)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Operator<'_> {
+    ///
+    /// Returns the number of distinguished arguments if this is a known body form. A named
+    /// `let` has one more.
+    ///
+    fn body_form(&self) -> Option<usize> {
+        let (_, distinguished) = BODY_FORMS.iter().find(|(name, _)| *name == self.name)?;
+        if self.name == "let" && self.named {
+            Some(distinguished + 1)
+        } else {
+            Some(*distinguished)
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+
+impl Builder {
+    fn new(printer: PrettyPrinter, datum: &Datum) -> Self {
+        Self {
+            printer,
+            labelled: labelled(datum, printer.mode),
+            labels: Default::default(),
+        }
+    }
+
+    fn datum(&mut self, datum: &Datum) -> Doc {
+        match datum {
+            Datum::Quote(v) => self.abbreviation(QUOTE_ABBREV.to_string(), v),
+            Datum::QuasiQuote(v) => self.abbreviation(QUASI_QUOTE_ABBREV.to_string(), v),
+            Datum::Unquote(v) => self.abbreviation(UNQUOTE_ABBREV.to_string(), v),
+            Datum::UnquoteSplicing(v) => {
+                self.abbreviation(format!("{UNQUOTE_ABBREV}{UNQUOTE_SPLICING_ABBREV}"), v)
+            }
+            Datum::List(list) => self.list(list),
+            Datum::Vector(vector) => {
+                let elements = vector
+                    .iter()
+                    .map(|datum| Element::from(self.datum(datum)))
+                    .collect();
+                list_doc(
+                    &self.printer,
+                    Doc::text(VECTOR_START),
+                    elements,
+                    None,
+                    Doc::text(VECTOR_END),
+                )
+            }
+            Datum::Shared(shared) => self.shared(shared),
+            _ => Doc::text(Written::new(datum, self.printer.mode).to_string()),
+        }
+    }
+
+    fn abbreviation(&mut self, prefix: String, datum: &Datum) -> Doc {
+        Doc::Concat(vec![Doc::Text(prefix), self.datum(datum)])
+    }
+
+    fn shared(&mut self, shared: &SShared) -> Doc {
+        let id = shared.id();
+        let mut prefix = None;
+        if self.labelled.contains(&id) {
+            if let Some(label) = self.labels.get(&id) {
+                return Doc::text(format!("{DATUM_LABEL_PREFIX}{label}{DATUM_REF_END}"));
+            }
+            let label = self.labels.len();
+            self.labels.insert(id, label);
+            prefix = Some(format!("{DATUM_LABEL_PREFIX}{label}{DATUM_ASSIGN_END}"));
+        }
+        let doc = match shared.get() {
+            Some(datum) => self.datum(&datum),
+            None => Doc::text(format!(
+                "{DATUM_LABEL_PREFIX}{}{DATUM_REF_END}",
+                shared.label()
+            )),
+        };
+        match prefix {
+            Some(prefix) => Doc::Concat(vec![Doc::Text(prefix), doc]),
+            None => doc,
+        }
+    }
+
+    fn list(&mut self, list: &SList) -> Doc {
+        let (elements, tail) = self.list_elements(list);
+        let mut docs: Vec<Element> = elements
+            .iter()
+            .map(|datum| Element::from(self.datum(datum)))
+            .collect();
+        if let Some(tail) = tail {
+            let tail = self.datum(&tail);
+            docs.push(Doc::Concat(vec![Doc::text(format!("{PAIR_DOT} ")), tail]).into());
+        }
+        let operator = elements
+            .first()
+            .and_then(|datum| datum.as_identifier())
+            .map(|identifier| Operator {
+                name: identifier.as_str(),
+                named: elements.get(1).is_some_and(|datum| datum.is_identifier()),
+            });
+        list_doc(
+            &self.printer,
+            Doc::text(PAIR_START),
+            docs,
+            operator,
+            Doc::text(PAIR_END),
+        )
+    }
+
+    ///
+    /// Returns the elements of `list`, and its tail if it is improper; as in the writer, an
+    /// unlabelled shared tail is returned as more elements of the same list.
+    ///
+    fn list_elements(&self, list: &SList) -> (Vec<Rc<Datum>>, Option<Rc<Datum>>) {
+        let mut elements = Vec::new();
+        let mut tail = None;
+        let mut next: Option<SPair> = list.as_pair().cloned();
+        while let Some(pair) = next {
+            elements.push(pair.car().clone());
+            next = None;
+            match pair.cdr().as_ref() {
+                Datum::List(list) => next = list.as_pair().cloned(),
+                Datum::Shared(shared) if !self.labelled.contains(&shared.id()) => {
+                    match shared.get() {
+                        Some(datum) if datum.is_list() => {
+                            next = datum.as_list().and_then(|list| list.as_pair()).cloned()
+                        }
+                        _ => tail = Some(pair.cdr().clone()),
+                    }
+                }
+                _ => tail = Some(pair.cdr().clone()),
+            }
+        }
+        (elements, tail)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// Private Functions
+// ------------------------------------------------------------------------------------------------
+
+///
+/// Returns the document for a list, or vector, of `elements`; `operator` is the identifier
+/// that the list starts with, if any.
+///
+pub(super) fn list_doc(
+    printer: &PrettyPrinter,
+    open: Doc,
+    elements: Vec<Element>,
+    operator: Option<Operator<'_>>,
+    close: Doc,
+) -> Doc {
+    let on_head_line =
+        |i: usize| i < elements.len() && !elements[i].own_line && !elements[i - 1].ends_line;
+    let ends_line = elements.last().is_some_and(|element| element.ends_line);
+    let layout = match operator {
+        Some(operator) if elements.len() > 1 => match operator.body_form() {
+            Some(distinguished) => {
+                let mut head = 1;
+                while head <= distinguished && on_head_line(head) {
+                    head += 1;
+                }
+                Layout::Body(head)
+            }
+            None if (printer.style == IndentStyle::Aligned
+                || ALIGNED_FORMS.contains(&operator.name))
+                && on_head_line(1) =>
+            {
+                Layout::Aligned
+            }
+            None => Layout::Body(1),
+        },
+        _ => Layout::Data,
+    };
+
+    let mut docs = elements.into_iter().map(|element| element.doc);
+    let body = match layout {
+        Layout::Data => Doc::lines(docs).align(),
+        Layout::Aligned => Doc::Concat(vec![
+            docs.next().unwrap(),
+            Doc::text(" "),
+            Doc::lines(docs).align(),
+        ]),
+        Layout::Body(head) => {
+            let mut result = vec![docs.next().unwrap()];
+            for doc in docs.by_ref().take(head - 1) {
+                result.push(Doc::text(" "));
+                result.push(doc);
+            }
+            result.push(
+                Doc::Concat(docs.flat_map(|doc| [Doc::Line, doc]).collect()).nest(printer.indent),
+            );
+            Doc::Concat(result)
+        }
+    };
+    let mut result = vec![open, body];
+    if ends_line {
+        result.push(Doc::HardLine);
+    }
+    result.push(close);
+    Doc::Concat(result).group().align()
+}
+
+///
+/// Returns `true` if `doc`, laid out flat, and the documents that follow it up to the next
+/// line break fit in `width` columns.
+///
+fn fits(width: usize, doc: &Doc, rest: &[(usize, Mode, &Doc)]) -> bool {
+    let mut width = width as isize;
+    let mut stack = vec![(Mode::Flat, doc)];
+    let mut rest = rest.iter().rev();
+    loop {
+        if width < 0 {
+            return false;
+        }
+        let (mode, doc) = match stack.pop() {
+            Some(next) => next,
+            None => match rest.next() {
+                Some((_, mode, doc)) => (*mode, *doc),
+                None => return true,
+            },
+        };
+        match doc {
+            Doc::Text(s) if s.contains('\n') => return mode == Mode::Break,
+            Doc::Text(s) => width -= s.chars().count() as isize,
+            Doc::Line if mode == Mode::Flat => width -= 1,
+            Doc::Line => return true,
+            Doc::HardLine | Doc::Blank | Doc::BreakParent if mode == Mode::Flat => return false,
+            Doc::HardLine | Doc::Blank => return true,
+            Doc::BreakParent => {}
+            Doc::Concat(docs) => stack.extend(docs.iter().rev().map(|doc| (mode, doc))),
+            Doc::Nest(_, doc) | Doc::Align(doc) | Doc::Group(doc) => stack.push((mode, doc)),
+        }
+    }
+}
@@ -14,7 +14,14 @@ pub fn init_tracing() -> DefaultGuard {
     tracing::subscriber::set_default(subscriber)
 }
 
+pub mod convert;
 pub mod datum;
 pub mod input;
 pub mod lexer;
+pub mod pattern;
+pub mod query;
 pub mod reader;
+#[cfg(feature = "serde")]
+pub mod serialize;
+pub mod visit;
+pub mod writer;
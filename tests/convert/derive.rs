@@ -0,0 +1,238 @@
+use ffsr::convert::{from_str, FromDatum, IntoDatum};
+use ffsr::error::Error;
+use ffsr::writer::write;
+use pretty_assertions::assert_eq;
+
+#[derive(Clone, Debug, PartialEq, FromDatum, IntoDatum)]
+#[datum(tag = "point")]
+struct Point {
+    x: i64,
+    y: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, FromDatum, IntoDatum)]
+#[datum(tag = "rgb", positional)]
+struct Color {
+    red: u8,
+    green: u8,
+    blue: u8,
+}
+
+#[derive(Clone, Debug, PartialEq, FromDatum, IntoDatum)]
+struct Meters(f64);
+
+#[derive(Clone, Debug, PartialEq, FromDatum, IntoDatum)]
+struct Pair(i64, String);
+
+#[derive(Clone, Debug, PartialEq, FromDatum, IntoDatum)]
+#[datum(tag = "none")]
+struct Nothing;
+
+fn default_width() -> u32 {
+    80
+}
+
+#[derive(Clone, Debug, PartialEq, FromDatum, IntoDatum)]
+#[datum(tag = "config")]
+struct Config {
+    #[datum(rename = "file-name")]
+    name: String,
+    #[datum(default)]
+    verbose: bool,
+    #[datum(default = "default_width")]
+    width: u32,
+    origin: Option<Point>,
+    tags: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, FromDatum, IntoDatum)]
+enum Shape {
+    #[datum(rename = "empty")]
+    Empty,
+    Circle(f64),
+    Line(Point, Point),
+    Rect {
+        width: u32,
+        height: u32,
+    },
+    #[datum(positional)]
+    Square {
+        side: u32,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, FromDatum, IntoDatum)]
+#[datum(tag = "boxed")]
+struct Boxed<T> {
+    value: T,
+}
+
+fn written<T: IntoDatum>(value: T) -> String {
+    write(&value.into_datum()).to_string()
+}
+
+fn round_trip<T: Clone + std::fmt::Debug + PartialEq + FromDatum + IntoDatum>(value: T) {
+    assert_eq!(from_str::<T>(&written(value.clone())).unwrap(), value);
+}
+
+#[test]
+fn tagged_struct() {
+    let _guard = crate::init_tracing();
+
+    let point = Point { x: 1, y: 2 };
+    assert_eq!(from_str::<Point>("(point (x 1) (y 2))").unwrap(), point);
+    assert_eq!(from_str::<Point>("(point (y 2) (x 1))").unwrap(), point);
+    assert_eq!(written(point.clone()), "(point (x 1) (y 2))");
+    round_trip(point);
+}
+
+#[test]
+fn positional_struct() {
+    let _guard = crate::init_tracing();
+
+    let color = Color {
+        red: 255,
+        green: 0,
+        blue: 10,
+    };
+    assert_eq!(from_str::<Color>("(rgb 255 0 10)").unwrap(), color);
+    assert_eq!(written(color.clone()), "(rgb 255 0 10)");
+    round_trip(color);
+}
+
+#[test]
+fn tuple_structs() {
+    let _guard = crate::init_tracing();
+
+    assert_eq!(from_str::<Meters>("2.5").unwrap(), Meters(2.5));
+    assert_eq!(written(Meters(2.5)), "2.5");
+    assert_eq!(
+        from_str::<Pair>("(1 \"one\")").unwrap(),
+        Pair(1, "one".to_string())
+    );
+    round_trip(Pair(1, "one".to_string()));
+    assert_eq!(from_str::<Nothing>("none").unwrap(), Nothing);
+    assert_eq!(written(Nothing), "none");
+}
+
+#[test]
+fn optional_and_default_fields() {
+    let _guard = crate::init_tracing();
+
+    let config = from_str::<Config>("(config (file-name \"a.scm\") (tags (\"x\")))").unwrap();
+    assert_eq!(
+        config,
+        Config {
+            name: "a.scm".to_string(),
+            verbose: false,
+            width: 80,
+            origin: None,
+            tags: vec!["x".to_string()],
+        }
+    );
+    assert_eq!(
+        written(config.clone()),
+        "(config (file-name \"a.scm\") (verbose #f) (width 80) (origin ()) (tags (\"x\")))"
+    );
+    round_trip(Config {
+        origin: Some(Point { x: 0, y: 0 }),
+        ..config
+    });
+}
+
+#[test]
+fn enum_variants() {
+    let _guard = crate::init_tracing();
+
+    assert_eq!(from_str::<Shape>("empty").unwrap(), Shape::Empty);
+    assert_eq!(
+        from_str::<Shape>("(Circle 1.5)").unwrap(),
+        Shape::Circle(1.5)
+    );
+    assert_eq!(
+        from_str::<Shape>("(Rect (height 2) (width 1))").unwrap(),
+        Shape::Rect {
+            width: 1,
+            height: 2
+        }
+    );
+    assert_eq!(
+        from_str::<Shape>("(Square 3)").unwrap(),
+        Shape::Square { side: 3 }
+    );
+    assert_eq!(written(Shape::Empty), "empty");
+    assert_eq!(
+        written(Shape::Line(Point { x: 0, y: 0 }, Point { x: 1, y: 1 })),
+        "(Line (point (x 0) (y 0)) (point (x 1) (y 1)))"
+    );
+    round_trip(Shape::Rect {
+        width: 1,
+        height: 2,
+    });
+    round_trip(Shape::Square { side: 3 });
+}
+
+#[test]
+fn generic_struct() {
+    let _guard = crate::init_tracing();
+
+    round_trip(Boxed { value: 3u8 });
+    round_trip(Boxed {
+        value: Point { x: 1, y: 2 },
+    });
+}
+
+#[test]
+fn error_names_field_and_span() {
+    let _guard = crate::init_tracing();
+
+    let error = from_str::<Point>("(point (x 1) (y \"2\"))").unwrap_err();
+    match &error {
+        Error::DatumConversion { conversion, .. } => {
+            assert_eq!(conversion.type_name(), "Point");
+            assert_eq!(conversion.field(), Some("y"));
+        }
+        _ => panic!("unexpected error {error:?}"),
+    }
+    assert_eq!(error.span().unwrap().as_range(), 16..19);
+}
+
+#[test]
+fn nested_error_names_innermost_field() {
+    let _guard = crate::init_tracing();
+
+    let error = from_str::<Shape>("(Line (point (x 0) (y 0)) (point (x a) (y 1)))").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Could not convert datum to `Point`, field `x`: expecting an integer, found `identifier`; span: 36..37"
+    );
+}
+
+#[test]
+fn structure_errors() {
+    let _guard = crate::init_tracing();
+
+    let missing = from_str::<Point>("(point (x 1))").unwrap_err();
+    assert_eq!(
+        missing.to_string(),
+        "Could not convert datum to `Point`, field `y`: missing field; span: 0..13"
+    );
+
+    let unknown = from_str::<Point>("(point (x 1) (y 2) (z 3))").unwrap_err();
+    assert_eq!(unknown.span().unwrap().as_range(), 20..21);
+
+    let duplicate = from_str::<Point>("(point (x 1) (x 2))").unwrap_err();
+    assert_eq!(duplicate.span().unwrap().as_range(), 14..15);
+
+    let tag = from_str::<Point>("(pt (x 1) (y 2))").unwrap_err();
+    assert_eq!(tag.span().unwrap().as_range(), 1..3);
+
+    let extra = from_str::<Color>("(rgb 1 2 3 4)").unwrap_err();
+    assert_eq!(extra.span().unwrap().as_range(), 11..12);
+
+    let variant = from_str::<Shape>("(Triangle 1 2 3)").unwrap_err();
+    assert_eq!(
+        variant.to_string(),
+        "Could not convert datum to `Shape`: unknown variant `Triangle`; span: 0..16"
+    );
+}
@@ -0,0 +1,3 @@
+#[cfg(feature = "derive")]
+pub mod derive;
+pub mod values;
@@ -0,0 +1,71 @@
+use ffsr::convert::{from_str, IntoDatum};
+use ffsr::error::Error;
+use ffsr::reader::datum::Datum;
+use ffsr::writer::write;
+use pretty_assertions::assert_eq;
+
+fn written<T: IntoDatum>(value: T) -> String {
+    write(&value.into_datum()).to_string()
+}
+
+#[test]
+fn read_scalars() {
+    let _guard = crate::init_tracing();
+
+    assert_eq!(from_str::<i64>("-42").unwrap(), -42);
+    assert_eq!(from_str::<u8>("255").unwrap(), 255);
+    assert_eq!(from_str::<f64>("1.5").unwrap(), 1.5);
+    assert_eq!(from_str::<f64>("2").unwrap(), 2.0);
+    assert!(from_str::<bool>("#t").unwrap());
+    assert_eq!(from_str::<char>("#\\a").unwrap(), 'a');
+    assert_eq!(from_str::<String>("\"a b\"").unwrap(), "a b");
+    assert_eq!(from_str::<Datum>("(a b)").unwrap().to_string(), "(a b)");
+}
+
+#[test]
+fn read_containers() {
+    let _guard = crate::init_tracing();
+
+    assert_eq!(from_str::<Vec<u8>>("(1 2 3)").unwrap(), vec![1, 2, 3]);
+    assert_eq!(from_str::<Vec<u8>>("#(1 2)").unwrap(), vec![1, 2]);
+    assert_eq!(from_str::<Option<u8>>("()").unwrap(), None);
+    assert_eq!(from_str::<Option<u8>>("7").unwrap(), Some(7));
+    assert_eq!(*from_str::<Box<i32>>("7").unwrap(), 7);
+}
+
+#[test]
+fn write_values() {
+    let _guard = crate::init_tracing();
+
+    assert_eq!(written(-42i64), "-42");
+    assert_eq!(written(1.5f32), "1.5");
+    assert_eq!(written("a"), "\"a\"");
+    assert_eq!(written(vec![Some(1u8), None]), "(1 ())");
+}
+
+#[test]
+fn integer_out_of_range() {
+    let _guard = crate::init_tracing();
+
+    let error = from_str::<u8>("256").unwrap_err();
+    match &error {
+        Error::DatumConversion { conversion, .. } => {
+            assert_eq!(conversion.type_name(), "u8");
+            assert_eq!(conversion.field(), None);
+        }
+        _ => panic!("unexpected error {error:?}"),
+    }
+    assert_eq!(error.span().unwrap().as_range(), 0..3);
+}
+
+#[test]
+fn wrong_type_in_list() {
+    let _guard = crate::init_tracing();
+
+    let error = from_str::<Vec<i64>>("(1 two 3)").unwrap_err();
+    assert_eq!(error.span().unwrap().as_range(), 3..6);
+    assert_eq!(
+        error.to_string(),
+        "Could not convert datum to `i64`: expecting an integer, found `identifier`; span: 3..6"
+    );
+}
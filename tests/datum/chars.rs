@@ -1,4 +1,4 @@
-use ffsr::reader::datum::SChar;
+use ffsr::reader::datum::{fold_case_str, SChar};
 
 // ------------------------------------------------------------------------------------------------
 // Single-valued success cases
@@ -17,3 +17,26 @@ use ffsr::reader::datum::SChar;
 failure_case!(no_name, "#\\", SChar);
 
 failure_case!(unknown_name, "#\\unknown", SChar);
+
+// ------------------------------------------------------------------------------------------------
+// Case folding
+// ------------------------------------------------------------------------------------------------
+
+#[test]
+fn fold_case_chars() {
+    assert_eq!(SChar::from('A').fold_case(), SChar::from('a'));
+    assert_eq!(SChar::from('a').fold_case(), SChar::from('a'));
+    assert_eq!(SChar::from('ς').fold_case(), SChar::from('σ'));
+    assert_eq!(SChar::from('ẞ').fold_case(), SChar::from('ß'));
+    assert_eq!(SChar::from('ß').fold_case(), SChar::from('ß'));
+    assert_eq!(SChar::from('\u{AB70}').fold_case(), SChar::from('\u{13A0}'));
+    assert_eq!(SChar::from('\u{13A0}').fold_case(), SChar::from('\u{13A0}'));
+}
+
+#[test]
+fn fold_case_strings() {
+    assert_eq!(fold_case_str("Hello World"), "hello world");
+    assert_eq!(fold_case_str("Maße"), "masse");
+    assert_eq!(fold_case_str("ΣΑΣ ΌΣΟΣ"), "σασ όσοσ");
+    assert_eq!(fold_case_str("ſ"), "s");
+}
@@ -0,0 +1,57 @@
+use ffsr::lexer::Lexer;
+use ffsr::reader::datum::{Datum, SComment, SIdentifier};
+use ffsr::reader::Reader;
+use std::str::FromStr;
+
+// ------------------------------------------------------------------------------------------------
+// Single-valued success cases
+// ------------------------------------------------------------------------------------------------
+
+success_case!(line_comment, "; a line" => SComment, "; a line");
+
+success_case!(block_comment, "#| a block |#" => SComment, "#| a block |#");
+
+success_case!(datum_comment, "#;(a b)" => SComment, "#; (a b)");
+
+success_case!(datum_comment_quoted, "#; 'a" => SComment, "#; 'a");
+
+success_case!(
+    ! datum_comment_value,
+    "#; a" => SComment,
+    SComment::from(Datum::from(SIdentifier::from_str("a").unwrap()))
+);
+
+#[test]
+fn datum_comment_list_value() {
+    let _guard = crate::init_tracing();
+
+    let list = Reader::from(Lexer::from("(1 2)"))
+        .iter()
+        .next()
+        .unwrap()
+        .unwrap();
+    pretty_assertions::assert_eq!(
+        SComment::from_str("#; (1 2)").unwrap(),
+        SComment::from(list)
+    );
+}
+
+#[test]
+fn datum_comment_error_span() {
+    let _guard = crate::init_tracing();
+
+    let error = SComment::from_str("#; (a . )").unwrap_err();
+    assert_eq!(error.span().map(|span| span.start()), Some(6));
+}
+
+// ------------------------------------------------------------------------------------------------
+// Failure cases
+// ------------------------------------------------------------------------------------------------
+
+failure_case!(datum_comment_no_datum, "#;", SComment);
+
+failure_case!(datum_comment_two_datums, "#; a b", SComment);
+
+failure_case!(nested_datum_comments, "#; #; a b", SComment);
+
+failure_case!(not_a_comment, "a", SComment);
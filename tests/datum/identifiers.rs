@@ -45,3 +45,82 @@ failure_case!(incomplete_hex_escape, "str\\x20", SIdentifier);
 failure_case!(number, "12", SIdentifier);
 
 failure_case!(plus_number, "+12", SIdentifier);
+
+// ------------------------------------------------------------------------------------------------
+// Interning
+// ------------------------------------------------------------------------------------------------
+
+#[test]
+fn interned_identifiers_are_shared() {
+    use std::str::FromStr;
+
+    let _guard = crate::init_tracing();
+
+    let first = SIdentifier::from_str("interned-identifier").unwrap();
+    let second = SIdentifier::from_str("|interned-identifier|").unwrap();
+    assert_eq!(first, second);
+    assert!(std::ptr::eq(first.as_str(), second.as_str()));
+
+    let count = SIdentifier::interned_count();
+    let copy = first.clone();
+    let _third = SIdentifier::from_str("interned-identifier").unwrap();
+    assert_eq!(copy, first);
+    assert_eq!(SIdentifier::interned_count(), count);
+
+    assert_ne!(
+        first,
+        SIdentifier::from_str("interned-identifier?").unwrap()
+    );
+    assert_eq!(
+        SIdentifier::from_str("a b").unwrap(),
+        SIdentifier::from_str("|a b|").unwrap()
+    );
+}
+
+#[test]
+fn unused_identifiers_released() {
+    use std::str::FromStr;
+
+    let _guard = crate::init_tracing();
+
+    let kept = SIdentifier::from_str("kept-identifier").unwrap();
+    drop(SIdentifier::from_str("released-identifier").unwrap());
+    SIdentifier::release_unused();
+
+    let again = SIdentifier::from_str("kept-identifier").unwrap();
+    assert_eq!(again, kept);
+    assert!(std::ptr::eq(again.as_str(), kept.as_str()));
+    assert_eq!(
+        SIdentifier::from_str("released-identifier")
+            .unwrap()
+            .as_str(),
+        "released-identifier"
+    );
+}
+
+#[test]
+fn interned_identifiers_ordered_by_text() {
+    use std::collections::{BTreeSet, HashSet};
+    use std::str::FromStr;
+
+    let _guard = crate::init_tracing();
+
+    let names = ["lambda", "car", "define", "car", "cdr", "define"];
+    let identifiers: Vec<SIdentifier> = names
+        .iter()
+        .map(|name| SIdentifier::from_str(name).unwrap())
+        .collect();
+
+    let ordered: Vec<&str> = identifiers
+        .iter()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(SIdentifier::as_str)
+        .collect();
+    assert_eq!(ordered, vec!["car", "cdr", "define", "lambda"]);
+    assert_eq!(identifiers.iter().collect::<HashSet<_>>().len(), 4);
+
+    let folded = SIdentifier::from_str("CAR").unwrap().fold_case();
+    assert_eq!(folded, identifiers[1]);
+    assert_eq!(String::from(folded), "car");
+}
@@ -66,6 +66,7 @@ macro_rules! failure_case {
 
 pub mod booleans;
 pub mod chars;
+pub mod comments;
 pub mod directives;
 pub mod identifiers;
 pub mod lists;
@@ -0,0 +1,120 @@
+use ffsr::input::encoding::Encoding;
+use ffsr::input::indices::Index;
+use ffsr::input::{input_from_file, input_from_file_with_encoding};
+use ffsr::lexer::Lexer;
+use ffsr::reader::Reader;
+use ffsr::Sourced;
+use std::path::PathBuf;
+
+fn write_temp(name: &str, bytes: &[u8]) -> PathBuf {
+    let path = std::env::temp_dir().join(name);
+    std::fs::write(&path, bytes).unwrap();
+    path
+}
+
+fn utf16(s: &str, bom: &[u8], to_bytes: fn(u16) -> [u8; 2]) -> Vec<u8> {
+    let mut bytes = bom.to_vec();
+    bytes.extend(s.encode_utf16().flat_map(to_bytes));
+    bytes
+}
+
+fn read_all(input: ffsr::input::Input<'_>) -> Vec<String> {
+    let reader = Reader::from(Lexer::from(input));
+    reader.iter().map(|d| d.unwrap().to_string()).collect()
+}
+
+#[test]
+fn file_without_bom() {
+    let _guard = crate::init_tracing();
+
+    let path = write_temp("ffsr-encoding-plain.scm", "(λ x)".as_bytes());
+    let input = input_from_file(&path).unwrap();
+
+    assert_eq!(input.source_str(), "(λ x)");
+    assert_eq!(input.encoding(), Encoding::Utf8);
+    assert!(!input.has_byte_order_mark());
+}
+
+#[test]
+fn file_with_utf8_bom() {
+    let _guard = crate::init_tracing();
+
+    let path = write_temp("ffsr-encoding-utf8-bom.scm", b"\xEF\xBB\xBF(a b)");
+    let input = input_from_file(&path).unwrap();
+
+    assert_eq!(input.source_str(), "(a b)");
+    assert_eq!(input.encoding(), Encoding::Utf8);
+    assert!(input.has_byte_order_mark());
+    assert_eq!(input.original_byte_offset(Index::new(1, 1)), 4);
+    assert_eq!(read_all(input), vec!["(a b)".to_string()]);
+}
+
+#[test]
+fn file_with_utf16le_bom() {
+    let _guard = crate::init_tracing();
+
+    let bytes = utf16("(λ 𝄞)", Encoding::UTF16LE_BOM, u16::to_le_bytes);
+    let path = write_temp("ffsr-encoding-utf16le.scm", &bytes);
+    let input = input_from_file(&path).unwrap();
+
+    assert_eq!(input.source_str(), "(λ 𝄞)");
+    assert_eq!(input.encoding(), Encoding::Utf16Le);
+    assert!(input.has_byte_order_mark());
+    // the `)` follows a surrogate pair.
+    assert_eq!(input.original_byte_offset(Index::new(8, 4)), 2 + 10);
+    assert_eq!(read_all(input), vec!["(λ 𝄞)".to_string()]);
+}
+
+#[test]
+fn file_with_utf16be_bom() {
+    let _guard = crate::init_tracing();
+
+    let bytes = utf16("#t", Encoding::UTF16BE_BOM, u16::to_be_bytes);
+    let path = write_temp("ffsr-encoding-utf16be.scm", &bytes);
+    let input = input_from_file(&path).unwrap();
+
+    assert_eq!(input.source_str(), "#t");
+    assert_eq!(input.encoding(), Encoding::Utf16Be);
+    assert_eq!(input.original_byte_offset(Index::new(1, 1)), 4);
+}
+
+#[test]
+fn file_with_explicit_latin1() {
+    let _guard = crate::init_tracing();
+
+    let path = write_temp("ffsr-encoding-latin1.scm", b"\"caf\xE9\" na\xEFve");
+    assert!(input_from_file(&path).is_err());
+
+    let input = input_from_file_with_encoding(&path, Encoding::Latin1).unwrap();
+    assert_eq!(input.source_str(), "\"café\" naïve");
+    assert_eq!(input.encoding(), Encoding::Latin1);
+    assert!(!input.has_byte_order_mark());
+    // `n` is after the multi-byte `é` in UTF-8, but not in the original.
+    assert_eq!(input.original_byte_offset(Index::new(8, 7)), 7);
+    assert_eq!(
+        read_all(input),
+        vec!["café".to_string(), "naïve".to_string()]
+    );
+}
+
+#[test]
+fn file_with_mismatched_bom() {
+    let _guard = crate::init_tracing();
+
+    let path = write_temp("ffsr-encoding-mismatched.scm", b"\xEF\xBB\xBFa");
+    let input = input_from_file_with_encoding(&path, Encoding::Latin1).unwrap();
+
+    assert_eq!(input.source_str(), "\u{EF}\u{BB}\u{BF}a");
+    assert!(!input.has_byte_order_mark());
+}
+
+#[test]
+fn file_with_invalid_utf16() {
+    let _guard = crate::init_tracing();
+
+    let path = write_temp("ffsr-encoding-odd-utf16.scm", b"\xFF\xFEa\x00b");
+    assert!(input_from_file(&path).is_err());
+
+    let path = write_temp("ffsr-encoding-lone-surrogate.scm", b"\xFF\xFE\x00\xD8a\x00");
+    assert!(input_from_file(&path).is_err());
+}
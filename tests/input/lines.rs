@@ -0,0 +1,96 @@
+use ffsr::input::input_from_str;
+use ffsr::input::lines::{LineIndex, Position};
+use ffsr::lexer::token::Span;
+use ffsr::lexer::Lexer;
+use ffsr::reader::Reader;
+
+#[test]
+fn empty_source() {
+    let index = LineIndex::new("");
+
+    assert!(index.is_empty());
+    assert_eq!(index.line_count(), 1);
+    assert_eq!(index.position(0), Position::new(0, 0));
+    assert_eq!(index.position(10), Position::new(0, 0));
+    assert_eq!(index.char_offset(Position::new(0, 0)), Some(0));
+    assert_eq!(index.char_offset(Position::new(0, 1)), None);
+    assert_eq!(index.char_offset(Position::new(1, 0)), None);
+}
+
+#[test]
+fn line_endings() {
+    let index = LineIndex::new("a\nbc\r\nd\re\n");
+
+    assert_eq!(index.line_count(), 5);
+    assert_eq!(index.line_span(0), Some(Span::new(0, 1)));
+    assert_eq!(index.line_span(1), Some(Span::new(2, 4)));
+    assert_eq!(index.line_span(2), Some(Span::new(6, 7)));
+    assert_eq!(index.line_span(3), Some(Span::new(8, 9)));
+    assert_eq!(index.line_span(4), Some(Span::new(10, 10)));
+    assert_eq!(index.line_span(5), None);
+
+    assert_eq!(index.position(0), Position::new(0, 0));
+    assert_eq!(index.position(1), Position::new(0, 1));
+    assert_eq!(index.position(2), Position::new(1, 0));
+    // both halves of CRLF belong to the line they terminate.
+    assert_eq!(index.position(4), Position::new(1, 2));
+    assert_eq!(index.position(5), Position::new(1, 3));
+    assert_eq!(index.position(6), Position::new(2, 0));
+    assert_eq!(index.position(8), Position::new(3, 0));
+    assert_eq!(index.position(10), Position::new(4, 0));
+
+    assert_eq!(index.char_offset(Position::new(1, 2)), Some(4));
+    assert_eq!(index.char_offset(Position::new(1, 3)), None);
+    assert_eq!(index.char_offset(Position::new(3, 1)), Some(9));
+}
+
+#[test]
+fn multi_byte_and_utf16() {
+    let source = "λ𝄞\n a𝄞b";
+    let index = LineIndex::new(source);
+
+    for (character, (byte, _)) in source.char_indices().enumerate() {
+        assert_eq!(index.char_to_byte(character), byte);
+        assert_eq!(index.byte_to_char(byte), character);
+        let utf16: usize = source[..byte].chars().map(char::len_utf16).sum();
+        assert_eq!(index.char_to_utf16(character), utf16);
+        assert_eq!(index.utf16_to_char(utf16), character);
+    }
+    assert_eq!(index.len().byte(), source.len());
+    assert_eq!(index.len().character(), 7);
+    assert_eq!(index.len_utf16(), 9);
+
+    // inside a multi-byte character or a surrogate pair.
+    assert_eq!(index.byte_to_char(1), 0);
+    assert_eq!(index.byte_to_char(4), 1);
+    assert_eq!(index.utf16_to_char(2), 1);
+
+    assert_eq!(index.position(6), Position::new(1, 3));
+    assert_eq!(index.position_utf16(6), Position::new(1, 4));
+    assert_eq!(index.char_offset(Position::new(1, 3)), Some(6));
+    assert_eq!(index.char_offset_utf16(Position::new(1, 4)), Some(6));
+    assert_eq!(index.char_offset_utf16(Position::new(1, 6)), None);
+    assert_eq!(index.index(6).byte(), 13);
+}
+
+#[test]
+fn input_line_index_is_cached() {
+    let input = input_from_str("(a\n b)");
+
+    assert!(std::ptr::eq(input.line_index(), input.line_index()));
+    assert_eq!(input.line_index().line_count(), 2);
+}
+
+#[test]
+fn error_positions() {
+    let _guard = crate::init_tracing();
+
+    let reader = Reader::from(Lexer::from("(a b)\r\n(c λ\r\n . )"));
+    let error = reader.iter().find_map(|d| d.err()).unwrap();
+    let lines = reader.line_index();
+
+    assert_eq!(error.span(), Some(Span::new(14, 15)));
+    assert_eq!(error.start_position(lines), Some(Position::new(2, 1)));
+    assert_eq!(error.end_position(lines), Some(Position::new(2, 2)));
+    assert_eq!(error.start_position(lines).unwrap().to_string(), "3:2");
+}
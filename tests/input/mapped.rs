@@ -0,0 +1,60 @@
+use ffsr::input::{input_from_file, input_from_mapped_file};
+use ffsr::lexer::Lexer;
+use ffsr::reader::Reader;
+use ffsr::{SourceId, Sourced};
+use std::path::PathBuf;
+
+const TEST_FILE: &str = "tests/files/LispKit/Prelude.scm";
+
+#[test]
+fn mapped_file_matches_read_file() {
+    let _guard = crate::init_tracing();
+
+    let mapped = unsafe { input_from_mapped_file(TEST_FILE) }.unwrap();
+    let read = input_from_file(TEST_FILE).unwrap();
+
+    assert_eq!(
+        mapped.source_id(),
+        &SourceId::File(PathBuf::from(TEST_FILE))
+    );
+    assert_eq!(mapped.source_str(), read.source_str());
+
+    let mapped = Reader::from(Lexer::from(mapped));
+    let read = Reader::from(Lexer::from(read));
+    let mapped: Vec<String> = mapped.iter().map(|d| format!("{d:?}")).collect();
+    let read: Vec<String> = read.iter().map(|d| format!("{d:?}")).collect();
+    pretty_assertions::assert_eq!(mapped, read);
+}
+
+#[test]
+fn mapped_empty_file() {
+    let _guard = crate::init_tracing();
+
+    let path = std::env::temp_dir().join("ffsr-mapped-empty.scm");
+    std::fs::write(&path, "").unwrap();
+
+    let input = unsafe { input_from_mapped_file(&path) }.unwrap();
+    assert_eq!(input.source_str(), "");
+}
+
+#[test]
+fn mapped_file_rejects_invalid_utf8() {
+    let _guard = crate::init_tracing();
+
+    let path = std::env::temp_dir().join("ffsr-mapped-invalid.scm");
+    std::fs::write(&path, b"(a \xff)").unwrap();
+
+    assert!(unsafe { input_from_mapped_file(&path) }.is_err());
+}
+
+#[test]
+fn mapped_file_skips_bom() {
+    let _guard = crate::init_tracing();
+
+    let path = std::env::temp_dir().join("ffsr-mapped-bom.scm");
+    std::fs::write(&path, b"\xEF\xBB\xBF(a b)").unwrap();
+
+    let input = unsafe { input_from_mapped_file(&path) }.unwrap();
+    assert_eq!(input.source_str(), "(a b)");
+    assert!(input.has_byte_order_mark());
+}
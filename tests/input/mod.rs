@@ -23,5 +23,10 @@ macro_rules! assert_complete {
 }
 
 pub mod ascii;
+pub mod encoding;
+pub mod lines;
+#[cfg(feature = "mmap")]
+pub mod mapped;
 pub mod pushback;
+pub mod source_map;
 pub mod unicode;
@@ -0,0 +1,101 @@
+use ffsr::input::input_from_string;
+use ffsr::lexer::token::Span;
+use ffsr::lexer::Lexer;
+use ffsr::reader::Reader;
+use ffsr::source_map::SourceMap;
+use ffsr::{SourceId, Sourced};
+use std::path::PathBuf;
+
+#[test]
+fn file_ids_are_distinct() {
+    let mut sources = SourceMap::default();
+    assert!(sources.is_empty());
+
+    let first = sources.add_named("cell-1", "(a)");
+    let second = sources.add_named("cell-2", "(b)");
+    let third = sources.add(input_from_string("(c)".to_string()));
+
+    assert_eq!(sources.len(), 3);
+    assert_ne!(first, second);
+    assert_ne!(second, third);
+    assert_eq!(
+        sources.get(first).unwrap().source_id(),
+        &SourceId::Named("cell-1".to_string())
+    );
+    assert_eq!(sources.get(second).unwrap().file_id(), Some(second));
+    assert_eq!(
+        sources.find(&SourceId::Named("cell-2".to_string())),
+        Some(second)
+    );
+    assert_eq!(sources.find(&SourceId::StdIn), None);
+}
+
+#[test]
+fn tokens_carry_file_id() {
+    let _guard = crate::init_tracing();
+
+    let mut sources = SourceMap::default();
+    let _ = sources.add_named("other", "#t");
+    let file_id = sources.add_named("generated", "(a b)");
+
+    let lexer = Lexer::from(sources.input(file_id).unwrap());
+    assert_eq!(lexer.file_id(), Some(file_id));
+    for token in lexer.tokens() {
+        assert_eq!(token.unwrap().span().file(), Some(file_id));
+    }
+
+    let lexer = Lexer::from("(a b)");
+    for token in lexer.tokens() {
+        assert_eq!(token.unwrap().span().file(), None);
+    }
+}
+
+#[test]
+fn errors_carry_file_id() {
+    let _guard = crate::init_tracing();
+
+    let mut sources = SourceMap::default();
+    let good = sources.add_named("good", "(a b)");
+    let bad = sources.add_named("bad", "(a\n  . )");
+
+    let reader = Reader::from(Lexer::from(sources.input(good).unwrap()));
+    assert!(reader.iter().all(|datum| datum.is_ok()));
+
+    let reader = Reader::from(Lexer::from(sources.input(bad).unwrap()));
+    let error = reader.iter().find_map(|datum| datum.err()).unwrap();
+    let span = error.span().unwrap();
+
+    assert_eq!(span.file(), Some(bad));
+    assert_eq!(span, Span::new(5, 6).with_file(bad));
+    assert_eq!(
+        error.start_position(sources.get(bad).unwrap().line_index()),
+        Some((1, 2).into())
+    );
+    error.print_with(&sources);
+
+    let lexer = Lexer::from(sources.input(bad).unwrap());
+    let error = lexer.tokens().find_map(|token| token.err());
+    assert!(error.is_none());
+
+    let unterminated = sources.add_named("unterminated", "\"abc");
+    let lexer = Lexer::from(sources.input(unterminated).unwrap());
+    let error = lexer.tokens().find_map(|token| token.err()).unwrap();
+    assert_eq!(error.span().unwrap().file(), Some(unterminated));
+    error.print_with(&sources);
+}
+
+#[test]
+fn add_file() {
+    let mut sources = SourceMap::default();
+    let file_id = sources.add_file("tests/files/LispKit/Prelude.scm").unwrap();
+
+    assert_eq!(
+        sources.get(file_id).unwrap().source_id(),
+        &SourceId::File(PathBuf::from("tests/files/LispKit/Prelude.scm"))
+    );
+    assert_eq!(
+        sources.get(file_id).unwrap().source_id().to_string(),
+        "tests/files/LispKit/Prelude.scm"
+    );
+    assert!(sources.add_file("tests/files/does-not-exist.scm").is_err());
+}
@@ -7,6 +7,7 @@
 // ------------------------------------------------------------------------------------------------
 
 success_case!(empty, "()" => (open_parenthesis, "("), (close_parenthesis, ")"));
+success_case!(!dot_at_end_of_input, "(a ." => (open_parenthesis, "("), (identifier, "a"), (dot, "."));
 success_case!(empty_with_inner_whitespace, "(\t\n)" => (open_parenthesis, "("), (close_parenthesis, ")"));
 
 // ------------------------------------------------------------------------------------------------
@@ -104,5 +104,6 @@ pub mod identifiers;
 pub mod lists;
 pub mod numbers;
 pub mod quotes;
+pub mod recovery;
 pub mod specials;
 pub mod strings;
@@ -57,6 +57,8 @@ success_case!(hex_flonum_alt_exp, "#x#if0.01^a" => number);
 success_case!(complex_fixnum, "3+4i" => number);
 success_case!(complex_ratnum, "1/2+3/4i" => number);
 success_case!(complex_flonum, "3.0+4.0i" => number);
+success_case!(complex_unit_imaginary, "3+i" => number);
+success_case!(complex_negative_unit_imaginary, "3-i" => number);
 
 // ------------------------------------------------------------------------------------------------
 
@@ -72,6 +74,9 @@ success_case!(!negative_infinity, "-inf.0" => number);
 success_case!(positive_nan, "+nan.0" => number);
 success_case!(negative_nan, "-nan.0" => number);
 
+success_case!(infinity_prefix, "+inf" => identifier);
+success_case!(nan_prefix, "-nan." => identifier);
+
 // ------------------------------------------------------------------------------------------------
 // Multi-valued success cases
 // ------------------------------------------------------------------------------------------------
@@ -0,0 +1,154 @@
+use ffsr::error::Error;
+use ffsr::lexer::Lexer;
+
+///
+/// Each token as its kind and text, or each error as its code and the text its span covers.
+///
+fn recover_all(input: &str) -> Vec<String> {
+    let lexer = Lexer::from(input);
+    lexer
+        .tokens_with_recovery()
+        .map(|result| match result {
+            Ok(token) => format!("{:?} {:?}", token.kind(), lexer.token_str(&token)),
+            Err(e) => format!("error {} {:?}", e.code(), error_text(input, &e)),
+        })
+        .collect()
+}
+
+fn error_text(input: &str, error: &Error) -> String {
+    let span = error.span().unwrap();
+    input
+        .chars()
+        .skip(span.start())
+        .take(span.end() - span.start() + 1)
+        .collect()
+}
+
+#[test]
+fn not_recovering_by_default() {
+    let lexer = Lexer::from("#q");
+    assert!(!lexer.tokens().is_recovering());
+    assert!(lexer.tokens_with_recovery().is_recovering());
+}
+
+#[test]
+fn bad_special_skipped() {
+    let _guard = crate::init_tracing();
+
+    assert_eq!(
+        recover_all("(a #qwerty b)"),
+        vec![
+            "OpenParenthesis \"(\"",
+            "Identifier \"a\"",
+            "error 10 \"#qwerty\"",
+            "Identifier \"b\"",
+            "CloseParenthesis \")\"",
+        ]
+    );
+}
+
+#[test]
+fn skipped_range_reported() {
+    let _guard = crate::init_tracing();
+
+    let lexer = Lexer::from("(a #qwerty b)");
+    let results: Vec<_> = lexer.tokens_with_recovery().collect();
+    let span = results[2].as_ref().unwrap_err().span().unwrap();
+    assert_eq!((span.start(), span.end()), (3, 9));
+    let next = results[3].as_ref().unwrap();
+    assert_eq!(next.start(), span.end() + 2);
+    assert_eq!(lexer.token_str(next), "b");
+}
+
+#[test]
+fn resume_at_delimiter() {
+    let _guard = crate::init_tracing();
+
+    assert_eq!(
+        recover_all("#u(1) #!(x)"),
+        vec![
+            "error 87 \"#u\"",
+            "OpenParenthesis \"(\"",
+            "Number \"1\"",
+            "CloseParenthesis \")\"",
+            "error 11 \"#!\"",
+            "OpenParenthesis \"(\"",
+            "Identifier \"x\"",
+            "CloseParenthesis \")\"",
+        ]
+    );
+}
+
+#[test]
+fn every_error_reported() {
+    let _guard = crate::init_tracing();
+
+    assert_eq!(
+        recover_all("12e; done\n#\\x41 [a] #1x |a\\qb| #xg"),
+        vec![
+            "error 70 \"12e\"",
+            "LineComment \"; done\\n\"",
+            "error 53 \"#\\\\x41\"",
+            "error 17 \"[a]\"",
+            "error 13 \"#1x\"",
+            "error 21 \"|a\\\\qb|\"",
+            "error 70 \"#xg\"",
+        ]
+    );
+}
+
+#[test]
+fn incomplete_forms_at_end_of_input() {
+    let _guard = crate::init_tracing();
+
+    for input in ["\"abc\\", "|abc\\", "#| abc |", "#x", "12e", "1+", "#12"] {
+        let lexer = Lexer::from(input);
+        let mut tokens = lexer.tokens();
+        assert!(
+            matches!(tokens.next(), Some(Err(_))),
+            "expected an error for {input:?}"
+        );
+        assert!(tokens.next().is_none());
+    }
+}
+
+#[test]
+fn no_panic_on_arbitrary_input() {
+    let _guard = crate::init_tracing();
+
+    const ALPHABET: &[char] = &[
+        '(', ')', '[', '{', '#', '\\', '|', '"', ';', '\'', '`', ',', '@', '.', '+', '-', 'e', 'i',
+        'x', 'u', '8', '!', '=', '0', '1', 'a', 'λ', ' ', '\n', '\t', '/',
+    ];
+    let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+    let mut next_random = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    for _ in 0..2000 {
+        let len = (next_random() % 24) as usize;
+        let input: String = (0..len)
+            .map(|_| ALPHABET[(next_random() % ALPHABET.len() as u64) as usize])
+            .collect();
+
+        let lexer = Lexer::from(input.as_str());
+        let count = lexer.tokens().take(100).count();
+        assert!(count <= input.chars().count(), "{input:?}");
+
+        // every character is covered by at most one token or error.
+        let mut last_end = None;
+        for result in lexer.tokens_with_recovery().take(100) {
+            let start = match &result {
+                Ok(token) => token.start(),
+                Err(e) => e.span().unwrap().start(),
+            };
+            if let Some(last_end) = last_end {
+                assert!(start >= last_end, "{input:?}: {result:?}");
+            }
+            last_end = Some(start + 1);
+        }
+    }
+}
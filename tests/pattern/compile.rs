@@ -0,0 +1,91 @@
+use ffsr::error::Error;
+use ffsr::pattern::{Pattern, PatternCompiler};
+use pretty_assertions::assert_eq;
+use std::str::FromStr;
+
+fn compile_error(pattern: &str) -> String {
+    match Pattern::from_str(pattern).unwrap_err() {
+        Error::InvalidPattern { message, span } => {
+            assert!(span.is_some());
+            message
+        }
+        e => panic!("unexpected error {e:?}"),
+    }
+}
+
+#[test]
+fn variables_with_depth() {
+    let _guard = crate::init_tracing();
+
+    let pattern = Pattern::from_str("(_ (name value ...) ... body)").unwrap();
+    assert_eq!(
+        pattern.variables().collect::<Vec<_>>(),
+        vec![("name", 1), ("value", 2), ("body", 0)]
+    );
+}
+
+#[test]
+fn literals_are_not_variables() {
+    let _guard = crate::init_tracing();
+
+    let pattern = PatternCompiler::default()
+        .with_literals(["else", "=>"])
+        .compile_str("(cond (else e) (test => f))")
+        .unwrap();
+    assert_eq!(
+        pattern.variables().collect::<Vec<_>>(),
+        vec![("cond", 0), ("e", 0), ("test", 0), ("f", 0)]
+    );
+}
+
+#[test]
+fn compile_from_datum() {
+    let _guard = crate::init_tracing();
+
+    let datum = super::read_one("(a b ...)");
+    let pattern = Pattern::try_from(&datum).unwrap();
+    assert_eq!(pattern.variables().count(), 2);
+}
+
+#[test]
+fn duplicate_variable() {
+    let _guard = crate::init_tracing();
+
+    assert_eq!(
+        compile_error("(a b a)"),
+        "the pattern variable `a` appears more than once"
+    );
+}
+
+#[test]
+fn misplaced_ellipsis() {
+    let _guard = crate::init_tracing();
+
+    assert_eq!(
+        compile_error("(... a)"),
+        "an ellipsis must follow a pattern within a list or vector"
+    );
+    assert_eq!(
+        compile_error("..."),
+        "an ellipsis must follow a pattern within a list or vector"
+    );
+    assert_eq!(
+        compile_error("(a ... b ...)"),
+        "there may only be one ellipsis in a list or vector"
+    );
+    assert_eq!(
+        compile_error("(a . ...)"),
+        "an ellipsis cannot be the tail of a list"
+    );
+}
+
+#[test]
+fn error_has_pattern_span() {
+    let _guard = crate::init_tracing();
+
+    let error = Pattern::from_str("  (a a)").unwrap_err();
+    assert_eq!(error.span().unwrap().as_range(), 2..7);
+
+    let datum = super::read_one("(a a)");
+    assert!(Pattern::try_from(&datum).unwrap_err().span().is_none());
+}
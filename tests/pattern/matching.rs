@@ -0,0 +1,200 @@
+use super::{bound, match_str, match_with_literals, read_one};
+use ffsr::pattern::{Binding, PatternCompiler};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn variables_and_underscore() {
+    let _guard = crate::init_tracing();
+
+    let bindings = match_str("(_ a (b c))", "(f 1 (\"two\" #\\3))").unwrap();
+    assert_eq!(bindings.len(), 3);
+    assert_eq!(bound(&bindings, "a"), "1");
+    assert_eq!(bound(&bindings, "b"), "two");
+    assert_eq!(bindings.datum("c").unwrap().to_string(), "#\\3");
+    assert!(bindings.get("_").is_none());
+}
+
+#[test]
+fn list_lengths_must_agree() {
+    let _guard = crate::init_tracing();
+
+    assert!(match_str("(a b)", "(1 2 3)").is_none());
+    assert!(match_str("(a b)", "(1)").is_none());
+    assert!(match_str("(a b)", "(1 . 2)").is_none());
+    assert!(match_str("()", "()").is_some());
+    assert!(match_str("()", "(1)").is_none());
+}
+
+#[test]
+fn constants_and_literals() {
+    let _guard = crate::init_tracing();
+
+    assert!(match_str("(1 \"a\" #t x)", "(1 \"a\" #t 2)").is_some());
+    assert!(match_str("(1 x)", "(2 2)").is_none());
+
+    assert!(match_with_literals(&["else"], "(else e)", "(else 1)").is_some());
+    assert!(match_with_literals(&["else"], "(else e)", "(other 1)").is_none());
+    assert!(match_with_literals(&["else"], "(else e)", "(\"else\" 1)").is_none());
+}
+
+#[test]
+fn dotted_tail() {
+    let _guard = crate::init_tracing();
+
+    let bindings = match_str("(define (name . args) body)", "(define (f x y) x)").unwrap();
+    assert_eq!(bound(&bindings, "name"), "f");
+    assert_eq!(bound(&bindings, "args"), "(x y)");
+
+    let bindings = match_str("(a . b)", "(1 . 2)").unwrap();
+    assert_eq!(bound(&bindings, "b"), "2");
+
+    let bindings = match_str("(a . b)", "(1)").unwrap();
+    assert_eq!(bound(&bindings, "b"), "()");
+
+    assert!(match_str("(a . b)", "()").is_none());
+    assert!(match_str("(a b . c)", "(1)").is_none());
+}
+
+#[test]
+fn ellipsis_binds_each_repetition() {
+    let _guard = crate::init_tracing();
+
+    let bindings = match_str("(_ x ...)", "(f 1 2 3)").unwrap();
+    assert_eq!(bound(&bindings, "x"), "[1 2 3]");
+    assert_eq!(
+        bindings
+            .get("x")
+            .unwrap()
+            .datums()
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>(),
+        vec!["1", "2", "3"]
+    );
+
+    let bindings = match_str("(_ x ...)", "(f)").unwrap();
+    assert_eq!(bindings.get("x"), Some(&Binding::Many(Vec::new())));
+}
+
+#[test]
+fn ellipsis_with_following_patterns() {
+    let _guard = crate::init_tracing();
+
+    let bindings = match_str("(_ a ... b c)", "(f 1 2 3 4)").unwrap();
+    assert_eq!(bound(&bindings, "a"), "[1 2]");
+    assert_eq!(bound(&bindings, "b"), "3");
+    assert_eq!(bound(&bindings, "c"), "4");
+
+    let bindings = match_str("(_ a ... b c)", "(f 3 4)").unwrap();
+    assert_eq!(bound(&bindings, "a"), "[]");
+
+    assert!(match_str("(_ a ... b c)", "(f 4)").is_none());
+}
+
+#[test]
+fn nested_ellipsis() {
+    let _guard = crate::init_tracing();
+
+    let bindings = match_str(
+        "(let ((name value) ...) body ...)",
+        "(let ((a 1) (b 2)) (display a) b)",
+    )
+    .unwrap();
+    assert_eq!(bound(&bindings, "name"), "[a b]");
+    assert_eq!(bound(&bindings, "value"), "[1 2]");
+    assert_eq!(bound(&bindings, "body"), "[(display a) b]");
+
+    let bindings = match_str("(_ (a b ...) ...)", "(f (1 2 3) (4) (5 6))").unwrap();
+    assert_eq!(bound(&bindings, "a"), "[1 4 5]");
+    assert_eq!(bound(&bindings, "b"), "[[2 3] [] [6]]");
+
+    assert!(match_str("(_ (a b) ...)", "(f (1 2) (3))").is_none());
+}
+
+#[test]
+fn ellipsis_with_dotted_tail() {
+    let _guard = crate::init_tracing();
+
+    let bindings = match_str("(a ... . r)", "(1 2 . 3)").unwrap();
+    assert_eq!(bound(&bindings, "a"), "[1 2]");
+    assert_eq!(bound(&bindings, "r"), "3");
+
+    let bindings = match_str("(a ... . r)", "(1 2)").unwrap();
+    assert_eq!(bound(&bindings, "a"), "[1 2]");
+    assert_eq!(bound(&bindings, "r"), "()");
+}
+
+#[test]
+fn vector_patterns() {
+    let _guard = crate::init_tracing();
+
+    let bindings = match_str("#(a b ... c)", "#(1 2 3 4)").unwrap();
+    assert_eq!(bound(&bindings, "a"), "1");
+    assert_eq!(bound(&bindings, "b"), "[2 3]");
+    assert_eq!(bound(&bindings, "c"), "4");
+
+    assert!(match_str("#(a b)", "(1 2)").is_none());
+    assert!(match_str("(a b)", "#(1 2)").is_none());
+    assert!(match_str("#()", "#()").is_some());
+}
+
+#[test]
+fn quoted_patterns() {
+    let _guard = crate::init_tracing();
+
+    let bindings = match_str("(f 'x)", "(f 'sym)").unwrap();
+    assert_eq!(bound(&bindings, "x"), "sym");
+    assert!(match_str("(f 'x)", "(f sym)").is_none());
+    assert!(match_str("(f 'x)", "(f `sym)").is_none());
+}
+
+#[test]
+fn custom_ellipsis() {
+    let _guard = crate::init_tracing();
+
+    let pattern = PatternCompiler::default()
+        .with_ellipsis(":::")
+        .compile_str("(_ x ::: ...)")
+        .unwrap();
+    let bindings = pattern.matches(&read_one("(f 1 2 end)")).unwrap();
+    assert_eq!(bound(&bindings, "x"), "[1 2]");
+    assert_eq!(bound(&bindings, "..."), "end");
+}
+
+#[test]
+fn ellipsis_as_literal() {
+    let _guard = crate::init_tracing();
+
+    let bindings = match_with_literals(&["..."], "(a ...)", "(1 ...)").unwrap();
+    assert_eq!(bound(&bindings, "a"), "1");
+    assert!(match_with_literals(&["..."], "(a ...)", "(1 2)").is_none());
+}
+
+#[test]
+fn shared_datum_matched_by_content() {
+    let _guard = crate::init_tracing();
+
+    let bindings = match_str("(a (b))", "(#0=(1) #0#)").unwrap();
+    assert_eq!(bound(&bindings, "a"), "(1)");
+    assert_eq!(bound(&bindings, "b"), "1");
+    assert!(bindings.datum("a").unwrap().is_shared());
+    assert!(match_str("(a (b c))", "(#0=(1) #0#)").is_none());
+
+    let bindings = match_str("(x . rest)", "#0=(1 2 . #0#)").unwrap();
+    assert_eq!(bound(&bindings, "x"), "1");
+    let rest = bindings
+        .datum("rest")
+        .unwrap()
+        .as_list()
+        .unwrap()
+        .as_pair()
+        .unwrap();
+    assert_eq!(rest.car().to_string(), "2");
+    assert!(rest.cdr().is_shared());
+    assert!(match_str("(x y z w)", "#0=(1 2 . #0#)").is_none());
+    assert!(match_str("(x ...)", "#0=(1 2 . #0#)").is_none());
+
+    let bindings = match_str("(x ... . tail)", "#0=(1 2 . #0#)").unwrap();
+    assert!(bindings.datum("tail").unwrap().is_shared());
+    assert_eq!(bound(&bindings, "x"), "[1 2 1 2]");
+}
@@ -0,0 +1,24 @@
+use crate::reader::read_one;
+use ffsr::pattern::{Bindings, Pattern, PatternCompiler};
+use std::str::FromStr;
+
+fn match_str(pattern: &str, input: &str) -> Option<Bindings> {
+    Pattern::from_str(pattern)
+        .unwrap()
+        .matches(&read_one(input))
+}
+
+fn match_with_literals(literals: &[&str], pattern: &str, input: &str) -> Option<Bindings> {
+    PatternCompiler::default()
+        .with_literals(literals.iter().copied())
+        .compile_str(pattern)
+        .unwrap()
+        .matches(&read_one(input))
+}
+
+fn bound(bindings: &Bindings, name: &str) -> String {
+    bindings.get(name).unwrap().to_string()
+}
+
+pub mod compile;
+pub mod matching;
@@ -0,0 +1,24 @@
+use crate::reader::read_all;
+use ffsr::query::Query;
+use ffsr::reader::syntax::SyntaxDatum;
+use std::str::FromStr;
+
+fn read_syntax(input: &str) -> Vec<SyntaxDatum> {
+    let reader = ::ffsr::reader::Reader::from(::ffsr::lexer::Lexer::from(input));
+    reader
+        .syntax_iter()
+        .collect::<Result<_, _>>()
+        .expect("datum parsing fail")
+}
+
+fn select_str(query: &str, input: &str) -> Vec<String> {
+    Query::from_str(query)
+        .unwrap()
+        .select(&read_all(input))
+        .into_iter()
+        .map(|datum| ::ffsr::writer::write(datum).to_string())
+        .collect()
+}
+
+pub mod parse;
+pub mod select;
@@ -0,0 +1,55 @@
+use ffsr::error::Error;
+use ffsr::query::Query;
+use pretty_assertions::assert_eq;
+use std::str::FromStr;
+
+fn error_span(query: &str) -> std::ops::Range<usize> {
+    match Query::from_str(query) {
+        Err(e @ Error::InvalidQuery { .. }) => e.span().unwrap().as_range(),
+        result => panic!("expecting an invalid query, found {result:?}"),
+    }
+}
+
+#[test]
+fn display_round_trips() {
+    let _guard = crate::init_tracing();
+
+    for (query, displayed) in [
+        ("define", "/define"),
+        ("/define/*", "/define/*"),
+        ("//lambda/1[pair-or-list]", "//lambda/1[pair-or-list]"),
+        ("a/-1[fixnum][ integer ]", "/a/-1[fixnum][integer]"),
+        ("|a/b|/|12|/||", "/|a/b|/|12|/||"),
+    ] {
+        let parsed = Query::from_str(query).unwrap();
+        assert_eq!(parsed.to_string(), displayed);
+        assert_eq!(Query::from_str(displayed).unwrap(), parsed);
+    }
+}
+
+#[test]
+fn invalid_queries() {
+    let _guard = crate::init_tracing();
+
+    assert_eq!(error_span(""), 0..0);
+    assert_eq!(error_span("a/"), 2..2);
+    assert_eq!(error_span("a//"), 3..3);
+    assert_eq!(error_span("a/[string]"), 2..3);
+    assert_eq!(error_span("a[string"), 8..8);
+    assert_eq!(error_span("a[]"), 1..3);
+    assert_eq!(error_span("|a/b"), 0..4);
+    assert_eq!(error_span("a|b|"), 1..2);
+    assert_eq!(error_span("a]"), 1..2);
+    assert_eq!(error_span("a/99999999999999999999999"), 2..25);
+}
+
+#[test]
+fn error_message() {
+    let _guard = crate::init_tracing();
+
+    let error = Query::from_str("a/").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Invalid query, expecting a name, an index, or `*`; span: 2..2"
+    );
+}
@@ -0,0 +1,125 @@
+use super::{read_syntax, select_str};
+use ffsr::input::lines::LineIndex;
+use ffsr::query::Query;
+use pretty_assertions::assert_eq;
+use std::str::FromStr;
+
+#[test]
+fn select_by_head_symbol() {
+    let _guard = crate::init_tracing();
+
+    let source = "(define x 1) (define (f) 2) (set! x 3) (|define| y)";
+    assert_eq!(
+        select_str("define", source),
+        vec!["(define x 1)", "(define (f) 2)", "(define y)"]
+    );
+    assert_eq!(select_str("/set!/2", source), vec!["3"]);
+    assert_eq!(select_str("lambda", source), Vec::<String>::new());
+}
+
+#[test]
+fn select_by_index() {
+    let _guard = crate::init_tracing();
+
+    let source = "(a b c) #(d e) (f . g)";
+    assert_eq!(select_str("0", source), vec!["(a b c)"]);
+    assert_eq!(select_str("-1", source), vec!["(f . g)"]);
+    assert_eq!(select_str("*/0", source), vec!["a", "d", "f"]);
+    assert_eq!(select_str("*/-1", source), vec!["c", "e", "f"]);
+    assert_eq!(select_str("*/2", source), vec!["c"]);
+    assert_eq!(select_str("*/-4", source), Vec::<String>::new());
+}
+
+#[test]
+fn select_quoted_datum() {
+    let _guard = crate::init_tracing();
+
+    let source = "(list '(1 2) `#(3 ,x))";
+    assert_eq!(select_str("list/1/0/1", source), vec!["2"]);
+    assert_eq!(select_str("list/2/0/1/0", source), vec!["x"]);
+}
+
+#[test]
+fn select_descendants() {
+    let _guard = crate::init_tracing();
+
+    let source = "(define (f x) (let ((y (g x))) (g y))) (g 1)";
+    assert_eq!(select_str("//g", source), vec!["(g x)", "(g y)", "(g 1)"]);
+    assert_eq!(select_str("define//x", source), Vec::<String>::new());
+    assert_eq!(
+        select_str("define//*[identifier]", source),
+        vec!["define", "f", "x", "let", "y", "g", "x", "g", "y"]
+    );
+    assert_eq!(
+        select_str("//let//0", source),
+        vec!["let", "(y (g x))", "y", "g", "g"]
+    );
+}
+
+#[test]
+fn selected_once() {
+    let _guard = crate::init_tracing();
+
+    assert_eq!(select_str("//a//*[fixnum]", "(a (a 1) 2)"), vec!["1", "2"]);
+    assert_eq!(select_str("//a", "(a (a (a)))").len(), 3);
+}
+
+#[test]
+fn select_within_shared() {
+    let _guard = crate::init_tracing();
+
+    let source = "(#0=(f x) #0#) #1=(g (h) . #1#)";
+    assert_eq!(select_str("*/f", source), vec!["(f x)", "(f x)"]);
+    assert_eq!(select_str("*/*/1", source), vec!["x"]);
+    assert_eq!(
+        select_str("//*[identifier]", source),
+        vec!["f", "x", "g", "h"]
+    );
+    assert_eq!(select_str("g/1/0", source), vec!["h"]);
+    assert_eq!(select_str("//h", source), vec!["(h)"]);
+}
+
+#[test]
+fn filter_by_type() {
+    let _guard = crate::init_tracing();
+
+    let source = "(v 1 2.5 \"s\" #\\c (x) #(1) #u8(1) 'q)";
+    assert_eq!(select_str("v/*[fixnum]", source), vec!["1"]);
+    assert_eq!(select_str("v/*[flonum]", source), vec!["2.5"]);
+    assert_eq!(select_str("v/*[string]", source), vec!["\"s\""]);
+    assert_eq!(select_str("v/*[char]", source), vec!["#\\c"]);
+    assert_eq!(select_str("v/*[pair-or-list]", source), vec!["(x)"]);
+    assert_eq!(select_str("v/*[vector]", source), vec!["#(1)"]);
+    assert_eq!(select_str("v/*[byte-vector]", source), vec!["#u8(1)"]);
+    assert_eq!(select_str("v/*[quote]", source), vec!["'q"]);
+    assert_eq!(
+        select_str("v/*[fixnum][string]", source),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn select_syntax_with_spans() {
+    let _guard = crate::init_tracing();
+
+    let source = "(define-record-type point\n  (make-point x y)\n  point?\n  (x point-x))";
+    let forms = read_syntax(source);
+    let lines = LineIndex::new(source);
+
+    let query = Query::from_str("define-record-type/-1/1[identifier]").unwrap();
+    let selected = query.select(&forms);
+    assert_eq!(selected.len(), 1);
+    assert_eq!(selected[0].to_datum().to_string(), "point-x");
+    assert_eq!(selected[0].span().as_range(), 59..66);
+    assert_eq!(selected[0].start_position(&lines).to_string(), "4:6");
+
+    let query = Query::from_str("//make-point/*").unwrap();
+    let spans: Vec<_> = query
+        .select(&forms)
+        .into_iter()
+        .map(|datum| datum.span().as_range())
+        .collect();
+    assert_eq!(spans, vec![29..39, 40..41, 42..43]);
+
+    assert!(query.select_first(&forms[..0]).is_none());
+}
@@ -0,0 +1,133 @@
+use ffsr::error::Error;
+use ffsr::lexer::Lexer;
+use ffsr::reader::arena::{Arena, ArenaDatum};
+use ffsr::reader::datum::Datum;
+use ffsr::reader::Reader;
+
+fn read_datums(reader: &Reader<'_>) -> Vec<Result<Datum, Error>> {
+    let arena = Arena::new();
+    reader
+        .arena_iter(&arena)
+        .map(|datum| datum.map(|datum| datum.to_datum()))
+        .collect()
+}
+
+#[test]
+fn nodes_live_in_the_arena() {
+    let _guard = crate::init_tracing();
+
+    let arena = Arena::new();
+    assert_eq!(arena.allocated_bytes(), 0);
+    let reader = Reader::from(Lexer::from(r#"(a "b\x43;" 'c #(1 #\d) #u8(0 255) . e)"#));
+    let datum = reader.arena_iter(&arena).next().unwrap().unwrap();
+    assert!(arena.allocated_bytes() > 0);
+
+    let ArenaDatum::List(elements, Some(tail)) = datum else {
+        panic!("expecting an improper list, not {datum:?}");
+    };
+    assert_eq!(elements.len(), 5);
+    assert_eq!(elements[0].as_identifier(), Some("a"));
+    assert_eq!(elements[1].as_string(), Some("bC"));
+    assert!(matches!(
+        elements[2],
+        ArenaDatum::Quote(ArenaDatum::Identifier(_))
+    ));
+    assert_eq!(elements[3].elements().map(<[_]>::len), Some(2));
+    assert_eq!(elements[4], ArenaDatum::ByteVector(&[0, 255]));
+    assert_eq!(tail.type_string(), "identifier");
+    assert_eq!(
+        datum.to_datum().to_string(),
+        "(a bC 'c #(1 #\\d) #u8(0 255) . e)"
+    );
+}
+
+#[test]
+fn labels_share_nodes() {
+    let _guard = crate::init_tracing();
+
+    let arena = Arena::new();
+    let reader = Reader::from(Lexer::from("#0=(a b) (#0# #0#)"));
+    let data: Vec<_> = reader.arena_iter(&arena).map(Result::unwrap).collect();
+    let labelled = data[0].elements().unwrap();
+    let references = data[1].elements().unwrap();
+    assert!(std::ptr::eq(labelled, references[0].elements().unwrap()));
+    assert!(std::ptr::eq(labelled, references[1].elements().unwrap()));
+}
+
+#[test]
+fn circular_labels() {
+    let _guard = crate::init_tracing();
+
+    let arena = Arena::new();
+    let reader = Reader::from(Lexer::from("#0=(a . #0#) #1=(a b #1#) #2=#(x '#2#)"));
+    let data: Vec<_> = reader.arena_iter(&arena).map(Result::unwrap).collect();
+    let written: Vec<String> = data.iter().map(|d| d.to_datum().to_string()).collect();
+    assert_eq!(
+        written,
+        vec!["#0=(a . #0#)", "#1=(a b #1#)", "#2=#(x '#2#)"]
+    );
+
+    let ArenaDatum::Shared(shared) = data[0] else {
+        panic!("expecting a shared datum, not {:?}", data[0]);
+    };
+    assert_eq!(shared.label(), 0);
+    let Some(ArenaDatum::List(_, Some(ArenaDatum::Shared(tail)))) = shared.get() else {
+        panic!("expecting a circular list, not {:?}", shared.get());
+    };
+    assert!(std::ptr::eq(shared, *tail));
+    assert_eq!(data[1].elements().map(<[_]>::len), Some(3));
+    assert_eq!(data[1], data[1].clone());
+}
+
+#[test]
+fn fold_case() {
+    let _guard = crate::init_tracing();
+
+    let arena = Arena::new();
+    let reader = Reader::from(Lexer::from("(Hello #!fold-case Hello |Hello| #\\X)"));
+    let datum = reader.arena_iter(&arena).next().unwrap().unwrap();
+    assert_eq!(
+        datum.to_datum().to_string(),
+        "(Hello fold-case hello Hello #\\X)"
+    );
+
+    let reader = Reader::from(Lexer::from("(Hello)"));
+    let datum = reader
+        .arena_iter(&arena)
+        .with_fold_case(true)
+        .next()
+        .unwrap()
+        .unwrap();
+    assert_eq!(datum.to_datum().to_string(), "(hello)");
+}
+
+#[test]
+fn errors_and_labels() {
+    let _guard = crate::init_tracing();
+
+    super::assert_matches_reader_cases(read_datums);
+}
+
+#[test]
+fn reset_frees_everything() {
+    let _guard = crate::init_tracing();
+
+    let mut arena = Arena::with_capacity(1024);
+    let source = "(define (f x) (g x))".repeat(1000);
+    let count = {
+        let reader = Reader::from(Lexer::from(source.as_str()));
+        reader.arena_iter(&arena).filter(Result::is_ok).count()
+    };
+    assert_eq!(count, 1000);
+    let allocated = arena.allocated_bytes();
+    assert!(allocated > 1024);
+    arena.reset();
+    assert!(arena.allocated_bytes() <= allocated);
+}
+
+#[test]
+fn preludes() {
+    let _guard = crate::init_tracing();
+
+    super::assert_matches_reader_files(read_datums);
+}
@@ -0,0 +1,137 @@
+use ffsr::error::Error;
+use ffsr::lexer::Lexer;
+use ffsr::reader::borrowed::BorrowedDatum;
+use ffsr::reader::datum::Datum;
+use ffsr::reader::Reader;
+use std::borrow::Cow;
+
+fn read_borrowed(source: &str) -> Vec<BorrowedDatum<'static>> {
+    let reader = Reader::from(Lexer::from(source));
+    reader
+        .borrowed_iter()
+        .map(|datum| datum.expect("datum reading fail").into_owned())
+        .collect()
+}
+
+fn read_datums(reader: &Reader<'_>) -> Vec<Result<Datum, Error>> {
+    reader
+        .borrowed_iter()
+        .map(|datum| datum.map(Datum::from))
+        .collect()
+}
+
+fn borrowed_flags(datum: &BorrowedDatum<'_>) -> Vec<bool> {
+    datum
+        .elements()
+        .unwrap()
+        .iter()
+        .map(BorrowedDatum::is_borrowed)
+        .collect()
+}
+
+#[test]
+fn unescaped_text_is_borrowed() {
+    let _guard = crate::init_tracing();
+
+    let source = String::from(r#"(car "a string" |with bars| |a b| "" ||)"#);
+    let reader = Reader::from(Lexer::from(source.as_str()));
+    let datum = reader.borrowed_iter().next().unwrap().unwrap();
+    assert_eq!(
+        borrowed_flags(&datum),
+        vec![true, true, true, true, true, true]
+    );
+
+    let elements = datum.elements().unwrap();
+    assert_eq!(elements[0].as_identifier(), Some("car"));
+    assert_eq!(elements[1].as_string(), Some("a string"));
+    assert_eq!(elements[2].as_identifier(), Some("|with bars|"));
+    assert_eq!(elements[3].as_identifier(), Some("|a b|"));
+    assert_eq!(elements[4].as_string(), Some(""));
+    assert_eq!(elements[5].as_identifier(), Some("||"));
+
+    let text = elements[1].as_string().unwrap();
+    assert!(source.as_bytes().as_ptr_range().contains(&text.as_ptr()));
+}
+
+#[test]
+fn escaped_text_is_owned() {
+    let _guard = crate::init_tracing();
+
+    let datum = &read_borrowed(r#"("a\x42;c" "line\n" |\x41;| |a\|b| "a\\b" |abc|)"#)[0];
+    let elements = datum.elements().unwrap();
+    assert_eq!(elements[0], BorrowedDatum::String(Cow::Borrowed("aBc")));
+    assert_eq!(elements[1].as_string(), Some("line\n"));
+    assert_eq!(elements[2].as_identifier(), Some("|A|"));
+    assert_eq!(elements[3].as_identifier(), Some("|a|b|"));
+    assert_eq!(elements[4].as_string(), Some("a\\b"));
+    assert_eq!(elements[5].as_identifier(), Some("abc"));
+
+    let reader = Reader::from(Lexer::from(r#"("a\x42;c" |\x41;| |abc|)"#));
+    let datum = reader.borrowed_iter().next().unwrap().unwrap();
+    assert_eq!(borrowed_flags(&datum), vec![false, false, true]);
+}
+
+#[test]
+fn fold_case_owns_changed_identifiers() {
+    let _guard = crate::init_tracing();
+
+    let reader = Reader::from(Lexer::from("#!fold-case (Hello world |World|)"));
+    let data: Vec<_> = reader.borrowed_iter().map(Result::unwrap).collect();
+    assert_eq!(data.len(), 2);
+    assert_eq!(borrowed_flags(&data[1]), vec![false, true, true]);
+    assert_eq!(
+        Datum::from(data[1].clone()).to_string(),
+        "(hello world World)"
+    );
+}
+
+#[test]
+fn into_owned_detaches() {
+    let _guard = crate::init_tracing();
+
+    let data = read_borrowed("(define (f x) `(,x . \"x\") #(1 #\\a) #u8(1 2)) #0=(a) #0#");
+    assert!(data.iter().all(|datum| !datum.is_borrowed()));
+    let written: Vec<String> = data
+        .into_iter()
+        .map(|datum| Datum::from(datum).to_string())
+        .collect();
+    assert_eq!(
+        written,
+        vec!["(define (f x) `(,x . x) #(1 #\\a) #u8(1 2))", "(a)", "(a)"]
+    );
+}
+
+#[test]
+fn circular_labels() {
+    let _guard = crate::init_tracing();
+
+    let reader = Reader::from(Lexer::from("#0=(a . #0#) #1=(a b #1#) (#2=(c) #2# |d|)"));
+    let data: Vec<_> = reader.borrowed_iter().map(Result::unwrap).collect();
+    let written: Vec<String> = data.iter().map(|d| d.to_datum().to_string()).collect();
+    assert_eq!(written, vec!["#0=(a . #0#)", "#1=(a b #1#)", "((c) (c) d)"]);
+    assert!(matches!(data[0], BorrowedDatum::Shared(_)));
+    assert_eq!(data[0].type_string(), "pair-or-list");
+
+    let elements = data[2].elements().unwrap();
+    let (BorrowedDatum::Shared(first), BorrowedDatum::Shared(second)) =
+        (&elements[0], &elements[1])
+    else {
+        panic!("expecting shared elements, not {elements:?}");
+    };
+    assert!(first.ptr_eq(second));
+    assert!(elements[2].is_borrowed());
+}
+
+#[test]
+fn errors_and_labels() {
+    let _guard = crate::init_tracing();
+
+    super::assert_matches_reader_cases(read_datums);
+}
+
+#[test]
+fn preludes() {
+    let _guard = crate::init_tracing();
+
+    super::assert_matches_reader_files(read_datums);
+}
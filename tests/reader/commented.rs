@@ -0,0 +1,221 @@
+use ffsr::lexer::Lexer;
+use ffsr::reader::comments::{CommentStyle, CommentedTree};
+use ffsr::reader::cst::{Node, SyntaxTree};
+use pretty_assertions::assert_eq;
+
+fn parse(source: &str) -> SyntaxTree {
+    SyntaxTree::parse(Lexer::from(source).tokens()).unwrap()
+}
+
+fn texts(comments: &[ffsr::reader::comments::Comment]) -> Vec<&str> {
+    comments.iter().map(|comment| comment.text()).collect()
+}
+
+#[test]
+fn leading_run_documents_form() {
+    let _guard = crate::init_tracing();
+
+    let tree = parse(";; old\n\n;; first\n;; second\n(define x 1)");
+    let commented = CommentedTree::from(&tree);
+
+    assert_eq!(commented.forms().len(), 1);
+    let define = &commented.forms()[0];
+    assert_eq!(texts(define.leading()), vec!["first", "second"]);
+    assert_eq!(define.documentation(), Some("first\nsecond".to_string()));
+    assert_eq!(texts(commented.detached()), vec!["old"]);
+}
+
+#[test]
+fn empty_line_detaches_comments() {
+    let _guard = crate::init_tracing();
+
+    let tree = parse(";; about the file\n\n(define x 1)");
+    let commented = CommentedTree::from(&tree);
+
+    assert!(commented.forms()[0].leading().is_empty());
+    assert_eq!(commented.forms()[0].documentation(), None);
+    assert_eq!(texts(commented.detached()), vec!["about the file"]);
+}
+
+#[test]
+fn trailing_and_leading_separated_by_line() {
+    let _guard = crate::init_tracing();
+
+    let tree = parse("(a) ; after a\n; before b\n(b) #| also b |# ; and b");
+    let commented = CommentedTree::from(&tree);
+
+    let forms = commented.forms();
+    assert_eq!(texts(forms[0].trailing()), vec!["after a"]);
+    assert_eq!(texts(forms[1].leading()), vec!["before b"]);
+    assert_eq!(texts(forms[1].trailing()), vec!["also b", "and b"]);
+    assert!(commented.detached().is_empty());
+}
+
+#[test]
+fn comment_styles() {
+    let _guard = crate::init_tracing();
+
+    let tree = parse("; one\n;; two\n;;; three\n;;;; four\n#| block |#\nx");
+    let commented = CommentedTree::from(&tree);
+
+    let styles: Vec<CommentStyle> = commented.forms()[0]
+        .leading()
+        .iter()
+        .map(|comment| comment.style())
+        .collect();
+    assert_eq!(
+        styles,
+        vec![
+            CommentStyle::Single,
+            CommentStyle::Double,
+            CommentStyle::Triple,
+            CommentStyle::Triple,
+            CommentStyle::Block,
+        ]
+    );
+    assert_eq!(
+        texts(commented.forms()[0].leading()),
+        vec!["one", "two", "three", "four", "block"]
+    );
+    assert_eq!(
+        commented.forms()[0]
+            .leading_with_style(CommentStyle::Triple)
+            .count(),
+        2
+    );
+}
+
+#[test]
+fn markers_stripped_consistently() {
+    let _guard = crate::init_tracing();
+
+    let tree = parse(";;   indented\n;;no space\n;;\n;; trailing space   \nx");
+    let commented = CommentedTree::from(&tree);
+
+    assert_eq!(
+        texts(commented.forms()[0].leading()),
+        vec!["  indented", "no space", "", "trailing space"]
+    );
+}
+
+#[test]
+fn block_comment_dedented() {
+    let _guard = crate::init_tracing();
+
+    let tree = parse(
+        "#|\n    Squares a number.\n\n      (square 2) => 4\n  |#\n(define (square x) (* x x))",
+    );
+    let commented = CommentedTree::from(&tree);
+
+    assert_eq!(
+        commented.forms()[0].documentation().unwrap(),
+        "Squares a number.\n\n  (square 2) => 4"
+    );
+}
+
+#[test]
+fn nested_forms_documented() {
+    let _guard = crate::init_tracing();
+
+    let tree = parse(
+        "(define-library (lib)
+  ;; the exports
+  (export f)
+  (begin
+    ;; f returns one
+    (define (f) 1) ; always
+    ))",
+    );
+    let commented = CommentedTree::from(&tree);
+
+    let documented = commented.documented();
+    assert_eq!(documented.len(), 2);
+    assert_eq!(documented[0].node().text(), "(export f)");
+    assert_eq!(documented[1].node().text(), "(define (f) 1)");
+    assert_eq!(documented[1].documentation().unwrap(), "f returns one");
+    assert_eq!(texts(documented[1].trailing()), vec!["always"]);
+    assert_eq!(
+        documented[1].datum().unwrap().unwrap().to_string(),
+        "(define (f) 1)"
+    );
+}
+
+#[test]
+fn first_element_comment() {
+    let _guard = crate::init_tracing();
+
+    let tree = parse("(; about a\n a ; after a\n ; before close\n)");
+    let commented = CommentedTree::from(&tree);
+
+    let a = &commented.forms()[0].children()[0];
+    assert_eq!(texts(a.leading()), vec!["about a"]);
+    assert_eq!(texts(a.trailing()), vec!["after a"]);
+    assert_eq!(texts(commented.detached()), vec!["before close"]);
+}
+
+#[test]
+fn datum_comments_detached() {
+    let _guard = crate::init_tracing();
+
+    let tree = parse(";; old code\n#; (a ; inside\n b) ; after\n(c)");
+    let commented = CommentedTree::from(&tree);
+
+    assert_eq!(commented.forms().len(), 1);
+    assert_eq!(commented.forms()[0].node().text(), "(c)");
+    assert!(commented.forms()[0].leading().is_empty());
+    assert_eq!(
+        texts(commented.detached()),
+        vec!["old code", "inside", "after"]
+    );
+}
+
+#[test]
+fn trailing_at_end_of_source() {
+    let _guard = crate::init_tracing();
+
+    let tree = parse("(a) ; the end\n; really\n");
+    let commented = CommentedTree::from(&tree);
+
+    assert_eq!(texts(commented.forms()[0].trailing()), vec!["the end"]);
+    assert_eq!(texts(commented.detached()), vec!["really"]);
+}
+
+#[test]
+fn every_comment_attached_once() {
+    let _guard = crate::init_tracing();
+
+    fn count_attached(commented: &[ffsr::reader::comments::Commented<'_>]) -> usize {
+        commented
+            .iter()
+            .map(|c| c.leading().len() + c.trailing().len() + count_attached(c.children()))
+            .sum()
+    }
+    fn count_trivia(nodes: &[Node]) -> usize {
+        nodes
+            .iter()
+            .map(|node| {
+                let close = match node.kind() {
+                    ffsr::reader::cst::NodeKind::Delimited { close_leading, .. } => {
+                        close_leading.iter().filter(|t| t.is_comment()).count()
+                    }
+                    _ => 0,
+                };
+                node.leading().iter().filter(|t| t.is_comment()).count()
+                    + close
+                    + count_trivia(node.children())
+            })
+            .sum()
+    }
+
+    let source = std::fs::read_to_string("tests/files/LispKit/Prelude.scm").unwrap();
+    let tree = parse(&source);
+    let commented = CommentedTree::from(&tree);
+
+    let total =
+        count_trivia(tree.nodes()) + tree.trailing().iter().filter(|t| t.is_comment()).count();
+    assert!(total > 0);
+    assert_eq!(
+        count_attached(commented.forms()) + commented.detached().len(),
+        total
+    );
+}
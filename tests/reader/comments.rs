@@ -1,4 +1,5 @@
-use ffsr::reader::datum::SBoolean;
+use ffsr::lexer::token::Span;
+use ffsr::reader::datum::{Datum, SBoolean, SComment};
 
 // ------------------------------------------------------------------------------------------------
 // Single-valued success cases
@@ -51,3 +52,107 @@ success_case!(
 // ------------------------------------------------------------------------------------------------
 
 failure_case!(incomplete, "#;");
+
+// ------------------------------------------------------------------------------------------------
+// Returned comments
+// ------------------------------------------------------------------------------------------------
+
+fn read_with_comments(source: &str) -> Vec<String> {
+    let reader = ::ffsr::reader::Reader::from(::ffsr::lexer::Lexer::from(source));
+    reader
+        .iter_with_comments()
+        .map(|datum| datum.unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn datum_comment_returned() {
+    let _guard = crate::init_tracing();
+
+    pretty_assertions::assert_eq!(
+        read_with_comments("#; (1 2 3) #f"),
+        vec!["#; (1 2 3)", "#f"]
+    );
+}
+
+#[test]
+fn datum_comment_returned_as_datum() {
+    let _guard = crate::init_tracing();
+
+    let reader = ::ffsr::reader::Reader::from(::ffsr::lexer::Lexer::from("#; 'a"));
+    let mut iter = reader.iter_with_comments();
+    let quoted = ::ffsr::reader::Reader::from(::ffsr::lexer::Lexer::from("'a"))
+        .iter()
+        .next()
+        .unwrap()
+        .unwrap();
+    pretty_assertions::assert_eq!(
+        iter.next().unwrap().unwrap(),
+        Datum::from(SComment::from(quoted))
+    );
+    assert_complete!(iter);
+}
+
+#[test]
+fn datum_comment_span() {
+    let _guard = crate::init_tracing();
+
+    let reader = ::ffsr::reader::Reader::from(::ffsr::lexer::Lexer::from(" #; (a b) c"));
+    let mut iter = reader.iter_with_comments();
+    assert!(iter.next().unwrap().is_ok());
+    assert_eq!(iter.last_datum_span(), Some(Span::new(1, 9)));
+    assert!(iter.next().unwrap().is_ok());
+    assert_eq!(iter.last_datum_span(), Some(Span::new(10, 11)));
+}
+
+#[test]
+fn nested_datum_comments_returned() {
+    let _guard = crate::init_tracing();
+
+    pretty_assertions::assert_eq!(read_with_comments("#; #; a b c"), vec!["#; a", "#; b", "c"]);
+}
+
+#[test]
+fn datum_comment_in_list_returned() {
+    let _guard = crate::init_tracing();
+
+    pretty_assertions::assert_eq!(
+        read_with_comments("(a #; b c) d"),
+        vec!["#; b", "(a c)", "d"]
+    );
+}
+
+#[test]
+fn datum_comment_in_vector_returned() {
+    let _guard = crate::init_tracing();
+
+    pretty_assertions::assert_eq!(
+        read_with_comments("#(1 #; #(2) 3)"),
+        vec!["#; #(2)", "#(1 3)"]
+    );
+}
+
+#[test]
+fn datum_comment_in_pair_returned() {
+    let _guard = crate::init_tracing();
+
+    pretty_assertions::assert_eq!(read_with_comments("(a . #; b c)"), vec!["#; b", "(a . c)"]);
+}
+
+#[test]
+fn line_comment_in_list_returned() {
+    let _guard = crate::init_tracing();
+
+    let comments = read_with_comments("(a ; note\n b)");
+    assert_eq!(comments.len(), 2);
+    assert_eq!(comments[1], "(a b)");
+}
+
+#[test]
+fn datum_comment_in_list_ignored() {
+    let _guard = crate::init_tracing();
+
+    let reader = ::ffsr::reader::Reader::from(::ffsr::lexer::Lexer::from("(a #; #; b c d)"));
+    let data: Vec<String> = reader.iter().map(|d| d.unwrap().to_string()).collect();
+    pretty_assertions::assert_eq!(data, vec!["(a d)"]);
+}
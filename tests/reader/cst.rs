@@ -0,0 +1,255 @@
+use ffsr::error::Error;
+use ffsr::input::indices::Index;
+use ffsr::input::Input;
+use ffsr::lexer::token::TokenKind;
+use ffsr::lexer::Lexer;
+use ffsr::reader::cst::{Node, NodeKind, SyntaxTree, TriviaKind};
+use ffsr::reader::datum::{Datum, SComment};
+use ffsr::reader::Reader;
+use ffsr::{SourceId, Sourced};
+
+fn parse(source: &str) -> SyntaxTree {
+    SyntaxTree::parse(Lexer::from(source).tokens()).expect("syntax tree parse fail")
+}
+
+fn trivia_kinds(tree: &SyntaxTree, node: usize) -> Vec<TriviaKind> {
+    tree.nodes()[node]
+        .leading()
+        .iter()
+        .map(|trivia| trivia.kind())
+        .collect()
+}
+
+fn read_datums(reader: &Reader<'_>) -> Vec<Result<Datum, Error>> {
+    let source = reader.source_str();
+    let tree = parse(source);
+    assert_eq!(tree.to_string(), source);
+    tree.nodes()
+        .iter()
+        .filter(|node| !node.is_datum_comment())
+        .filter_map(Node::to_datum)
+        .collect()
+}
+
+#[test]
+fn empty_input() {
+    let tree = parse("");
+    assert!(tree.nodes().is_empty());
+    assert!(tree.trailing().is_empty());
+    assert_eq!(tree.to_string(), "");
+}
+
+#[test]
+fn only_trivia() {
+    let source = "  ; nothing\r\n#| here |#\r\t";
+    let tree = parse(source);
+    assert!(tree.nodes().is_empty());
+    assert_eq!(
+        tree.trailing()
+            .iter()
+            .map(|trivia| (trivia.kind(), trivia.text()))
+            .collect::<Vec<_>>(),
+        vec![
+            (TriviaKind::Whitespace, "  "),
+            (TriviaKind::LineComment, "; nothing"),
+            (TriviaKind::Newline, "\r\n"),
+            (TriviaKind::BlockComment, "#| here |#"),
+            (TriviaKind::Newline, "\r"),
+            (TriviaKind::Whitespace, "\t"),
+        ]
+    );
+    assert_eq!(tree.to_string(), source);
+}
+
+#[test]
+fn trivia_attached_to_following_node() {
+    let _guard = crate::init_tracing();
+
+    let tree = parse("; one\n(a ; two\n  b\n  ; three\n  )  c ; four");
+    assert_eq!(tree.nodes().len(), 2);
+    assert_eq!(
+        trivia_kinds(&tree, 0),
+        vec![TriviaKind::LineComment, TriviaKind::Newline]
+    );
+    assert_eq!(trivia_kinds(&tree, 1), vec![TriviaKind::Whitespace]);
+
+    let NodeKind::Delimited {
+        open,
+        nodes,
+        close_leading,
+        close,
+    } = tree.nodes()[0].kind()
+    else {
+        panic!("expected a list node");
+    };
+    assert_eq!(open.kind(), TokenKind::OpenParenthesis);
+    assert_eq!(close.text(), ")");
+    assert_eq!(nodes.len(), 2);
+    assert_eq!(nodes[1].leading()[1].text(), "; two");
+    assert_eq!(nodes[1].text(), "b");
+    assert_eq!(
+        close_leading
+            .iter()
+            .filter(|trivia| trivia.is_comment())
+            .map(|trivia| trivia.text())
+            .collect::<Vec<_>>(),
+        vec!["; three"]
+    );
+
+    assert_eq!(tree.trailing()[1].text(), "; four");
+    assert_eq!(tree.trailing()[1].span().start(), 36);
+    assert_eq!(tree.trailing()[1].span().end(), 42);
+}
+
+#[test]
+fn prefixed_nodes() {
+    let tree = parse("'a `(b ,c ,@d) #0=(e) #0# #; f");
+    let prefixes: Vec<Option<TokenKind>> = tree
+        .nodes()
+        .iter()
+        .map(|node| match node.kind() {
+            NodeKind::Prefixed { prefix, node: _ } => Some(prefix.kind()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        prefixes,
+        vec![
+            Some(TokenKind::Quote),
+            Some(TokenKind::QuasiQuote),
+            Some(TokenKind::DatumAssign),
+            None,
+            Some(TokenKind::DatumComment),
+        ]
+    );
+    assert_eq!(tree.nodes()[1].children()[0].children().len(), 3);
+    assert_eq!(tree.nodes()[4].span().start(), 26);
+    assert_eq!(tree.nodes()[4].leading()[0].span().start(), 25);
+}
+
+#[test]
+fn node_spans() {
+    let tree = parse("  #(1 λ) #u8(2)");
+    assert_eq!(tree.nodes()[0].span().start(), 2);
+    assert_eq!(tree.nodes()[0].text(), "#(1 λ)");
+    assert_eq!(tree.nodes()[0].children()[1].text(), "λ");
+    assert_eq!(tree.nodes()[0].children()[1].span().start(), 6);
+    assert_eq!(tree.nodes()[1].span().start(), 9);
+    assert_eq!(tree.nodes()[1].leading()[0].span().start(), 8);
+}
+
+#[test]
+fn offset_input() {
+    let source = "  (a b) ; c\n";
+    let input = Input::new_at(SourceId::String, source, Index::new(10, 10));
+    let tree = SyntaxTree::parse(Lexer::from(input).tokens()).unwrap();
+    assert_eq!(tree.to_string(), source);
+    assert_eq!(trivia_kinds(&tree, 0), vec![TriviaKind::Whitespace]);
+    assert_eq!(tree.nodes()[0].leading()[0].span().start(), 10);
+    assert_eq!(tree.nodes()[0].span().start(), 12);
+    assert_eq!(tree.nodes()[0].children()[1].span().start(), 15);
+    assert_eq!(tree.trailing()[0].span().start(), 17);
+    assert_eq!(tree.to_datums()[0].as_ref().unwrap().to_string(), "(a b)");
+}
+
+#[test]
+fn datum_comment_to_datum() {
+    let tree = parse("#; (a b)");
+    assert!(tree.nodes()[0].is_datum_comment());
+
+    let inner = Reader::from(Lexer::from("(a b)"))
+        .iter()
+        .next()
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        tree.nodes()[0].to_datum().unwrap().unwrap(),
+        Datum::from(SComment::from(inner))
+    );
+}
+
+#[test]
+fn nested_datum_comment_skipped() {
+    super::assert_matches_reader("(a #;(b c) d) #(1 #; 2 3)", read_datums);
+}
+
+#[test]
+fn datum_comment_after_prefix() {
+    let tree = parse("'#;a b #0=#; x y (c #;#;d e f)");
+    assert_eq!(tree.to_string(), "'#;a b #0=#; x y (c #;#;d e f)");
+    assert_eq!(tree.nodes().len(), 3);
+
+    let quoted = &tree.nodes()[0].children()[0];
+    assert_eq!(quoted.text(), "b");
+    assert_eq!(
+        quoted
+            .leading()
+            .iter()
+            .map(|trivia| (trivia.kind(), trivia.text()))
+            .collect::<Vec<_>>(),
+        vec![
+            (TriviaKind::DatumComment, "#;a"),
+            (TriviaKind::Whitespace, " ")
+        ]
+    );
+    assert_eq!(quoted.leading()[0].span().start(), 1);
+    assert_eq!(tree.nodes()[1].children()[0].leading()[0].text(), "#; x");
+    assert!(tree.nodes()[2].children()[1].is_datum_comment());
+
+    super::assert_matches_reader("'#;a b #0=#; x y (c #;#;d e f)", read_datums);
+}
+
+#[test]
+fn dot_is_not_a_datum() {
+    let tree = parse("(a . b)");
+    let children = tree.nodes()[0].children();
+    assert_eq!(children[1].text(), ".");
+    assert!(children[1].to_datum().is_none());
+    assert_eq!(children[2].to_datum().unwrap().unwrap().to_string(), "b");
+}
+
+#[test]
+fn to_datums_with_labels() {
+    let source = "#0=(a b) (c . #0#)";
+    let tree = parse(source);
+    let expected: Vec<String> = Reader::from(Lexer::from(source))
+        .iter()
+        .map(|d| format!("{d:?}"))
+        .collect();
+    let actual: Vec<String> = tree.to_datums().iter().map(|d| format!("{d:?}")).collect();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn unexpected_close() {
+    let error = SyntaxTree::parse(Lexer::from("(a))").tokens()).unwrap_err();
+    assert_eq!(error.span().unwrap().start(), 3);
+}
+
+#[test]
+fn incomplete_list() {
+    let error = SyntaxTree::parse(Lexer::from("(a (b)").tokens()).unwrap_err();
+    assert_eq!(error.span().unwrap().start(), 0);
+    assert_eq!(error.span().unwrap().end(), 6);
+}
+
+#[test]
+fn incomplete_quote() {
+    assert!(SyntaxTree::parse(Lexer::from("(a) '").tokens()).is_err());
+}
+
+#[test]
+fn preludes() {
+    let _guard = crate::init_tracing();
+
+    super::assert_matches_reader_files(read_datums);
+}
+
+#[test]
+fn lispkit_match_library() {
+    let _guard = crate::init_tracing();
+
+    let source =
+        std::fs::read_to_string("tests/files/LispKit/Libraries/lispkit/match.sld").unwrap();
+    super::assert_matches_reader(&source, read_datums);
+}
@@ -0,0 +1,150 @@
+use ffsr::input::chunked::ChunkedInput;
+use ffsr::lexer::Lexer;
+use ffsr::reader::datum::{SChar, SDirective, SIdentifier};
+use ffsr::reader::incremental::{Document, TextEdit};
+use ffsr::reader::stream::DatumStream;
+use ffsr::reader::Reader;
+use ffsr::SourceId;
+use std::str::FromStr;
+
+fn read_all(input: &str, fold_case: bool, return_directives: bool) -> Vec<String> {
+    let reader = Reader::from(Lexer::from(input));
+    let iter = reader
+        .iter()
+        .with_fold_case(fold_case)
+        .with_directives(return_directives);
+    iter.map(|datum| datum.unwrap().to_string()).collect()
+}
+
+// ------------------------------------------------------------------------------------------------
+// Single-valued success cases
+// ------------------------------------------------------------------------------------------------
+
+success_case!(fold_case, "#!fold-case" => Directive, SDirective::FoldCase(true));
+
+success_case!(no_fold_case, "#!no-fold-case" => Directive, SDirective::FoldCase(false));
+
+// ------------------------------------------------------------------------------------------------
+// Multi-valued success cases
+// ------------------------------------------------------------------------------------------------
+
+success_case!(
+    fold_case_identifier,
+    "#!fold-case Hello" =>
+        (Directive, SDirective::FoldCase(true)),
+        (Identifier, SIdentifier::from_str("hello").unwrap())
+);
+
+success_case!(
+    fold_case_char_name,
+    "#!fold-case #\\NewLine #\\A" =>
+        (Directive, SDirective::FoldCase(true)),
+        (Char, SChar::from('\n')),
+        (Char, SChar::from('A'))
+);
+
+success_case!(
+    fold_case_vbar_identifier,
+    "#!fold-case |Hello|" =>
+        (Directive, SDirective::FoldCase(true)),
+        (Identifier, SIdentifier::from_str("Hello").unwrap())
+);
+
+success_case!(
+    fold_then_no_fold,
+    "#!fold-case ABC #!no-fold-case ABC" =>
+        (Directive, SDirective::FoldCase(true)),
+        (Identifier, SIdentifier::from_str("abc").unwrap()),
+        (Directive, SDirective::FoldCase(false)),
+        (Identifier, SIdentifier::from_str("ABC").unwrap())
+);
+
+// ------------------------------------------------------------------------------------------------
+// Failure cases
+// ------------------------------------------------------------------------------------------------
+
+failure_case!(unknown_directive, "#!fold-everything");
+
+failure_case!(
+    char_name_without_fold_case,
+    "#\\NewLine",
+    "Unknown character name \"NewLine\"; span: 0..8"
+);
+
+// ------------------------------------------------------------------------------------------------
+// Reader options
+// ------------------------------------------------------------------------------------------------
+
+#[test]
+fn no_fold_case_by_default() {
+    let _guard = crate::init_tracing();
+
+    assert_eq!(
+        read_all("Hello (World)", false, true),
+        vec!["Hello", "(World)"]
+    );
+}
+
+#[test]
+fn fold_case_by_default() {
+    let _guard = crate::init_tracing();
+
+    assert_eq!(
+        read_all("Hello (World #\\SPACE) |Keep|", true, true),
+        vec!["hello", "(world #\\ )", "Keep"]
+    );
+}
+
+#[test]
+fn fold_case_within_list() {
+    let _guard = crate::init_tracing();
+
+    assert_eq!(
+        read_all("(A #!fold-case B #!no-fold-case C) D", false, false),
+        vec!["(A b C)", "D"]
+    );
+}
+
+#[test]
+fn directives_not_returned() {
+    let _guard = crate::init_tracing();
+
+    assert_eq!(
+        read_all("#!fold-case Straße ΣΑΣ #!no-fold-case Straße", false, false),
+        vec!["strasse", "σασ", "Straße"]
+    );
+}
+
+#[test]
+fn state_visible_on_iterator() {
+    let _guard = crate::init_tracing();
+
+    let reader = Reader::from(Lexer::from("a #!fold-case b"));
+    let mut iter = reader.iter().with_directives(false);
+    assert!(!iter.is_folding_case());
+    let _ = iter.next();
+    assert!(!iter.is_folding_case());
+    let _ = iter.next();
+    assert!(iter.is_folding_case());
+}
+
+#[test]
+fn fold_case_state_kept_between_streamed_datums() {
+    let _guard = crate::init_tracing();
+
+    let data = "#!fold-case FOO (BAR) #!no-fold-case BAZ";
+    let input = ChunkedInput::new(data.as_bytes(), SourceId::String, 4);
+    let stream = DatumStream::from(input).with_directives(false);
+    let datums: Vec<String> = stream.map(|datum| datum.unwrap().to_string()).collect();
+    assert_eq!(datums, vec!["foo", "(bar)", "BAZ"]);
+}
+
+#[test]
+fn fold_case_in_incremental_document() {
+    let _guard = crate::init_tracing();
+
+    let mut document = Document::from("#!fold-case (A) (B) (C)");
+    document.apply(TextEdit::replace(17, 18, "X"));
+    assert_eq!(document.reused(), 0);
+    assert_eq!(document.datums()[2].datum().unwrap().to_string(), "(x)");
+}
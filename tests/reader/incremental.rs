@@ -0,0 +1,185 @@
+use ffsr::lexer::Lexer;
+use ffsr::reader::incremental::{Document, TextEdit};
+use ffsr::reader::Reader;
+use ffsr::SourceId;
+
+fn summary(document: &Document) -> Vec<String> {
+    document
+        .datums()
+        .iter()
+        .map(|datum| {
+            format!(
+                "{:?} {:?} {:?} {:?}",
+                datum.start_index(),
+                datum.end_index(),
+                datum.span(),
+                datum.result()
+            )
+        })
+        .collect()
+}
+
+fn assert_same_as_full_read(document: &Document) {
+    let expected = Document::new(SourceId::String, document.text());
+    pretty_assertions::assert_eq!(summary(document), summary(&expected));
+
+    let reader = Reader::from(Lexer::from(document.text()));
+    let full: Vec<String> = reader.iter().map(|d| format!("{d:?}")).collect();
+    let incremental: Vec<String> = document
+        .datums()
+        .iter()
+        .map(|d| format!("{:?}", d.result()))
+        .collect();
+    pretty_assertions::assert_eq!(incremental, full);
+}
+
+#[test]
+fn edit_within_datum() {
+    let _guard = crate::init_tracing();
+
+    let mut document = Document::from("(a 1) (b 2) (c 3) (d 4)");
+    document.apply(TextEdit::replace(9, 10, "λλ"));
+
+    assert_eq!(document.text(), "(a 1) (b λλ) (c 3) (d 4)");
+    assert_eq!(document.reused(), 3);
+    assert_eq!(document.datums()[2].span().start(), 13);
+    assert_same_as_full_read(&document);
+}
+
+#[test]
+fn edit_joins_datums() {
+    let _guard = crate::init_tracing();
+
+    let mut document = Document::from("(a 1) (b 2) (c 3)");
+    document.apply(TextEdit::delete(5..6));
+
+    assert_eq!(document.text(), "(a 1)(b 2) (c 3)");
+    assert_same_as_full_read(&document);
+
+    document.apply(TextEdit::delete(4..6));
+    assert_eq!(document.text(), "(a 1b 2) (c 3)");
+    assert_eq!(document.datums().len(), 2);
+    assert_same_as_full_read(&document);
+}
+
+#[test]
+fn edit_opens_and_closes_list() {
+    let _guard = crate::init_tracing();
+
+    let mut document = Document::from("(a 1)\n(b 2)\n(c 3)\n");
+    document.apply(TextEdit::delete(4..5));
+    assert_same_as_full_read(&document);
+    assert_eq!(document.datums().len(), 1);
+    assert!(!document.datums()[0].is_ok());
+
+    document.apply(TextEdit::insert(4, ")"));
+    assert_eq!(document.text(), "(a 1)\n(b 2)\n(c 3)\n");
+    assert_eq!(document.datums().len(), 3);
+    assert_same_as_full_read(&document);
+}
+
+#[test]
+fn edit_with_errors_and_comments() {
+    let _guard = crate::init_tracing();
+
+    let mut document = Document::from("; start\n(a . ) #| block |# \"str\" (b . c . d) 'e");
+    assert_same_as_full_read(&document);
+
+    document.apply(TextEdit::insert(4, "\n(x)"));
+    assert_same_as_full_read(&document);
+
+    document.apply(TextEdit::replace(18, 20, "#|"));
+    assert_same_as_full_read(&document);
+}
+
+#[test]
+fn edit_with_datum_labels() {
+    let _guard = crate::init_tracing();
+
+    let mut document = Document::from("#0=(a b) (c d) #0#");
+    document.apply(TextEdit::replace(10, 11, "x"));
+
+    assert_eq!(document.reused(), 0);
+    assert_same_as_full_read(&document);
+}
+
+#[test]
+fn overlapping_deletes_out_of_order() {
+    let _guard = crate::init_tracing();
+
+    let mut document = Document::from("(a 1) (b 2) (c 3) (d 4)");
+    document.apply(TextEdit::delete(6..18));
+    assert_eq!(document.text(), "(a 1) (d 4)");
+    assert_same_as_full_read(&document);
+
+    document.apply(TextEdit::delete(0..8));
+    assert_eq!(document.text(), " 4)");
+    assert_same_as_full_read(&document);
+
+    document.apply(TextEdit::delete(0..1));
+    assert_eq!(document.text(), "4)");
+    assert_same_as_full_read(&document);
+}
+
+#[test]
+fn random_edits_match_full_read() {
+    const FRAGMENTS: &[&str] = &[
+        "(",
+        ")",
+        " ",
+        "\n",
+        "a",
+        "λ",
+        "12",
+        "#t",
+        "\"",
+        "'",
+        ";",
+        "#(",
+        "#u8(",
+        ".",
+        "#|",
+        "|#",
+        "(define (f x) (* x 2))",
+        "#\\a",
+        "\"s\"",
+    ];
+
+    let mut document = Document::from(
+        "(define (f x) (* x 2))\n; comment\n(display \"λ\")\n#(1 2 3)\n'(a . b)\n#u8(1 2)\n",
+    );
+    let mut reused = 0;
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = |bound: usize| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        (seed % bound as u64) as usize
+    };
+
+    for _ in 0..500 {
+        let boundaries: Vec<usize> = document
+            .text()
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(std::iter::once(document.text().len()))
+            .collect();
+        let start = boundaries[next(boundaries.len())];
+        let end = boundaries
+            .iter()
+            .copied()
+            .filter(|i| *i >= start)
+            .nth(next(4))
+            .unwrap_or(start);
+        let text = if next(3) == 0 {
+            ""
+        } else {
+            FRAGMENTS[next(FRAGMENTS.len())]
+        };
+
+        document.apply(TextEdit::replace(start, end, text));
+        assert_same_as_full_read(&document);
+        reused += document.reused();
+    }
+    assert!(reused > 500, "only {reused} datums reused");
+}
@@ -86,6 +86,99 @@ macro_rules! failure_case {
     };
 }
 
+///
+/// The files that every alternate reader is checked against [`Reader::iter`] with, and that
+/// the source formatter is checked with.
+///
+pub const MATCHES_READER_FILES: &[&str] = &[
+    "tests/files/LispKit/Prelude.scm",
+    "tests/files/LispPad/Prelude.scm",
+];
+
+///
+/// Inputs with errors and datum labels that every alternate reader is checked against
+/// [`Reader::iter`] with.
+///
+pub const MATCHES_READER_CASES: &[&str] = &[
+    "(a #(b .) c) #u8(x) #u8(256) (d . e f) (g)",
+    "(a (b",
+    "(a #0=(b) #0# . #0#)",
+    "#0=(a . #0#)",
+    "#0=(a b #0#)",
+    "#0=#(a #0#)",
+    "(#0='(q) #0#)",
+    "#0=(a #1=(b . #0#) #1#)",
+    "(#0=(a . #0#) #0#)",
+];
+
+///
+/// Assert that `read` returns the same datums and errors as [`Reader::iter`] for `source`.
+///
+pub fn assert_matches_reader<F>(source: &str, read: F)
+where
+    F: Fn(
+        &::ffsr::reader::Reader<'_>,
+    ) -> Vec<Result<::ffsr::reader::datum::Datum, ::ffsr::error::Error>>,
+{
+    let reader = ::ffsr::reader::Reader::from(::ffsr::lexer::Lexer::from(source));
+    let expected: Vec<String> = reader.iter().map(|d| format!("{d:?}")).collect();
+    let actual: Vec<String> = read(&reader).iter().map(|d| format!("{d:?}")).collect();
+    ::pretty_assertions::assert_eq!(actual, expected, "reading {source:?}");
+}
+
+///
+/// Assert that `read` matches [`Reader::iter`] for each of [`MATCHES_READER_FILES`].
+///
+pub fn assert_matches_reader_files<F>(read: F)
+where
+    F: Fn(
+        &::ffsr::reader::Reader<'_>,
+    ) -> Vec<Result<::ffsr::reader::datum::Datum, ::ffsr::error::Error>>,
+{
+    for path in MATCHES_READER_FILES {
+        assert_matches_reader(&std::fs::read_to_string(path).unwrap(), &read);
+    }
+}
+
+///
+/// Assert that `read` matches [`Reader::iter`] for each of [`MATCHES_READER_CASES`].
+///
+pub fn assert_matches_reader_cases<F>(read: F)
+where
+    F: Fn(
+        &::ffsr::reader::Reader<'_>,
+    ) -> Vec<Result<::ffsr::reader::datum::Datum, ::ffsr::error::Error>>,
+{
+    for source in MATCHES_READER_CASES {
+        assert_matches_reader(source, &read);
+    }
+}
+
+///
+/// Read `input`, which must hold exactly one datum, and return that datum.
+///
+pub fn read_one(input: &str) -> ::ffsr::reader::datum::Datum {
+    let reader = ::ffsr::reader::Reader::from(::ffsr::lexer::Lexer::from(input));
+    let mut iter = reader.iter();
+    let datum = iter
+        .next()
+        .expect("no next datum")
+        .expect("datum parsing fail");
+    assert!(iter.next().is_none());
+    datum
+}
+
+///
+/// Read all the datums in `input`, which must read without errors.
+///
+pub fn read_all(input: &str) -> Vec<::ffsr::reader::datum::Datum> {
+    let reader = ::ffsr::reader::Reader::from(::ffsr::lexer::Lexer::from(input));
+    reader
+        .iter()
+        .collect::<Result<_, _>>()
+        .expect("datum parsing fail")
+}
+
 #[test]
 fn empty_input() {
     let _guard = crate::init_tracing();
@@ -96,13 +189,23 @@ fn empty_input() {
     assert_complete!(iter);
 }
 
+#[cfg(feature = "arena")]
+pub mod arena;
 pub mod booleans;
+pub mod borrowed;
 pub mod chars;
+pub mod commented;
 pub mod comments;
+pub mod cst;
+pub mod directives;
 pub mod identifiers;
+pub mod incremental;
 pub mod lists;
 pub mod numbers;
 pub mod quotes;
+pub mod recovery;
 pub mod references;
+pub mod stream;
 pub mod strings;
+pub mod syntax;
 pub mod vectors;
@@ -0,0 +1,163 @@
+use ffsr::lexer::token::Span;
+use ffsr::lexer::Lexer;
+use ffsr::reader::datum::Datum;
+use ffsr::reader::Reader;
+
+fn read_recovering(input: &str) -> Vec<String> {
+    let reader = Reader::from(Lexer::from(input));
+    reader
+        .iter_with_recovery()
+        .map(|result| match result {
+            Ok(datum) => datum.to_string(),
+            Err(e) => format!("error {}", e.code()),
+        })
+        .collect()
+}
+
+#[test]
+fn not_recovering_by_default() {
+    let _guard = crate::init_tracing();
+
+    let reader = Reader::from(Lexer::from("(a b"));
+    assert!(!reader.iter().is_recovering());
+    assert!(reader.iter_with_recovery().is_recovering());
+}
+
+#[test]
+fn unexpected_close_at_top_level() {
+    let _guard = crate::init_tracing();
+
+    pretty_assertions::assert_eq!(
+        read_recovering("(a) ) (b)"),
+        vec!["(a)", "error 110", "(b)"]
+    );
+}
+
+#[test]
+fn stray_dot_in_list() {
+    let _guard = crate::init_tracing();
+
+    pretty_assertions::assert_eq!(
+        read_recovering("(a . b . (c d) e) (f)"),
+        vec!["error 84", "#<error>", "(f)"]
+    );
+}
+
+#[test]
+fn stray_dot_at_top_level() {
+    let _guard = crate::init_tracing();
+
+    pretty_assertions::assert_eq!(read_recovering(". a"), vec!["error 110", "a"]);
+}
+
+#[test]
+fn missing_cdr_closes_list() {
+    let _guard = crate::init_tracing();
+
+    pretty_assertions::assert_eq!(
+        read_recovering("(x (a . ) y) z"),
+        vec!["error 83", "(x #<error> y)", "z"]
+    );
+}
+
+#[test]
+fn non_byte_in_byte_vector() {
+    let _guard = crate::init_tracing();
+
+    pretty_assertions::assert_eq!(
+        read_recovering("(#u8(1 a (2) 3) b) c"),
+        vec!["error 89", "(#<error> b)", "c"]
+    );
+}
+
+#[test]
+fn bad_literal_replaced() {
+    let _guard = crate::init_tracing();
+
+    pretty_assertions::assert_eq!(
+        read_recovering("(a #\\nosuchchar b) #\\nosuchchar c"),
+        vec!["error 51", "(a #<error> b)", "error 51", "#<error>", "c"]
+    );
+}
+
+#[test]
+fn lexical_errors_recovered() {
+    let _guard = crate::init_tracing();
+
+    let results = read_recovering("(a #z b) #(1 #q 2) c");
+    assert_eq!(results.len(), 5);
+    assert!(results[0].starts_with("error"));
+    assert_eq!(results[1], "(a #<error> b)");
+    assert!(results[2].starts_with("error"));
+    assert_eq!(results[3], "#(1 #<error> 2)");
+    assert_eq!(results[4], "c");
+}
+
+#[test]
+fn unknown_label_replaced() {
+    let _guard = crate::init_tracing();
+
+    pretty_assertions::assert_eq!(
+        read_recovering("(#1# a) b"),
+        vec!["error 15", "(#<error> a)", "b"]
+    );
+}
+
+#[test]
+fn unexpected_token_within_form() {
+    let _guard = crate::init_tracing();
+
+    let reader = Reader::from(Lexer::from("(a #(b .) c)"));
+    let mut iter = reader.iter_with_recovery();
+    let error = iter.next().unwrap().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "The token dot was not expected; span: 7..8, within: 3..8"
+    );
+    assert_eq!(iter.next().unwrap().unwrap().to_string(), "(a #<error> c)");
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn placeholder_span() {
+    let _guard = crate::init_tracing();
+
+    let reader = Reader::from(Lexer::from("x (a . b . c)"));
+    let results: Vec<_> = reader.iter_with_recovery().collect();
+    assert_eq!(results.len(), 3);
+    assert!(results[1].is_err());
+    assert_eq!(
+        results[2].as_ref().unwrap(),
+        &Datum::Error(Span::new(2, 12))
+    );
+}
+
+#[test]
+fn incomplete_form_still_reported() {
+    let _guard = crate::init_tracing();
+
+    pretty_assertions::assert_eq!(read_recovering("(a . b . c"), vec!["error 84", "error 80"]);
+}
+
+#[test]
+fn no_panic_on_arbitrary_input() {
+    const FRAGMENTS: &[&str] = &[
+        "(", ")", " ", "a", "12", ".", "'", "#(", "#u8(", "#0=", "#0#", "#;", "#\\x", "#z", "\"s\"",
+    ];
+    let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut next = |bound: usize| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        (seed % bound as u64) as usize
+    };
+
+    for _ in 0..1000 {
+        let input: String = (0..next(20))
+            .map(|_| FRAGMENTS[next(FRAGMENTS.len())])
+            .collect();
+        let reader = Reader::from(Lexer::from(input.as_str()));
+        let count = reader.iter_with_recovery().take(1000).count();
+        assert!(count < 1000, "no progress reading {input:?}");
+    }
+}
@@ -1,3 +1,4 @@
+use super::read_one;
 use ffsr::reader::datum::{Datum, Fixnum, SBoolean, SIdentifier, SList};
 use std::str::FromStr;
 
@@ -54,3 +55,148 @@ success_case!(!
 // ------------------------------------------------------------------------------------------------
 // Failure cases
 // ------------------------------------------------------------------------------------------------
+
+failure_case!(assign_only_to_itself, "#0=#0#");
+
+failure_case!(reference_before_assignment, "(#0# #0=a)");
+
+// ------------------------------------------------------------------------------------------------
+// Shared and circular structure
+// ------------------------------------------------------------------------------------------------
+
+fn list_elements(datum: &Datum) -> Vec<Datum> {
+    let mut elements = Vec::new();
+    let mut next = datum.as_list().and_then(|list| list.as_pair());
+    while let Some(pair) = next {
+        elements.push(pair.car().as_ref().clone());
+        next = pair.cdr().as_list().and_then(|list| list.as_pair());
+    }
+    elements
+}
+
+#[test]
+fn shared_structure_keeps_identity() {
+    let _guard = crate::init_tracing();
+
+    let datum = read_one("(#0=(x y) #0# #0#)");
+    let elements = list_elements(&datum);
+    assert_eq!(elements.len(), 3);
+
+    let first = elements[0].as_shared().unwrap();
+    assert!(elements
+        .iter()
+        .all(|element| element.as_shared().unwrap().ptr_eq(first)));
+    assert!(!first.is_cyclic());
+    assert_eq!(datum.to_string(), "((x y) (x y) (x y))");
+}
+
+#[test]
+fn shared_equals_unshared_copy() {
+    let _guard = crate::init_tracing();
+
+    assert_eq!(read_one("(#0=(x y) #0#)"), read_one("((x y) (x y))"));
+    assert_ne!(read_one("(#0=(x y) #0#)"), read_one("((x y) (x z))"));
+}
+
+#[test]
+fn simple_datums_are_not_shared() {
+    let _guard = crate::init_tracing();
+
+    let datum = read_one("(#0=a #1=() #0# #1#)");
+    assert!(list_elements(&datum)
+        .iter()
+        .all(|element| !element.is_shared()));
+}
+
+#[test]
+fn circular_list() {
+    let _guard = crate::init_tracing();
+
+    let datum = read_one("#0=(a b . #0#)");
+    let shared = datum.as_shared().unwrap();
+    assert!(shared.is_cyclic());
+    assert_eq!(shared.label(), 0);
+    assert_eq!(datum.to_string(), "#0=(a b . #0#)");
+    assert_eq!(format!("{datum:?}"), "#0=(a . (b . #0#))");
+    assert_eq!(datum.type_string(), "pair-or-list");
+
+    let pair = shared.get().unwrap();
+    let pair = pair.as_list().unwrap().as_pair().unwrap();
+    let tail = pair.cdr().as_list().unwrap().as_pair().unwrap().cdr();
+    assert!(tail.as_shared().unwrap().ptr_eq(shared));
+}
+
+#[test]
+fn circular_vector_and_quote() {
+    let _guard = crate::init_tracing();
+
+    let datum = read_one("#1=#(1 '#1# (x #1#))");
+    assert!(datum.as_shared().unwrap().is_cyclic());
+    assert_eq!(datum.to_string(), "#1=#(1 '#1# (x #1#))");
+}
+
+#[test]
+fn circular_equality_terminates() {
+    let _guard = crate::init_tracing();
+
+    let one = read_one("#0=(a b . #0#)");
+    let two = read_one("#0=(a b a b . #0#)");
+    let three = read_one("#0=(a c . #0#)");
+    assert_eq!(one, one.clone());
+    assert_eq!(one, two);
+    assert_ne!(one, three);
+    assert_ne!(one, read_one("(a b a b)"));
+}
+
+#[test]
+fn nested_cycles() {
+    let _guard = crate::init_tracing();
+
+    let datum = read_one("#0=(a #1=(b #0# #1#))");
+    assert_eq!(datum.to_string(), "#0=(a #1=(b #0# #1#))");
+    assert_eq!(datum, read_one("#5=(a #6=(b #5# #6#))"));
+}
+
+#[test]
+fn circular_reference_without_owner() {
+    let _guard = crate::init_tracing();
+
+    let datum = read_one("#0=(a . #0#)");
+    let tail = {
+        let pair = datum.as_shared().unwrap().get().unwrap();
+        pair.as_list().unwrap().as_pair().unwrap().cdr().clone()
+    };
+    assert!(tail.as_shared().unwrap().get().is_some());
+    drop(datum);
+    assert!(tail.as_shared().unwrap().get().is_none());
+    assert_eq!(tail.to_string(), "#0#");
+}
+
+#[test]
+fn labelled_quote() {
+    let _guard = crate::init_tracing();
+
+    let datum = read_one("(#0='(q) #0#)");
+    assert_eq!(datum.to_string(), "('(q) '(q))");
+    let elements = list_elements(&datum);
+    assert!(elements[0].as_shared().unwrap().get().unwrap().is_quote());
+    assert!(elements[0]
+        .as_shared()
+        .unwrap()
+        .ptr_eq(elements[1].as_shared().unwrap()));
+
+    assert_eq!(read_one("'#0=(q)").to_string(), "'(q)");
+}
+
+#[test]
+fn later_occurrences_written_as_references() {
+    let _guard = crate::init_tracing();
+
+    let datum = read_one("#0=(a #1=(b . #0#) #1#)");
+    assert_eq!(datum.to_string(), "#0=(a #1=(b . #0#) #1#)");
+    assert_eq!(read_one(&datum.to_string()), datum);
+
+    let datum = read_one("(#0=(a . #0#) #0#)");
+    assert_eq!(datum.to_string(), "(#0=(a . #0#) #0#)");
+    assert_eq!(read_one(&datum.to_string()), datum);
+}
@@ -0,0 +1,102 @@
+use ffsr::input::chunked::ChunkedInput;
+use ffsr::input::chunked_input_from_reader;
+use ffsr::lexer::Lexer;
+use ffsr::reader::datum::Datum;
+use ffsr::reader::stream::DatumStream;
+use ffsr::reader::Reader;
+use ffsr::SourceId;
+
+fn read_all(source: &str) -> Vec<String> {
+    let reader = Reader::from(Lexer::from(source));
+    reader
+        .iter()
+        .map(|datum| format!("{:?}", datum.map(|d| d.to_string())))
+        .collect()
+}
+
+fn stream_all(source: &str, chunk_size: usize) -> Vec<String> {
+    let input = ChunkedInput::new(source.as_bytes(), SourceId::String, chunk_size);
+    DatumStream::from(input)
+        .map(|datum| format!("{:?}", datum.map(|d| d.to_string())))
+        .collect()
+}
+
+#[test]
+fn stream_matches_reader_for_small_chunks() {
+    let _guard = crate::init_tracing();
+
+    let source =
+        "(define (f x) (* x 2)) ; comment\n'sym \"a λ string\" #(1 2.5 #\\a) 12345 #u8(1 2)";
+    let expected = read_all(source);
+
+    for chunk_size in 1..8 {
+        pretty_assertions::assert_eq!(stream_all(source, chunk_size), expected);
+    }
+}
+
+#[test]
+fn stream_matches_reader_for_file() {
+    let source = std::fs::read_to_string("tests/files/LispKit/Prelude.scm").unwrap();
+
+    pretty_assertions::assert_eq!(stream_all(&source, 64), read_all(&source));
+}
+
+#[test]
+fn stream_consumes_each_datum() {
+    let _guard = crate::init_tracing();
+
+    let source = "(a b c) (d e f) (g h i)";
+    let mut stream = DatumStream::from(ChunkedInput::new(source.as_bytes(), SourceId::String, 4));
+
+    let datum = stream.next().unwrap().unwrap();
+    assert!(matches!(datum, Datum::List(_)));
+    assert!(stream.source().buffered_str().len() < source.len());
+    assert_eq!(stream.source().start_index().byte(), 7);
+
+    assert_eq!(stream.count(), 2);
+}
+
+#[test]
+fn stream_error_spans_are_absolute() {
+    let _guard = crate::init_tracing();
+
+    let source = "(a b c) (d . )";
+    let mut stream = DatumStream::from(ChunkedInput::new(source.as_bytes(), SourceId::String, 2));
+
+    assert!(stream.next().unwrap().is_ok());
+    let error = stream.next().unwrap().unwrap_err();
+    assert!(error.to_string().contains("11..12"), "{error}");
+    assert!(stream.next().is_none());
+}
+
+#[test]
+fn stream_with_comments() {
+    let _guard = crate::init_tracing();
+
+    let source = "; first\n#t";
+    let stream = DatumStream::from(chunked_input_from_reader(
+        source.as_bytes(),
+        SourceId::String,
+    ))
+    .with_comments();
+
+    let reader = Reader::from(Lexer::from(source));
+    let expected: Vec<String> = reader
+        .iter_with_comments()
+        .map(|d| d.unwrap().to_string())
+        .collect();
+
+    let data: Vec<String> = stream.map(|d| d.unwrap().to_string()).collect();
+    assert_eq!(data.len(), 2);
+    pretty_assertions::assert_eq!(data, expected);
+}
+
+#[test]
+fn stream_rejects_invalid_utf8() {
+    let _guard = crate::init_tracing();
+
+    let source: &[u8] = b"(a \xff)";
+    let mut stream = DatumStream::from(ChunkedInput::new(source, SourceId::String, 16));
+
+    assert!(stream.next().unwrap().is_err());
+}
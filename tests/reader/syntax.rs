@@ -0,0 +1,202 @@
+use ffsr::error::Error;
+use ffsr::input::Input;
+use ffsr::lexer::Lexer;
+use ffsr::reader::datum::Datum;
+use ffsr::reader::syntax::{SyntaxDatum, SyntaxValue};
+use ffsr::reader::Reader;
+use ffsr::SourceId;
+
+fn read_syntax(source: &str) -> Vec<SyntaxDatum> {
+    let reader = Reader::from(Lexer::from(source));
+    reader
+        .syntax_iter()
+        .map(|syntax| syntax.expect("syntax reading fail"))
+        .collect()
+}
+
+fn text<'a>(source: &'a str, syntax: &SyntaxDatum) -> &'a str {
+    let range = syntax.as_range();
+    let start = source.char_indices().nth(range.start).unwrap().0;
+    let end = source
+        .char_indices()
+        .nth(range.end)
+        .map(|(i, _)| i)
+        .unwrap_or(source.len());
+    &source[start..end]
+}
+
+fn read_datums(reader: &Reader<'_>) -> Vec<Result<Datum, Error>> {
+    reader
+        .syntax_iter()
+        .map(|syntax| syntax.map(Datum::from))
+        .collect()
+}
+
+#[test]
+fn empty_input() {
+    let _guard = crate::init_tracing();
+
+    assert!(read_syntax("  ; nothing here\n").is_empty());
+}
+
+#[test]
+fn atom_spans() {
+    let _guard = crate::init_tracing();
+
+    let source = "λx #\\a \"str\" 42 #t";
+    let nodes = read_syntax(source);
+    let texts: Vec<&str> = nodes.iter().map(|node| text(source, node)).collect();
+    assert_eq!(texts, vec!["λx", "#\\a", "\"str\"", "42", "#t"]);
+    assert!(nodes
+        .iter()
+        .all(|node| matches!(node.value(), SyntaxValue::Atom(_))));
+}
+
+#[test]
+fn list_element_spans() {
+    let _guard = crate::init_tracing();
+
+    let source = "(define (square x) (* x x))";
+    let nodes = read_syntax(source);
+    assert_eq!(nodes.len(), 1);
+    assert_eq!(text(source, &nodes[0]), source);
+
+    let elements = nodes[0].elements();
+    assert_eq!(elements.len(), 3);
+    assert_eq!(text(source, &elements[0]), "define");
+    assert_eq!(text(source, &elements[1]), "(square x)");
+    assert_eq!(text(source, &elements[2]), "(* x x)");
+    assert_eq!(text(source, &elements[2].elements()[2]), "x");
+    assert_eq!(elements[2].elements()[2].as_range(), 24..25);
+}
+
+#[test]
+fn quote_spans() {
+    let _guard = crate::init_tracing();
+
+    let source = "`(a ,b ,@(c))";
+    let nodes = read_syntax(source);
+    assert!(matches!(nodes[0].value(), SyntaxValue::QuasiQuote(_)));
+    assert_eq!(text(source, &nodes[0]), source);
+
+    let list = nodes[0].quoted().unwrap();
+    assert_eq!(text(source, list), "(a ,b ,@(c))");
+    assert!(matches!(
+        list.elements()[1].value(),
+        SyntaxValue::Unquote(_)
+    ));
+    assert_eq!(text(source, &list.elements()[1]), ",b");
+    assert_eq!(text(source, list.elements()[1].quoted().unwrap()), "b");
+    assert!(matches!(
+        list.elements()[2].value(),
+        SyntaxValue::UnquoteSplicing(_)
+    ));
+    assert_eq!(text(source, &list.elements()[2]), ",@(c)");
+}
+
+#[test]
+fn improper_tail_span() {
+    let _guard = crate::init_tracing();
+
+    let source = "(a b . \"tail\")";
+    let nodes = read_syntax(source);
+    assert_eq!(nodes[0].elements().len(), 2);
+    assert_eq!(text(source, nodes[0].tail().unwrap()), "\"tail\"");
+    assert_eq!(nodes[0].to_datum().to_string(), "(a b . tail)");
+}
+
+#[test]
+fn vector_element_spans() {
+    let _guard = crate::init_tracing();
+
+    let source = "#(1 #(2) #u8(3 255))";
+    let nodes = read_syntax(source);
+    let elements = nodes[0].elements();
+    assert!(matches!(nodes[0].value(), SyntaxValue::Vector(_)));
+    assert_eq!(text(source, &elements[1]), "#(2)");
+    assert!(matches!(elements[2].value(), SyntaxValue::ByteVector(_)));
+    assert_eq!(text(source, &elements[2].elements()[1]), "255");
+    assert_eq!(nodes[0].to_datum().to_string(), "#(1 #(2) #u8(3 255))");
+}
+
+#[test]
+fn comments_skipped() {
+    let _guard = crate::init_tracing();
+
+    let source = "(a #;(ignored) #| block |# b) ; line\n#;c d";
+    let nodes = read_syntax(source);
+    assert_eq!(nodes.len(), 2);
+    assert_eq!(nodes[0].elements().len(), 2);
+    assert_eq!(text(source, &nodes[0].elements()[1]), "b");
+    assert_eq!(text(source, &nodes[1]), "d");
+}
+
+#[test]
+fn datum_labels() {
+    let _guard = crate::init_tracing();
+
+    let source = "(#0=(x) #0#)";
+    let nodes = read_syntax(source);
+    let elements = nodes[0].elements();
+    assert_eq!(text(source, &elements[0]), "(x)");
+    assert_eq!(text(source, &elements[1]), "#0#");
+    assert_eq!(text(source, &elements[1].elements()[0]), "x");
+    assert_eq!(nodes[0].to_datum().to_string(), "((x) (x))");
+}
+
+#[test]
+fn circular_datum_labels() {
+    let _guard = crate::init_tracing();
+
+    let source = "#0=(a b #0#) (#1='(q) #1#)";
+    let nodes = read_syntax(source);
+    let elements = nodes[0].elements();
+    assert_eq!(text(source, &elements[2]), "#0#");
+    assert!(matches!(
+        elements[2].value(),
+        SyntaxValue::Atom(Datum::Shared(_))
+    ));
+    assert_eq!(nodes[0].to_datum().to_string(), "#0=(a b #0#)");
+
+    let elements = nodes[1].elements();
+    assert_eq!(text(source, &elements[0]), "'(q)");
+    assert!(matches!(elements[1].value(), SyntaxValue::Quote(_)));
+    assert_eq!(nodes[1].to_datum().to_string(), "('(q) '(q))");
+}
+
+#[test]
+fn source_id_recorded() {
+    let _guard = crate::init_tracing();
+
+    let input = Input::from("(a)").with_source_id(SourceId::Named("cell-1".to_string()));
+    let reader = Reader::from(Lexer::from(input));
+    let syntax = reader.syntax_iter().next().unwrap().unwrap();
+    assert_eq!(syntax.source_id(), &SourceId::Named("cell-1".to_string()));
+    assert_eq!(
+        syntax.elements()[0].source_id(),
+        &SourceId::Named("cell-1".to_string())
+    );
+}
+
+#[test]
+fn fold_case_directive() {
+    let _guard = crate::init_tracing();
+
+    let nodes = read_syntax("#!fold-case (Hello |World|)");
+    assert_eq!(nodes.len(), 2);
+    assert_eq!(nodes[1].to_datum().to_string(), "(hello World)");
+}
+
+#[test]
+fn errors_and_labels() {
+    let _guard = crate::init_tracing();
+
+    super::assert_matches_reader_cases(read_datums);
+}
+
+#[test]
+fn preludes() {
+    let _guard = crate::init_tracing();
+
+    super::assert_matches_reader_files(read_datums);
+}
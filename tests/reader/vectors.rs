@@ -43,3 +43,7 @@ success_case!(
 // ------------------------------------------------------------------------------------------------
 // Failure cases
 // ------------------------------------------------------------------------------------------------
+
+failure_case!(byte_vector_out_of_range, "#u8(1 256)");
+
+failure_case!(byte_vector_non_number, "#u8(1 a)");
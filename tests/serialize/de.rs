@@ -0,0 +1,139 @@
+use super::{config, Bytes, Config, Shape};
+use ffsr::de::{from_datum, from_str};
+use ffsr::ser::{to_datum, to_string, Serializer, StructStyle};
+use pretty_assertions::assert_eq;
+use std::collections::BTreeMap;
+
+#[test]
+fn deserialize_scalars() {
+    let _guard = crate::init_tracing();
+
+    assert!(from_str::<bool>("#t").unwrap());
+    assert_eq!(from_str::<i8>("-12").unwrap(), -12);
+    assert_eq!(from_str::<f32>("2.5").unwrap(), 2.5);
+    assert_eq!(from_str::<char>("#\\a").unwrap(), 'a');
+    assert_eq!(from_str::<String>("\"a b\"").unwrap(), "a b");
+    assert_eq!(from_str::<String>("symbol").unwrap(), "symbol");
+    assert_eq!(from_str::<Option<u8>>("()").unwrap(), None);
+    assert_eq!(from_str::<Option<u8>>("1").unwrap(), Some(1));
+    from_str::<()>("()").unwrap();
+}
+
+#[test]
+fn deserialize_sequences() {
+    let _guard = crate::init_tracing();
+
+    assert_eq!(from_str::<Vec<u8>>("(1 2 3)").unwrap(), vec![1, 2, 3]);
+    assert_eq!(from_str::<Vec<u8>>("#(1 2 3)").unwrap(), vec![1, 2, 3]);
+    assert_eq!(from_str::<Vec<u8>>("'(1 2)").unwrap(), vec![1, 2]);
+    assert_eq!(
+        from_str::<(u8, String)>("(1 \"a\")").unwrap(),
+        (1, "a".to_string())
+    );
+    assert_eq!(
+        from_str::<Bytes>("#u8(0 255)").unwrap(),
+        Bytes(vec![0, 255])
+    );
+    assert_eq!(from_str::<Bytes>("(1 2)").unwrap(), Bytes(vec![1, 2]));
+}
+
+#[test]
+fn deserialize_enum_variants() {
+    let _guard = crate::init_tracing();
+
+    assert_eq!(from_str::<Shape>("Empty").unwrap(), Shape::Empty);
+    assert_eq!(
+        from_str::<Shape>("(Circle 2.5)").unwrap(),
+        Shape::Circle(2.5)
+    );
+    assert_eq!(
+        from_str::<Shape>("(Point 1 2)").unwrap(),
+        Shape::Point(1, 2)
+    );
+    assert_eq!(
+        from_str::<Shape>("(Rect (width . 1) (height 2))").unwrap(),
+        Shape::Rect {
+            width: 1,
+            height: 2
+        }
+    );
+    assert_eq!(
+        from_str::<Shape>("(Rect width: 1 height: 2)").unwrap(),
+        Shape::Rect {
+            width: 1,
+            height: 2
+        }
+    );
+}
+
+#[test]
+fn deserialize_maps() {
+    let _guard = crate::init_tracing();
+
+    let map: BTreeMap<String, Vec<u8>> = from_str("((a 1 2) (b . (3)) (c))").unwrap();
+    assert_eq!(map["a"], vec![1, 2]);
+    assert_eq!(map["b"], vec![3]);
+    assert!(map["c"].is_empty());
+}
+
+#[test]
+fn round_trip_config() {
+    let _guard = crate::init_tracing();
+
+    let value = config();
+    assert_eq!(
+        from_str::<Config>(&to_string(&value).unwrap()).unwrap(),
+        value
+    );
+    assert_eq!(
+        from_datum::<Config>(&to_datum(&value).unwrap()).unwrap(),
+        value
+    );
+
+    let keywords = Serializer::default().with_struct_style(StructStyle::KeywordList);
+    assert_eq!(
+        from_str::<Config>(&keywords.to_string(&value).unwrap()).unwrap(),
+        value
+    );
+}
+
+#[test]
+fn missing_option_field() {
+    let _guard = crate::init_tracing();
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Partial {
+        name: String,
+        port: Option<u16>,
+    }
+
+    assert_eq!(
+        from_str::<Partial>("(name: web)").unwrap(),
+        Partial {
+            name: "web".to_string(),
+            port: None
+        }
+    );
+}
+
+#[test]
+fn error_has_datum_span() {
+    let _guard = crate::init_tracing();
+
+    let error = from_str::<Vec<u8>>("(1 2 300)").unwrap_err();
+    assert_eq!(error.span().unwrap().as_range(), 5..8);
+
+    let error = from_str::<Shape>("(Point 1 x)").unwrap_err();
+    assert_eq!(error.span().unwrap().as_range(), 9..10);
+
+    let error = from_str::<(u8,)>("(1 2)").unwrap_err();
+    assert_eq!(error.span().unwrap().as_range(), 3..4);
+
+    let error = from_str::<u8>("1 2").unwrap_err();
+    assert_eq!(error.span().unwrap().as_range(), 2..3);
+
+    assert!(from_datum::<u8>(&to_datum(&"x").unwrap())
+        .unwrap_err()
+        .span()
+        .is_none());
+}
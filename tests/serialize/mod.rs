@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+enum Shape {
+    Empty,
+    Circle(f64),
+    Point(i64, i64),
+    Rect { width: u32, height: u32 },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Meters(f64);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Config {
+    name: String,
+    enabled: bool,
+    retries: u8,
+    initial: char,
+    scale: Meters,
+    timeout: Option<u32>,
+    tags: Vec<String>,
+    shapes: Vec<Shape>,
+    limits: BTreeMap<String, i32>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Bytes(Vec<u8>);
+
+impl Serialize for Bytes {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Bytes {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BytesVisitor;
+        impl<'de> serde::de::Visitor<'de> for BytesVisitor {
+            type Value = Bytes;
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("bytes")
+            }
+            fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Bytes, E> {
+                Ok(Bytes(v.to_vec()))
+            }
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Bytes, A::Error> {
+                let mut bytes = Vec::new();
+                while let Some(byte) = seq.next_element()? {
+                    bytes.push(byte);
+                }
+                Ok(Bytes(bytes))
+            }
+        }
+        deserializer.deserialize_byte_buf(BytesVisitor)
+    }
+}
+
+fn config() -> Config {
+    Config {
+        name: "server one".to_string(),
+        enabled: true,
+        retries: 3,
+        initial: 'x',
+        scale: Meters(1.5),
+        timeout: None,
+        tags: vec!["a".to_string(), "b".to_string()],
+        shapes: vec![
+            Shape::Empty,
+            Shape::Circle(2.0),
+            Shape::Point(1, -1),
+            Shape::Rect {
+                width: 4,
+                height: 5,
+            },
+        ],
+        limits: [("low".to_string(), 1), ("high".to_string(), 10)]
+            .into_iter()
+            .collect(),
+    }
+}
+
+pub mod de;
+pub mod ser;
@@ -0,0 +1,79 @@
+use super::{config, Bytes, Shape};
+use ffsr::ser::{to_string, SequenceStyle, Serializer, StructStyle};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn serialize_scalars() {
+    let _guard = crate::init_tracing();
+
+    assert_eq!(to_string(&true).unwrap(), "#t");
+    assert_eq!(to_string(&-12i16).unwrap(), "-12");
+    assert_eq!(to_string(&'a').unwrap(), "#\\a");
+    assert_eq!(to_string(&"a \"b\"").unwrap(), "\"a \\\"b\\\"\"");
+    assert_eq!(to_string(&()).unwrap(), "()");
+    assert_eq!(to_string(&None::<u8>).unwrap(), "()");
+    assert_eq!(to_string(&Some(1u8)).unwrap(), "1");
+}
+
+#[test]
+fn serialize_out_of_range_integer() {
+    let _guard = crate::init_tracing();
+
+    assert!(to_string(&u64::MAX).is_err());
+    assert_eq!(to_string(&u64::from(u32::MAX)).unwrap(), "4294967295");
+}
+
+#[test]
+fn serialize_enum_variants() {
+    let _guard = crate::init_tracing();
+
+    assert_eq!(to_string(&Shape::Empty).unwrap(), "Empty");
+    assert_eq!(to_string(&Shape::Circle(2.5)).unwrap(), "(Circle 2.5)");
+    assert_eq!(to_string(&Shape::Point(1, 2)).unwrap(), "(Point 1 2)");
+    assert_eq!(
+        to_string(&Shape::Rect {
+            width: 1,
+            height: 2
+        })
+        .unwrap(),
+        "(Rect (width . 1) (height . 2))"
+    );
+}
+
+#[test]
+fn serialize_struct_styles() {
+    let _guard = crate::init_tracing();
+
+    let value = Shape::Rect {
+        width: 1,
+        height: 2,
+    };
+    let keywords = Serializer::default().with_struct_style(StructStyle::KeywordList);
+    assert_eq!(
+        keywords.to_string(&value).unwrap(),
+        "(Rect width: 1 height: 2)"
+    );
+
+    let vectors = Serializer::default().with_sequence_style(SequenceStyle::Vector);
+    assert_eq!(vectors.to_string(&vec![1, 2]).unwrap(), "#(1 2)");
+    assert_eq!(vectors.to_string(&(1, "a")).unwrap(), "(1 \"a\")");
+}
+
+#[test]
+fn serialize_bytes() {
+    let _guard = crate::init_tracing();
+
+    assert_eq!(to_string(&Bytes(vec![0, 255])).unwrap(), "#u8(0 255)");
+}
+
+#[test]
+fn serialize_config() {
+    let _guard = crate::init_tracing();
+
+    assert_eq!(
+        to_string(&config()).unwrap(),
+        "((name . \"server one\") (enabled . #t) (retries . 3) (initial . #\\x) (scale . 1.5) \
+         (timeout) (tags \"a\" \"b\") (shapes Empty (Circle 2.0) (Point 1 -1) \
+         (Rect (width . 4) (height . 5))) (limits (\"high\" . 10) (\"low\" . 1)))"
+    );
+}
@@ -0,0 +1,124 @@
+use super::{deeply_nested, read_one};
+use ffsr::reader::datum::{Datum, SComment, SIdentifier, SList, SVector};
+use ffsr::visit::{fold, transform, transform_rc};
+use pretty_assertions::assert_eq;
+use std::rc::Rc;
+use std::str::FromStr;
+
+fn rename_x(datum: &Datum) -> Option<Datum> {
+    (datum.as_identifier().map(|v| v.as_str()) == Some("x"))
+        .then(|| SIdentifier::from_str("y").unwrap().into())
+}
+
+#[test]
+fn fold_children_in_order() {
+    let _guard = crate::init_tracing();
+
+    let datum: Datum = SVector::from(vec![
+        read_one("(a 'b . c)"),
+        Datum::Comment(SComment::from(read_one("d"))),
+        read_one("#u8(1)"),
+    ])
+    .into();
+    let written = fold(&datum, |datum, children: Vec<String>| {
+        if children.is_empty() {
+            datum.to_string()
+        } else {
+            format!("{}[{}]", datum.type_string(), children.join(" "))
+        }
+    });
+    assert_eq!(
+        written,
+        "vector[pair-or-list[a quote[b] c] datum-comment[d] #u8(1)]"
+    );
+}
+
+#[test]
+fn fold_deeply_nested() {
+    let _guard = crate::init_tracing();
+
+    let datum = deeply_nested(200_000);
+    let depth = fold(&datum, |_, children: Vec<usize>| {
+        children.into_iter().max().map_or(0, |depth| depth + 1)
+    });
+    assert_eq!(depth, 200_000);
+
+    std::mem::forget(datum);
+}
+
+#[test]
+fn transform_rebuilds_changes() {
+    let _guard = crate::init_tracing();
+
+    let datum = read_one("(x (a x) #(x 'x) `(,x . x) \"x\")");
+    assert_eq!(
+        ffsr::writer::write(&transform(&datum, rename_x)).to_string(),
+        "(y (a y) #(y 'y) `(,y . y) \"x\")"
+    );
+}
+
+#[test]
+fn transform_sees_rebuilt_children() {
+    let _guard = crate::init_tracing();
+
+    let datum = read_one("(+ 1 (+ 2 3) (+ (+ 4) 5))");
+    let summed = transform(&datum, |datum| {
+        let list = datum.as_list()?;
+        let mut elements = Vec::new();
+        let mut next = list.as_pair();
+        while let Some(pair) = next {
+            elements.push(pair.car().to_string());
+            next = pair.cdr().as_list().and_then(SList::as_pair);
+        }
+        let sum: i64 = elements[1..]
+            .iter()
+            .map(|v| v.parse::<i64>().unwrap())
+            .sum();
+        Some(ffsr::convert::from_str::<Datum>(&sum.to_string()).unwrap())
+    });
+    assert_eq!(summed.to_string(), "15");
+}
+
+#[test]
+fn transform_keeps_sharing() {
+    let _guard = crate::init_tracing();
+
+    let changed = Rc::new(read_one("(x)"));
+    let unchanged = Rc::new(read_one("(z)"));
+    let datum = Rc::new(Datum::from(SList::from(vec![
+        changed.clone(),
+        changed.clone(),
+        unchanged.clone(),
+    ])));
+
+    let result = transform_rc(&datum, rename_x);
+    assert_eq!(result.to_string(), "((y) (y) (z))");
+    let pair = result.as_list().unwrap().as_pair().unwrap();
+    let second = pair.cdr().as_list().unwrap().as_pair().unwrap();
+    let third = second.cdr().as_list().unwrap().as_pair().unwrap();
+    assert!(Rc::ptr_eq(pair.car(), second.car()));
+    assert!(Rc::ptr_eq(third.car(), &unchanged));
+    assert_eq!(changed.to_string(), "(x)");
+
+    assert!(Rc::ptr_eq(&transform_rc(&unchanged, rename_x), &unchanged));
+}
+
+#[test]
+fn transform_deeply_nested() {
+    let _guard = crate::init_tracing();
+
+    let datum = deeply_nested(200_000);
+    let result = transform(&datum, |datum| {
+        datum
+            .is_string()
+            .then(|| SIdentifier::from_str("y").unwrap().into())
+    });
+    let depth = fold(&result, |datum, children: Vec<usize>| {
+        assert!(!datum.is_string());
+        children.into_iter().max().map_or(0, |depth| depth + 1)
+    });
+    assert_eq!(depth, 200_000);
+
+    std::mem::forget(datum);
+    std::mem::forget(result);
+}
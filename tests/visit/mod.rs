@@ -0,0 +1,17 @@
+use crate::reader::read_one;
+use ffsr::reader::datum::{Datum, SList};
+
+///
+/// A list nested `depth` deep, `(((... x ...)))`; it must be forgotten rather than dropped, as
+/// dropping it recurses.
+///
+fn deeply_nested(depth: usize) -> Datum {
+    let mut datum = Datum::from("x".to_string());
+    for _ in 0..depth {
+        datum = SList::from(vec![datum]).into();
+    }
+    datum
+}
+
+pub mod fold;
+pub mod walk;
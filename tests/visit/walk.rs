@@ -0,0 +1,337 @@
+use super::{deeply_nested, read_one};
+use ffsr::reader::datum::{
+    Datum, Fixnum, SComment, SIdentifier, SList, SNumber, SPair, SString, SVector, EMPTY_LIST,
+};
+use ffsr::visit::{walk, walk_mut, Visit, VisitMut, Walk};
+use pretty_assertions::assert_eq;
+use std::rc::Rc;
+use std::str::FromStr;
+
+#[derive(Default)]
+struct Events(Vec<String>);
+
+impl Visit for Events {
+    fn enter_datum(&mut self, datum: &Datum) -> Walk {
+        self.0.push(format!("> {}", datum.type_string()));
+        Walk::Continue
+    }
+
+    fn leave_datum(&mut self, datum: &Datum) -> Walk {
+        self.0.push(format!("< {}", datum.type_string()));
+        Walk::Continue
+    }
+
+    fn visit_quote(&mut self, _datum: &Datum) -> Walk {
+        self.0.push("quote".to_string());
+        Walk::Continue
+    }
+
+    fn visit_identifier(&mut self, identifier: &SIdentifier) -> Walk {
+        self.0.push(identifier.to_string());
+        Walk::Continue
+    }
+
+    fn visit_number(&mut self, number: &SNumber) -> Walk {
+        self.0.push(number.to_string());
+        Walk::Continue
+    }
+
+    fn visit_list(&mut self, _list: &SList) -> Walk {
+        self.0.push("list".to_string());
+        Walk::Continue
+    }
+
+    fn visit_pair(&mut self, _pair: &SPair) -> Walk {
+        self.0.push("pair".to_string());
+        Walk::Continue
+    }
+
+    fn visit_improper_tail(&mut self, _tail: &Datum) -> Walk {
+        self.0.push("tail".to_string());
+        Walk::Continue
+    }
+
+    fn visit_vector(&mut self, _vector: &SVector) -> Walk {
+        self.0.push("vector".to_string());
+        Walk::Continue
+    }
+
+    fn visit_byte(&mut self, byte: &Fixnum) -> Walk {
+        self.0.push(format!("byte {}", byte));
+        Walk::Continue
+    }
+
+    fn visit_comment(&mut self, _comment: &SComment) -> Walk {
+        self.0.push("comment".to_string());
+        Walk::Continue
+    }
+}
+
+#[derive(Default)]
+struct Identifiers(Vec<String>);
+
+impl Visit for Identifiers {
+    fn visit_identifier(&mut self, identifier: &SIdentifier) -> Walk {
+        self.0.push(identifier.to_string());
+        Walk::Continue
+    }
+}
+
+fn events(datum: &Datum) -> Vec<String> {
+    let mut events = Events::default();
+    assert_eq!(walk(&mut events, datum), Walk::Continue);
+    events.0
+}
+
+#[test]
+fn walk_list_order() {
+    let _guard = crate::init_tracing();
+
+    assert_eq!(
+        events(&read_one("(a 'b . 1)")),
+        vec![
+            "> pair-or-list",
+            "list",
+            "pair",
+            "> identifier",
+            "a",
+            "< identifier",
+            "pair",
+            "> quote",
+            "quote",
+            "> identifier",
+            "b",
+            "< identifier",
+            "< quote",
+            "tail",
+            "> fixnum",
+            "1",
+            "< fixnum",
+            "< pair-or-list",
+        ]
+    );
+    assert_eq!(
+        events(&read_one("()")),
+        vec!["> pair-or-list", "list", "< pair-or-list"]
+    );
+}
+
+#[test]
+fn walk_vectors_and_comments() {
+    let _guard = crate::init_tracing();
+
+    let datum: Datum = SVector::from(vec![
+        read_one("#u8(1 2)"),
+        Datum::Comment(SComment::from(read_one("c"))),
+        Datum::Comment(SComment::Line("text".to_string())),
+    ])
+    .into();
+    assert_eq!(
+        events(&datum),
+        vec![
+            "> vector",
+            "vector",
+            "> byte-vector",
+            "byte 1",
+            "byte 2",
+            "< byte-vector",
+            "> datum-comment",
+            "comment",
+            "> identifier",
+            "c",
+            "< identifier",
+            "< datum-comment",
+            "> line-comment",
+            "comment",
+            "< line-comment",
+            "< vector",
+        ]
+    );
+}
+
+#[test]
+fn walk_skip_and_stop() {
+    struct SkipQuotes(Identifiers);
+
+    impl Visit for SkipQuotes {
+        fn enter_datum(&mut self, datum: &Datum) -> Walk {
+            if datum.is_quote() {
+                Walk::Skip
+            } else {
+                Walk::Continue
+            }
+        }
+
+        fn visit_identifier(&mut self, identifier: &SIdentifier) -> Walk {
+            self.0.visit_identifier(identifier);
+            if identifier.as_str() == "stop" {
+                Walk::Stop
+            } else {
+                Walk::Continue
+            }
+        }
+    }
+
+    let _guard = crate::init_tracing();
+
+    let mut visitor = SkipQuotes(Identifiers::default());
+    let datum = read_one("(a '(b c) #(d 'e) (f stop g) h)");
+    assert_eq!(walk(&mut visitor, &datum), Walk::Stop);
+    assert_eq!(visitor.0 .0, vec!["a", "d", "f", "stop"]);
+}
+
+#[test]
+fn walk_skip_rest_of_list() {
+    struct FirstOnly(Identifiers, bool);
+
+    impl Visit for FirstOnly {
+        fn visit_list(&mut self, _list: &SList) -> Walk {
+            self.1 = true;
+            Walk::Continue
+        }
+
+        fn visit_pair(&mut self, _pair: &SPair) -> Walk {
+            if std::mem::take(&mut self.1) {
+                Walk::Continue
+            } else {
+                Walk::Skip
+            }
+        }
+
+        fn visit_identifier(&mut self, identifier: &SIdentifier) -> Walk {
+            self.0.visit_identifier(identifier)
+        }
+    }
+
+    let _guard = crate::init_tracing();
+
+    let mut visitor = FirstOnly(Identifiers::default(), false);
+    walk(&mut visitor, &read_one("((a b) c . d)"));
+    assert_eq!(visitor.0 .0, vec!["a"]);
+}
+
+#[test]
+fn walk_shared_once() {
+    let _guard = crate::init_tracing();
+
+    let mut identifiers = Identifiers::default();
+    let datum = read_one("#0=(a b . #0#)");
+    assert_eq!(walk(&mut identifiers, &datum), Walk::Continue);
+    assert_eq!(identifiers.0, vec!["a", "b"]);
+
+    let mut identifiers = Identifiers::default();
+    let datum = read_one("(#0=(a) #0# #1=#(b #1#))");
+    walk(&mut identifiers, &datum);
+    assert_eq!(identifiers.0, vec!["a", "b"]);
+}
+
+#[test]
+fn walk_deeply_nested() {
+    struct Lists(usize);
+
+    impl Visit for Lists {
+        fn visit_list(&mut self, _list: &SList) -> Walk {
+            self.0 += 1;
+            Walk::Continue
+        }
+    }
+
+    struct Rename;
+
+    impl VisitMut for Rename {
+        fn visit_string_mut(&mut self, string: &mut SString) -> Walk {
+            *string = SString::from("y".to_string());
+            Walk::Continue
+        }
+    }
+
+    let _guard = crate::init_tracing();
+
+    let mut datum = deeply_nested(200_000);
+    let mut lists = Lists(0);
+    walk(&mut lists, &datum);
+    assert_eq!(lists.0, 200_000);
+
+    walk_mut(&mut Rename, &mut datum);
+    let mut innermost = &datum;
+    while let Some(pair) = innermost.as_list().and_then(SList::as_pair) {
+        innermost = pair.car();
+    }
+    assert_eq!(innermost.as_string().unwrap().to_string(), "y");
+
+    std::mem::forget(datum);
+}
+
+// ------------------------------------------------------------------------------------------------
+
+struct Rewrite;
+
+impl VisitMut for Rewrite {
+    fn enter_datum_mut(&mut self, datum: &mut Datum) -> Walk {
+        if datum.is_string() {
+            *datum = SIdentifier::from_str(datum.as_string().unwrap().as_ref())
+                .unwrap()
+                .into();
+        }
+        Walk::Continue
+    }
+
+    fn visit_identifier_mut(&mut self, identifier: &mut SIdentifier) -> Walk {
+        if identifier.as_str() == "x" {
+            *identifier = SIdentifier::from_str("y").unwrap();
+        }
+        Walk::Continue
+    }
+
+    fn visit_quote_mut(&mut self, datum: &mut Datum) -> Walk {
+        if datum.as_quote().is_some_and(|quoted| quoted.is_vector()) {
+            Walk::Skip
+        } else {
+            Walk::Continue
+        }
+    }
+}
+
+#[test]
+fn walk_mut_rewrites() {
+    let _guard = crate::init_tracing();
+
+    let mut datum = read_one("(x \"x\" #(x 'x '#(x)) (x . x))");
+    assert_eq!(walk_mut(&mut Rewrite, &mut datum), Walk::Continue);
+    assert_eq!(datum.to_string(), "(y y #(y 'y '#(x)) (y . y))");
+}
+
+#[test]
+fn walk_mut_copies_shared_values() {
+    let _guard = crate::init_tracing();
+
+    let inner = Rc::new(read_one("(x)"));
+    let mut datum: Datum = SList::from(vec![inner.clone(), Rc::new(read_one("x"))]).into();
+    walk_mut(&mut Rewrite, &mut datum);
+    assert_eq!(datum.to_string(), "((y) y)");
+    assert_eq!(inner.to_string(), "(x)");
+}
+
+#[test]
+fn walk_mut_changes_rest_of_list() {
+    struct Truncate;
+
+    impl VisitMut for Truncate {
+        fn visit_pair_mut(&mut self, pair: &mut SPair) -> Walk {
+            if pair
+                .car()
+                .as_identifier()
+                .is_some_and(|v| v.as_str() == "end")
+            {
+                pair.set_cdr(Rc::new(EMPTY_LIST));
+            }
+            Walk::Continue
+        }
+    }
+
+    let _guard = crate::init_tracing();
+
+    let mut datum = read_one("(a (b end c) end d . e)");
+    walk_mut(&mut Truncate, &mut datum);
+    assert_eq!(datum.to_string(), "(a (b end) end)");
+}
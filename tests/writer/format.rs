@@ -0,0 +1,236 @@
+use ffsr::lexer::Lexer;
+use ffsr::reader::cst::{Node, NodeKind, SyntaxTree, Trivia};
+use ffsr::writer::format::{format, SourceFormatter};
+use ffsr::writer::pretty::IndentStyle;
+
+macro_rules! format_case {
+    ($test_name:ident, $formatter:expr, $input:expr => $expected:expr) => {
+        #[test]
+        fn $test_name() {
+            let _guard = crate::init_tracing();
+
+            let formatter = $formatter;
+            let formatted = formatter.format_str($input).unwrap();
+            ::pretty_assertions::assert_eq!(formatted, $expected);
+            ::pretty_assertions::assert_eq!(formatter.format_str(&formatted).unwrap(), formatted);
+            assert_equivalent($input, &formatted);
+        }
+    };
+}
+
+fn narrow(width: usize) -> SourceFormatter {
+    SourceFormatter::default().with_width(width)
+}
+
+fn comments(source: &str) -> Vec<String> {
+    let tree = SyntaxTree::parse(Lexer::from(source).tokens()).unwrap();
+    let mut comments = Vec::new();
+    tree.nodes()
+        .iter()
+        .for_each(|node| node_comments(node, &mut comments));
+    trivia_comments(tree.trailing(), &mut comments);
+    comments
+}
+
+fn node_comments(node: &Node, comments: &mut Vec<String>) {
+    trivia_comments(node.leading(), comments);
+    node.children()
+        .iter()
+        .for_each(|child| node_comments(child, comments));
+    if let NodeKind::Delimited { close_leading, .. } = node.kind() {
+        trivia_comments(close_leading, comments);
+    }
+}
+
+fn trivia_comments(trivia: &[Trivia], comments: &mut Vec<String>) {
+    comments.extend(
+        trivia
+            .iter()
+            .filter(|trivia| trivia.is_comment())
+            .map(|trivia| trivia.text().trim_end().to_string()),
+    );
+}
+
+fn datums(source: &str) -> Vec<String> {
+    let tree = SyntaxTree::parse(Lexer::from(source).tokens()).unwrap();
+    tree.nodes()
+        .iter()
+        .map(|node| format!("{:?}", node.to_datum()))
+        .collect()
+}
+
+fn assert_equivalent(source: &str, formatted: &str) {
+    pretty_assertions::assert_eq!(datums(formatted), datums(source));
+    pretty_assertions::assert_eq!(comments(formatted), comments(source));
+}
+
+format_case!(empty, SourceFormatter::default(), "" => "");
+
+format_case!(
+    only_comments,
+    SourceFormatter::default(),
+    "  ; one  \n\n\n#| two |#" =>
+    "; one\n\n#| two |#\n"
+);
+
+format_case!(
+    reindents,
+    SourceFormatter::default(),
+    "(define (f x)\n        (* x\n   x))" =>
+    "(define (f x) (* x x))\n"
+);
+
+format_case!(
+    atoms_as_written,
+    SourceFormatter::default(),
+    "(list #f #\\x41; 1e2 \"a\\x41;\" |a b|)" =>
+    "(list #f #\\x41; 1e2 \"a\\x41;\" |a b|)\n"
+);
+
+format_case!(
+    blank_lines_between_forms,
+    SourceFormatter::default(),
+    "\n\n(a)\n(b)\n\n\n\n(c)\n" =>
+    "(a)\n(b)\n\n(c)\n"
+);
+
+format_case!(
+    more_blank_lines,
+    SourceFormatter::default().with_blank_lines(2),
+    "(a)\n\n\n\n(b)" =>
+    "(a)\n\n\n(b)\n"
+);
+
+format_case!(
+    blank_lines_within_body,
+    narrow(30),
+    "(define-library (lib)\n\n  (export f)\n\n\n  (import (scheme base)))" =>
+    "(define-library (lib)
+
+  (export f)
+
+  (import (scheme base)))
+"
+);
+
+format_case!(
+    end_of_line_comments,
+    narrow(30),
+    "(define (f x) ; doc\n  (g x) ; call\n  )" =>
+    "(define (f x) ; doc
+  (g x) ; call
+)
+"
+);
+
+format_case!(
+    end_of_line_comment_breaks_form,
+    SourceFormatter::default(),
+    "(list a ; first\n b)" =>
+    "(list a ; first
+      b)
+"
+);
+
+format_case!(
+    own_line_comments,
+    SourceFormatter::default(),
+    "(define (f x)\n    ; first\n    #| then |#\n (g x))" =>
+    "(define (f x)
+  ; first
+  #| then |#
+  (g x))
+"
+);
+
+format_case!(
+    inline_block_comment,
+    SourceFormatter::default(),
+    "(f #| arg |#   x)" =>
+    "(f #| arg |# x)\n"
+);
+
+format_case!(
+    comment_before_distinguished_argument,
+    SourceFormatter::default(),
+    "(define ; name\n  x 1)" =>
+    "(define ; name
+  x
+  1)
+"
+);
+
+format_case!(
+    datum_comment_kept,
+    SourceFormatter::default(),
+    "(a #;   (b c) d)\n#; (e)" =>
+    "(a #;(b c) d)\n#;(e)\n"
+);
+
+format_case!(
+    directives_kept,
+    SourceFormatter::default(),
+    "#!fold-case\n(A)\n#!no-fold-case" =>
+    "#!fold-case\n(A)\n#!no-fold-case\n"
+);
+
+format_case!(
+    dotted_pair,
+    narrow(10),
+    "((alpha) beta . gamma)" =>
+    "((alpha)
+ beta
+ . gamma)
+"
+);
+
+format_case!(
+    block_style,
+    narrow(12).with_style(IndentStyle::Block),
+    "(list alpha beta)" =>
+    "(list
+  alpha
+  beta)
+"
+);
+
+format_case!(
+    labels_and_quotes,
+    SourceFormatter::default(),
+    "(#0= (a) ' b `( ,c ,@ d) #0#)" =>
+    "(#0=(a) 'b `(,c ,@d) #0#)\n"
+);
+
+#[test]
+fn syntax_errors() {
+    let _guard = crate::init_tracing();
+
+    assert!(format("(a (b)").is_err());
+    assert!(format("(a))").is_err());
+}
+
+#[test]
+fn is_formatted() {
+    let _guard = crate::init_tracing();
+
+    let formatter = SourceFormatter::default();
+    assert!(formatter.is_formatted("(a b)\n").unwrap());
+    assert!(!formatter.is_formatted("(a  b)").unwrap());
+}
+
+#[test]
+fn preludes() {
+    let _guard = crate::init_tracing();
+
+    for path in crate::reader::MATCHES_READER_FILES {
+        let source = std::fs::read_to_string(path).unwrap();
+        for formatter in [narrow(60), narrow(100).with_style(IndentStyle::Block)] {
+            let formatted = formatter.format_str(&source).unwrap();
+            assert!(
+                formatter.is_formatted(&formatted).unwrap(),
+                "formatting {path}"
+            );
+            assert_equivalent(&source, &formatted);
+        }
+    }
+}
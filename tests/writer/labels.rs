@@ -0,0 +1,90 @@
+use ffsr::writer::{write, write_shared, WriteMode, Written};
+
+write_case!(
+    shared_not_cyclic,
+    "(#0=(x y) #0# #0#)" =>
+    "((x y) (x y) (x y))",
+    "(#0=(x y) #0# #0#)"
+);
+
+write_case!(
+    simple_datums_never_labelled,
+    "(#0=a #1=\"s\" #0# #1#)" =>
+    "(a \"s\" a \"s\")"
+);
+
+write_case!(circular_list, "#0=(a b . #0#)" => "#0=(a b . #0#)");
+
+write_case!(circular_car, "#0=(#0# . a)" => "#0=(#0# . a)");
+
+write_case!(circular_vector, "#1=#(1 '#1# (x #1#))" => "#0=#(1 '#0# (x #0#))");
+
+write_case!(
+    nested_cycles,
+    "#4=(a #7=(b #4# #7#))" =>
+    "#0=(a #1=(b #0# #1#))"
+);
+
+write_case!(
+    cycle_within_shared,
+    "(#3=(a . #3#) #5=(b) #5#)" =>
+    "(#0=(a . #0#) (b) (b))",
+    "(#0=(a . #0#) #1=(b) #1#)"
+);
+
+write_case!(
+    shared_tail_spliced,
+    "(a b . #0=(c d))" =>
+    "(a b c d)"
+);
+
+write_case!(
+    shared_tail_labelled,
+    "(#0=(c d) a b . #0#)" =>
+    "((c d) a b c d)",
+    "(#0=(c d) a b . #0#)"
+);
+
+#[test]
+fn write_mode() {
+    let _guard = crate::init_tracing();
+
+    let datum = super::read_one("(a)");
+    assert_eq!(write(&datum).mode(), WriteMode::Cycles);
+    assert_eq!(write_shared(&datum).mode(), WriteMode::Shared);
+    assert_eq!(WriteMode::default(), WriteMode::Cycles);
+    assert_eq!(
+        Written::new(&datum, WriteMode::Shared).to_string(),
+        write(&datum).to_string()
+    );
+}
+
+#[test]
+fn round_trip_keeps_cycles_and_sharing() {
+    let _guard = crate::init_tracing();
+
+    let datum = super::read_one("(#0=(x . #0#) #1=(y) #1#)");
+
+    let read_back = super::read_one(&write(&datum).to_string());
+    let elements = list_elements(&read_back);
+    assert!(elements[0].as_shared().unwrap().is_cyclic());
+    assert!(!elements[1].is_shared());
+
+    let read_back = super::read_one(&write_shared(&datum).to_string());
+    let elements = list_elements(&read_back);
+    assert!(elements[0].as_shared().unwrap().is_cyclic());
+    assert!(elements[1]
+        .as_shared()
+        .unwrap()
+        .ptr_eq(elements[2].as_shared().unwrap()));
+}
+
+fn list_elements(datum: &ffsr::reader::datum::Datum) -> Vec<ffsr::reader::datum::Datum> {
+    let mut elements = Vec::new();
+    let mut next = datum.as_list().and_then(|list| list.as_pair());
+    while let Some(pair) = next {
+        elements.push(pair.car().as_ref().clone());
+        next = pair.cdr().as_list().and_then(|list| list.as_pair());
+    }
+    elements
+}
@@ -0,0 +1,28 @@
+use crate::reader::read_one;
+
+macro_rules! write_case {
+    ($test_name:ident, $input:expr => $written:expr, $shared:expr) => {
+        #[test]
+        fn $test_name() {
+            let _guard = crate::init_tracing();
+
+            let datum = super::read_one($input);
+
+            let written = ::ffsr::writer::write(&datum).to_string();
+            ::pretty_assertions::assert_eq!(written, $written);
+            ::pretty_assertions::assert_eq!(super::read_one(&written), datum);
+
+            let shared = ::ffsr::writer::write_shared(&datum).to_string();
+            ::pretty_assertions::assert_eq!(shared, $shared);
+            ::pretty_assertions::assert_eq!(super::read_one(&shared), datum);
+        }
+    };
+    ($test_name:ident, $input:expr => $written:expr) => {
+        write_case!($test_name, $input => $written, $written);
+    };
+}
+
+pub mod format;
+pub mod labels;
+pub mod pretty;
+pub mod values;
@@ -0,0 +1,193 @@
+use ffsr::writer::pretty::{pretty, IndentStyle, PrettyPrinter};
+use ffsr::writer::WriteMode;
+
+macro_rules! pretty_case {
+    ($test_name:ident, $printer:expr, $input:expr => $expected:expr) => {
+        #[test]
+        fn $test_name() {
+            let _guard = crate::init_tracing();
+
+            let datum = super::read_one($input);
+            let printed = $printer.print(&datum).to_string();
+            ::pretty_assertions::assert_eq!(printed, $expected);
+            ::pretty_assertions::assert_eq!(super::read_one(&printed), datum);
+        }
+    };
+}
+
+fn narrow(width: usize) -> PrettyPrinter {
+    PrettyPrinter::default().with_width(width)
+}
+
+pretty_case!(
+    fits_on_one_line,
+    PrettyPrinter::default(),
+    "(define   (f x)\n  (* x\n x))" =>
+    "(define (f x) (* x x))"
+);
+
+pretty_case!(
+    atoms_are_written,
+    PrettyPrinter::default(),
+    r#"("a\nb" #\space #t 1/2)"# =>
+    r#"("a\nb" #\space #t 1/2)"#
+);
+
+pretty_case!(
+    define_and_cond,
+    narrow(30),
+    "(define (sign x) (cond ((> x 0) 'positive) ((< x 0) 'negative) (else 'zero)))" =>
+    "(define (sign x)
+  (cond ((> x 0) 'positive)
+        ((< x 0) 'negative)
+        (else 'zero)))"
+);
+
+pretty_case!(
+    lambda_body,
+    narrow(20),
+    "(lambda (a b) (display a) (display b))" =>
+    "(lambda (a b)
+  (display a)
+  (display b))"
+);
+
+pretty_case!(
+    named_let,
+    narrow(24),
+    "(let loop ((i 0)) (when (< i 10) (loop (+ i 1))))" =>
+    "(let loop ((i 0))
+  (when (< i 10)
+    (loop (+ i 1))))"
+);
+
+pretty_case!(
+    aligned_call,
+    narrow(16),
+    "(list alpha beta gamma)" =>
+    "(list alpha
+      beta
+      gamma)"
+);
+
+pretty_case!(
+    block_style_keeps_aligned_forms,
+    narrow(20).with_style(IndentStyle::Block),
+    "(if (ready? x) (go x) (wait x))" =>
+    "(if (ready? x)
+    (go x)
+    (wait x))"
+);
+
+pretty_case!(
+    block_call,
+    narrow(16).with_style(IndentStyle::Block).with_indent(4),
+    "(list alpha beta gamma)" =>
+    "(list
+    alpha
+    beta
+    gamma)"
+);
+
+pretty_case!(
+    data_list,
+    narrow(12),
+    "((alpha 1) (beta 2) . gamma)" =>
+    "((alpha 1)
+ (beta 2)
+ . gamma)"
+);
+
+pretty_case!(
+    vector,
+    narrow(10),
+    "#(alpha beta 'gamma)" =>
+    "#(alpha
+  beta
+  'gamma)"
+);
+
+pretty_case!(
+    closing_parens_count_against_width,
+    narrow(14),
+    "(f (g (h a b)))" =>
+    "(f (g (h a
+         b)))"
+);
+
+pretty_case!(
+    cycle_labels,
+    narrow(8),
+    "#0=(1 2 . #0#)" =>
+    "#0=(1
+    2
+    . #0#)"
+);
+
+pretty_case!(
+    shared_labels,
+    narrow(12).with_mode(WriteMode::Shared),
+    "(#0=(a b) #0#)" =>
+    "(#0=(a b)
+ #0#)"
+);
+
+pretty_case!(
+    identifiers_round_trip,
+    narrow(12),
+    r"(abc|d| |a\x7c;b| |a b|)" =>
+    r"(abc d
+     |a\x7c;b|
+     |a b|)"
+);
+
+#[test]
+fn lines_fit_width() {
+    let _guard = crate::init_tracing();
+
+    let datum = super::read_one(
+        "(define (fold f acc lst) (if (null? lst) acc (fold f (f acc (car lst)) (cdr lst))))",
+    );
+    for printer in [
+        narrow(30),
+        narrow(40),
+        narrow(20).with_style(IndentStyle::Block),
+    ] {
+        let printed = printer.print(&datum).to_string();
+        assert!(
+            printed.lines().all(|line| line.len() <= printer.width()),
+            "{printed}"
+        );
+        assert_eq!(super::read_one(&printed), datum);
+    }
+}
+
+#[test]
+fn write_to_fmt_and_io() {
+    let _guard = crate::init_tracing();
+
+    let datum = super::read_one("(when ready (go))");
+    let printer = narrow(10);
+
+    let mut string = String::new();
+    printer.write_to_fmt(&datum, &mut string).unwrap();
+    let mut bytes = Vec::new();
+    printer.write_to_io(&datum, &mut bytes).unwrap();
+
+    assert_eq!(string, "(when ready\n  (go))");
+    assert_eq!(String::from_utf8(bytes).unwrap(), string);
+}
+
+#[test]
+fn alternate_debug() {
+    let _guard = crate::init_tracing();
+
+    let datum = super::read_one("(a \"b\")");
+    assert_eq!(format!("{datum:#?}"), pretty(&datum).to_string());
+    assert_eq!(format!("{datum:#?}"), "(a \"b\")");
+
+    let datum = super::read_one(r"(|a\x7c;b| |c d|)");
+    let printed = format!("{datum:#?}");
+    assert_eq!(printed, r"(|a\x7c;b| |c d|)");
+    assert_eq!(super::read_one(&printed), datum);
+}
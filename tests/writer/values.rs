@@ -0,0 +1,35 @@
+write_case!(boolean, "#t" => "#t");
+
+write_case!(number, "-12.5" => "-12.5");
+
+write_case!(identifier, "hello" => "hello");
+
+write_case!(identifier_adjacent_bars, "(abc|d|)" => "(abc d)");
+
+write_case!(identifier_escaped_bar, r"|a\x7c;b|" => r"|a\x7c;b|");
+
+write_case!(identifier_escaped_backslash, r"|a\\b|" => r"|a\\b|");
+
+write_case!(identifier_needs_bars, "(|a b| |.| |#x| |+a|)" => "(|a b| |.| |#x| +a)");
+
+write_case!(string_escapes, r#""a\"b\\c\nd""# => r#""a\"b\\c\nd""#);
+
+write_case!(char_plain, r"#\a" => r"#\a");
+
+write_case!(char_named, r"#\space" => r"#\space");
+
+write_case!(char_newline, r"#\newline" => r"#\newline");
+
+write_case!(char_hex_control, r"#\x1;" => r"#\x1;");
+
+write_case!(empty_list, "()" => "()");
+
+write_case!(proper_list, "(a b c)" => "(a b c)");
+
+write_case!(dotted_list, "(a b . c)" => "(a b . c)");
+
+write_case!(nested_list, "(a (b \"c\") #\\d)" => "(a (b \"c\") #\\d)");
+
+write_case!(vector, "#(1 \"two\" (3))" => "#(1 \"two\" (3))");
+
+write_case!(quotes, "('a `(b ,c ,@d))" => "('a `(b ,c ,@d))");